@@ -1,17 +1,20 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use tauri_specta::Event;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Type, Event)]
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[cfg_attr(feature = "tauri", derive(tauri_specta::Event))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum SyncEvent {
     Start { ip: String },
     Stop,
     Subscribed,
     Derivation,
+    DerivationLimitReached { max_index: u32 },
     CoinState,
     PuzzleBatchSynced,
     CatInfo,
     DidInfo,
     NftData,
+    NftPrefetch { fetched: u32, pending: u32 },
+    SyncHealth { stalled: bool },
 }
@@ -12,4 +12,14 @@ pub struct CatRecord {
     pub icon_url: Option<String>,
     pub visible: bool,
     pub balance: Amount,
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct KnownCatRecord {
+    pub asset_id: String,
+    pub name: String,
+    pub ticker: String,
+    pub decimals: u8,
+    pub icon_url: Option<String>,
 }
@@ -13,4 +13,5 @@ pub struct CoinRecord {
     pub create_transaction_id: Option<String>,
     pub spend_transaction_id: Option<String>,
     pub offer_id: Option<String>,
+    pub label: Option<String>,
 }
@@ -10,6 +10,7 @@ pub struct NftRecord {
     pub owner_did: Option<String>,
     pub visible: bool,
     pub sensitive_content: bool,
+    pub burned: bool,
     pub name: Option<String>,
     pub created_height: Option<u32>,
     pub coin_id: String,
@@ -19,4 +19,5 @@ pub enum OfferRecordStatus {
     Completed = 1,
     Cancelled = 2,
     Expired = 3,
+    PendingCancel = 4,
 }
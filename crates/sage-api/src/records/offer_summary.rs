@@ -2,7 +2,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::Amount;
+use crate::{Amount, AssetId};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct OfferSummary {
@@ -16,28 +16,91 @@ pub struct OfferAssets {
     pub xch: OfferXch,
     pub cats: IndexMap<String, OfferCat>,
     pub nfts: IndexMap<String, OfferNft>,
+    pub unknown: Vec<OfferUnknown>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct OfferXch {
+    pub asset: AssetId,
     pub amount: Amount,
     pub royalty: Amount,
+    pub royalties: Vec<RoyaltyPaymentRecord>,
+    pub decimals: u8,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct OfferCat {
+    pub asset: AssetId,
     pub amount: Amount,
     pub royalty: Amount,
+    pub royalties: Vec<RoyaltyPaymentRecord>,
+    pub decimals: u8,
     pub name: Option<String>,
     pub ticker: Option<String>,
     pub icon_url: Option<String>,
+    pub tail_revealed: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct OfferNft {
+    pub asset: AssetId,
     pub image_data: Option<String>,
     pub image_mime_type: Option<String>,
     pub name: Option<String>,
     pub royalty_ten_thousandths: u16,
     pub royalty_address: String,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub struct RoyaltyPaymentRecord {
+    pub nft_id: String,
+    pub address: String,
+    pub amount: Amount,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct OfferUnknown {
+    pub puzzle_hash: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_royalty_payment_record_serde() -> anyhow::Result<()> {
+        let royalty = RoyaltyPaymentRecord {
+            nft_id: "nft1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqsel2t2".to_string(),
+            address: "xch1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqm6ks6e8".to_string(),
+            amount: Amount::u64(u64::MAX),
+        };
+
+        let json = serde_json::to_value(&royalty)?;
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "nft_id": royalty.nft_id,
+                "address": royalty.address,
+                "amount": "18446744073709551615",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<RoyaltyPaymentRecord>(json)?,
+            royalty
+        );
+
+        let royalty = RoyaltyPaymentRecord {
+            amount: Amount::u64(250),
+            ..royalty
+        };
+
+        let json = serde_json::to_string(&royalty)?;
+        assert!(json.contains("\"amount\":250"));
+        assert_eq!(
+            serde_json::from_str::<RoyaltyPaymentRecord>(&json)?,
+            royalty
+        );
+
+        Ok(())
+    }
+}
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::Amount;
+use crate::{Amount, TransactionCoin};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct PendingTransactionRecord {
     pub transaction_id: String,
     pub fee: Amount,
     pub submitted_at: Option<String>,
+    pub spent: Vec<TransactionCoin>,
+    pub created: Vec<TransactionCoin>,
+    pub rebroadcasts: u32,
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::{Amount, AssetKind};
+use crate::{Amount, AssetId, AssetKind};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct TransactionRecord {
@@ -15,6 +15,7 @@ pub struct TransactionCoin {
     pub coin_id: String,
     pub amount: Amount,
     pub address: Option<String>,
+    pub asset: Option<AssetId>,
     #[serde(flatten)]
     pub kind: AssetKind,
 }
@@ -37,6 +37,9 @@ pub struct TransactionInput {
     #[serde(flatten)]
     pub kind: AssetKind,
     pub outputs: Vec<TransactionOutput>,
+    /// Whether the coin is already spent by a pending transaction, which this one would conflict
+    /// with, so that the user can be warned before submitting it.
+    pub pending_spend: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
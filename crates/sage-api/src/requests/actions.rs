@@ -37,3 +37,44 @@ pub struct UpdateNft {
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct UpdateNftResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct FreezeCoins {
+    pub coin_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct FreezeCoinsResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct UnfreezeCoins {
+    pub coin_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct UnfreezeCoinsResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetCoinLabel {
+    pub coin_id: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetCoinLabelResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct AddBurnAddress {
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct AddBurnAddressResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct RemoveBurnAddress {
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct RemoveBurnAddressResponse {}
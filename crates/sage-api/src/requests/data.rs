@@ -2,8 +2,8 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 
 use crate::{
-    Amount, CatRecord, CoinRecord, DerivationRecord, DidRecord, NftCollectionRecord, NftData,
-    NftRecord, PendingTransactionRecord, TransactionRecord, Unit,
+    Amount, CatRecord, CoinRecord, DerivationRecord, DidRecord, KnownCatRecord,
+    NftCollectionRecord, NftData, NftRecord, PendingTransactionRecord, TransactionRecord, Unit,
 };
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
@@ -18,14 +18,24 @@ pub struct GetDerivationsResponse {
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
-pub struct GetSyncStatus {}
+pub struct GetSyncStatus {
+    #[serde(default)]
+    pub fingerprint: Option<u32>,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GetSyncStatusResponse {
     pub balance: Amount,
+    pub dust_balance: Amount,
     pub unit: Unit,
     pub synced_coins: u32,
     pub total_coins: u32,
+    pub synced_height: Option<u32>,
+    pub peak_height: Option<u32>,
+    pub synced: bool,
+    /// Whether nothing has been synced for too long, in which case the sync is being restarted.
+    pub stalled: bool,
+    pub derivations: u32,
     pub receive_address: String,
     pub burn_address: String,
 }
@@ -38,6 +48,22 @@ pub struct GetXchCoinsResponse {
     pub coins: Vec<CoinRecord>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct GetFrozenCoins {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetFrozenCoinsResponse {
+    pub coins: Vec<CoinRecord>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct GetBurnAddresses {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetBurnAddressesResponse {
+    pub addresses: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GetCatCoins {
     pub asset_id: String,
@@ -48,6 +74,19 @@ pub struct GetCatCoinsResponse {
     pub coins: Vec<CoinRecord>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetSpendableCoins {
+    pub asset_id: Option<String>,
+    pub amount: Amount,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetSpendableCoinsResponse {
+    pub coins: Vec<CoinRecord>,
+    pub selected: Amount,
+    pub change: Amount,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct GetCats {}
 
@@ -66,6 +105,18 @@ pub struct GetCatResponse {
     pub cat: Option<CatRecord>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct GetKnownCats {
+    /// Replaces the cached token list with the latest one from dexie.space.
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetKnownCatsResponse {
+    pub cats: Vec<KnownCatRecord>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct GetDids {}
 
@@ -105,6 +156,20 @@ pub struct GetNftStatusResponse {
     pub visible_collections: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetNftCount {
+    #[serde(default)]
+    pub fingerprint: Option<u32>,
+    #[serde(default)]
+    pub collection_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct GetNftCountResponse {
+    pub total: u32,
+    pub pending: u32,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct GetNftCollections {
     pub offset: u32,
@@ -127,6 +192,38 @@ pub struct GetNftCollectionResponse {
     pub collection: Option<NftCollectionRecord>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetRarity {
+    pub collection_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetRarityResponse {
+    pub nfts: Vec<NftRarity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NftRarity {
+    pub nft_id: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct GetNftGroups {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetNftGroupsResponse {
+    pub groups: Vec<NftGroup>,
+}
+
+/// Owned NFTs with the same metadata hash, which can be collapsed into a single item.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NftGroup {
+    pub metadata_hash: Option<String>,
+    pub nft_ids: Vec<String>,
+    pub count: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GetNfts {
     pub collection_id: Option<String>,
@@ -158,6 +255,25 @@ pub struct GetNftResponse {
     pub nft: Option<NftRecord>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetNftProvenance {
+    pub nft_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct GetNftProvenanceResponse {
+    pub entries: Vec<NftProvenanceEntry>,
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NftProvenanceEntry {
+    pub coin_id: String,
+    pub owner_address: String,
+    pub created_height: u32,
+    pub spent_height: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct GetNftData {
     pub nft_id: String,
@@ -45,6 +45,8 @@ pub struct ImportKey {
     pub save_secrets: bool,
     #[serde(default = "yes")]
     pub login: bool,
+    #[serde(default)]
+    pub derivation_path: Option<String>,
 }
 
 fn yes() -> bool {
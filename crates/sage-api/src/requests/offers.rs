@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -9,6 +10,24 @@ pub struct MakeOffer {
     pub offered_assets: Assets,
     pub fee: Amount,
     pub expires_at_second: Option<u64>,
+    #[serde(default)]
+    pub starts_at_second: Option<u64>,
+    #[serde(default)]
+    pub coin_ids: Vec<String>,
+    #[serde(default)]
+    pub requested_nft_memos: IndexMap<String, Vec<String>>,
+    #[serde(default = "yes")]
+    pub allow_change: bool,
+    #[serde(default)]
+    pub recipient_puzzle_hash: Option<String>,
+    #[serde(default)]
+    pub offer_memo: Option<String>,
+    #[serde(default)]
+    pub display_units: bool,
+}
+
+fn yes() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -22,6 +41,9 @@ pub struct Assets {
 pub struct CatAmount {
     pub asset_id: String,
     pub amount: Amount,
+    /// The puzzle hash to receive the CAT with, if requested. Defaults to the wallet's own.
+    #[serde(default)]
+    pub puzzle_hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -36,6 +58,14 @@ pub struct TakeOffer {
     pub fee: Amount,
     #[serde(default)]
     pub auto_submit: bool,
+    #[serde(default)]
+    pub allow_royalty_waiver: bool,
+    /// Paid to the maker on top of the requested XCH, in mojos.
+    #[serde(default)]
+    pub overfill_xch: Option<Amount>,
+    /// Paid to the maker on top of the requested amount of each CAT, keyed by asset id.
+    #[serde(default)]
+    pub overfill_cats: IndexMap<String, Amount>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -55,6 +85,30 @@ pub struct ViewOfferResponse {
     pub offer: OfferSummary,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CompareOffers {
+    pub first: String,
+    pub second: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct CompareOffersResponse {
+    pub comparable: bool,
+    pub first_price: Option<f64>,
+    pub second_price: Option<f64>,
+    pub first_fee: Option<Amount>,
+    pub second_fee: Option<Amount>,
+    pub best: Option<BestOffer>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum BestOffer {
+    First,
+    Second,
+    Equal,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ImportOffer {
     pub offer: String,
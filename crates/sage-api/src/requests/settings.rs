@@ -3,7 +3,7 @@ use sage_config::Network;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::PeerRecord;
+use crate::{Amount, PeerRecord};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct GetPeers {}
@@ -73,6 +73,51 @@ pub struct SetDerivationBatchSize {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct SetDerivationBatchSizeResponse {}
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMaxDerivationIndex {
+    pub fingerprint: u32,
+    pub max_derivation_index: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMaxDerivationIndexResponse {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMaxBundleCost {
+    pub fingerprint: u32,
+    pub max_bundle_cost: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMaxBundleCostResponse {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMaxOfferSpends {
+    pub fingerprint: u32,
+    pub max_offer_spends: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMaxOfferSpendsResponse {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct SetDustThreshold {
+    pub fingerprint: u32,
+    pub dust_threshold: Amount,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetDustThresholdResponse {}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMinConfirmations {
+    pub fingerprint: u32,
+    pub min_confirmations: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+pub struct SetMinConfirmationsResponse {}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct GetNetworks {}
 
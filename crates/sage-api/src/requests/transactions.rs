@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::{Amount, CoinSpendJson, SpendBundleJson, TransactionSummary};
+use crate::{Amount, CoinSpendJson, NftOwner, SpendBundleJson, TransactionSummary};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct SendXch {
@@ -67,6 +67,8 @@ pub struct SendCat {
     #[serde(default)]
     pub memos: Vec<String>,
     #[serde(default)]
+    pub display_units: bool,
+    #[serde(default)]
     pub auto_submit: bool,
 }
 
@@ -102,6 +104,8 @@ pub struct NftMint {
 pub struct TransferNfts {
     pub nft_ids: Vec<String>,
     pub address: String,
+    #[serde(default)]
+    pub new_owner: Option<NftOwner>,
     pub fee: Amount,
     #[serde(default)]
     pub auto_submit: bool,
@@ -175,6 +179,26 @@ pub struct SubmitTransaction {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
 pub struct SubmitTransactionResponse {}
 
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EncodeSpendBundle {
+    pub spend_bundle: SpendBundleJson,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct EncodeSpendBundleResponse {
+    pub spend_bundle: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DecodeSpendBundle {
+    pub spend_bundle: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct DecodeSpendBundleResponse {
+    pub spend_bundle: SpendBundleJson,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct TransactionResponse {
     pub summary: TransactionSummary,
@@ -1,11 +1,17 @@
 mod amount;
+mod asset_id;
 mod asset_kind;
 mod error_kind;
 mod key_info;
+mod nft_owner;
+mod spend_bundle;
 mod unit;
 
 pub use amount::*;
+pub use asset_id::*;
 pub use asset_kind::*;
 pub use error_kind::*;
 pub use key_info::*;
+pub use nft_owner::*;
+pub use spend_bundle::*;
 pub use unit::*;
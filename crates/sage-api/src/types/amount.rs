@@ -2,6 +2,7 @@ use std::fmt;
 
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use thiserror::Error;
 
 pub const MAX_JS_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
 
@@ -49,6 +50,87 @@ impl Amount {
             Self::Number(value) => Some(*value as u128),
         }
     }
+
+    pub fn to_decimal_string(&self, decimals: u8) -> Option<String> {
+        Some(format_amount(self.to_u128()?, decimals))
+    }
+}
+
+/// Formats a raw amount (such as mojos) as a decimal string with the given number of decimal places.
+/// Trailing zeros are omitted, so `1500` with 3 decimals is formatted as `1.5`.
+pub fn format_amount(amount: u128, decimals: u8) -> String {
+    let Some(divisor) = 10u128.checked_pow(decimals.into()) else {
+        return format!("0.{amount:0>width$}", width = decimals as usize)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+    };
+
+    let whole = amount / divisor;
+    let fraction = amount % divisor;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{fraction:0>width$}", width = decimals as usize);
+
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+/// An amount given in display units that can't be converted to a raw amount.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AmountError {
+    #[error("Amount cannot be negative: {0}")]
+    Negative(String),
+
+    #[error("Malformed amount: {0}")]
+    Malformed(String),
+
+    #[error("Amount {amount} has more than {decimals} decimal places")]
+    TooManyDecimals { amount: String, decimals: u8 },
+
+    #[error("Amount is too large: {0}")]
+    Overflow(String),
+}
+
+/// Converts a CAT amount in display units to its raw amount, so `1.5` with 3 decimals is `1500`.
+/// Trailing zeros past the number of decimal places are allowed, since they don't lose precision.
+pub fn cat_display_to_raw(amount: &str, decimals: u8) -> Result<u64, AmountError> {
+    if amount.starts_with('-') {
+        return Err(AmountError::Negative(amount.to_string()));
+    }
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    let is_digits = |value: &str| value.bytes().all(|byte| byte.is_ascii_digit());
+
+    if whole.is_empty()
+        || !is_digits(whole)
+        || !is_digits(fraction)
+        || (fraction.is_empty() && amount.ends_with('.'))
+    {
+        return Err(AmountError::Malformed(amount.to_string()));
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.len() > decimals as usize {
+        return Err(AmountError::TooManyDecimals {
+            amount: amount.to_string(),
+            decimals,
+        });
+    }
+
+    // Both parts are known to be digits, so the only way parsing can fail is by overflowing.
+    format!("{whole}{fraction:0<width$}", width = decimals as usize)
+        .parse()
+        .map_err(|_| AmountError::Overflow(amount.to_string()))
+}
+
+/// Converts a raw CAT amount to display units, which is the inverse of [`cat_display_to_raw`].
+pub fn cat_raw_to_display(amount: u64, decimals: u8) -> String {
+    format_amount(amount.into(), decimals)
 }
 
 impl fmt::Display for Amount {
@@ -59,3 +141,109 @@ impl fmt::Display for Amount {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(1_500_000_000_000, 12), "1.5");
+        assert_eq!(format_amount(250, 12), "0.00000000025");
+        assert_eq!(format_amount(1000, 3), "1");
+        assert_eq!(format_amount(1, 0), "1");
+    }
+
+    #[test]
+    fn test_cat_display_to_raw() {
+        assert_eq!(cat_display_to_raw("0", 3), Ok(0));
+        assert_eq!(cat_display_to_raw("0.000", 3), Ok(0));
+        assert_eq!(cat_display_to_raw("1.5", 3), Ok(1500));
+        assert_eq!(cat_display_to_raw("1.500000", 3), Ok(1500));
+        assert_eq!(cat_display_to_raw("0.001", 3), Ok(1));
+        assert_eq!(cat_display_to_raw("42", 0), Ok(42));
+        assert_eq!(cat_display_to_raw("42.0", 0), Ok(42));
+
+        // The largest number of decimals that still fits in a u64.
+        assert_eq!(
+            cat_display_to_raw("1.0000000000000000001", 19),
+            Ok(10_000_000_000_000_000_001)
+        );
+    }
+
+    #[test]
+    fn test_cat_display_to_raw_too_many_decimals() {
+        assert_eq!(
+            cat_display_to_raw("1.0001", 3),
+            Err(AmountError::TooManyDecimals {
+                amount: "1.0001".to_string(),
+                decimals: 3,
+            })
+        );
+        assert_eq!(
+            cat_display_to_raw("0.1", 0),
+            Err(AmountError::TooManyDecimals {
+                amount: "0.1".to_string(),
+                decimals: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_cat_display_to_raw_malformed() {
+        for amount in ["", ".", "1.", ".5", "1.2.3", "1,5", "abc", " 1", "+1"] {
+            assert_eq!(
+                cat_display_to_raw(amount, 3),
+                Err(AmountError::Malformed(amount.to_string()))
+            );
+        }
+
+        assert_eq!(
+            cat_display_to_raw("-1", 3),
+            Err(AmountError::Negative("-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cat_display_to_raw_overflow() {
+        let max = u64::MAX.to_string();
+
+        assert_eq!(cat_display_to_raw(&max, 0), Ok(u64::MAX));
+        assert_eq!(cat_display_to_raw("18446744073709551.615", 3), Ok(u64::MAX));
+        assert_eq!(
+            cat_display_to_raw("18446744073709551.616", 3),
+            Err(AmountError::Overflow("18446744073709551.616".to_string()))
+        );
+        assert_eq!(
+            cat_display_to_raw("18446744073709552", 3),
+            Err(AmountError::Overflow("18446744073709552".to_string()))
+        );
+        assert_eq!(
+            cat_display_to_raw("1", 20),
+            Err(AmountError::Overflow("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_cat_raw_to_display() {
+        assert_eq!(cat_raw_to_display(0, 3), "0");
+        assert_eq!(cat_raw_to_display(0, 0), "0");
+        assert_eq!(cat_raw_to_display(1, 3), "0.001");
+        assert_eq!(cat_raw_to_display(1500, 3), "1.5");
+        assert_eq!(cat_raw_to_display(1000, 3), "1");
+        assert_eq!(cat_raw_to_display(42, 0), "42");
+        assert_eq!(cat_raw_to_display(u64::MAX, 3), "18446744073709551.615");
+        assert_eq!(cat_raw_to_display(u64::MAX, 19), "1.8446744073709551615");
+        assert_eq!(cat_raw_to_display(u64::MAX, 20), "0.18446744073709551615");
+    }
+
+    #[test]
+    fn test_cat_amount_round_trip() {
+        for decimals in [0, 3, 19] {
+            for amount in [0, 1, 999, 1000, 123_456_789, u64::MAX] {
+                let display = cat_raw_to_display(amount, decimals);
+                assert_eq!(cat_display_to_raw(&display, decimals), Ok(amount));
+            }
+        }
+    }
+}
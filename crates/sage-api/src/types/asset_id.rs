@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::{AssetKind, CatRecord, NftRecord};
+
+/// Identifies an asset that can be held or traded, regardless of its kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssetId {
+    Xch,
+    Cat { asset_id: String },
+    Nft { launcher_id: String },
+}
+
+impl AssetId {
+    pub fn cat(asset_id: String) -> Self {
+        Self::Cat { asset_id }
+    }
+
+    pub fn nft(launcher_id: String) -> Self {
+        Self::Nft { launcher_id }
+    }
+}
+
+impl AssetKind {
+    /// Returns `None` for kinds that aren't fungible assets or NFTs.
+    pub fn asset_id(&self) -> Option<AssetId> {
+        match self {
+            Self::Xch | Self::Clawback => Some(AssetId::Xch),
+            Self::Cat { asset_id, .. } => Some(AssetId::cat(asset_id.clone())),
+            Self::Nft { launcher_id, .. } => Some(AssetId::nft(launcher_id.clone())),
+            Self::Unknown | Self::Launcher | Self::Did { .. } => None,
+        }
+    }
+}
+
+impl From<&CatRecord> for AssetId {
+    fn from(record: &CatRecord) -> Self {
+        Self::cat(record.asset_id.clone())
+    }
+}
+
+impl From<&NftRecord> for AssetId {
+    fn from(record: &NftRecord) -> Self {
+        Self::nft(record.launcher_id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_asset_id_serde() -> anyhow::Result<()> {
+        let cases = [
+            (AssetId::Xch, json!({ "type": "xch" })),
+            (
+                AssetId::cat(
+                    "a628c1c2c6fcb74d53746157e438e108eab5c0bb3e5c80ff9b1910b3e4832913".to_string(),
+                ),
+                json!({
+                    "type": "cat",
+                    "asset_id": "a628c1c2c6fcb74d53746157e438e108eab5c0bb3e5c80ff9b1910b3e4832913",
+                }),
+            ),
+            (
+                AssetId::nft(
+                    "nft1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqsel2t2".to_string(),
+                ),
+                json!({
+                    "type": "nft",
+                    "launcher_id": "nft1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqsel2t2",
+                }),
+            ),
+        ];
+
+        for (asset, expected) in cases {
+            let json = serde_json::to_value(&asset)?;
+            assert_eq!(json, expected);
+            assert_eq!(serde_json::from_value::<AssetId>(json)?, asset);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_asset_id_unknown_type() {
+        assert!(serde_json::from_value::<AssetId>(json!({ "type": "did" })).is_err());
+        assert!(serde_json::from_value::<AssetId>(json!({ "type": "cat" })).is_err());
+    }
+}
@@ -6,6 +6,8 @@ use specta::Type;
 pub enum AssetKind {
     Unknown,
     Xch,
+    /// XCH locked in a clawback, which hasn't been claimed or clawed back yet.
+    Clawback,
     Launcher,
     Cat {
         asset_id: String,
@@ -0,0 +1,260 @@
+use chia::{
+    bls::Signature,
+    protocol::{Bytes32, Coin, CoinSpend, Program, SpendBundle},
+    traits::Streamable,
+};
+use thiserror::Error;
+
+use crate::{Amount, CoinJson, CoinSpendJson, SpendBundleJson};
+
+/// A spend bundle that can't be encoded or decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SpendBundleError {
+    #[error("Invalid hex: {0}")]
+    Hex(String),
+
+    #[error("Invalid JSON: {0}")]
+    Json(String),
+
+    #[error("Invalid {field}: {value}")]
+    InvalidField { field: &'static str, value: String },
+
+    #[error("Malformed spend bundle: {0}")]
+    Malformed(String),
+}
+
+/// Encodes the spend bundle as hex in its serialized form, which is what full nodes
+/// and most other tools accept.
+pub fn encode_spend_bundle(spend_bundle: &SpendBundle) -> Result<String, SpendBundleError> {
+    let bytes = spend_bundle
+        .to_bytes()
+        .map_err(|error| SpendBundleError::Malformed(error.to_string()))?;
+
+    Ok(format!("0x{}", hex::encode(bytes)))
+}
+
+/// Decodes a spend bundle from either hex or the JSON format used by the full node RPC. The whole input must be consumed,
+/// and every coin spend and the aggregated signature must be well formed.
+pub fn decode_spend_bundle(input: &str) -> Result<SpendBundle, SpendBundleError> {
+    let input = input.trim();
+
+    if input.starts_with('{') {
+        let json: SpendBundleJson = serde_json::from_str(input)
+            .map_err(|error| SpendBundleError::Json(error.to_string()))?;
+
+        return json.try_into();
+    }
+
+    let bytes = decode_hex(input)?;
+
+    SpendBundle::from_bytes(&bytes).map_err(|error| SpendBundleError::Malformed(error.to_string()))
+}
+
+impl From<&SpendBundle> for SpendBundleJson {
+    fn from(spend_bundle: &SpendBundle) -> Self {
+        Self {
+            coin_spends: spend_bundle.coin_spends.iter().map(Into::into).collect(),
+            aggregated_signature: format!(
+                "0x{}",
+                hex::encode(spend_bundle.aggregated_signature.to_bytes())
+            ),
+        }
+    }
+}
+
+impl From<&CoinSpend> for CoinSpendJson {
+    fn from(coin_spend: &CoinSpend) -> Self {
+        Self {
+            coin: (&coin_spend.coin).into(),
+            puzzle_reveal: hex::encode(&coin_spend.puzzle_reveal),
+            solution: hex::encode(&coin_spend.solution),
+        }
+    }
+}
+
+impl From<&Coin> for CoinJson {
+    fn from(coin: &Coin) -> Self {
+        Self {
+            parent_coin_info: format!("0x{}", hex::encode(coin.parent_coin_info)),
+            puzzle_hash: format!("0x{}", hex::encode(coin.puzzle_hash)),
+            amount: Amount::u64(coin.amount),
+        }
+    }
+}
+
+impl TryFrom<SpendBundleJson> for SpendBundle {
+    type Error = SpendBundleError;
+
+    fn try_from(spend_bundle: SpendBundleJson) -> Result<Self, Self::Error> {
+        let signature: [u8; 96] = decode_hex(&spend_bundle.aggregated_signature)?
+            .try_into()
+            .map_err(|_| invalid("aggregated signature", &spend_bundle.aggregated_signature))?;
+
+        let aggregated_signature = Signature::from_bytes(&signature)
+            .map_err(|_| invalid("aggregated signature", &spend_bundle.aggregated_signature))?;
+
+        Ok(Self {
+            coin_spends: spend_bundle
+                .coin_spends
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            aggregated_signature,
+        })
+    }
+}
+
+impl TryFrom<CoinSpendJson> for CoinSpend {
+    type Error = SpendBundleError;
+
+    fn try_from(coin_spend: CoinSpendJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            coin: coin_spend.coin.try_into()?,
+            puzzle_reveal: Program::from(decode_hex(&coin_spend.puzzle_reveal)?),
+            solution: Program::from(decode_hex(&coin_spend.solution)?),
+        })
+    }
+}
+
+impl TryFrom<CoinJson> for Coin {
+    type Error = SpendBundleError;
+
+    fn try_from(coin: CoinJson) -> Result<Self, Self::Error> {
+        Ok(Self {
+            parent_coin_info: decode_bytes32("parent coin info", &coin.parent_coin_info)?,
+            puzzle_hash: decode_bytes32("puzzle hash", &coin.puzzle_hash)?,
+            amount: coin
+                .amount
+                .to_u64()
+                .ok_or_else(|| invalid("coin amount", &coin.amount.to_string()))?,
+        })
+    }
+}
+
+fn decode_hex(input: &str) -> Result<Vec<u8>, SpendBundleError> {
+    hex::decode(input.strip_prefix("0x").unwrap_or(input))
+        .map_err(|_| SpendBundleError::Hex(input.to_string()))
+}
+
+fn decode_bytes32(field: &'static str, input: &str) -> Result<Bytes32, SpendBundleError> {
+    let bytes: [u8; 32] = decode_hex(input)?
+        .try_into()
+        .map_err(|_| invalid(field, input))?;
+
+    Ok(bytes.into())
+}
+
+fn invalid(field: &'static str, value: &str) -> SpendBundleError {
+    SpendBundleError::InvalidField {
+        field,
+        value: value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::bls::{sign, SecretKey};
+
+    use super::*;
+
+    fn spend_bundle() -> SpendBundle {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), u64::MAX);
+        let secret_key = SecretKey::from_seed(&[3; 32]);
+
+        SpendBundle::new(
+            vec![
+                CoinSpend::new(coin, Program::from(vec![1]), Program::from(vec![0x80])),
+                CoinSpend::new(
+                    Coin::new(coin.coin_id(), Bytes32::new([4; 32]), 0),
+                    Program::from(vec![0xff, 0x01, 0x80]),
+                    Program::from(vec![0x80]),
+                ),
+            ],
+            sign(&secret_key, b"message"),
+        )
+    }
+
+    #[test]
+    fn test_hex_round_trip() -> anyhow::Result<()> {
+        let spend_bundle = spend_bundle();
+
+        let encoded = encode_spend_bundle(&spend_bundle)?;
+        assert!(encoded.starts_with("0x"));
+        assert_eq!(decode_spend_bundle(&encoded)?, spend_bundle);
+
+        // The prefix and surrounding whitespace are optional.
+        assert_eq!(
+            decode_spend_bundle(&format!("  {}\n", &encoded[2..]))?,
+            spend_bundle
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_round_trip() -> anyhow::Result<()> {
+        let spend_bundle = spend_bundle();
+
+        let json = serde_json::to_string(&SpendBundleJson::from(&spend_bundle))?;
+        assert_eq!(decode_spend_bundle(&json)?, spend_bundle);
+
+        let json: SpendBundleJson = serde_json::from_str(&json)?;
+        assert_eq!(SpendBundle::try_from(json)?, spend_bundle);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_invalid_hex() {
+        assert!(matches!(
+            decode_spend_bundle("0xnot hex"),
+            Err(SpendBundleError::Hex(..))
+        ));
+    }
+
+    #[test]
+    fn test_decode_trailing_bytes() -> anyhow::Result<()> {
+        let encoded = encode_spend_bundle(&spend_bundle())?;
+
+        assert!(matches!(
+            decode_spend_bundle(&format!("{encoded}00")),
+            Err(SpendBundleError::Malformed(..))
+        ));
+        assert!(matches!(
+            decode_spend_bundle(&encoded[..encoded.len() - 2]),
+            Err(SpendBundleError::Malformed(..))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_invalid_json() {
+        assert!(matches!(
+            decode_spend_bundle("{\"coin_spends\": []}"),
+            Err(SpendBundleError::Json(..))
+        ));
+
+        let mut json = SpendBundleJson::from(&spend_bundle());
+        json.coin_spends[0].coin.puzzle_hash = "0x1234".to_string();
+
+        assert_eq!(
+            SpendBundle::try_from(json),
+            Err(SpendBundleError::InvalidField {
+                field: "puzzle hash",
+                value: "0x1234".to_string(),
+            })
+        );
+
+        let mut json = SpendBundleJson::from(&spend_bundle());
+        json.aggregated_signature = format!("0x{}", "00".repeat(96));
+
+        assert!(matches!(
+            SpendBundle::try_from(json),
+            Err(SpendBundleError::InvalidField {
+                field: "aggregated signature",
+                ..
+            })
+        ));
+    }
+}
@@ -2,6 +2,9 @@ use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+/// The number of decimal places that CAT amounts are displayed with.
+pub const CAT_DECIMALS: u8 = 3;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Type)]
 pub struct Unit {
     pub ticker: String,
@@ -12,7 +15,7 @@ impl Unit {
     pub fn cat(ticker: String) -> Self {
         Self {
             ticker,
-            decimals: 3,
+            decimals: CAT_DECIMALS,
         }
     }
 }
@@ -75,16 +75,24 @@ routes!(
     get_derivations await: GetDerivations = "/get_derivations",
     get_xch_coins await: GetXchCoins = "/get_xch_coins",
     get_cat_coins await: GetCatCoins = "/get_cat_coins",
+    get_frozen_coins await: GetFrozenCoins = "/get_frozen_coins",
+    get_burn_addresses await: GetBurnAddresses = "/get_burn_addresses",
+    get_spendable_coins await: GetSpendableCoins = "/get_spendable_coins",
     get_cats await: GetCats = "/get_cats",
     get_cat await: GetCat = "/get_cat",
+    get_known_cats await: GetKnownCats = "/get_known_cats",
     get_dids await: GetDids = "/get_dids",
     get_pending_transactions await: GetPendingTransactions = "/get_pending_transactions",
     get_transactions await: GetTransactions = "/get_transactions",
     get_nft_status await: GetNftStatus = "/get_nft_status",
+    get_nft_count await: GetNftCount = "/get_nft_count",
     get_nft_collections await: GetNftCollections = "/get_nft_collections",
     get_nft_collection await: GetNftCollection = "/get_nft_collection",
+    get_rarity await: GetRarity = "/get_rarity",
+    get_nft_groups await: GetNftGroups = "/get_nft_groups",
     get_nfts await: GetNfts = "/get_nfts",
     get_nft await: GetNft = "/get_nft",
+    get_nft_provenance await: GetNftProvenance = "/get_nft_provenance",
     get_nft_data await: GetNftData = "/get_nft_data",
 
     send_xch await: SendXch = "/send_xch",
@@ -103,10 +111,13 @@ routes!(
     sign_coin_spends await: SignCoinSpends = "/sign_coin_spends",
     view_coin_spends await: ViewCoinSpends = "/view_coin_spends",
     submit_transaction await: SubmitTransaction = "/submit_transaction",
+    encode_spend_bundle: EncodeSpendBundle = "/encode_spend_bundle",
+    decode_spend_bundle: DecodeSpendBundle = "/decode_spend_bundle",
 
     make_offer await: MakeOffer = "/make_offer",
     take_offer await: TakeOffer = "/take_offer",
     view_offer await: ViewOffer = "/view_offer",
+    compare_offers: CompareOffers = "/compare_offers",
     import_offer await: ImportOffer = "/import_offer",
     get_offers await: GetOffers = "/get_offers",
     get_offer await: GetOffer = "/get_offer",
@@ -120,12 +131,22 @@ routes!(
     set_network_id await: SetNetworkId = "/set_network_id",
     set_derive_automatically: SetDeriveAutomatically = "/set_derive_automatically",
     set_derivation_batch_size: SetDerivationBatchSize = "/set_derivation_batch_size",
+    set_max_derivation_index await: SetMaxDerivationIndex = "/set_max_derivation_index",
+    set_max_bundle_cost await: SetMaxBundleCost = "/set_max_bundle_cost",
+    set_max_offer_spends await: SetMaxOfferSpends = "/set_max_offer_spends",
+    set_dust_threshold await: SetDustThreshold = "/set_dust_threshold",
+    set_min_confirmations await: SetMinConfirmations = "/set_min_confirmations",
     get_networks: GetNetworks = "/get_networks",
 
     remove_cat await: RemoveCat = "/remove_cat",
     update_cat await: UpdateCat = "/update_cat",
     update_did await: UpdateDid = "/update_did",
     update_nft await: UpdateNft = "/update_nft",
+    freeze_coins await: FreezeCoins = "/freeze_coins",
+    unfreeze_coins await: UnfreezeCoins = "/unfreeze_coins",
+    set_coin_label await: SetCoinLabel = "/set_coin_label",
+    add_burn_address await: AddBurnAddress = "/add_burn_address",
+    remove_burn_address await: RemoveBurnAddress = "/remove_burn_address",
 );
 
 async fn start_rpc(path: PathBuf) -> Result<()> {
@@ -6,6 +6,11 @@ use specta::Type;
 pub struct AppConfig {
     pub log_level: String,
     pub active_fingerprint: Option<u32>,
+    pub default_cat_decimals: u8,
+    /// A directory where fetched NFT metadata is cached, so it doesn't need to be fetched again.
+    pub metadata_cache_dir: Option<String>,
+    /// Fetches the metadata of newly discovered NFTs eagerly, such as during the first sync.
+    pub prefetch_nft_metadata: bool,
 }
 
 impl Default for AppConfig {
@@ -13,6 +18,9 @@ impl Default for AppConfig {
         Self {
             log_level: "INFO".to_string(),
             active_fingerprint: None,
+            default_cat_decimals: 3,
+            metadata_cache_dir: None,
+            prefetch_nft_metadata: false,
         }
     }
 }
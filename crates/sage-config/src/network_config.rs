@@ -7,6 +7,7 @@ pub struct NetworkConfig {
     pub network_id: String,
     pub target_peers: u32,
     pub discover_peers: bool,
+    pub sync_retries: u32,
 }
 
 impl Default for NetworkConfig {
@@ -15,6 +16,7 @@ impl Default for NetworkConfig {
             network_id: "mainnet".to_string(),
             target_peers: 5,
             discover_peers: true,
+            sync_retries: 3,
         }
     }
 }
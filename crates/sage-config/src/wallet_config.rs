@@ -1,3 +1,4 @@
+use chia_wallet_sdk::MAINNET_CONSTANTS;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -7,6 +8,20 @@ pub struct WalletConfig {
     pub name: String,
     pub derive_automatically: bool,
     pub derivation_batch_size: u32,
+    /// Keys aren't derived at or beyond this index, so the limit has to be raised to derive more.
+    pub max_derivation_index: u32,
+    pub max_bundle_cost: u64,
+    pub max_offer_spends: u32,
+    /// Coins worth less than this many mojos are ignored by coin selection and the balance.
+    pub dust_threshold: u64,
+    /// How many blocks below the peak a coin must be created at before coin selection uses it.
+    pub min_confirmations: u32,
+    /// Overrides the standard path that keys are derived along, such as `m/12381/8444/2`.
+    pub derivation_path: Option<String>,
+    /// Makes sends and offers wait for each other, so that they can't select the same coins.
+    pub serialize_transactions: bool,
+    /// Spends the oldest coins first, rather than the ones that best fit the amount.
+    pub spend_oldest_coins_first: bool,
 }
 
 impl Default for WalletConfig {
@@ -15,6 +30,16 @@ impl Default for WalletConfig {
             name: "Unnamed Wallet".to_string(),
             derive_automatically: true,
             derivation_batch_size: 500,
+            max_derivation_index: 10_000,
+            // The mempool rejects transactions that use more than half of the block cost limit.
+            max_bundle_cost: MAINNET_CONSTANTS.max_block_cost_clvm / 2,
+            // Each NFT spend costs around 80 million, so this leaves room for the taker's spends.
+            max_offer_spends: 50,
+            dust_threshold: 0,
+            min_confirmations: 0,
+            derivation_path: None,
+            serialize_transactions: false,
+            spend_oldest_coins_first: false,
         }
     }
 }
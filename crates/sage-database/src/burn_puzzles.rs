@@ -0,0 +1,86 @@
+use chia::protocol::Bytes32;
+use sqlx::SqliteExecutor;
+
+use crate::{to_bytes32, Database, DatabaseTx, Result};
+
+impl Database {
+    pub async fn insert_burn_puzzle_hash(&self, puzzle_hash: Bytes32) -> Result<()> {
+        insert_burn_puzzle_hash(&self.pool, puzzle_hash).await?;
+        self.changed();
+        Ok(())
+    }
+
+    pub async fn remove_burn_puzzle_hash(&self, puzzle_hash: Bytes32) -> Result<()> {
+        remove_burn_puzzle_hash(&self.pool, puzzle_hash).await?;
+        self.changed();
+        Ok(())
+    }
+
+    pub async fn burn_puzzle_hashes(&self) -> Result<Vec<Bytes32>> {
+        burn_puzzle_hashes(&self.pool).await
+    }
+
+    pub async fn is_burn_puzzle_hash(&self, puzzle_hash: Bytes32) -> Result<bool> {
+        is_burn_puzzle_hash(&self.pool, puzzle_hash).await
+    }
+}
+
+impl<'a> DatabaseTx<'a> {
+    pub async fn is_burn_puzzle_hash(&mut self, puzzle_hash: Bytes32) -> Result<bool> {
+        is_burn_puzzle_hash(&mut *self.tx, puzzle_hash).await
+    }
+}
+
+async fn insert_burn_puzzle_hash(
+    conn: impl SqliteExecutor<'_>,
+    puzzle_hash: Bytes32,
+) -> Result<()> {
+    let puzzle_hash = puzzle_hash.as_ref();
+
+    sqlx::query!(
+        "INSERT OR IGNORE INTO `burn_puzzle_hashes` (`puzzle_hash`) VALUES (?)",
+        puzzle_hash
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn remove_burn_puzzle_hash(
+    conn: impl SqliteExecutor<'_>,
+    puzzle_hash: Bytes32,
+) -> Result<()> {
+    let puzzle_hash = puzzle_hash.as_ref();
+
+    sqlx::query!(
+        "DELETE FROM `burn_puzzle_hashes` WHERE `puzzle_hash` = ?",
+        puzzle_hash
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn burn_puzzle_hashes(conn: impl SqliteExecutor<'_>) -> Result<Vec<Bytes32>> {
+    sqlx::query!("SELECT `puzzle_hash` FROM `burn_puzzle_hashes`")
+        .fetch_all(conn)
+        .await?
+        .into_iter()
+        .map(|row| to_bytes32(&row.puzzle_hash))
+        .collect()
+}
+
+async fn is_burn_puzzle_hash(conn: impl SqliteExecutor<'_>, puzzle_hash: Bytes32) -> Result<bool> {
+    let puzzle_hash = puzzle_hash.as_ref();
+
+    Ok(sqlx::query!(
+        "SELECT COUNT(*) AS `count` FROM `burn_puzzle_hashes` WHERE `puzzle_hash` = ?",
+        puzzle_hash
+    )
+    .fetch_one(conn)
+    .await?
+    .count
+        > 0)
+}
@@ -1,11 +1,12 @@
-use std::cmp::Reverse;
+use std::{cmp::Reverse, collections::HashMap};
 
 use chia::protocol::{Bytes32, CoinState};
-use sqlx::SqliteExecutor;
+use sqlx::{QueryBuilder, SqliteExecutor};
 
 use crate::{
-    into_row, to_bytes32, CoinKind, CoinStateRow, CoinStateSql, Database, DatabaseTx, IntoRow,
-    Result,
+    into_row, to_bytes32, CoinAssetRow, CoinAssetSql, CoinKind, CoinStateRow, CoinStateSql,
+    Database, DatabaseTx, IntoRow, LabeledCoinStateRow, LabeledCoinStateSql, Result,
+    SettlementCoinRow, SettlementCoinSql,
 };
 
 impl Database {
@@ -29,8 +30,20 @@ impl Database {
         unspent_cat_coin_ids(&self.pool).await
     }
 
+    /// The ids of unspent coins that were created at or above the given height.
+    pub async fn unspent_coin_ids_since(&self, height: u32) -> Result<Vec<Bytes32>> {
+        unspent_coin_ids_since(&self.pool, height).await
+    }
+
+    /// Unspent settlement coins that aren't being spent by a pending transaction.
+    pub async fn unclaimed_settlement_coins(&self) -> Result<Vec<SettlementCoinRow>> {
+        unclaimed_settlement_coins(&self.pool).await
+    }
+
     pub async fn delete_coin_state(&self, coin_id: Bytes32) -> Result<()> {
-        delete_coin_state(&self.pool, coin_id).await
+        delete_coin_state(&self.pool, coin_id).await?;
+        self.changed();
+        Ok(())
     }
 
     pub async fn total_coin_count(&self) -> Result<u32> {
@@ -47,11 +60,18 @@ impl Database {
         hint: Option<Bytes32>,
         kind: CoinKind,
     ) -> Result<()> {
-        sync_coin(&self.pool, coin_id, hint, kind).await
+        sync_coin(&self.pool, coin_id, hint, kind).await?;
+        self.changed();
+        Ok(())
+    }
+
+    /// Whether the coin exists and isn't locked by a transaction, offer, or being frozen.
+    pub async fn is_coin_unlocked(&self, coin_id: Bytes32) -> Result<bool> {
+        is_coin_unlocked(&self.pool, coin_id).await
     }
 
-    pub async fn is_coin_locked(&self, coin_id: Bytes32) -> Result<bool> {
-        is_coin_locked(&self.pool, coin_id).await
+    pub async fn is_p2_coin(&self, coin_id: Bytes32) -> Result<Option<bool>> {
+        is_p2_coin(&self.pool, coin_id).await
     }
 
     pub async fn get_block_heights(&self) -> Result<Vec<u32>> {
@@ -68,6 +88,211 @@ impl Database {
     pub async fn get_coin_states_by_spent_height(&self, height: u32) -> Result<Vec<CoinStateRow>> {
         get_coin_states_by_spent_height(&self.pool, height).await
     }
+
+    /// The coins created between the heights (inclusive), ordered by height, along with the asset
+    /// that each coin belongs to, and the transaction or completed offer that spent its parent.
+    pub async fn created_coin_assets(&self, from: u32, to: u32) -> Result<Vec<CoinAssetRow>> {
+        created_coin_assets(&self.pool, from, to).await
+    }
+
+    /// The coins spent between the heights (inclusive), ordered by height, along with the asset
+    /// that each coin belongs to, and the transaction or completed offer that spent it.
+    pub async fn spent_coin_assets(&self, from: u32, to: u32) -> Result<Vec<CoinAssetRow>> {
+        spent_coin_assets(&self.pool, from, to).await
+    }
+
+    pub async fn get_coin_states_spent_by_transaction(
+        &self,
+        transaction_id: Bytes32,
+    ) -> Result<Vec<CoinStateRow>> {
+        get_coin_states_spent_by_transaction(&self.pool, transaction_id).await
+    }
+
+    pub async fn get_coin_states_created_by_transaction(
+        &self,
+        transaction_id: Bytes32,
+    ) -> Result<Vec<CoinStateRow>> {
+        get_coin_states_created_by_transaction(&self.pool, transaction_id).await
+    }
+
+    pub async fn freeze_coin(&self, coin_id: Bytes32) -> Result<()> {
+        freeze_coin(&self.pool, coin_id).await?;
+        self.changed();
+        Ok(())
+    }
+
+    pub async fn unfreeze_coin(&self, coin_id: Bytes32) -> Result<()> {
+        unfreeze_coin(&self.pool, coin_id).await?;
+        self.changed();
+        Ok(())
+    }
+
+    pub async fn frozen_coins(&self) -> Result<Vec<LabeledCoinStateRow>> {
+        frozen_coins(&self.pool).await
+    }
+
+    pub async fn set_coin_label(&self, coin_id: Bytes32, label: Option<String>) -> Result<()> {
+        set_coin_label(&self.pool, coin_id, label).await
+    }
+
+    pub async fn coin_label(&self, coin_id: Bytes32) -> Result<Option<String>> {
+        coin_label(&self.pool, coin_id).await
+    }
+
+    /// The labels of the given coins, leaving out those that aren't labeled.
+    pub async fn coin_labels(&self, coin_ids: &[Bytes32]) -> Result<HashMap<Bytes32, String>> {
+        coin_labels(&self.pool, coin_ids).await
+    }
+}
+
+async fn freeze_coin(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<()> {
+    let coin_id = coin_id.as_ref();
+
+    sqlx::query!(
+        "INSERT OR IGNORE INTO `frozen_coins` (`coin_id`) VALUES (?)",
+        coin_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn unfreeze_coin(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<()> {
+    let coin_id = coin_id.as_ref();
+
+    sqlx::query!("DELETE FROM `frozen_coins` WHERE `coin_id` = ?", coin_id)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn frozen_coins(conn: impl SqliteExecutor<'_>) -> Result<Vec<LabeledCoinStateRow>> {
+    let rows = sqlx::query_as!(
+        LabeledCoinStateSql,
+        "
+        SELECT
+            `coin_states`.`parent_coin_id`, `coin_states`.`puzzle_hash`, `coin_states`.`amount`,
+            `created_height`, `spent_height`, `coin_states`.`transaction_id`, `kind`, `label`
+        FROM `frozen_coins`
+        INNER JOIN `coin_states` ON `coin_states`.`coin_id` = `frozen_coins`.`coin_id`
+        LEFT JOIN `coin_labels` ON `coin_labels`.`coin_id` = `frozen_coins`.`coin_id`
+        ORDER BY `created_height` ASC, `coin_states`.`coin_id` ASC
+        "
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
+async fn set_coin_label(
+    conn: impl SqliteExecutor<'_>,
+    coin_id: Bytes32,
+    label: Option<String>,
+) -> Result<()> {
+    let coin_id = coin_id.as_ref();
+
+    if let Some(label) = label {
+        sqlx::query!(
+            "REPLACE INTO `coin_labels` (`coin_id`, `label`) VALUES (?, ?)",
+            coin_id,
+            label
+        )
+        .execute(conn)
+        .await?;
+    } else {
+        sqlx::query!("DELETE FROM `coin_labels` WHERE `coin_id` = ?", coin_id)
+            .execute(conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+async fn coin_label(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<Option<String>> {
+    let coin_id = coin_id.as_ref();
+
+    Ok(sqlx::query_scalar!(
+        "SELECT `label` FROM `coin_labels` WHERE `coin_id` = ?",
+        coin_id
+    )
+    .fetch_optional(conn)
+    .await?)
+}
+
+async fn coin_labels(
+    conn: impl SqliteExecutor<'_>,
+    coin_ids: &[Bytes32],
+) -> Result<HashMap<Bytes32, String>> {
+    if coin_ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    // The number of coins varies, so the query can't be checked at compile time.
+    let mut query =
+        QueryBuilder::new("SELECT `coin_id`, `label` FROM `coin_labels` WHERE `coin_id` IN (");
+
+    let mut separated = query.separated(", ");
+
+    for coin_id in coin_ids {
+        separated.push_bind(coin_id.to_vec());
+    }
+
+    separated.push_unseparated(")");
+
+    let rows: Vec<(Vec<u8>, String)> = query.build_query_as().fetch_all(conn).await?;
+
+    rows.into_iter()
+        .map(|(coin_id, label)| Ok((to_bytes32(&coin_id)?, label)))
+        .collect()
+}
+
+async fn get_coin_states_spent_by_transaction(
+    conn: impl SqliteExecutor<'_>,
+    transaction_id: Bytes32,
+) -> Result<Vec<CoinStateRow>> {
+    let transaction_id = transaction_id.as_ref();
+
+    let rows = sqlx::query_as!(
+        CoinStateSql,
+        "
+        SELECT
+            `coin_states`.`parent_coin_id`, `coin_states`.`puzzle_hash`, `coin_states`.`amount`,
+            `created_height`, `spent_height`, `coin_states`.`transaction_id`, `kind`
+        FROM `transaction_spends`
+        INNER JOIN `coin_states` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
+        WHERE `transaction_spends`.`transaction_id` = ?
+        ORDER BY `transaction_spends`.`index` ASC
+        ",
+        transaction_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
+async fn get_coin_states_created_by_transaction(
+    conn: impl SqliteExecutor<'_>,
+    transaction_id: Bytes32,
+) -> Result<Vec<CoinStateRow>> {
+    let transaction_id = transaction_id.as_ref();
+
+    let rows = sqlx::query_as!(
+        CoinStateSql,
+        "
+        SELECT `parent_coin_id`, `puzzle_hash`, `amount`, `created_height`, `spent_height`, `transaction_id`, `kind`
+        FROM `coin_states` INDEXED BY `coin_transaction`
+        WHERE `transaction_id` = ?
+        ",
+        transaction_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
 }
 
 impl<'a> DatabaseTx<'a> {
@@ -373,6 +598,53 @@ async fn unspent_cat_coin_ids(conn: impl SqliteExecutor<'_>) -> Result<Vec<Bytes
         .collect()
 }
 
+async fn unspent_coin_ids_since(
+    conn: impl SqliteExecutor<'_>,
+    height: u32,
+) -> Result<Vec<Bytes32>> {
+    let rows = sqlx::query!(
+        "
+        SELECT `coin_id`
+        FROM `coin_states` INDEXED BY `coin_created`
+        WHERE `created_height` >= ?
+        AND `spent_height` IS NULL
+        ",
+        height
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| to_bytes32(&row.coin_id))
+        .collect()
+}
+
+async fn unclaimed_settlement_coins(
+    conn: impl SqliteExecutor<'_>,
+) -> Result<Vec<SettlementCoinRow>> {
+    let kind = CoinKind::Settlement as u32;
+
+    let rows = sqlx::query_as!(
+        SettlementCoinSql,
+        "
+        SELECT
+            `coin_states`.`parent_coin_id`, `coin_states`.`puzzle_hash`, `coin_states`.`amount`,
+            `coin_states`.`hint`, `coin_states`.`created_height`
+        FROM `coin_states`
+        LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
+        WHERE `coin_states`.`kind` = ?
+        AND `coin_states`.`spent_height` IS NULL
+        AND `transaction_spends`.`transaction_id` IS NULL
+        ORDER BY `coin_states`.`created_height` DESC
+        ",
+        kind
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
 async fn is_p2_coin(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<Option<bool>> {
     let coin_id = coin_id.as_ref();
 
@@ -390,7 +662,7 @@ async fn is_p2_coin(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<O
     Ok(row.map(|row| row.kind == 1))
 }
 
-async fn is_coin_locked(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<bool> {
+async fn is_coin_unlocked(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Result<bool> {
     let coin_id = coin_id.as_ref();
 
     let row = sqlx::query!(
@@ -399,10 +671,12 @@ async fn is_coin_locked(conn: impl SqliteExecutor<'_>, coin_id: Bytes32) -> Resu
         FROM `coin_states`
         LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
         LEFT JOIN `offered_coins` ON `coin_states`.`coin_id` = `offered_coins`.`coin_id`
+        LEFT JOIN `frozen_coins` ON `coin_states`.`coin_id` = `frozen_coins`.`coin_id`
         WHERE `coin_states`.`coin_id` = ?
-        AND `offer_id` IS NULL
+        AND `offered_coins`.`offer_id` IS NULL
         AND `coin_states`.`transaction_id` IS NULL
         AND `transaction_spends`.`transaction_id` IS NULL
+        AND `frozen_coins`.`coin_id` IS NULL
         ",
         coin_id
     )
@@ -460,6 +734,104 @@ async fn get_coin_states_by_created_height(
     rows.into_iter().map(into_row).collect()
 }
 
+async fn created_coin_assets(
+    conn: impl SqliteExecutor<'_>,
+    from: u32,
+    to: u32,
+) -> Result<Vec<CoinAssetRow>> {
+    let rows = sqlx::query_as!(
+        CoinAssetSql,
+        "
+        SELECT
+            `coin_states`.`created_height` AS `height!`,
+            `coin_states`.`coin_id`,
+            `coin_states`.`parent_coin_id`,
+            `coin_states`.`amount`,
+            `coin_states`.`kind`,
+            COALESCE(`cat_coins`.`asset_id`, `nft_coins`.`launcher_id`, `did_coins`.`launcher_id`) AS `asset_id?: Vec<u8>`,
+            `cats`.`decimals`,
+            CASE
+                WHEN `clawback_coins`.`sender_puzzle_hash` IN (SELECT `p2_puzzle_hash` FROM `derivations`)
+                THEN `clawback_coins`.`recipient_puzzle_hash`
+                ELSE `clawback_coins`.`sender_puzzle_hash`
+            END AS `counterparty?: Vec<u8>`,
+            COALESCE(`transaction_history`.`transaction_id`, `completed_offers`.`offer_id`) AS `transaction_id?: Vec<u8>`,
+            COALESCE(`transaction_history`.`fee`, `completed_offers`.`fee`) AS `fee?: Vec<u8>`
+        FROM `coin_states` INDEXED BY `coin_created`
+        LEFT JOIN `cat_coins` ON `cat_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `cats` ON `cats`.`asset_id` = `cat_coins`.`asset_id`
+        LEFT JOIN `nft_coins` ON `nft_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `did_coins` ON `did_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `clawback_coins` ON `clawback_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `transaction_history_coins` ON `transaction_history_coins`.`coin_id` = `coin_states`.`parent_coin_id`
+        LEFT JOIN `transaction_history` ON `transaction_history`.`transaction_id` = `transaction_history_coins`.`transaction_id`
+        LEFT JOIN (
+            SELECT `offered_coins`.`coin_id`, `offers`.`offer_id`, `offers`.`fee`
+            FROM `offered_coins`
+            INNER JOIN `offers` ON `offers`.`offer_id` = `offered_coins`.`offer_id`
+            WHERE `offers`.`status` = 1
+        ) AS `completed_offers` ON `completed_offers`.`coin_id` = `coin_states`.`parent_coin_id`
+        WHERE `coin_states`.`created_height` BETWEEN ? AND ?
+        ORDER BY `coin_states`.`created_height` ASC
+        ",
+        from,
+        to
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
+async fn spent_coin_assets(
+    conn: impl SqliteExecutor<'_>,
+    from: u32,
+    to: u32,
+) -> Result<Vec<CoinAssetRow>> {
+    let rows = sqlx::query_as!(
+        CoinAssetSql,
+        "
+        SELECT
+            `coin_states`.`spent_height` AS `height!`,
+            `coin_states`.`coin_id`,
+            `coin_states`.`parent_coin_id`,
+            `coin_states`.`amount`,
+            `coin_states`.`kind`,
+            COALESCE(`cat_coins`.`asset_id`, `nft_coins`.`launcher_id`, `did_coins`.`launcher_id`) AS `asset_id?: Vec<u8>`,
+            `cats`.`decimals`,
+            CASE
+                WHEN `clawback_coins`.`sender_puzzle_hash` IN (SELECT `p2_puzzle_hash` FROM `derivations`)
+                THEN `clawback_coins`.`recipient_puzzle_hash`
+                ELSE `clawback_coins`.`sender_puzzle_hash`
+            END AS `counterparty?: Vec<u8>`,
+            COALESCE(`transaction_history`.`transaction_id`, `completed_offers`.`offer_id`) AS `transaction_id?: Vec<u8>`,
+            COALESCE(`transaction_history`.`fee`, `completed_offers`.`fee`) AS `fee?: Vec<u8>`
+        FROM `coin_states` INDEXED BY `coin_spent`
+        LEFT JOIN `cat_coins` ON `cat_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `cats` ON `cats`.`asset_id` = `cat_coins`.`asset_id`
+        LEFT JOIN `nft_coins` ON `nft_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `did_coins` ON `did_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `clawback_coins` ON `clawback_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `transaction_history_coins` ON `transaction_history_coins`.`coin_id` = `coin_states`.`coin_id`
+        LEFT JOIN `transaction_history` ON `transaction_history`.`transaction_id` = `transaction_history_coins`.`transaction_id`
+        LEFT JOIN (
+            SELECT `offered_coins`.`coin_id`, `offers`.`offer_id`, `offers`.`fee`
+            FROM `offered_coins`
+            INNER JOIN `offers` ON `offers`.`offer_id` = `offered_coins`.`offer_id`
+            WHERE `offers`.`status` = 1
+        ) AS `completed_offers` ON `completed_offers`.`coin_id` = `coin_states`.`coin_id`
+        WHERE `coin_states`.`spent_height` BETWEEN ? AND ?
+        ORDER BY `coin_states`.`spent_height` ASC
+        ",
+        from,
+        to
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
 async fn get_coin_states_by_spent_height(
     conn: impl SqliteExecutor<'_>,
     height: u32,
@@ -478,3 +850,70 @@ async fn get_coin_states_by_spent_height(
 
     rows.into_iter().map(into_row).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::Coin;
+    use sqlx::{migrate, SqlitePool};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_coin_labels() -> anyhow::Result<()> {
+        let pool = SqlitePool::connect("file:coinlabelsdb?mode=memory&cache=shared").await?;
+        migrate!("../../migrations").run(&pool).await?;
+        let db = Database::new(pool);
+
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1000);
+        let other = Coin::new(Bytes32::new([3; 32]), Bytes32::new([2; 32]), 500);
+
+        let mut tx = db.tx().await?;
+        for coin in [coin, other] {
+            tx.insert_coin_state(CoinState::new(coin, None, Some(10)), true, None)
+                .await?;
+            tx.sync_coin(coin.coin_id(), None, CoinKind::Xch).await?;
+        }
+        tx.commit().await?;
+
+        assert_eq!(db.coin_label(coin.coin_id()).await?, None);
+
+        db.set_coin_label(coin.coin_id(), Some("Savings".to_string()))
+            .await?;
+        assert_eq!(
+            db.coin_label(coin.coin_id()).await?.as_deref(),
+            Some("Savings")
+        );
+
+        // Setting a label again replaces it.
+        db.set_coin_label(coin.coin_id(), Some("Rent".to_string()))
+            .await?;
+        assert_eq!(
+            db.coin_label(coin.coin_id()).await?.as_deref(),
+            Some("Rent")
+        );
+
+        // The labels are joined onto the listed coins, and unlabeled coins are left out of the batch.
+        let labels: HashMap<Bytes32, Option<String>> = db
+            .p2_coin_states()
+            .await?
+            .into_iter()
+            .map(|row| (row.row.coin_state.coin.coin_id(), row.label))
+            .collect();
+        assert_eq!(labels[&coin.coin_id()].as_deref(), Some("Rent"));
+        assert_eq!(labels[&other.coin_id()], None);
+
+        let labels = db.coin_labels(&[coin.coin_id(), other.coin_id()]).await?;
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[&coin.coin_id()], "Rent");
+
+        db.freeze_coin(coin.coin_id()).await?;
+        assert_eq!(db.frozen_coins().await?[0].label.as_deref(), Some("Rent"));
+
+        // Clearing the label removes it.
+        db.set_coin_label(coin.coin_id(), None).await?;
+        assert_eq!(db.coin_label(coin.coin_id()).await?, None);
+        assert!(db.coin_labels(&[coin.coin_id()]).await?.is_empty());
+
+        Ok(())
+    }
+}
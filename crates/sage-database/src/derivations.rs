@@ -18,6 +18,14 @@ impl Database {
         p2_puzzle_hashes(&self.pool).await
     }
 
+    pub async fn subscribed_p2_puzzle_hashes(&self) -> Result<Vec<Bytes32>> {
+        subscribed_p2_puzzle_hashes(&self.pool, true).await
+    }
+
+    pub async fn unsubscribed_p2_puzzle_hashes(&self) -> Result<Vec<Bytes32>> {
+        subscribed_p2_puzzle_hashes(&self.pool, false).await
+    }
+
     pub async fn synthetic_key(&self, p2_puzzle_hash: Bytes32) -> Result<PublicKey> {
         synthetic_key(&self.pool, p2_puzzle_hash).await
     }
@@ -29,6 +37,10 @@ impl Database {
     pub async fn is_p2_puzzle_hash(&self, p2_puzzle_hash: Bytes32) -> Result<bool> {
         is_p2_puzzle_hash(&self.pool, p2_puzzle_hash).await
     }
+
+    pub async fn derivation_index(&self, hardened: bool) -> Result<u32> {
+        derivation_index(&self.pool, hardened).await
+    }
 }
 
 impl<'a> DatabaseTx<'a> {
@@ -53,6 +65,22 @@ impl<'a> DatabaseTx<'a> {
         derivation_index(&mut *self.tx, hardened).await
     }
 
+    pub async fn mark_subscribed(&mut self, p2_puzzle_hash: Bytes32) -> Result<()> {
+        mark_subscribed(&mut *self.tx, p2_puzzle_hash).await
+    }
+
+    pub async fn has_derivation(&mut self, index: u32, hardened: bool) -> Result<bool> {
+        has_derivation(&mut *self.tx, index, hardened).await
+    }
+
+    pub async fn derivation_count(&mut self, hardened: bool) -> Result<u32> {
+        derivation_count(&mut *self.tx, hardened).await
+    }
+
+    pub async fn derivation_indices(&mut self, hardened: bool) -> Result<Vec<u32>> {
+        derivation_indices(&mut *self.tx, hardened).await
+    }
+
     pub async fn max_used_derivation_index(&mut self, hardened: bool) -> Result<Option<u32>> {
         max_used_derivation_index(&mut *self.tx, hardened).await
     }
@@ -111,6 +139,54 @@ async fn derivation_index(conn: impl SqliteExecutor<'_>, hardened: bool) -> Resu
     .try_into()?)
 }
 
+async fn has_derivation(conn: impl SqliteExecutor<'_>, index: u32, hardened: bool) -> Result<bool> {
+    Ok(sqlx::query!(
+        "
+        SELECT COUNT(*) AS `count`
+        FROM `derivations` INDEXED BY `derivation_index`
+        WHERE `index` = ? AND `hardened` = ?
+        ",
+        index,
+        hardened
+    )
+    .fetch_one(conn)
+    .await?
+    .count
+        > 0)
+}
+
+async fn derivation_count(conn: impl SqliteExecutor<'_>, hardened: bool) -> Result<u32> {
+    Ok(sqlx::query!(
+        "
+        SELECT COUNT(*) AS `count`
+        FROM `derivations`
+        WHERE `hardened` = ?
+        ",
+        hardened
+    )
+    .fetch_one(conn)
+    .await?
+    .count
+    .try_into()?)
+}
+
+async fn derivation_indices(conn: impl SqliteExecutor<'_>, hardened: bool) -> Result<Vec<u32>> {
+    sqlx::query!(
+        "
+        SELECT `index`
+        FROM `derivations` INDEXED BY `derivation_index`
+        WHERE `hardened` = ?
+        ORDER BY `index` ASC
+        ",
+        hardened
+    )
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| Ok(row.index.try_into()?))
+    .collect()
+}
+
 async fn max_used_derivation_index(
     conn: impl SqliteExecutor<'_>,
     hardened: bool,
@@ -148,6 +224,39 @@ async fn p2_puzzle_hashes(conn: impl SqliteExecutor<'_>) -> Result<Vec<Bytes32>>
         .collect::<Result<_>>()
 }
 
+async fn subscribed_p2_puzzle_hashes(
+    conn: impl SqliteExecutor<'_>,
+    subscribed: bool,
+) -> Result<Vec<Bytes32>> {
+    let rows = sqlx::query!(
+        "
+        SELECT `p2_puzzle_hash`
+        FROM `derivations` INDEXED BY `derivation_subscribed`
+        WHERE `subscribed` = ?
+        ORDER BY `index` ASC, `hardened` ASC
+        ",
+        subscribed
+    )
+    .fetch_all(conn)
+    .await?;
+    rows.into_iter()
+        .map(|row| to_bytes32(&row.p2_puzzle_hash))
+        .collect::<Result<_>>()
+}
+
+async fn mark_subscribed(conn: impl SqliteExecutor<'_>, p2_puzzle_hash: Bytes32) -> Result<()> {
+    let p2_puzzle_hash = p2_puzzle_hash.as_ref();
+    sqlx::query!(
+        "
+        UPDATE `derivations` SET `subscribed` = TRUE WHERE `p2_puzzle_hash` = ?
+        ",
+        p2_puzzle_hash
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
 async fn unhardened_derivations(
     conn: impl SqliteExecutor<'_>,
     limit: u32,
@@ -156,7 +265,8 @@ async fn unhardened_derivations(
     sqlx::query_as!(
         DerivationSql,
         "
-        SELECT * FROM `derivations`
+        SELECT `p2_puzzle_hash`, `index`, `hardened`, `synthetic_key`
+        FROM `derivations`
         WHERE `hardened` = 0
         ORDER BY `index` ASC
         LIMIT ? OFFSET ?
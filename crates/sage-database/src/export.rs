@@ -0,0 +1,190 @@
+use std::io::Write;
+
+use chrono::{DateTime, SecondsFormat};
+use futures_util::TryStreamExt;
+
+use crate::{extrapolate_time, to_bytes32, to_u64, CoinKind, Database, Result};
+
+const COINS_CSV_HEADER: &str =
+    "coin_id,puzzle_hash,amount,asset_kind,asset_id,created_height,created_time,spent_height,spent_time";
+
+impl Database {
+    /// Writes every coin state in the database to the writer as CSV, one row at a time so that
+    /// large wallets don't need to be loaded into memory. The asset id is the CAT asset id, or the
+    /// launcher id of an NFT or DID, and is left empty for other coins. Pending coins have no heights.
+    ///
+    /// Each height is followed by an approximate ISO 8601 time, extrapolated from the closest
+    /// timed peak at or below it, or from the earliest one if there is none. The times are left
+    /// empty if no peak has been timed yet.
+    ///
+    /// Returns the number of coins that were written, not including the header.
+    pub async fn export_coins_csv(&self, mut writer: impl Write) -> Result<u64> {
+        let earliest_peak = self.earliest_timed_peak().await?;
+
+        writeln!(writer, "{COINS_CSV_HEADER}")?;
+
+        let mut rows = sqlx::query!(
+            "
+            SELECT
+                `coin_states`.`coin_id`, `puzzle_hash`, `amount`, `kind`,
+                `created_height`, `spent_height`,
+                COALESCE(
+                    `cat_coins`.`asset_id`, `nft_coins`.`launcher_id`, `did_coins`.`launcher_id`
+                ) AS `asset_id`,
+                `created_peak`.`height` AS `created_peak_height`,
+                `created_peak`.`timestamp` AS `created_peak_timestamp`,
+                `spent_peak`.`height` AS `spent_peak_height`,
+                `spent_peak`.`timestamp` AS `spent_peak_timestamp`
+            FROM `coin_states` INDEXED BY `coin_created`
+            LEFT JOIN `cat_coins` ON `cat_coins`.`coin_id` = `coin_states`.`coin_id`
+            LEFT JOIN `nft_coins` ON `nft_coins`.`coin_id` = `coin_states`.`coin_id`
+            LEFT JOIN `did_coins` ON `did_coins`.`coin_id` = `coin_states`.`coin_id`
+            LEFT JOIN `peaks` AS `created_peak` ON `created_peak`.`height` = (
+                SELECT MAX(`height`) FROM `peaks`
+                WHERE `timestamp` IS NOT NULL AND `height` <= `created_height`
+            )
+            LEFT JOIN `peaks` AS `spent_peak` ON `spent_peak`.`height` = (
+                SELECT MAX(`height`) FROM `peaks`
+                WHERE `timestamp` IS NOT NULL AND `height` <= `spent_height`
+            )
+            ORDER BY `created_height` ASC, `coin_states`.`coin_id` ASC
+            "
+        )
+        .fetch(&self.pool);
+
+        let mut count = 0;
+
+        while let Some(row) = rows.try_next().await? {
+            let kind = match CoinKind::from_i64(row.kind) {
+                CoinKind::Unknown => "unknown",
+                CoinKind::Xch => "xch",
+                CoinKind::Cat => "cat",
+                CoinKind::Nft => "nft",
+                CoinKind::Did => "did",
+                CoinKind::Clawback => "clawback",
+                CoinKind::Settlement => "settlement",
+            };
+
+            let asset_id = row
+                .asset_id
+                .as_deref()
+                .map(to_bytes32)
+                .transpose()?
+                .map_or_else(String::new, |asset_id| asset_id.to_string());
+
+            let created_height = row.created_height.map(u32::try_from).transpose()?;
+            let spent_height = row.spent_height.map(u32::try_from).transpose()?;
+
+            let created_peak =
+                timed_peak(row.created_peak_height, row.created_peak_timestamp)?.or(earliest_peak);
+            let spent_peak =
+                timed_peak(row.spent_peak_height, row.spent_peak_timestamp)?.or(earliest_peak);
+
+            writeln!(
+                writer,
+                "{},{},{},{kind},{asset_id},{},{},{},{}",
+                to_bytes32(&row.coin_id)?,
+                to_bytes32(&row.puzzle_hash)?,
+                to_u64(&row.amount)?,
+                created_height.map_or_else(String::new, |height| height.to_string()),
+                format_time(created_height, created_peak),
+                spent_height.map_or_else(String::new, |height| height.to_string()),
+                format_time(spent_height, spent_peak),
+            )?;
+
+            count += 1;
+        }
+
+        writer.flush()?;
+
+        Ok(count)
+    }
+}
+
+fn timed_peak(height: Option<i64>, timestamp: Option<i64>) -> Result<Option<(u32, u64)>> {
+    let (Some(height), Some(timestamp)) = (height, timestamp) else {
+        return Ok(None);
+    };
+
+    Ok(Some((height.try_into()?, timestamp.try_into()?)))
+}
+
+/// Formats the estimated time at the height as an ISO 8601 UTC time, or an empty string if
+/// there's no height or timed peak to estimate it from.
+fn format_time(height: Option<u32>, peak: Option<(u32, u64)>) -> String {
+    let (Some(height), Some(peak)) = (height, peak) else {
+        return String::new();
+    };
+
+    i64::try_from(extrapolate_time(peak, height))
+        .ok()
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::{Bytes32, Coin, CoinState};
+    use sqlx::{migrate, SqlitePool};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export_coins_csv() -> anyhow::Result<()> {
+        let pool = SqlitePool::connect("file:exportcoinsdb?mode=memory&cache=shared").await?;
+        migrate!("../../migrations").run(&pool).await?;
+        let db = Database::new(pool);
+
+        let puzzle_hash = Bytes32::new([1; 32]);
+        let spent = Coin::new(Bytes32::new([2; 32]), puzzle_hash, 1000);
+        let unspent = Coin::new(Bytes32::new([3; 32]), puzzle_hash, 750);
+        let pending = Coin::new(Bytes32::new([4; 32]), puzzle_hash, 250);
+
+        let mut tx = db.tx().await?;
+        for coin_state in [
+            CoinState::new(spent, Some(20), Some(5)),
+            CoinState::new(unspent, None, Some(20)),
+            CoinState::new(pending, None, None),
+        ] {
+            tx.insert_coin_state(coin_state, true, None).await?;
+            tx.sync_coin(coin_state.coin.coin_id(), None, CoinKind::Xch)
+                .await?;
+        }
+        tx.commit().await?;
+
+        db.insert_peak(10, Bytes32::default(), 1_000_000).await?;
+
+        let mut csv = Vec::new();
+        let count = db.export_coins_csv(&mut csv).await?;
+        let csv = String::from_utf8(csv)?;
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(count, 3);
+        assert_eq!(lines[0], COINS_CSV_HEADER);
+
+        // Pending coins come first, since they have no created height.
+        assert_eq!(
+            lines[1],
+            format!("{},{puzzle_hash},250,xch,,,,,", pending.coin_id())
+        );
+
+        // Heights before the earliest timed peak are extrapolated backwards from it.
+        assert_eq!(
+            lines[2],
+            format!(
+                "{},{puzzle_hash},1000,xch,,5,1970-01-12T13:45:07Z,20,1970-01-12T13:49:47Z",
+                spent.coin_id()
+            )
+        );
+        assert_eq!(
+            lines[3],
+            format!(
+                "{},{puzzle_hash},750,xch,,20,1970-01-12T13:49:47Z,,",
+                unspent.coin_id()
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,378 @@
+use std::path::Path;
+
+use sqlx::{Connection, SqliteConnection};
+
+use crate::{Database, DatabaseError, Result};
+
+/// The number of rows merged into each table by [`Database::import_from`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportedRows {
+    pub derivations: u64,
+    pub coin_states: u64,
+    pub cat_coins: u64,
+    pub did_coins: u64,
+    pub nft_coins: u64,
+    pub clawback_coins: u64,
+    pub frozen_coins: u64,
+    pub coin_labels: u64,
+    pub cats: u64,
+    pub collections: u64,
+    pub dids: u64,
+    pub nfts: u64,
+    pub nft_data: u64,
+    pub nft_uris: u64,
+    pub offers: u64,
+    pub offered_coins: u64,
+    pub offer_xch: u64,
+    pub offer_cats: u64,
+    pub offer_nfts: u64,
+}
+
+// The other database is attached to the connection, so these queries can't be checked at compile time.
+const IMPORT_QUERIES: [&str; 19] = [
+    "
+    INSERT OR IGNORE INTO `main`.`derivations` (`p2_puzzle_hash`, `index`, `hardened`, `synthetic_key`)
+    SELECT `p2_puzzle_hash`, `index`, `hardened`, `synthetic_key` FROM `import`.`derivations`
+    ",
+    "
+    INSERT INTO `main`.`coin_states` (
+        `coin_id`, `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`,
+        `created_height`, `hint`, `synced`, `transaction_id`, `kind`
+    )
+    SELECT
+        `coin_id`, `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`,
+        `created_height`, `hint`, `synced`, NULL, `kind`
+    FROM `import`.`coin_states` WHERE `transaction_id` IS NULL
+    ON CONFLICT (`coin_id`) DO UPDATE SET
+        `spent_height` = COALESCE(`coin_states`.`spent_height`, `excluded`.`spent_height`),
+        `created_height` = COALESCE(`coin_states`.`created_height`, `excluded`.`created_height`),
+        `hint` = COALESCE(`coin_states`.`hint`, `excluded`.`hint`),
+        `kind` = IIF(`coin_states`.`synced`, `coin_states`.`kind`, `excluded`.`kind`),
+        `synced` = `coin_states`.`synced` OR `excluded`.`synced`
+    WHERE `coin_states`.`transaction_id` IS NULL AND (
+        (`coin_states`.`spent_height` IS NULL AND `excluded`.`spent_height` IS NOT NULL)
+        OR (`coin_states`.`created_height` IS NULL AND `excluded`.`created_height` IS NOT NULL)
+        OR (NOT `coin_states`.`synced` AND `excluded`.`synced`)
+    )
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`cat_coins` (
+        `coin_id`, `parent_parent_coin_id`, `parent_inner_puzzle_hash`, `parent_amount`,
+        `p2_puzzle_hash`, `asset_id`, `memo`
+    )
+    SELECT
+        `coin_id`, `parent_parent_coin_id`, `parent_inner_puzzle_hash`, `parent_amount`,
+        `p2_puzzle_hash`, `asset_id`, `memo`
+    FROM `import`.`cat_coins`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`coin_states`)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`did_coins` SELECT * FROM `import`.`did_coins`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`coin_states`)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`nft_coins` SELECT * FROM `import`.`nft_coins`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`coin_states`)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`clawback_coins` SELECT * FROM `import`.`clawback_coins`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`coin_states`)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`frozen_coins` SELECT * FROM `import`.`frozen_coins`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`coin_states`)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`coin_labels` (`coin_id`, `label`)
+    SELECT `coin_id`, `label` FROM `import`.`coin_labels`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`coin_states`)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`cats` (
+        `asset_id`, `name`, `ticker`, `visible`, `icon`, `description`, `fetched`, `decimals`
+    )
+    SELECT `asset_id`, `name`, `ticker`, `visible`, `icon`, `description`, `fetched`, `decimals`
+    FROM `import`.`cats`
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`collections` (
+        `collection_id`, `did_id`, `metadata_collection_id`, `name`, `visible`, `icon`, `banner`
+    )
+    SELECT `collection_id`, `did_id`, `metadata_collection_id`, `name`, `visible`, `icon`, `banner`
+    FROM `import`.`collections`
+    ",
+    "
+    INSERT INTO `main`.`dids` (
+        `launcher_id`, `coin_id`, `name`, `visible`, `is_owned`, `created_height`
+    )
+    SELECT `launcher_id`, `coin_id`, `name`, `visible`, `is_owned`, `created_height`
+    FROM `import`.`dids`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`did_coins`)
+    ON CONFLICT (`launcher_id`) DO UPDATE SET
+        `coin_id` = `excluded`.`coin_id`,
+        `is_owned` = `excluded`.`is_owned`,
+        `created_height` = `excluded`.`created_height`
+    WHERE `excluded`.`created_height` > COALESCE(`dids`.`created_height`, -1)
+    ",
+    "
+    INSERT INTO `main`.`nfts` (
+        `launcher_id`, `coin_id`, `collection_id`, `minter_did`, `owner_did`, `visible`,
+        `sensitive_content`, `name`, `is_owned`, `created_height`, `metadata_hash`, `is_burned`
+    )
+    SELECT
+        `launcher_id`, `coin_id`, `collection_id`, `minter_did`, `owner_did`, `visible`,
+        `sensitive_content`, `name`, `is_owned`, `created_height`, `metadata_hash`, `is_burned`
+    FROM `import`.`nfts`
+    WHERE `coin_id` IN (SELECT `coin_id` FROM `main`.`nft_coins`)
+    ON CONFLICT (`launcher_id`) DO UPDATE SET
+        `coin_id` = `excluded`.`coin_id`,
+        `owner_did` = `excluded`.`owner_did`,
+        `is_owned` = `excluded`.`is_owned`,
+        `created_height` = `excluded`.`created_height`,
+        `metadata_hash` = `excluded`.`metadata_hash`,
+        `is_burned` = `excluded`.`is_burned`
+    WHERE `excluded`.`created_height` > COALESCE(`nfts`.`created_height`, -1)
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`nft_data` SELECT * FROM `import`.`nft_data`
+    ",
+    "
+    INSERT INTO `main`.`nft_uris` SELECT * FROM `import`.`nft_uris` WHERE TRUE
+    ON CONFLICT (`uri`, `hash`) DO UPDATE SET `checked` = TRUE
+    WHERE NOT `nft_uris`.`checked` AND `excluded`.`checked`
+    ",
+    "
+    INSERT INTO `main`.`offers` SELECT * FROM `import`.`offers` WHERE TRUE
+    ON CONFLICT (`offer_id`) DO UPDATE SET `status` = `excluded`.`status`
+    WHERE `offers`.`status` = 0 AND `excluded`.`status` != 0
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`offered_coins` SELECT * FROM `import`.`offered_coins`
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`offer_xch` SELECT * FROM `import`.`offer_xch`
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`offer_cats` SELECT * FROM `import`.`offer_cats`
+    ",
+    "
+    INSERT OR IGNORE INTO `main`.`offer_nfts` SELECT * FROM `import`.`offer_nfts`
+    ",
+];
+
+impl Database {
+    /// Merges the wallet data from another Sage database file into this one. Both databases must
+    /// belong to the same wallet and be migrated to the same schema version.
+    ///
+    /// Conflicting rows are resolved in favor of the most recent state, so a coin that is spent in
+    /// either database will be spent after the import, and an NFT or DID will point to whichever
+    /// coin was created at the higher height. Pending transactions aren't imported.
+    pub async fn import_from(&self, other: &Path) -> Result<ImportedRows> {
+        let mut conn = self.pool.acquire().await?;
+
+        sqlx::query("ATTACH DATABASE ? AS `import`")
+            .bind(other.to_string_lossy().into_owned())
+            .execute(&mut *conn)
+            .await?;
+
+        let result = import_attached(&mut conn).await;
+
+        sqlx::query("DETACH DATABASE `import`")
+            .execute(&mut *conn)
+            .await?;
+
+        self.changed();
+
+        result
+    }
+}
+
+async fn import_attached(conn: &mut SqliteConnection) -> Result<ImportedRows> {
+    let schema_versions: (Option<i64>, Option<i64>) = sqlx::query_as(
+        "
+        SELECT
+            (SELECT MAX(`version`) FROM `main`.`_sqlx_migrations`),
+            (SELECT MAX(`version`) FROM `import`.`_sqlx_migrations`)
+        ",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if schema_versions.0 != schema_versions.1 {
+        return Err(DatabaseError::SchemaMismatch);
+    }
+
+    // The first unhardened synthetic key is derived from the master public key,
+    // so it's only the same if both databases belong to the same fingerprint.
+    let synthetic_keys: (Option<Vec<u8>>, Option<Vec<u8>>) = sqlx::query_as(
+        "
+        SELECT
+            (SELECT `synthetic_key` FROM `main`.`derivations` WHERE `index` = 0 AND `hardened` = 0),
+            (SELECT `synthetic_key` FROM `import`.`derivations` WHERE `index` = 0 AND `hardened` = 0)
+        ",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if synthetic_keys.0.is_none() || synthetic_keys.0 != synthetic_keys.1 {
+        return Err(DatabaseError::WalletMismatch);
+    }
+
+    let mut tx = conn.begin().await?;
+    let mut counts = [0; IMPORT_QUERIES.len()];
+
+    for (count, query) in counts.iter_mut().zip(IMPORT_QUERIES) {
+        *count = sqlx::query(query).execute(&mut *tx).await?.rows_affected();
+    }
+
+    tx.commit().await?;
+
+    // The counts are in the same order as the queries.
+    let mut counts = counts.into_iter();
+    let mut next = || counts.next().unwrap_or_default();
+
+    Ok(ImportedRows {
+        derivations: next(),
+        coin_states: next(),
+        cat_coins: next(),
+        did_coins: next(),
+        nft_coins: next(),
+        clawback_coins: next(),
+        frozen_coins: next(),
+        coin_labels: next(),
+        cats: next(),
+        collections: next(),
+        dids: next(),
+        nfts: next(),
+        nft_data: next(),
+        nft_uris: next(),
+        offers: next(),
+        offered_coins: next(),
+        offer_xch: next(),
+        offer_cats: next(),
+        offer_nfts: next(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chia::{
+        bls::PublicKey,
+        protocol::{Bytes, Bytes32, Coin, CoinState},
+        puzzles::LineageProof,
+    };
+    use sqlx::{migrate, sqlite::SqliteConnectOptions, SqlitePool};
+
+    use crate::{CatRow, CollectionRow};
+
+    use super::*;
+
+    async fn open(name: &str) -> anyhow::Result<(Database, PathBuf)> {
+        let path =
+            std::env::temp_dir().join(format!("sage-import-{}-{name}.sqlite", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let pool = SqlitePool::connect_with(
+            SqliteConnectOptions::new()
+                .filename(&path)
+                .create_if_missing(true),
+        )
+        .await?;
+        migrate!("../../migrations").run(&pool).await?;
+
+        let db = Database::new(pool);
+
+        let mut tx = db.tx().await?;
+        tx.insert_derivation(Bytes32::default(), 0, false, PublicKey::default())
+            .await?;
+        tx.commit().await?;
+
+        Ok((db, path))
+    }
+
+    #[tokio::test]
+    async fn test_import_round_trip() -> anyhow::Result<()> {
+        let (db, db_path) = open("main").await?;
+        let (other, other_path) = open("other").await?;
+
+        let asset_id = Bytes32::new([1; 32]);
+        let coin = Coin::new(Bytes32::new([2; 32]), Bytes32::new([3; 32]), 1000);
+        let memo = Bytes::new(b"memo".to_vec());
+
+        let collection = CollectionRow {
+            collection_id: Bytes32::new([4; 32]),
+            did_id: Bytes32::new([5; 32]),
+            metadata_collection_id: "collection".to_string(),
+            visible: true,
+            name: Some("Collection".to_string()),
+            icon: None,
+            banner: Some("banner".to_string()),
+        };
+
+        let mut tx = other.tx().await?;
+        tx.insert_coin_state(CoinState::new(coin, None, Some(10)), true, None)
+            .await?;
+        tx.insert_cat(CatRow {
+            asset_id,
+            name: Some("Token".to_string()),
+            ticker: Some("TKN".to_string()),
+            description: None,
+            icon: None,
+            visible: true,
+            fetched: true,
+            decimals: Some(3),
+        })
+        .await?;
+        tx.insert_cat_coin(
+            coin.coin_id(),
+            LineageProof {
+                parent_parent_coin_info: Bytes32::default(),
+                parent_inner_puzzle_hash: Bytes32::default(),
+                parent_amount: 1000,
+            },
+            Bytes32::new([6; 32]),
+            asset_id,
+            Some(memo.clone()),
+        )
+        .await?;
+        tx.insert_collection(collection.clone()).await?;
+        tx.commit().await?;
+
+        other
+            .set_coin_label(coin.coin_id(), Some("Label".to_string()))
+            .await?;
+
+        let imported = db.import_from(&other_path).await?;
+
+        assert_eq!(imported.coin_states, 1);
+        assert_eq!(imported.cat_coins, 1);
+        assert_eq!(imported.coin_labels, 1);
+        assert_eq!(imported.cats, 1);
+        assert_eq!(imported.collections, 1);
+
+        assert!(db.coin_state(coin.coin_id()).await?.is_some());
+        assert_eq!(
+            db.cat_balances_by_memo(asset_id).await?,
+            vec![(Some(memo), 1000)]
+        );
+        assert_eq!(
+            db.cat(asset_id).await?.and_then(|cat| cat.decimals),
+            Some(3)
+        );
+        assert_eq!(
+            db.coin_label(coin.coin_id()).await?,
+            Some("Label".to_string())
+        );
+        assert_eq!(db.collection(collection.collection_id).await?, collection);
+
+        // Importing the same database again doesn't change anything.
+        assert_eq!(db.import_from(&other_path).await?, ImportedRows::default());
+
+        std::fs::remove_file(db_path).ok();
+        std::fs::remove_file(other_path).ok();
+
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
+mod burn_puzzles;
 mod coin_states;
 mod derivations;
+mod export;
+mod import;
 mod offers;
 mod peaks;
 mod primitives;
@@ -7,13 +10,21 @@ mod rows;
 mod transactions;
 mod utils;
 
+pub use import::*;
+pub use peaks::{extrapolate_height, extrapolate_time};
 pub use primitives::*;
 pub use rows::*;
 pub use transactions::*;
 
 pub(crate) use utils::*;
 
-use std::num::TryFromIntError;
+use std::{
+    num::TryFromIntError,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 use sqlx::{Sqlite, SqlitePool, Transaction};
 use thiserror::Error;
@@ -21,31 +32,49 @@ use thiserror::Error;
 #[derive(Debug, Clone)]
 pub struct Database {
     pub(crate) pool: SqlitePool,
+    changes: Arc<AtomicU64>,
 }
 
 impl Database {
     pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            changes: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     pub async fn tx(&self) -> Result<DatabaseTx<'_>> {
         let tx = self.pool.begin().await?;
-        Ok(DatabaseTx::new(tx))
+        Ok(DatabaseTx::new(tx, self.changes.clone()))
+    }
+
+    /// The number of changes that have been committed through this database and its clones.
+    /// It's incremented by every committed transaction, and by the writes outside of a transaction
+    /// that affect which coins are spendable, so that anything read before it changed can be discarded.
+    pub fn change_count(&self) -> u64 {
+        self.changes.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn changed(&self) {
+        self.changes.fetch_add(1, Ordering::SeqCst);
     }
 }
 
 #[derive(Debug)]
 pub struct DatabaseTx<'a> {
     pub(crate) tx: Transaction<'a, Sqlite>,
+    changes: Arc<AtomicU64>,
 }
 
 impl<'a> DatabaseTx<'a> {
-    pub fn new(tx: Transaction<'a, Sqlite>) -> Self {
-        Self { tx }
+    pub(crate) fn new(tx: Transaction<'a, Sqlite>, changes: Arc<AtomicU64>) -> Self {
+        Self { tx, changes }
     }
 
     pub async fn commit(self) -> Result<()> {
-        Ok(self.tx.commit().await?)
+        self.tx.commit().await?;
+        self.changes.fetch_add(1, Ordering::SeqCst);
+        Ok(())
     }
 
     pub async fn rollback(self) -> Result<()> {
@@ -58,6 +87,9 @@ pub enum DatabaseError {
     #[error("SQLx error: {0}")]
     Sqlx(#[from] sqlx::Error),
 
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("Precision lost during cast")]
     PrecisionLost(#[from] TryFromIntError),
 
@@ -72,6 +104,15 @@ pub enum DatabaseError {
 
     #[error("Invalid offer status {0}")]
     InvalidOfferStatus(i64),
+
+    #[error("Invalid clawback timelock")]
+    InvalidTimelock,
+
+    #[error("The databases have different schema versions")]
+    SchemaMismatch,
+
+    #[error("The databases belong to different wallets")]
+    WalletMismatch,
 }
 
 pub(crate) type Result<T> = std::result::Result<T, DatabaseError>;
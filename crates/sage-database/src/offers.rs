@@ -18,7 +18,9 @@ impl Database {
     }
 
     pub async fn delete_offer(&self, offer_id: Bytes32) -> Result<()> {
-        delete_offer(&self.pool, offer_id).await
+        delete_offer(&self.pool, offer_id).await?;
+        self.changed();
+        Ok(())
     }
 
     pub async fn offer_xch(&self, offer_id: Bytes32) -> Result<Vec<OfferXchRow>> {
@@ -38,7 +40,9 @@ impl Database {
     }
 
     pub async fn update_offer_status(&self, offer_id: Bytes32, status: OfferStatus) -> Result<()> {
-        update_offer_status(&self.pool, offer_id, status).await
+        update_offer_status(&self.pool, offer_id, status).await?;
+        self.changed();
+        Ok(())
     }
 
     pub async fn offer_coin_ids(&self, offer_id: Bytes32) -> Result<Vec<Bytes32>> {
@@ -70,6 +74,22 @@ impl DatabaseTx<'_> {
     pub async fn insert_offer_cat(&mut self, row: OfferCatRow) -> Result<()> {
         insert_offer_cat(&mut *self.tx, row).await
     }
+
+    pub async fn get_offer(&mut self, offer_id: Bytes32) -> Result<Option<OfferRow>> {
+        get_offer(&mut *self.tx, offer_id).await
+    }
+
+    pub async fn update_offer_status(
+        &mut self,
+        offer_id: Bytes32,
+        status: OfferStatus,
+    ) -> Result<()> {
+        update_offer_status(&mut *self.tx, offer_id, status).await
+    }
+
+    pub async fn coin_offer_id(&mut self, coin_id: Bytes32) -> Result<Option<Bytes32>> {
+        coin_offer_id(&mut *self.tx, coin_id).await
+    }
 }
 
 async fn insert_offer(conn: impl SqliteExecutor<'_>, row: OfferRow) -> Result<()> {
@@ -215,7 +235,7 @@ async fn insert_offer_cat(conn: impl SqliteExecutor<'_>, row: OfferCatRow) -> Re
 async fn active_offers(conn: impl SqliteExecutor<'_>) -> Result<Vec<OfferRow>> {
     sqlx::query_as!(
         OfferSql,
-        "SELECT * FROM `offers` INDEXED BY `offer_status` WHERE `status` = 0"
+        "SELECT * FROM `offers` INDEXED BY `offer_status` WHERE `status` IN (0, 4)"
     )
     .fetch_all(conn)
     .await?
@@ -3,14 +3,65 @@ use sqlx::SqliteExecutor;
 
 use crate::{to_bytes32, Database, DatabaseTx, Result};
 
+/// The average time between blocks, in milliseconds, from the target of 4608 blocks per day.
+/// Peak heights count every block, not only transaction blocks, which are about 52 seconds apart.
+const AVERAGE_BLOCK_TIME_MS: u64 = 18_750;
+
+/// Extrapolates the height at the unix timestamp, in seconds, from a timed peak using the
+/// average block time.
+pub fn extrapolate_height((height, at): (u32, u64), timestamp: u64) -> u32 {
+    let blocks = |elapsed: u64| {
+        u32::try_from(u128::from(elapsed) * 1000 / u128::from(AVERAGE_BLOCK_TIME_MS))
+            .unwrap_or(u32::MAX)
+    };
+
+    if timestamp >= at {
+        height.saturating_add(blocks(timestamp - at))
+    } else {
+        height.saturating_sub(blocks(at - timestamp))
+    }
+}
+
+/// Extrapolates the unix timestamp, in seconds, at the height from a timed peak using the
+/// average block time.
+pub fn extrapolate_time((height, at): (u32, u64), target: u32) -> u64 {
+    let elapsed = |blocks: u32| u64::from(blocks) * AVERAGE_BLOCK_TIME_MS / 1000;
+
+    if target >= height {
+        at.saturating_add(elapsed(target - height))
+    } else {
+        at.saturating_sub(elapsed(height - target))
+    }
+}
+
 impl Database {
-    pub async fn insert_peak(&self, height: u32, header_hash: Bytes32) -> Result<()> {
-        insert_peak(&self.pool, height, header_hash).await
+    pub async fn insert_peak(
+        &self,
+        height: u32,
+        header_hash: Bytes32,
+        timestamp: u64,
+    ) -> Result<()> {
+        insert_peak(&self.pool, height, header_hash, timestamp).await
     }
 
     pub async fn latest_peak(&self) -> Result<Option<(u32, Bytes32)>> {
         latest_peak(&self.pool).await
     }
+
+    /// The height and timestamp of the latest peak observed at or before the timestamp.
+    pub async fn timed_peak_before(&self, timestamp: u64) -> Result<Option<(u32, u64)>> {
+        timed_peak_before(&self.pool, timestamp).await
+    }
+
+    /// The height and timestamp of the highest peak at or below the height.
+    pub async fn timed_peak_below(&self, height: u32) -> Result<Option<(u32, u64)>> {
+        timed_peak_below(&self.pool, height).await
+    }
+
+    /// The height and timestamp of the earliest peak that has a timestamp.
+    pub async fn earliest_timed_peak(&self) -> Result<Option<(u32, u64)>> {
+        earliest_timed_peak(&self.pool).await
+    }
 }
 
 impl<'a> DatabaseTx<'a> {
@@ -23,15 +74,18 @@ async fn insert_peak(
     conn: impl SqliteExecutor<'_>,
     height: u32,
     header_hash: Bytes32,
+    timestamp: u64,
 ) -> Result<()> {
     let header_hash = header_hash.as_ref();
+    let timestamp: i64 = timestamp.try_into()?;
     sqlx::query!(
         "
-        REPLACE INTO `peaks` (`height`, `header_hash`)
-        VALUES (?, ?)
+        REPLACE INTO `peaks` (`height`, `header_hash`, `timestamp`)
+        VALUES (?, ?, ?)
         ",
         height,
-        header_hash
+        header_hash,
+        timestamp
     )
     .execute(conn)
     .await?;
@@ -52,3 +106,60 @@ async fn latest_peak(conn: impl SqliteExecutor<'_>) -> Result<Option<(u32, Bytes
     .map(|row| Ok((row.height.try_into()?, to_bytes32(&row.header_hash)?)))
     .transpose()
 }
+
+async fn timed_peak_before(
+    conn: impl SqliteExecutor<'_>,
+    timestamp: u64,
+) -> Result<Option<(u32, u64)>> {
+    let timestamp: i64 = timestamp.try_into()?;
+    sqlx::query!(
+        "
+        SELECT `height`, `timestamp` AS `timestamp!`
+        FROM `peaks`
+        WHERE `timestamp` IS NOT NULL AND `timestamp` <= ?
+        ORDER BY `timestamp` DESC, `height` DESC
+        LIMIT 1
+        ",
+        timestamp
+    )
+    .fetch_optional(conn)
+    .await?
+    .map(|row| Ok((row.height.try_into()?, row.timestamp.try_into()?)))
+    .transpose()
+}
+
+async fn timed_peak_below(
+    conn: impl SqliteExecutor<'_>,
+    height: u32,
+) -> Result<Option<(u32, u64)>> {
+    sqlx::query!(
+        "
+        SELECT `height`, `timestamp` AS `timestamp!`
+        FROM `peaks`
+        WHERE `timestamp` IS NOT NULL AND `height` <= ?
+        ORDER BY `height` DESC
+        LIMIT 1
+        ",
+        height
+    )
+    .fetch_optional(conn)
+    .await?
+    .map(|row| Ok((row.height.try_into()?, row.timestamp.try_into()?)))
+    .transpose()
+}
+
+async fn earliest_timed_peak(conn: impl SqliteExecutor<'_>) -> Result<Option<(u32, u64)>> {
+    sqlx::query!(
+        "
+        SELECT `height`, `timestamp` AS `timestamp!`
+        FROM `peaks`
+        WHERE `timestamp` IS NOT NULL
+        ORDER BY `height` ASC
+        LIMIT 1
+        "
+    )
+    .fetch_optional(conn)
+    .await?
+    .map(|row| Ok((row.height.try_into()?, row.timestamp.try_into()?)))
+    .transpose()
+}
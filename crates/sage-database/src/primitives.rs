@@ -1,4 +1,5 @@
 mod cats;
+mod clawbacks;
 mod dids;
 mod nfts;
 mod xch;
@@ -1,10 +1,16 @@
-use chia::{protocol::Bytes32, puzzles::LineageProof};
+use std::collections::BTreeMap;
+
+use chia::{
+    protocol::{Bytes, Bytes32},
+    puzzles::LineageProof,
+};
 use chia_wallet_sdk::Cat;
 use sqlx::SqliteExecutor;
 
 use crate::{
-    into_row, to_bytes, to_bytes32, CatCoinRow, CatCoinSql, CatRow, CatSql, CoinStateRow,
-    CoinStateSql, Database, DatabaseTx, FullCatCoinSql, Result,
+    into_row, to_bytes, to_bytes32, to_u64, BalanceCoin, CatCoinRow, CatCoinSql, CatRow, CatSql,
+    CoinStateRow, CoinStateSql, Database, DatabaseTx, FullCatCoinSql, LabeledCoinStateRow,
+    LabeledCoinStateSql, Result,
 };
 
 impl Database {
@@ -36,6 +42,20 @@ impl Database {
         cat_balance(&self.pool, asset_id).await
     }
 
+    /// The coins that make up the balance of the CAT, which are the same ones counted by [`Database::cat_balance`].
+    pub async fn cat_balance_coins(&self, asset_id: Bytes32) -> Result<Vec<BalanceCoin>> {
+        cat_balance_coins(&self.pool, asset_id).await
+    }
+
+    /// The balance of the CAT, grouped by the memo after the hint of each coin.
+    /// Coins without a memo are grouped under `None`, which is always listed first.
+    pub async fn cat_balances_by_memo(
+        &self,
+        asset_id: Bytes32,
+    ) -> Result<Vec<(Option<Bytes>, u128)>> {
+        cat_balances_by_memo(&self.pool, asset_id).await
+    }
+
     pub async fn cat_coin(&self, coin_id: Bytes32) -> Result<Option<Cat>> {
         cat_coin(&self.pool, coin_id).await
     }
@@ -44,7 +64,7 @@ impl Database {
         refetch_cat(&self.pool, asset_id).await
     }
 
-    pub async fn cat_coin_states(&self, asset_id: Bytes32) -> Result<Vec<CoinStateRow>> {
+    pub async fn cat_coin_states(&self, asset_id: Bytes32) -> Result<Vec<LabeledCoinStateRow>> {
         cat_coin_states(&self.pool, asset_id).await
     }
 
@@ -69,6 +89,7 @@ impl<'a> DatabaseTx<'a> {
         lineage_proof: LineageProof,
         p2_puzzle_hash: Bytes32,
         asset_id: Bytes32,
+        memo: Option<Bytes>,
     ) -> Result<()> {
         insert_cat_coin(
             &mut *self.tx,
@@ -76,6 +97,7 @@ impl<'a> DatabaseTx<'a> {
             lineage_proof,
             p2_puzzle_hash,
             asset_id,
+            memo,
         )
         .await
     }
@@ -93,8 +115,9 @@ async fn insert_cat(conn: impl SqliteExecutor<'_>, row: CatRow) -> Result<()> {
             `description`,
             `icon`,
             `visible`,
-            `fetched`
-        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            `fetched`,
+            `decimals`
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         ",
         asset_id,
         row.name,
@@ -102,7 +125,8 @@ async fn insert_cat(conn: impl SqliteExecutor<'_>, row: CatRow) -> Result<()> {
         row.description,
         row.icon,
         row.visible,
-        row.fetched
+        row.fetched,
+        row.decimals
     )
     .execute(conn)
     .await?;
@@ -121,8 +145,9 @@ async fn update_cat(conn: impl SqliteExecutor<'_>, row: CatRow) -> Result<()> {
             `description`,
             `icon`,
             `visible`,
-            `fetched`
-        ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            `fetched`,
+            `decimals`
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         ",
         asset_id,
         row.name,
@@ -130,7 +155,8 @@ async fn update_cat(conn: impl SqliteExecutor<'_>, row: CatRow) -> Result<()> {
         row.description,
         row.icon,
         row.visible,
-        row.fetched
+        row.fetched,
+        row.decimals
     )
     .execute(conn)
     .await?;
@@ -141,7 +167,7 @@ async fn cats_by_name(conn: impl SqliteExecutor<'_>) -> Result<Vec<CatRow>> {
     let rows = sqlx::query_as!(
         CatSql,
         "
-        SELECT `asset_id`, `name`, `ticker`, `description`, `icon`, `visible`, `fetched`
+        SELECT `asset_id`, `name`, `ticker`, `description`, `icon`, `visible`, `fetched`, `decimals`
         FROM `cats` INDEXED BY `cat_name`
         ORDER BY `visible` DESC, `is_named` DESC, `name` ASC, `asset_id` ASC
         "
@@ -158,7 +184,7 @@ async fn cat(conn: impl SqliteExecutor<'_>, asset_id: Bytes32) -> Result<Option<
     let row = sqlx::query_as!(
         CatSql,
         "
-        SELECT `asset_id`, `name`, `ticker`, `description`, `icon`, `visible`, `fetched`
+        SELECT `asset_id`, `name`, `ticker`, `description`, `icon`, `visible`, `fetched`, `decimals`
         FROM `cats`
         WHERE `asset_id` = ?
         ",
@@ -188,6 +214,7 @@ async fn insert_cat_coin(
     lineage_proof: LineageProof,
     p2_puzzle_hash: Bytes32,
     asset_id: Bytes32,
+    memo: Option<Bytes>,
 ) -> Result<()> {
     let coin_id = coin_id.as_ref();
     let parent_parent_coin_id = lineage_proof.parent_parent_coin_info.as_ref();
@@ -196,6 +223,7 @@ async fn insert_cat_coin(
     let parent_amount = parent_amount.as_ref();
     let p2_puzzle_hash = p2_puzzle_hash.as_ref();
     let asset_id = asset_id.as_ref();
+    let memo = memo.as_deref();
 
     sqlx::query!(
         "
@@ -205,16 +233,18 @@ async fn insert_cat_coin(
             `parent_inner_puzzle_hash`,
             `parent_amount`,
             `p2_puzzle_hash`,
-            `asset_id`
+            `asset_id`,
+            `memo`
         )
-        VALUES (?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
         ",
         coin_id,
         parent_parent_coin_id,
         parent_inner_puzzle_hash,
         parent_amount,
         p2_puzzle_hash,
-        asset_id
+        asset_id,
+        memo
     )
     .execute(conn)
     .await?;
@@ -239,11 +269,13 @@ async fn spendable_cat_coins(
         LEFT JOIN `transaction_spends` ON cs.`coin_id` = `transaction_spends`.`coin_id`
         LEFT JOIN `offered_coins` ON cs.`coin_id` = `offered_coins`.`coin_id`
         LEFT JOIN `offers` ON `offered_coins`.`offer_id` = `offers`.`offer_id`
+        LEFT JOIN `frozen_coins` ON cs.`coin_id` = `frozen_coins`.`coin_id`
         WHERE `cat_coins`.`asset_id` = ?
         AND cs.`spent_height` IS NULL
         AND `transaction_spends`.`coin_id` IS NULL
         AND (`offered_coins`.`coin_id` IS NULL OR `offers`.`status` > 0)
         AND cs.`transaction_id` IS NULL
+        AND `frozen_coins`.`coin_id` IS NULL
         ",
         asset_id
     )
@@ -275,18 +307,97 @@ async fn cat_balance(conn: impl SqliteExecutor<'_>, asset_id: Bytes32) -> Result
         .sum::<Result<u128>>()
 }
 
+async fn cat_balance_coins(
+    conn: impl SqliteExecutor<'_>,
+    asset_id: Bytes32,
+) -> Result<Vec<BalanceCoin>> {
+    let asset_id = asset_id.as_ref();
+
+    let rows = sqlx::query!(
+        "
+        SELECT
+            `coin_states`.`coin_id`, `coin_states`.`amount`, `coin_states`.`created_height`,
+            `coin_states`.`transaction_id` IS NOT NULL AS `pending`,
+            (
+                EXISTS (
+                    SELECT 1 FROM `frozen_coins`
+                    WHERE `frozen_coins`.`coin_id` = `coin_states`.`coin_id`
+                )
+                OR EXISTS (
+                    SELECT 1 FROM `offered_coins`
+                    INNER JOIN `offers` ON `offered_coins`.`offer_id` = `offers`.`offer_id`
+                    WHERE `offered_coins`.`coin_id` = `coin_states`.`coin_id`
+                    AND `offers`.`status` = 0
+                )
+            ) AS `locked`
+        FROM `coin_states` INDEXED BY `coin_spent`
+        INNER JOIN `cat_coins` ON `coin_states`.`coin_id` = `cat_coins`.`coin_id`
+        LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
+        WHERE `coin_states`.`spent_height` IS NULL
+        AND `cat_coins`.`asset_id` = ?
+        AND `transaction_spends`.`coin_id` IS NULL
+        ",
+        asset_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(BalanceCoin {
+                coin_id: to_bytes32(&row.coin_id)?,
+                amount: to_u64(&row.amount)?,
+                created_height: row.created_height.map(TryInto::try_into).transpose()?,
+                pending: row.pending != 0,
+                locked: row.locked != 0,
+            })
+        })
+        .collect()
+}
+
+async fn cat_balances_by_memo(
+    conn: impl SqliteExecutor<'_>,
+    asset_id: Bytes32,
+) -> Result<Vec<(Option<Bytes>, u128)>> {
+    let asset_id = asset_id.as_ref();
+
+    let rows = sqlx::query!(
+        "
+        SELECT `coin_states`.`amount`, `cat_coins`.`memo` FROM `coin_states` INDEXED BY `coin_spent`
+        INNER JOIN `cat_coins` ON `coin_states`.`coin_id` = `cat_coins`.`coin_id`
+        LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
+        WHERE `coin_states`.`spent_height` IS NULL
+        AND `cat_coins`.`asset_id` = ?
+        AND `transaction_spends`.`coin_id` IS NULL
+        ",
+        asset_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    let mut balances = BTreeMap::new();
+
+    for row in rows {
+        let amount = u64::from_be_bytes(to_bytes(&row.amount)?) as u128;
+        *balances.entry(row.memo.map(Bytes::new)).or_insert(0) += amount;
+    }
+
+    Ok(balances.into_iter().collect())
+}
+
 async fn cat_coin_states(
     conn: impl SqliteExecutor<'_>,
     asset_id: Bytes32,
-) -> Result<Vec<CoinStateRow>> {
+) -> Result<Vec<LabeledCoinStateRow>> {
     let asset_id = asset_id.as_ref();
 
     let rows = sqlx::query_as!(
-        CoinStateSql,
+        LabeledCoinStateSql,
         "
-        SELECT `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`, `created_height`, `transaction_id`, `kind`
+        SELECT `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`, `created_height`, `transaction_id`, `kind`, `label`
         FROM `cat_coins` INDEXED BY `cat_asset_id`
         INNER JOIN `coin_states` ON `coin_states`.coin_id = `cat_coins`.coin_id
+        LEFT JOIN `coin_labels` ON `coin_labels`.`coin_id` = `cat_coins`.`coin_id`
         WHERE `asset_id` = ?
         ",
         asset_id
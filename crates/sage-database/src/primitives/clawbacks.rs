@@ -0,0 +1,107 @@
+use chia::protocol::Bytes32;
+use chia_wallet_sdk::Clawback;
+use sqlx::SqliteExecutor;
+
+use crate::{into_row, ClawbackCoinRow, ClawbackCoinSql, Database, DatabaseTx, Result};
+
+impl Database {
+    pub async fn clawback_coins(&self) -> Result<Vec<ClawbackCoinRow>> {
+        clawback_coins(&self.pool).await
+    }
+
+    pub async fn spendable_clawback_coin(
+        &self,
+        coin_id: Bytes32,
+    ) -> Result<Option<ClawbackCoinRow>> {
+        spendable_clawback_coin(&self.pool, coin_id).await
+    }
+}
+
+impl<'a> DatabaseTx<'a> {
+    pub async fn insert_clawback_coin(
+        &mut self,
+        coin_id: Bytes32,
+        clawback: Clawback,
+    ) -> Result<()> {
+        insert_clawback_coin(&mut *self.tx, coin_id, clawback).await
+    }
+}
+
+async fn insert_clawback_coin(
+    conn: impl SqliteExecutor<'_>,
+    coin_id: Bytes32,
+    clawback: Clawback,
+) -> Result<()> {
+    let coin_id = coin_id.as_ref();
+    let sender_puzzle_hash = clawback.sender_puzzle_hash.as_ref();
+    let recipient_puzzle_hash = clawback.recipient_puzzle_hash.as_ref();
+    let timelock = clawback.timelock.get().to_be_bytes();
+    let timelock = timelock.as_ref();
+
+    sqlx::query!(
+        "
+        INSERT OR IGNORE INTO `clawback_coins` (
+            `coin_id`,
+            `sender_puzzle_hash`,
+            `recipient_puzzle_hash`,
+            `timelock`
+        ) VALUES (?, ?, ?, ?)
+        ",
+        coin_id,
+        sender_puzzle_hash,
+        recipient_puzzle_hash,
+        timelock
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn clawback_coins(conn: impl SqliteExecutor<'_>) -> Result<Vec<ClawbackCoinRow>> {
+    let rows = sqlx::query_as!(
+        ClawbackCoinSql,
+        "
+        SELECT
+            `parent_coin_id`, `puzzle_hash`, `amount`,
+            `sender_puzzle_hash`, `recipient_puzzle_hash`, `timelock`
+        FROM `coin_states`
+        INNER JOIN `clawback_coins` ON `coin_states`.`coin_id` = `clawback_coins`.`coin_id`
+        WHERE `spent_height` IS NULL
+        ORDER BY `created_height` DESC
+        "
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
+async fn spendable_clawback_coin(
+    conn: impl SqliteExecutor<'_>,
+    coin_id: Bytes32,
+) -> Result<Option<ClawbackCoinRow>> {
+    let coin_id = coin_id.as_ref();
+
+    let row = sqlx::query_as!(
+        ClawbackCoinSql,
+        "
+        SELECT
+            `coin_states`.`parent_coin_id`, `coin_states`.`puzzle_hash`, `coin_states`.`amount`,
+            `sender_puzzle_hash`, `recipient_puzzle_hash`, `timelock`
+        FROM `coin_states`
+        INNER JOIN `clawback_coins` ON `coin_states`.`coin_id` = `clawback_coins`.`coin_id`
+        LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
+        WHERE `coin_states`.`coin_id` = ?
+        AND `spent_height` IS NULL
+        AND `created_height` IS NOT NULL
+        AND `coin_states`.`transaction_id` IS NULL
+        AND `transaction_spends`.`transaction_id` IS NULL
+        ",
+        coin_id
+    )
+    .fetch_optional(conn)
+    .await?;
+
+    row.map(into_row).transpose()
+}
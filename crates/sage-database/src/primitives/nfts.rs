@@ -1,3 +1,5 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use chia::{
     protocol::{Bytes32, Program},
     puzzles::LineageProof,
@@ -22,11 +24,46 @@ pub struct NftUri {
     pub uri: String,
 }
 
+/// How many owned NFTs with a metadata hash are in each stage of having their metadata fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftMetadataCounts {
+    pub fetched: u32,
+    /// There's at least one URI that hasn't been tried yet.
+    pub pending: u32,
+    /// Every URI has been tried without finding the metadata.
+    pub failed: u32,
+}
+
+/// Owned NFTs that share the same metadata hash, such as editions of the same item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftGroup {
+    /// The shared metadata hash, or `None` for an NFT without one, which is always in a group of its own.
+    pub metadata_hash: Option<Bytes32>,
+    pub launcher_ids: Vec<Bytes32>,
+    pub count: u32,
+}
+
 impl Database {
     pub async fn unchecked_nft_uris(&self, limit: u32) -> Result<Vec<NftUri>> {
         unchecked_nft_uris(&self.pool, limit).await
     }
 
+    pub async fn nft_metadata_counts(&self) -> Result<NftMetadataCounts> {
+        nft_metadata_counts(&self.pool).await
+    }
+
+    /// The owned NFTs whose metadata couldn't be fetched from any of its URIs,
+    /// along with the most recent error from one of them.
+    pub async fn failed_nft_metadata(&self, limit: u32) -> Result<Vec<(Bytes32, Option<String>)>> {
+        failed_nft_metadata(&self.pool, limit).await
+    }
+
+    /// Groups the owned NFTs by their metadata hash, from the largest group to the smallest.
+    /// NFTs with unique metadata are returned as groups with a single member.
+    pub async fn nft_groups_by_metadata_hash(&self) -> Result<Vec<NftGroup>> {
+        nft_groups_by_metadata_hash(&self.pool).await
+    }
+
     pub async fn set_nft_visible(&self, launcher_id: Bytes32, visible: bool) -> Result<()> {
         set_nft_visible(&self.pool, launcher_id, visible).await
     }
@@ -151,6 +188,12 @@ impl Database {
         no_collection_visible_nft_count(&self.pool).await
     }
 
+    /// Counts the owned NFTs, including hidden ones, optionally in a single collection.
+    /// Returns the total and how many of them are still pending.
+    pub async fn owned_nft_counts(&self, collection_id: Option<Bytes32>) -> Result<(u32, u32)> {
+        owned_nft_counts(&self.pool, collection_id).await
+    }
+
     pub async fn collections_visible_named(
         &self,
         limit: u32,
@@ -205,6 +248,10 @@ impl Database {
     pub async fn nft_by_coin_id(&self, coin_id: Bytes32) -> Result<Option<Nft<Program>>> {
         nft_by_coin_id(&self.pool, coin_id).await
     }
+
+    pub async fn nft_coin_states(&self, launcher_id: Bytes32) -> Result<Vec<CoinStateRow>> {
+        nft_coin_states(&self.pool, launcher_id).await
+    }
 }
 
 impl<'a> DatabaseTx<'a> {
@@ -233,8 +280,13 @@ impl<'a> DatabaseTx<'a> {
         insert_nft_uri(&mut *self.tx, uri, hash).await
     }
 
-    pub async fn set_nft_uri_checked(&mut self, uri: String, hash: Bytes32) -> Result<()> {
-        set_nft_uri_checked(&mut *self.tx, uri, hash).await
+    pub async fn set_nft_uri_checked(
+        &mut self,
+        uri: String,
+        hash: Bytes32,
+        error: Option<String>,
+    ) -> Result<()> {
+        set_nft_uri_checked(&mut *self.tx, uri, hash, error).await
     }
 
     pub async fn insert_nft_data(&mut self, hash: Bytes32, nft_data: NftData) -> Result<()> {
@@ -287,6 +339,7 @@ async fn insert_collection(conn: impl SqliteExecutor<'_>, row: CollectionRow) ->
     let did_id = row.did_id.as_ref();
     let name = row.name.as_deref();
     let icon = row.icon.as_deref();
+    let banner = row.banner.as_deref();
 
     sqlx::query!(
         "
@@ -296,16 +349,18 @@ async fn insert_collection(conn: impl SqliteExecutor<'_>, row: CollectionRow) ->
             `metadata_collection_id`,
             `visible`,
             `name`,
-            `icon`
+            `icon`,
+            `banner`
         )
-        VALUES (?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
         ",
         collection_id,
         did_id,
         row.metadata_collection_id,
         row.visible,
         name,
-        icon
+        icon,
+        banner
     )
     .execute(conn)
     .await?;
@@ -338,7 +393,7 @@ async fn collection(
     sqlx::query_as!(
         CollectionSql,
         "
-        SELECT `collection_id`, `did_id`, `metadata_collection_id`, `visible`, `name`, `icon`
+        SELECT `collection_id`, `did_id`, `metadata_collection_id`, `visible`, `name`, `icon`, `banner`
         FROM `collections` WHERE `collection_id` = ?
         ",
         collection_id
@@ -356,7 +411,7 @@ async fn collections_visible_named(
     sqlx::query_as!(
         CollectionSql,
         "
-        SELECT `collection_id`, `did_id`, `metadata_collection_id`, `visible`, `name`, `icon`
+        SELECT `collection_id`, `did_id`, `metadata_collection_id`, `visible`, `name`, `icon`, `banner`
         FROM `collections` INDEXED BY `col_name`
         WHERE `visible` = 1
         ORDER BY `is_named` DESC, `name` ASC, `collection_id` ASC
@@ -380,7 +435,7 @@ async fn collections_named(
     sqlx::query_as!(
         CollectionSql,
         "
-        SELECT `collection_id`, `did_id`, `metadata_collection_id`, `visible`, `name`, `icon`
+        SELECT `collection_id`, `did_id`, `metadata_collection_id`, `visible`, `name`, `icon`, `banner`
         FROM `collections` INDEXED BY `col_name`
         ORDER BY `visible` DESC, `is_named` DESC, `name` ASC, `collection_id` ASC
         LIMIT ? OFFSET ?
@@ -511,6 +566,42 @@ async fn no_collection_visible_nft_count(conn: impl SqliteExecutor<'_>) -> Resul
     Ok(row.count.try_into()?)
 }
 
+async fn owned_nft_counts(
+    conn: impl SqliteExecutor<'_>,
+    collection_id: Option<Bytes32>,
+) -> Result<(u32, u32)> {
+    let (count, pending) = if let Some(collection_id) = collection_id {
+        let collection_id = collection_id.as_ref();
+
+        let row = sqlx::query!(
+            "
+            SELECT COUNT(*) AS `count`, COUNT(CASE WHEN `is_pending` THEN 1 END) AS `pending`
+            FROM `nfts` INDEXED BY `nft_col_recent`
+            WHERE `is_owned` = 1 AND `collection_id` = ?
+            ",
+            collection_id
+        )
+        .fetch_one(conn)
+        .await?;
+
+        (row.count, row.pending)
+    } else {
+        let row = sqlx::query!(
+            "
+            SELECT COUNT(*) AS `count`, COUNT(CASE WHEN `is_pending` THEN 1 END) AS `pending`
+            FROM `nfts`
+            WHERE `is_owned` = 1
+            "
+        )
+        .fetch_one(conn)
+        .await?;
+
+        (row.count, row.pending)
+    };
+
+    Ok((count.try_into()?, pending.try_into()?))
+}
+
 async fn insert_nft_uri(conn: impl SqliteExecutor<'_>, uri: String, hash: Bytes32) -> Result<()> {
     let hash = hash.as_ref();
 
@@ -550,11 +641,24 @@ async fn set_nft_uri_checked(
     conn: impl SqliteExecutor<'_>,
     uri: String,
     hash: Bytes32,
+    error: Option<String>,
 ) -> Result<()> {
     let hash = hash.as_ref();
 
+    // In milliseconds, so that the most recent of several attempts in a row can be told apart.
+    let timestamp: i64 = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the UNIX epoch")
+        .as_millis()
+        .try_into()?;
+
     sqlx::query!(
-        "UPDATE `nft_uris` SET `checked` = 1 WHERE `hash` = ? AND `uri` = ?",
+        "
+        UPDATE `nft_uris` SET `checked` = 1, `last_error` = ?, `checked_timestamp` = ?
+        WHERE `hash` = ? AND `uri` = ?
+        ",
+        error,
+        timestamp,
         hash,
         uri
     )
@@ -564,6 +668,108 @@ async fn set_nft_uri_checked(
     Ok(())
 }
 
+async fn nft_metadata_counts(conn: impl SqliteExecutor<'_>) -> Result<NftMetadataCounts> {
+    let row = sqlx::query!(
+        "
+        SELECT
+            COUNT(CASE WHEN `fetched` THEN 1 END) AS `fetched`,
+            COUNT(CASE WHEN NOT `fetched` AND `unchecked` THEN 1 END) AS `pending`,
+            COUNT(CASE WHEN NOT `fetched` AND NOT `unchecked` THEN 1 END) AS `failed`
+        FROM (
+            SELECT
+                EXISTS (
+                    SELECT 1 FROM `nft_data` WHERE `nft_data`.`hash` = `nfts`.`metadata_hash`
+                ) AS `fetched`,
+                EXISTS (
+                    SELECT 1 FROM `nft_uris`
+                    WHERE `nft_uris`.`hash` = `nfts`.`metadata_hash` AND `checked` = 0
+                ) AS `unchecked`
+            FROM `nfts` INDEXED BY `nft_metadata`
+            WHERE `is_owned` = 1 AND `metadata_hash` IS NOT NULL
+        )
+        "
+    )
+    .fetch_one(conn)
+    .await?;
+
+    Ok(NftMetadataCounts {
+        fetched: row.fetched.try_into()?,
+        pending: row.pending.try_into()?,
+        failed: row.failed.try_into()?,
+    })
+}
+
+async fn failed_nft_metadata(
+    conn: impl SqliteExecutor<'_>,
+    limit: u32,
+) -> Result<Vec<(Bytes32, Option<String>)>> {
+    let rows = sqlx::query!(
+        "
+        SELECT
+            `launcher_id`,
+            (
+                SELECT `last_error` FROM `nft_uris`
+                WHERE `nft_uris`.`hash` = `nfts`.`metadata_hash` AND `last_error` IS NOT NULL
+                ORDER BY `checked_timestamp` DESC, `rowid` DESC
+                LIMIT 1
+            ) AS `last_error`
+        FROM `nfts` INDEXED BY `nft_metadata`
+        WHERE `is_owned` = 1 AND `metadata_hash` IS NOT NULL
+        AND NOT EXISTS (
+            SELECT 1 FROM `nft_data` WHERE `nft_data`.`hash` = `nfts`.`metadata_hash`
+        )
+        AND NOT EXISTS (
+            SELECT 1 FROM `nft_uris`
+            WHERE `nft_uris`.`hash` = `nfts`.`metadata_hash` AND `checked` = 0
+        )
+        ORDER BY `launcher_id` ASC
+        LIMIT ?
+        ",
+        limit
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| Ok((to_bytes32(&row.launcher_id)?, row.last_error)))
+        .collect()
+}
+
+async fn nft_groups_by_metadata_hash(conn: impl SqliteExecutor<'_>) -> Result<Vec<NftGroup>> {
+    let rows = sqlx::query!(
+        "
+        SELECT `launcher_id`, `metadata_hash` FROM `nfts` INDEXED BY `nft_metadata`
+        WHERE `is_owned` = 1
+        ORDER BY `metadata_hash` ASC, `launcher_id` ASC
+        "
+    )
+    .fetch_all(conn)
+    .await?;
+
+    let mut groups: Vec<NftGroup> = Vec::new();
+
+    for row in rows {
+        let launcher_id = to_bytes32(&row.launcher_id)?;
+        let metadata_hash = row.metadata_hash.as_deref().map(to_bytes32).transpose()?;
+
+        match groups.last_mut() {
+            Some(group) if metadata_hash.is_some() && group.metadata_hash == metadata_hash => {
+                group.launcher_ids.push(launcher_id);
+                group.count += 1;
+            }
+            _ => groups.push(NftGroup {
+                metadata_hash,
+                launcher_ids: vec![launcher_id],
+                count: 1,
+            }),
+        }
+    }
+
+    groups.sort_by(|a, b| b.count.cmp(&a.count));
+
+    Ok(groups)
+}
+
 async fn insert_nft_data(
     conn: impl SqliteExecutor<'_>,
     hash: Bytes32,
@@ -622,8 +828,9 @@ async fn insert_nft(conn: impl SqliteExecutor<'_>, row: NftRow) -> Result<()> {
             `name`,
             `is_owned`,
             `created_height`,
-            `metadata_hash`
-        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            `metadata_hash`,
+            `is_burned`
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         launcher_id,
         coin_id,
         collection_id,
@@ -634,7 +841,8 @@ async fn insert_nft(conn: impl SqliteExecutor<'_>, row: NftRow) -> Result<()> {
         name,
         row.is_owned,
         row.created_height,
-        metadata_hash
+        metadata_hash,
+        row.is_burned
     )
     .execute(conn)
     .await?;
@@ -717,6 +925,7 @@ async fn spendable_nft(
         AND `created_height` IS NOT NULL
         AND `coin_states`.`transaction_id` IS NULL
         AND `transaction_spends`.`transaction_id` IS NULL
+        AND `p2_puzzle_hash` NOT IN (SELECT `puzzle_hash` FROM `burn_puzzle_hashes`)
         ",
         launcher_id
     )
@@ -1247,6 +1456,30 @@ async fn created_unspent_nft_coin_state(
     rows.into_iter().map(into_row).collect()
 }
 
+async fn nft_coin_states(
+    conn: impl SqliteExecutor<'_>,
+    launcher_id: Bytes32,
+) -> Result<Vec<CoinStateRow>> {
+    let launcher_id = launcher_id.as_ref();
+
+    let rows = sqlx::query_as!(
+        CoinStateSql,
+        "
+        SELECT `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`, `created_height`, `transaction_id`, `kind`
+        FROM `coin_states`
+        INNER JOIN `nft_coins` ON `coin_states`.coin_id = `nft_coins`.coin_id
+        WHERE `launcher_id` = ?
+        AND `created_height` IS NOT NULL
+        ORDER BY `created_height`, `coin_states`.`coin_id`
+        ",
+        launcher_id
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter().map(into_row).collect()
+}
+
 async fn nft_by_coin_id(
     conn: impl SqliteExecutor<'_>,
     coin_id: Bytes32,
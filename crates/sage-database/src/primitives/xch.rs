@@ -2,7 +2,8 @@ use chia::protocol::{Bytes32, Coin};
 use sqlx::SqliteExecutor;
 
 use crate::{
-    into_row, to_bytes, CoinSql, CoinStateRow, CoinStateSql, Database, DatabaseTx, Result,
+    into_row, to_bytes, to_bytes32, to_u64, BalanceCoin, CoinSql, CoinStateRow, CoinStateSql,
+    Database, DatabaseTx, LabeledCoinStateRow, LabeledCoinStateSql, Result,
 };
 
 impl Database {
@@ -14,7 +15,12 @@ impl Database {
         balance(&self.pool).await
     }
 
-    pub async fn p2_coin_states(&self) -> Result<Vec<CoinStateRow>> {
+    /// The coins that make up the balance, which are the same ones counted by [`Database::balance`].
+    pub async fn p2_balance_coins(&self) -> Result<Vec<BalanceCoin>> {
+        p2_balance_coins(&self.pool).await
+    }
+
+    pub async fn p2_coin_states(&self) -> Result<Vec<LabeledCoinStateRow>> {
         p2_coin_states(&self.pool).await
     }
 
@@ -66,6 +72,47 @@ async fn balance(conn: impl SqliteExecutor<'_>) -> Result<u128> {
         .sum::<Result<u128>>()
 }
 
+async fn p2_balance_coins(conn: impl SqliteExecutor<'_>) -> Result<Vec<BalanceCoin>> {
+    let rows = sqlx::query!(
+        "
+        SELECT
+            `coin_states`.`coin_id`, `coin_states`.`amount`, `coin_states`.`created_height`,
+            `coin_states`.`transaction_id` IS NOT NULL AS `pending`,
+            (
+                EXISTS (
+                    SELECT 1 FROM `frozen_coins`
+                    WHERE `frozen_coins`.`coin_id` = `coin_states`.`coin_id`
+                )
+                OR EXISTS (
+                    SELECT 1 FROM `offered_coins`
+                    INNER JOIN `offers` ON `offered_coins`.`offer_id` = `offers`.`offer_id`
+                    WHERE `offered_coins`.`coin_id` = `coin_states`.`coin_id`
+                    AND `offers`.`status` = 0
+                )
+            ) AS `locked`
+        FROM `coin_states` INDEXED BY `coin_kind_spent`
+        LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
+        WHERE `coin_states`.`spent_height` IS NULL
+        AND `transaction_spends`.`coin_id` IS NULL
+        AND `kind` = 1
+        "
+    )
+    .fetch_all(conn)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            Ok(BalanceCoin {
+                coin_id: to_bytes32(&row.coin_id)?,
+                amount: to_u64(&row.amount)?,
+                created_height: row.created_height.map(TryInto::try_into).transpose()?,
+                pending: row.pending != 0,
+                locked: row.locked != 0,
+            })
+        })
+        .collect()
+}
+
 async fn spendable_coins(conn: impl SqliteExecutor<'_>) -> Result<Vec<Coin>> {
     sqlx::query_as!(
         CoinSql,
@@ -74,10 +121,12 @@ async fn spendable_coins(conn: impl SqliteExecutor<'_>) -> Result<Vec<Coin>> {
         LEFT JOIN `transaction_spends` ON `coin_states`.`coin_id` = `transaction_spends`.`coin_id`
         LEFT JOIN `offered_coins` ON `coin_states`.`coin_id` = `offered_coins`.`coin_id`
         LEFT JOIN `offers` ON `offered_coins`.`offer_id` = `offers`.`offer_id`
+        LEFT JOIN `frozen_coins` ON `coin_states`.`coin_id` = `frozen_coins`.`coin_id`
         WHERE `coin_states`.`spent_height` IS NULL
         AND `transaction_spends`.`coin_id` IS NULL
         AND (`offered_coins`.`coin_id` IS NULL OR `offers`.`status` > 0)
         AND `coin_states`.`transaction_id` IS NULL
+        AND `frozen_coins`.`coin_id` IS NULL
         AND `kind` = 1
         "
     )
@@ -88,12 +137,14 @@ async fn spendable_coins(conn: impl SqliteExecutor<'_>) -> Result<Vec<Coin>> {
     .collect()
 }
 
-async fn p2_coin_states(conn: impl SqliteExecutor<'_>) -> Result<Vec<CoinStateRow>> {
+async fn p2_coin_states(conn: impl SqliteExecutor<'_>) -> Result<Vec<LabeledCoinStateRow>> {
     let rows = sqlx::query_as!(
-        CoinStateSql,
+        LabeledCoinStateSql,
         "
-        SELECT `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`, `created_height`, `transaction_id`, `kind`
-        FROM `coin_states` WHERE `kind` = 1
+        SELECT `parent_coin_id`, `puzzle_hash`, `amount`, `spent_height`, `created_height`, `transaction_id`, `kind`, `label`
+        FROM `coin_states`
+        LEFT JOIN `coin_labels` ON `coin_labels`.`coin_id` = `coin_states`.`coin_id`
+        WHERE `kind` = 1
         "
     )
     .fetch_all(conn)
@@ -1,5 +1,6 @@
 mod cat;
 mod cat_coin;
+mod clawback_coin;
 mod coin_state;
 mod collection;
 mod derivation;
@@ -12,6 +13,7 @@ mod offer;
 
 pub use cat::*;
 pub use cat_coin::*;
+pub use clawback_coin::*;
 pub use coin_state::*;
 pub use collection::*;
 pub use derivation::*;
@@ -12,6 +12,7 @@ pub(crate) struct CatSql {
     pub icon: Option<String>,
     pub visible: bool,
     pub fetched: bool,
+    pub decimals: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +24,8 @@ pub struct CatRow {
     pub icon: Option<String>,
     pub visible: bool,
     pub fetched: bool,
+    /// The number of decimal places the CAT is displayed with, if it's known.
+    pub decimals: Option<u8>,
 }
 
 impl IntoRow for CatSql {
@@ -37,6 +40,7 @@ impl IntoRow for CatSql {
             icon: self.icon.clone(),
             visible: self.visible,
             fetched: self.fetched,
+            decimals: self.decimals.map(TryInto::try_into).transpose()?,
         })
     }
 }
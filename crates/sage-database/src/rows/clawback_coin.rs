@@ -0,0 +1,43 @@
+use std::num::NonZeroU64;
+
+use chia::protocol::Coin;
+use chia_wallet_sdk::Clawback;
+
+use crate::{to_bytes32, to_u64, DatabaseError};
+
+use super::IntoRow;
+
+pub(crate) struct ClawbackCoinSql {
+    pub parent_coin_id: Vec<u8>,
+    pub puzzle_hash: Vec<u8>,
+    pub amount: Vec<u8>,
+    pub sender_puzzle_hash: Vec<u8>,
+    pub recipient_puzzle_hash: Vec<u8>,
+    pub timelock: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClawbackCoinRow {
+    pub coin: Coin,
+    pub clawback: Clawback,
+}
+
+impl IntoRow for ClawbackCoinSql {
+    type Row = ClawbackCoinRow;
+
+    fn into_row(self) -> Result<ClawbackCoinRow, DatabaseError> {
+        Ok(ClawbackCoinRow {
+            coin: Coin {
+                parent_coin_info: to_bytes32(&self.parent_coin_id)?,
+                puzzle_hash: to_bytes32(&self.puzzle_hash)?,
+                amount: to_u64(&self.amount)?,
+            },
+            clawback: Clawback {
+                timelock: NonZeroU64::new(to_u64(&self.timelock)?)
+                    .ok_or(DatabaseError::InvalidTimelock)?,
+                sender_puzzle_hash: to_bytes32(&self.sender_puzzle_hash)?,
+                recipient_puzzle_hash: to_bytes32(&self.recipient_puzzle_hash)?,
+            },
+        })
+    }
+}
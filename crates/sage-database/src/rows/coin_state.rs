@@ -14,12 +14,56 @@ pub(crate) struct CoinStateSql {
     pub kind: i64,
 }
 
+pub(crate) struct LabeledCoinStateSql {
+    pub parent_coin_id: Vec<u8>,
+    pub puzzle_hash: Vec<u8>,
+    pub amount: Vec<u8>,
+    pub spent_height: Option<i64>,
+    pub created_height: Option<i64>,
+    pub transaction_id: Option<Vec<u8>>,
+    pub kind: i64,
+    pub label: Option<String>,
+}
+
+pub(crate) struct SettlementCoinSql {
+    pub parent_coin_id: Vec<u8>,
+    pub puzzle_hash: Vec<u8>,
+    pub amount: Vec<u8>,
+    pub hint: Option<Vec<u8>>,
+    pub created_height: Option<i64>,
+}
+
+pub(crate) struct CoinAssetSql {
+    pub height: i64,
+    pub coin_id: Vec<u8>,
+    pub parent_coin_id: Vec<u8>,
+    pub amount: Vec<u8>,
+    pub kind: i64,
+    pub asset_id: Option<Vec<u8>>,
+    pub decimals: Option<i64>,
+    pub counterparty: Option<Vec<u8>>,
+    pub transaction_id: Option<Vec<u8>>,
+    pub fee: Option<Vec<u8>>,
+}
+
 pub(crate) struct CoinSql {
     pub parent_coin_id: Vec<u8>,
     pub puzzle_hash: Vec<u8>,
     pub amount: Vec<u8>,
 }
 
+/// An unspent coin that counts towards the balance, along with whether it can be spent right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalanceCoin {
+    pub coin_id: Bytes32,
+    pub amount: u64,
+    pub created_height: Option<u32>,
+    /// The coin was created by a transaction that hasn't been confirmed yet.
+    pub pending: bool,
+    /// The coin is frozen, or offered by an offer that's still active.
+    pub locked: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CoinKind {
     Unknown,
@@ -27,6 +71,8 @@ pub enum CoinKind {
     Cat,
     Nft,
     Did,
+    Clawback,
+    Settlement,
 }
 
 impl CoinKind {
@@ -36,6 +82,8 @@ impl CoinKind {
             2 => Self::Cat,
             3 => Self::Nft,
             4 => Self::Did,
+            5 => Self::Clawback,
+            6 => Self::Settlement,
             _ => Self::Unknown,
         }
     }
@@ -68,6 +116,99 @@ impl IntoRow for CoinStateSql {
     }
 }
 
+/// A coin state along with the label that the user has given the coin, if any.
+#[derive(Debug, Clone)]
+pub struct LabeledCoinStateRow {
+    pub row: CoinStateRow,
+    pub label: Option<String>,
+}
+
+impl IntoRow for LabeledCoinStateSql {
+    type Row = LabeledCoinStateRow;
+
+    fn into_row(self) -> Result<LabeledCoinStateRow, DatabaseError> {
+        Ok(LabeledCoinStateRow {
+            row: CoinStateSql {
+                parent_coin_id: self.parent_coin_id,
+                puzzle_hash: self.puzzle_hash,
+                amount: self.amount,
+                spent_height: self.spent_height,
+                created_height: self.created_height,
+                transaction_id: self.transaction_id,
+                kind: self.kind,
+            }
+            .into_row()?,
+            label: self.label,
+        })
+    }
+}
+
+/// A coin locked in the settlement payments puzzle, which is hinted to one of the wallet's puzzle hashes.
+#[derive(Debug, Clone, Copy)]
+pub struct SettlementCoinRow {
+    pub coin: Coin,
+    pub p2_puzzle_hash: Bytes32,
+    pub created_height: Option<u32>,
+}
+
+impl IntoRow for SettlementCoinSql {
+    type Row = SettlementCoinRow;
+
+    fn into_row(self) -> Result<SettlementCoinRow, DatabaseError> {
+        Ok(SettlementCoinRow {
+            coin: Coin {
+                parent_coin_info: to_bytes32(&self.parent_coin_id)?,
+                puzzle_hash: to_bytes32(&self.puzzle_hash)?,
+                amount: to_u64(&self.amount)?,
+            },
+            p2_puzzle_hash: to_bytes32(self.hint.as_deref().unwrap_or_default())?,
+            created_height: self.created_height.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+/// A coin that was created or spent at a height, along with the asset that it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoinAssetRow {
+    pub height: u32,
+    pub coin_id: Bytes32,
+    pub parent_coin_id: Bytes32,
+    pub amount: u64,
+    pub kind: CoinKind,
+    /// The asset id of a CAT coin, or the launcher id of an NFT or DID coin.
+    pub asset_id: Option<Bytes32>,
+    /// The number of decimal places of a CAT coin's asset, if it's known.
+    pub decimals: Option<u8>,
+    /// The puzzle hash on the other side of a clawback coin, which is the only kind of coin
+    /// that records who sent or received it.
+    pub counterparty: Option<Bytes32>,
+    /// The wallet's transaction, or the id of its completed offer, that spent the coin, or the
+    /// coin's parent if it was created. This is only known for transactions and offers that
+    /// were made by the wallet.
+    pub transaction_id: Option<Bytes32>,
+    /// The fee that was paid by the transaction or offer.
+    pub fee: Option<u64>,
+}
+
+impl IntoRow for CoinAssetSql {
+    type Row = CoinAssetRow;
+
+    fn into_row(self) -> Result<CoinAssetRow, DatabaseError> {
+        Ok(CoinAssetRow {
+            height: self.height.try_into()?,
+            coin_id: to_bytes32(&self.coin_id)?,
+            parent_coin_id: to_bytes32(&self.parent_coin_id)?,
+            amount: to_u64(&self.amount)?,
+            kind: CoinKind::from_i64(self.kind),
+            asset_id: self.asset_id.as_deref().map(to_bytes32).transpose()?,
+            decimals: self.decimals.map(TryInto::try_into).transpose()?,
+            counterparty: self.counterparty.as_deref().map(to_bytes32).transpose()?,
+            transaction_id: self.transaction_id.as_deref().map(to_bytes32).transpose()?,
+            fee: self.fee.as_deref().map(to_u64).transpose()?,
+        })
+    }
+}
+
 impl IntoRow for CoinSql {
     type Row = Coin;
 
@@ -11,9 +11,10 @@ pub(crate) struct CollectionSql {
     pub visible: bool,
     pub name: Option<String>,
     pub icon: Option<String>,
+    pub banner: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CollectionRow {
     pub collection_id: Bytes32,
     pub did_id: Bytes32,
@@ -21,6 +22,7 @@ pub struct CollectionRow {
     pub visible: bool,
     pub name: Option<String>,
     pub icon: Option<String>,
+    pub banner: Option<String>,
 }
 
 impl IntoRow for CollectionSql {
@@ -34,6 +36,7 @@ impl IntoRow for CollectionSql {
             visible: self.visible,
             name: self.name.clone(),
             icon: self.icon.clone(),
+            banner: self.banner.clone(),
         })
     }
 }
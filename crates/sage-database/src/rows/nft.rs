@@ -4,7 +4,7 @@ use crate::{to_bytes32, DatabaseError};
 
 use super::IntoRow;
 
-#[allow(unused)]
+#[allow(unused, clippy::struct_excessive_bools)]
 pub(crate) struct NftSql {
     pub launcher_id: Vec<u8>,
     pub coin_id: Vec<u8>,
@@ -17,10 +17,12 @@ pub(crate) struct NftSql {
     pub is_owned: bool,
     pub created_height: Option<i64>,
     pub metadata_hash: Option<Vec<u8>>,
+    pub is_burned: bool,
     pub is_named: Option<bool>,
     pub is_pending: Option<bool>,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone)]
 pub struct NftRow {
     pub launcher_id: Bytes32,
@@ -34,6 +36,7 @@ pub struct NftRow {
     pub is_owned: bool,
     pub created_height: Option<u32>,
     pub metadata_hash: Option<Bytes32>,
+    pub is_burned: bool,
 }
 
 impl IntoRow for NftSql {
@@ -52,6 +55,7 @@ impl IntoRow for NftSql {
             is_owned: self.is_owned,
             created_height: self.created_height.map(TryInto::try_into).transpose()?,
             metadata_hash: self.metadata_hash.as_deref().map(to_bytes32).transpose()?,
+            is_burned: self.is_burned,
         })
     }
 }
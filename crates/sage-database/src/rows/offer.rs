@@ -32,6 +32,8 @@ pub enum OfferStatus {
     Completed = 1,
     Cancelled = 2,
     Expired = 3,
+    /// A transaction spending the offered coins has been submitted, but isn't confirmed yet.
+    PendingCancel = 4,
 }
 
 impl IntoRow for OfferSql {
@@ -53,6 +55,7 @@ impl IntoRow for OfferSql {
                 1 => OfferStatus::Completed,
                 2 => OfferStatus::Cancelled,
                 3 => OfferStatus::Expired,
+                4 => OfferStatus::PendingCancel,
                 _ => return Err(DatabaseError::InvalidOfferStatus(self.status)),
             },
             inserted_timestamp: to_u64(&self.inserted_timestamp)?,
@@ -1,8 +1,10 @@
+use std::collections::HashSet;
+
 use chia::{
     bls::Signature,
     protocol::{Bytes32, Coin, CoinSpend, Program},
 };
-use sqlx::SqliteExecutor;
+use sqlx::{QueryBuilder, SqliteExecutor};
 
 use crate::{to_bytes, to_bytes32, Database, DatabaseTx, Result};
 
@@ -11,6 +13,7 @@ pub struct TransactionRow {
     pub transaction_id: Bytes32,
     pub fee: u64,
     pub submitted_at: Option<i64>,
+    pub submission_count: u32,
 }
 
 impl Database {
@@ -22,6 +25,10 @@ impl Database {
         update_transaction_mempool_time(&self.pool, transaction_id, timestamp).await
     }
 
+    pub async fn increment_transaction_submissions(&self, transaction_id: Bytes32) -> Result<()> {
+        increment_transaction_submissions(&self.pool, transaction_id).await
+    }
+
     pub async fn transactions(&self) -> Result<Vec<TransactionRow>> {
         transactions(&self.pool).await
     }
@@ -40,6 +47,11 @@ impl Database {
     pub async fn coin_transaction_id(&self, coin_id: Bytes32) -> Result<Option<Bytes32>> {
         coin_transaction_id(&self.pool, coin_id).await
     }
+
+    /// Returns the coins that are already spent by a pending transaction, in the order given.
+    pub async fn conflicting_pending_spends(&self, coin_ids: &[Bytes32]) -> Result<Vec<Bytes32>> {
+        conflicting_pending_spends(&self.pool, coin_ids).await
+    }
 }
 
 impl<'a> DatabaseTx<'a> {
@@ -79,6 +91,29 @@ impl<'a> DatabaseTx<'a> {
     pub async fn transaction_coin_ids(&mut self, transaction_id: Bytes32) -> Result<Vec<Bytes32>> {
         transaction_coin_ids(&mut *self.tx, transaction_id).await
     }
+
+    /// Records the fee paid by a transaction, so that it's still known after the transaction is
+    /// confirmed and removed.
+    pub async fn insert_transaction_history(
+        &mut self,
+        transaction_id: Bytes32,
+        fee: u64,
+    ) -> Result<()> {
+        insert_transaction_history(&mut *self.tx, transaction_id, fee).await
+    }
+
+    /// Records that a coin was spent by a transaction in the history.
+    pub async fn insert_transaction_history_coin(
+        &mut self,
+        transaction_id: Bytes32,
+        coin_id: Bytes32,
+    ) -> Result<()> {
+        insert_transaction_history_coin(&mut *self.tx, transaction_id, coin_id).await
+    }
+
+    pub async fn remove_transaction_history(&mut self, transaction_id: Bytes32) -> Result<()> {
+        remove_transaction_history(&mut *self.tx, transaction_id).await
+    }
 }
 
 async fn insert_pending_transaction(
@@ -158,6 +193,70 @@ async fn insert_transaction_spend(
     Ok(())
 }
 
+async fn insert_transaction_history(
+    conn: impl SqliteExecutor<'_>,
+    transaction_id: Bytes32,
+    fee: u64,
+) -> Result<()> {
+    let transaction_id = transaction_id.as_ref();
+    let fee = fee.to_be_bytes();
+    let fee = fee.as_ref();
+
+    sqlx::query!(
+        "
+        INSERT INTO `transaction_history` (`transaction_id`, `fee`)
+        VALUES (?, ?)
+        ",
+        transaction_id,
+        fee
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn insert_transaction_history_coin(
+    conn: impl SqliteExecutor<'_>,
+    transaction_id: Bytes32,
+    coin_id: Bytes32,
+) -> Result<()> {
+    let transaction_id = transaction_id.as_ref();
+    let coin_id = coin_id.as_ref();
+
+    sqlx::query!(
+        "
+        INSERT OR REPLACE INTO `transaction_history_coins` (`coin_id`, `transaction_id`)
+        VALUES (?, ?)
+        ",
+        coin_id,
+        transaction_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn remove_transaction_history(
+    conn: impl SqliteExecutor<'_>,
+    transaction_id: Bytes32,
+) -> Result<()> {
+    let transaction_id = transaction_id.as_ref();
+
+    sqlx::query!(
+        "
+        DELETE FROM `transaction_history`
+        WHERE `transaction_id` = ?
+        ",
+        transaction_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
 async fn update_transaction_mempool_time(
     conn: impl SqliteExecutor<'_>,
     transaction_id: Bytes32,
@@ -180,15 +279,36 @@ async fn update_transaction_mempool_time(
     Ok(())
 }
 
+async fn increment_transaction_submissions(
+    conn: impl SqliteExecutor<'_>,
+    transaction_id: Bytes32,
+) -> Result<()> {
+    let transaction_id = transaction_id.as_ref();
+
+    sqlx::query!(
+        "
+        UPDATE `transactions`
+        SET `submission_count` = `submission_count` + 1
+        WHERE `transaction_id` = ?
+        ",
+        transaction_id
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
 async fn transactions(conn: impl SqliteExecutor<'_>) -> Result<Vec<TransactionRow>> {
     let rows = sqlx::query!(
         "
         SELECT
             `transaction_id`,
             `fee`,
-            `submitted_at`
+            `submitted_at`,
+            `submission_count`
         FROM `transactions`
-        ORDER BY `submitted_at` DESC, `transaction_id` ASC
+        ORDER BY `submitted_at` IS NULL DESC, `submitted_at` DESC, `transaction_id` ASC
         "
     )
     .fetch_all(conn)
@@ -200,6 +320,7 @@ async fn transactions(conn: impl SqliteExecutor<'_>) -> Result<Vec<TransactionRo
                 transaction_id: to_bytes32(&row.transaction_id)?,
                 fee: u64::from_be_bytes(to_bytes(&row.fee)?),
                 submitted_at: row.submitted_at,
+                submission_count: row.submission_count.try_into()?,
             })
         })
         .collect()
@@ -225,6 +346,40 @@ async fn coin_transaction_id(
     .transpose()
 }
 
+async fn conflicting_pending_spends(
+    conn: impl SqliteExecutor<'_>,
+    coin_ids: &[Bytes32],
+) -> Result<Vec<Bytes32>> {
+    if coin_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // The number of coins varies, so the query can't be checked at compile time.
+    let mut query =
+        QueryBuilder::new("SELECT `coin_id` FROM `transaction_spends` WHERE `coin_id` IN (");
+
+    let mut separated = query.separated(", ");
+
+    for coin_id in coin_ids {
+        separated.push_bind(coin_id.to_vec());
+    }
+
+    separated.push_unseparated(")");
+
+    let rows: Vec<(Vec<u8>,)> = query.build_query_as().fetch_all(conn).await?;
+
+    let pending = rows
+        .iter()
+        .map(|(coin_id,)| to_bytes32(coin_id))
+        .collect::<Result<HashSet<_>>>()?;
+
+    Ok(coin_ids
+        .iter()
+        .copied()
+        .filter(|coin_id| pending.contains(coin_id))
+        .collect())
+}
+
 async fn resubmittable_transactions(
     conn: impl SqliteExecutor<'_>,
     threshold: i64,
@@ -1,10 +1,16 @@
+use std::num::NonZeroU64;
+
 use chia::{
     clvm_traits::{FromClvm, ToClvm},
-    protocol::{Bytes32, Coin, Program},
-    puzzles::{nft::NftMetadata, singleton::SINGLETON_LAUNCHER_PUZZLE_HASH, LineageProof, Proof},
+    clvm_utils::ToTreeHash,
+    protocol::{Bytes, Bytes32, Coin, Program},
+    puzzles::{
+        nft::NftMetadata, offer::SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+        singleton::SINGLETON_LAUNCHER_PUZZLE_HASH, LineageProof, Proof,
+    },
 };
 use chia_wallet_sdk::{
-    run_puzzle, Cat, Condition, Did, DidInfo, HashedPtr, Memos, Nft, NftInfo, Puzzle,
+    run_puzzle, Cat, Clawback, Condition, Did, DidInfo, HashedPtr, Nft, NftInfo, Puzzle,
 };
 use clvmr::{Allocator, NodePtr};
 use tracing::{debug_span, warn};
@@ -22,6 +28,8 @@ pub enum ChildKind {
         asset_id: Bytes32,
         p2_puzzle_hash: Bytes32,
         lineage_proof: LineageProof,
+        /// The memo after the hint, which some CATs use to tell apart balances held for different purposes.
+        memo: Option<Bytes>,
     },
     Did {
         info: DidInfo<Program>,
@@ -32,6 +40,14 @@ pub enum ChildKind {
         lineage_proof: LineageProof,
         metadata: Option<NftMetadata>,
     },
+    Clawback {
+        clawback: Clawback,
+    },
+    /// XCH locked in the settlement payments puzzle, which anyone can claim with a notarized payment.
+    /// The hint is used to recognize coins intended for the wallet.
+    Settlement {
+        hint: Bytes32,
+    },
 }
 
 impl ChildKind {
@@ -87,15 +103,23 @@ impl ChildKind {
             return Ok(Self::Unknown { hint: None });
         };
 
-        let hint = if let Some(memos) = create_coin.memos {
-            let memos = Memos::<(Bytes32, NodePtr)>::from_clvm(allocator, memos.value).ok();
-            memos.map(|memos| memos.value.0)
-        } else {
-            None
-        };
+        let memos = create_coin.memos.map(|memos| memos.value);
+
+        // The hint is the first memo, if it's a puzzle hash.
+        let hint = memos
+            .and_then(|memos| <(Bytes32, NodePtr)>::from_clvm(allocator, memos).ok())
+            .map(|(hint, _rest)| hint);
+
+        let memo = memos
+            .and_then(|memos| <(Bytes32, (Bytes, NodePtr))>::from_clvm(allocator, memos).ok())
+            .map(|(_hint, (memo, _rest))| memo);
 
         let unknown = Self::Unknown { hint };
 
+        if coin.puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH.into() {
+            return Ok(hint.map_or(unknown, |hint| Self::Settlement { hint }));
+        }
+
         match Cat::parse_children(allocator, parent_coin, parent_puzzle, parent_solution) {
             // If there was an error parsing the CAT, we can exit early.
             Err(error) => {
@@ -119,6 +143,7 @@ impl ChildKind {
                     asset_id: cat.asset_id,
                     lineage_proof,
                     p2_puzzle_hash: cat.p2_puzzle_hash,
+                    memo,
                 });
             }
 
@@ -192,6 +217,11 @@ impl ChildKind {
             Ok(None) => {}
         }
 
+        // Clawback coins are identified by their memos, since the puzzle is hidden behind a merkle root.
+        if let Some(clawback) = memos.and_then(|memos| parse_clawback(allocator, memos, coin)) {
+            return Ok(Self::Clawback { clawback });
+        }
+
         Ok(unknown)
     }
 
@@ -201,10 +231,46 @@ impl ChildKind {
             Self::Cat { p2_puzzle_hash, .. } => Some(*p2_puzzle_hash),
             Self::Did { info, .. } => Some(info.p2_puzzle_hash),
             Self::Nft { info, .. } => Some(info.p2_puzzle_hash),
+            Self::Clawback { clawback } => Some(clawback.recipient_puzzle_hash),
+            Self::Settlement { hint } => Some(*hint),
+        }
+    }
+
+    /// The p2 puzzle hashes that are able to spend the coin.
+    pub fn spender_puzzle_hashes(&self) -> Vec<Bytes32> {
+        match self {
+            Self::Clawback { clawback } => {
+                vec![clawback.recipient_puzzle_hash, clawback.sender_puzzle_hash]
+            }
+            _ => self.p2_puzzle_hash().into_iter().collect(),
         }
     }
 
     pub fn subscribe(&self) -> bool {
-        matches!(self, Self::Cat { .. } | Self::Did { .. } | Self::Nft { .. })
+        matches!(
+            self,
+            Self::Cat { .. }
+                | Self::Did { .. }
+                | Self::Nft { .. }
+                | Self::Clawback { .. }
+                | Self::Settlement { .. }
+        )
     }
 }
+
+/// Parses the memos of a clawback coin, which are the recipient puzzle hash (as the hint),
+/// the sender puzzle hash, and the timelock in seconds.
+fn parse_clawback(allocator: &Allocator, memos: NodePtr, coin: Coin) -> Option<Clawback> {
+    let (recipient_puzzle_hash, (sender_puzzle_hash, (timelock, _rest))) =
+        <(Bytes32, (Bytes32, (u64, NodePtr)))>::from_clvm(allocator, memos).ok()?;
+
+    let clawback = Clawback {
+        timelock: NonZeroU64::new(timelock)?,
+        sender_puzzle_hash,
+        recipient_puzzle_hash,
+    };
+
+    let puzzle_hash: Bytes32 = clawback.to_layer().tree_hash().into();
+
+    (puzzle_hash == coin.puzzle_hash).then_some(clawback)
+}
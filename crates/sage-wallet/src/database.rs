@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     time::{Duration, Instant},
 };
 
@@ -7,9 +7,12 @@ use chia::{
     bls::Signature,
     protocol::{Bytes32, CoinState},
 };
-use sage_database::{CatRow, CoinKind, Database, DatabaseTx, DidRow, NftRow};
+use sage_database::{CatRow, CoinKind, Database, DatabaseTx, DidRow, NftRow, OfferStatus};
 
-use crate::{compute_nft_info, fetch_nft_did, ChildKind, Transaction, WalletError, WalletPeer};
+use crate::{
+    compute_nft_info, fetch_nft_did, onchain_metadata_json, ChildKind, Transaction, WalletError,
+    WalletPeer,
+};
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct UpsertCounters {
@@ -75,10 +78,24 @@ pub async fn insert_puzzle(
 
     match info {
         ChildKind::Launcher | ChildKind::Unknown { .. } => {}
+        ChildKind::Settlement { hint } => {
+            tx.sync_coin(coin_id, Some(hint), CoinKind::Settlement)
+                .await?;
+        }
+        ChildKind::Clawback { clawback } => {
+            tx.sync_coin(
+                coin_id,
+                Some(clawback.recipient_puzzle_hash),
+                CoinKind::Clawback,
+            )
+            .await?;
+            tx.insert_clawback_coin(coin_id, clawback).await?;
+        }
         ChildKind::Cat {
             asset_id,
             lineage_proof,
             p2_puzzle_hash,
+            memo,
         } => {
             tx.sync_coin(coin_id, Some(p2_puzzle_hash), CoinKind::Cat)
                 .await?;
@@ -90,9 +107,10 @@ pub async fn insert_puzzle(
                 icon: None,
                 visible: true,
                 fetched: false,
+                decimals: None,
             })
             .await?;
-            tx.insert_cat_coin(coin_id, lineage_proof, p2_puzzle_hash, asset_id)
+            tx.insert_cat_coin(coin_id, lineage_proof, p2_puzzle_hash, asset_id, memo)
                 .await?;
         }
         ChildKind::Did {
@@ -143,6 +161,21 @@ pub async fn insert_puzzle(
             let license_hash = metadata.as_ref().and_then(|m| m.license_hash);
             let launcher_id = info.launcher_id;
             let owner_did = info.current_owner;
+            let burned = tx.is_burn_puzzle_hash(info.p2_puzzle_hash).await?;
+
+            // If there are no URIs to fetch the off-chain metadata from, the NFT is described
+            // entirely by its on-chain metadata instead, and there's nothing to wait for.
+            let offchain_metadata_hash = metadata_hash.filter(|_| {
+                metadata
+                    .as_ref()
+                    .is_some_and(|metadata| !metadata.metadata_uris.is_empty())
+            });
+
+            let onchain_metadata_blob = if offchain_metadata_hash.is_none() {
+                onchain_metadata_json(&info.metadata)
+            } else {
+                None
+            };
 
             tx.sync_coin(coin_id, Some(info.p2_puzzle_hash), CoinKind::Nft)
                 .await?;
@@ -172,24 +205,27 @@ pub async fn insert_puzzle(
                 name: None,
                 is_owned: coin_state.spent_height.is_none(),
                 created_height: coin_state.created_height,
-                metadata_hash,
+                metadata_hash: offchain_metadata_hash,
+                is_burned: false,
             });
 
             if coin_state.spent_height.is_none() {
-                row.is_owned = true;
+                row.is_owned = !burned;
+                row.is_burned = burned;
             }
 
-            let metadata_blob = if let Some(metadata_hash) = metadata_hash {
+            let metadata_blob = if let Some(metadata_hash) = offchain_metadata_hash {
                 tx.fetch_nft_data(metadata_hash)
                     .await?
                     .map(|data| data.blob)
             } else {
-                None
+                onchain_metadata_blob
             };
 
             let computed_info = compute_nft_info(minter_did, metadata_blob.as_deref());
 
             row.coin_id = coin_id;
+            row.metadata_hash = offchain_metadata_hash;
             row.sensitive_content = computed_info.sensitive_content;
             row.name = computed_info.name;
             row.collection_id = computed_info
@@ -287,6 +323,41 @@ pub async fn insert_transaction(
     tx.insert_pending_transaction(transaction_id, aggregated_signature, transaction.fee)
         .await?;
 
+    // The fee is whatever the wallet's own spends don't pay to any coin, which includes the
+    // coins that they create and spend within the transaction. It's kept in the history, along
+    // with every coin that was spent, since the transaction is removed once it's confirmed.
+    let mut own_coin_ids = HashSet::new();
+    let mut spent_amount = 0;
+    let mut created_amount = 0;
+
+    for input in &transaction.inputs {
+        let coin = input.coin_spend.coin;
+
+        if tx.is_p2_coin(coin.coin_id()).await?.is_none()
+            && !own_coin_ids.contains(&coin.parent_coin_info)
+        {
+            continue;
+        }
+
+        own_coin_ids.insert(coin.coin_id());
+        spent_amount += u128::from(coin.amount);
+        created_amount += input
+            .outputs
+            .iter()
+            .map(|output| u128::from(output.coin.amount))
+            .sum::<u128>();
+    }
+
+    let fee = u64::try_from(spent_amount.saturating_sub(created_amount))
+        .map_err(|_| WalletError::FeeOverflow)?;
+
+    tx.insert_transaction_history(transaction_id, fee).await?;
+
+    for &coin_id in coin_spends.keys() {
+        tx.insert_transaction_history_coin(transaction_id, coin_id)
+            .await?;
+    }
+
     for coin_id in transaction
         .inputs
         .iter()
@@ -295,6 +366,23 @@ pub async fn insert_transaction(
         delete_puzzle(&mut tx, coin_id).await?;
     }
 
+    // Spending an offered coin invalidates the offer, so it's pending cancellation from the time
+    // the transaction is submitted until the offer queue sees the coin spent.
+    for &coin_id in coin_spends.keys() {
+        let Some(offer_id) = tx.coin_offer_id(coin_id).await? else {
+            continue;
+        };
+
+        if tx
+            .get_offer(offer_id)
+            .await?
+            .is_some_and(|offer| offer.status == OfferStatus::Active)
+        {
+            tx.update_offer_status(offer_id, OfferStatus::PendingCancel)
+                .await?;
+        }
+    }
+
     let mut subscriptions = Vec::new();
 
     for (index, input) in transaction.inputs.into_iter().enumerate() {
@@ -305,6 +393,14 @@ pub async fn insert_transaction(
             let coin_state = CoinState::new(output.coin, None, None);
             let coin_id = output.coin.coin_id();
 
+            // An NFT sent to a burn address no longer belongs to the wallet, but its coin is
+            // subscribed to so that it's flagged as burned once the transfer is confirmed.
+            if let ChildKind::Nft { info, .. } = &output.kind {
+                if tx.is_burn_puzzle_hash(info.p2_puzzle_hash).await? {
+                    subscriptions.push(coin_id);
+                }
+            }
+
             if tx.is_p2_puzzle_hash(output.coin.puzzle_hash).await? {
                 tx.insert_coin_state(coin_state, true, Some(transaction_id))
                     .await?;
@@ -312,14 +408,19 @@ pub async fn insert_transaction(
                 continue;
             }
 
-            let Some(p2_puzzle_hash) = output.kind.p2_puzzle_hash() else {
-                continue;
-            };
+            let mut p2_puzzle_hash = None;
 
-            if !tx.is_p2_puzzle_hash(p2_puzzle_hash).await? {
-                continue;
+            for puzzle_hash in output.kind.spender_puzzle_hashes() {
+                if tx.is_p2_puzzle_hash(puzzle_hash).await? {
+                    p2_puzzle_hash = Some(puzzle_hash);
+                    break;
+                }
             }
 
+            let Some(p2_puzzle_hash) = p2_puzzle_hash else {
+                continue;
+            };
+
             tx.insert_coin_state(coin_state, true, Some(transaction_id))
                 .await?;
             tx.sync_coin(
@@ -330,6 +431,8 @@ pub async fn insert_transaction(
                     ChildKind::Cat { .. } => CoinKind::Cat,
                     ChildKind::Did { .. } => CoinKind::Did,
                     ChildKind::Nft { .. } => CoinKind::Nft,
+                    ChildKind::Clawback { .. } => CoinKind::Clawback,
+                    ChildKind::Settlement { .. } => CoinKind::Settlement,
                 },
             )
             .await?;
@@ -353,6 +456,30 @@ pub async fn insert_transaction(
     Ok(subscriptions)
 }
 
+/// Reactivates offers that were pending cancellation by a transaction which failed, since their
+/// coins are still unspent.
+pub async fn revert_offer_cancellations(
+    tx: &mut DatabaseTx<'_>,
+    transaction_id: Bytes32,
+) -> Result<(), WalletError> {
+    for coin_id in tx.transaction_coin_ids(transaction_id).await? {
+        let Some(offer_id) = tx.coin_offer_id(coin_id).await? else {
+            continue;
+        };
+
+        if tx
+            .get_offer(offer_id)
+            .await?
+            .is_some_and(|offer| offer.status == OfferStatus::PendingCancel)
+        {
+            tx.update_offer_status(offer_id, OfferStatus::Active)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn safely_remove_transaction(
     tx: &mut DatabaseTx<'_>,
     transaction_id: Bytes32,
@@ -367,3 +494,52 @@ pub async fn safely_remove_transaction(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_conflicting_pending_spends() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        // A separate database isn't synced, so the pending transaction is never submitted.
+        let db = TestWallet::empty_database().await?;
+
+        let coin_spends = test
+            .wallet
+            .send_xch(test.puzzle_hash, 100, 0, Vec::new(), false, true)
+            .await?;
+        let coin_id = coin_spends[0].coin.coin_id();
+        let other_coin_id = Bytes32::new([42; 32]);
+
+        assert!(db
+            .conflicting_pending_spends(&[coin_id, other_coin_id])
+            .await?
+            .is_empty());
+
+        let transaction_id = Bytes32::new([1; 32]);
+        let mut tx = db.tx().await?;
+        tx.insert_pending_transaction(transaction_id, Signature::default(), 0)
+            .await?;
+        tx.insert_transaction_spend(transaction_id, coin_spends[0].clone(), 0)
+            .await?;
+        tx.commit().await?;
+
+        assert_eq!(
+            db.conflicting_pending_spends(&[other_coin_id, coin_id])
+                .await?,
+            [coin_id]
+        );
+        assert!(db
+            .conflicting_pending_spends(&[other_coin_id])
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+use std::{fmt, str::FromStr};
+
+use chia::bls::{DerivableKey, PublicKey, SecretKey};
+
+use crate::WalletError;
+
+/// The path that wallet keys are derived along from the master key, before the key index.
+/// Only unhardened indices are supported, since keys must be derivable from the master public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// The fixed prefix of Chia's standard unhardened wallet derivation path.
+    pub const STANDARD: [u32; 3] = [12381, 8444, 2];
+
+    pub fn new(indices: Vec<u32>) -> Result<Self, WalletError> {
+        let path = Self(indices);
+
+        if path.0.iter().any(|&index| index >= 1 << 31) {
+            return Err(WalletError::InvalidDerivationPath(path.to_string()));
+        }
+
+        Ok(path)
+    }
+
+    pub fn intermediate_pk(&self, master_pk: &PublicKey) -> PublicKey {
+        self.0
+            .iter()
+            .fold(*master_pk, |pk, &index| pk.derive_unhardened(index))
+    }
+
+    pub fn intermediate_sk(&self, master_sk: &SecretKey) -> SecretKey {
+        self.0
+            .iter()
+            .fold(master_sk.clone(), |sk, &index| sk.derive_unhardened(index))
+    }
+}
+
+impl Default for DerivationPath {
+    fn default() -> Self {
+        Self(Self::STANDARD.to_vec())
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+
+        for index in &self.0 {
+            write!(f, "/{index}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = WalletError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || WalletError::InvalidDerivationPath(s.to_string());
+
+        let mut parts = s.trim().split('/');
+
+        if parts.next() != Some("m") {
+            return Err(error());
+        }
+
+        // Hardened indices are rejected here, since they end with an apostrophe or `h`.
+        let indices = parts
+            .map(|part| {
+                if part.is_empty() || !part.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return Err(error());
+                }
+                part.parse::<u32>().map_err(|_| error())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(indices).map_err(|_| error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::{
+        bls::master_to_wallet_unhardened_intermediate,
+        puzzles::{standard::StandardArgs, DeriveSynthetic},
+    };
+    use chia_wallet_sdk::{encode_address, test_secret_key};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_derivation_path() -> anyhow::Result<()> {
+        assert_eq!(
+            "m/12381/8444/2".parse::<DerivationPath>()?,
+            DerivationPath::default()
+        );
+        assert_eq!(DerivationPath::default().to_string(), "m/12381/8444/2");
+        assert_eq!(
+            "m".parse::<DerivationPath>()?,
+            DerivationPath::new(Vec::new())?
+        );
+
+        for path in [
+            "",
+            "12381/8444/2",
+            "m/",
+            "m//2",
+            "m/12381'/8444/2",
+            "m/12381h/8444/2",
+            "m/-1",
+            "m/+1",
+            "m/2147483648",
+            "m/4294967296",
+        ] {
+            assert!(
+                matches!(
+                    path.parse::<DerivationPath>(),
+                    Err(WalletError::InvalidDerivationPath(..))
+                ),
+                "{path}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_custom_path() -> anyhow::Result<()> {
+        let master_sk = test_secret_key()?;
+        let master_pk = master_sk.public_key();
+
+        // The standard path matches the SDK's derivation.
+        let standard = DerivationPath::default();
+        assert_eq!(
+            standard.intermediate_pk(&master_pk),
+            master_to_wallet_unhardened_intermediate(&master_pk)
+        );
+
+        let custom: DerivationPath = "m/12381/8444/7".parse()?;
+        let intermediate_pk = custom.intermediate_pk(&master_pk);
+        assert_ne!(intermediate_pk, standard.intermediate_pk(&master_pk));
+        assert_eq!(
+            custom.intermediate_sk(&master_sk).public_key(),
+            intermediate_pk
+        );
+
+        let puzzle_hash =
+            StandardArgs::curry_tree_hash(intermediate_pk.derive_unhardened(0).derive_synthetic());
+        let address = encode_address(puzzle_hash.into(), "xch")?;
+        assert_eq!(
+            address,
+            "xch1qq5rf4xjdpk069sh48gz6yk88uqj7dmr29r860a4lrqkhxyrs26s64rl2l"
+        );
+
+        Ok(())
+    }
+}
@@ -2,21 +2,35 @@ use std::time::SystemTimeError;
 
 use chia::{
     clvm_traits::{FromClvmError, ToClvmError},
+    consensus::gen::validation_error::ErrorCode,
     protocol::Bytes32,
 };
-use chia_wallet_sdk::{ClientError, CoinSelectionError, DriverError, OfferError, SignerError};
+use chia_wallet_sdk::{
+    AddressError, ClientError, CoinSelectionError, DriverError, OfferError, SignerError,
+};
 use clvmr::reduction::EvalErr;
 use sage_database::DatabaseError;
 use thiserror::Error;
 use tokio::{task::JoinError, time::error::Elapsed};
 
+use crate::UnverifiedSignature;
+
 #[derive(Debug, Error)]
 pub enum WalletError {
     #[error("Database error: {0}")]
     Database(#[from] DatabaseError),
 
     #[error("Client error: {0}")]
-    Client(#[from] ClientError),
+    Client(ClientError),
+
+    #[error("Peer is unavailable: {0}")]
+    PeerUnavailable(ClientError),
+
+    #[error("Peer request timed out")]
+    PeerTimeout,
+
+    #[error("Peer is missing chain data for coin with id {0}")]
+    ChainDataMissing(Bytes32),
 
     #[error("Driver error: {0}")]
     Driver(#[from] DriverError),
@@ -36,9 +50,36 @@ pub enum WalletError {
     #[error("Timeout exceeded")]
     Elapsed(#[from] Elapsed),
 
+    #[error("Address error: {0}")]
+    Address(#[from] AddressError),
+
+    #[error("Wrong address prefix: {0}")]
+    AddressPrefix(String),
+
+    #[error("Invalid network parameter: {0}")]
+    InvalidNetwork(String),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("Missing coin with id {0}")]
     MissingCoin(Bytes32),
 
+    #[error("Coin with id {0} is not a spendable p2 coin")]
+    UnspendableCoin(Bytes32),
+
+    #[error("Conditions were given for coin with id {0}, which isn't being spent")]
+    UnexpectedConditions(Bytes32),
+
+    #[error("Coin with id {0} is spent more than once")]
+    DuplicateCoin(Bytes32),
+
+    #[error("Extra conditions can't create coins or reserve fees")]
+    ConflictingConditions,
+
     #[error("Missing spend with id {0}")]
     MissingSpend(Bytes32),
 
@@ -51,6 +92,9 @@ pub enum WalletError {
     #[error("Subscription limit reached")]
     SubscriptionLimitReached,
 
+    #[error("Sync failed on all peers after {0} attempts")]
+    SyncFailed(usize),
+
     #[error("System time error: {0}")]
     SystemTime(#[from] SystemTimeError),
 
@@ -72,28 +116,111 @@ pub enum WalletError {
     #[error("Insufficient derivations")]
     InsufficientDerivations,
 
+    #[error("Missing derivation for puzzle hash {0}")]
+    MissingDerivation(Bytes32),
+
+    #[error("Hardened derivation at index {0} requires the secret key")]
+    HardenedDerivation(u32),
+
+    #[error("Derivation index {index} is beyond the maximum of {max}, which must be raised to derive more keys")]
+    DerivationLimitReached { index: u32, max: u32 },
+
     #[error("Missing secret key")]
     UnknownPublicKey,
 
     #[error("SECP is not supported")]
     SecpNotSupported,
 
+    #[error("Invalid aggregated signature")]
+    InvalidSignature {
+        unverified: Vec<UnverifiedSignature>,
+    },
+
     #[error("Missing DID with id {0}")]
     MissingDid(Bytes32),
 
     #[error("Missing NFT with id {0}")]
     MissingNft(Bytes32),
 
+    #[error("NFT with id {0} has been burned")]
+    BurnedNft(Bytes32),
+
+    #[error("NFT with id {nft_id} is out of date, the wallet needs to resync")]
+    StaleNft { nft_id: Bytes32 },
+
+    #[error("Coin with id {coin_id} has already been spent, the wallet needs to resync")]
+    CoinAlreadySpent { coin_id: Bytes32 },
+
+    #[error("Offer must offer or request at least one asset")]
+    EmptyOffer,
+
+    #[error("None of the coins in the offer belong to this wallet")]
+    OfferNotOwned,
+
+    #[error("The alternative offers don't spend any of the same coins")]
+    NoSharedOfferCoins,
+
+    #[error("Offer would have {spends} coin spends, but the maximum is {max}")]
+    OfferTooLarge { spends: usize, max: usize },
+
+    #[error("Offer starts at {starts_at}, which isn't before it expires at {expires_at}")]
+    InvalidOfferWindow { starts_at: u64, expires_at: u64 },
+
+    #[error("No set of coins adds up to exactly the amount needed")]
+    NoExactCoins,
+
+    #[error("Memo is {size} bytes, but the maximum is {max}")]
+    MemoTooLarge { size: usize, max: usize },
+
+    #[error("An offer memo requires requested XCH or CATs to be attached to")]
+    OfferMemoWithoutPayment,
+
+    #[error("Offer isn't in a known compressed or uncompressed format")]
+    UnknownOfferFormat,
+
+    #[error("Taking the offer would pay more than requested of {}", asset_id.map_or_else(|| "XCH".to_string(), |id| format!("asset {id}")))]
+    OfferOverfill { asset_id: Option<Bytes32> },
+
+    #[error("Can't pay extra {}, since the offer doesn't request it", asset_id.map_or_else(|| "XCH".to_string(), |id| format!("of asset {id}")))]
+    OverfillNotRequested { asset_id: Option<Bytes32> },
+
+    #[error("Invalid offer chunk: {0}")]
+    InvalidOfferChunk(String),
+
+    #[error("Offer chunk {index} is missing")]
+    MissingOfferChunk { index: usize },
+
+    #[error("Offer chunk {index} was given more than once")]
+    DuplicateOfferChunk { index: usize },
+
+    #[error("Cannot both offer and request {}", .0.map_or_else(|| "XCH".to_string(), |id| format!("asset {id}")))]
+    SelfTrade(Option<Bytes32>),
+
+    #[error("Cannot take an offer made by this wallet")]
+    CannotTakeOwnOffer,
+
     #[error("Invalid trade price")]
     InvalidTradePrice,
 
     #[error("Invalid royalty amount")]
     InvalidRoyaltyAmount,
 
+    #[error("Royalty total overflow")]
+    RoyaltyOverflow,
+
+    #[error(
+        "Royalty for NFT with id {nft_id} is underpaid, {paid} was paid but {required} is required"
+    )]
+    RoyaltyUnderpaid {
+        nft_id: Bytes32,
+        required: u64,
+        paid: u64,
+    },
+
     #[error("Invalid requested payment")]
     InvalidRequestedPayment,
 
-    #[error("Unknown requested payment mod hash {0}")]
+    #[error("Unknown requested payment puzzle hash {0}")]
     UnknownRequestedPayment(Bytes32),
 
     #[error("Duplicate NFT requested payment with id {0}")]
@@ -101,4 +228,54 @@ pub enum WalletError {
 
     #[error("Empty bulk transfer")]
     EmptyBulkTransfer,
+
+    #[error("Transaction cost {cost} exceeds the maximum bundle cost of {max}")]
+    BundleCostExceeded { cost: u64, max: u64 },
+
+    #[error("No coins to spend")]
+    EmptySpend,
+
+    #[error("Invalid spend: {0:?}")]
+    InvalidSpend(ErrorCode),
+
+    #[error("Fee overflow")]
+    FeeOverflow,
+
+    #[error("Amount overflow for {}", asset_id.map_or_else(|| "XCH".to_string(), |id| format!("asset {id}")))]
+    AmountOverflow { asset_id: Option<Bytes32> },
+
+    #[error("Clawback timelock must be greater than zero")]
+    InvalidClawbackTimelock,
+
+    #[error("Unsupported metadata updater puzzle hash {0}")]
+    UnsupportedMetadataUpdater(Bytes32),
+
+    #[error("Invalid derivation path {0}")]
+    InvalidDerivationPath(String),
+
+    #[error("No peak with a timestamp has been recorded yet")]
+    MissingTimedPeak,
+}
+
+impl WalletError {
+    /// Whether the error was caused by the peer rather than the request itself,
+    /// in which case the request can be retried against another peer.
+    pub fn is_network_error(&self) -> bool {
+        matches!(
+            self,
+            Self::PeerUnavailable(..) | Self::PeerTimeout | Self::ChainDataMissing(..)
+        )
+    }
+}
+
+impl From<ClientError> for WalletError {
+    fn from(error: ClientError) -> Self {
+        match error {
+            ClientError::WebSocket(..)
+            | ClientError::Recv(..)
+            | ClientError::Io(..)
+            | ClientError::BannedPeer => Self::PeerUnavailable(error),
+            error => Self::Client(error),
+        }
+    }
 }
@@ -1,27 +1,31 @@
 mod child_kind;
 mod coin_kind;
 mod database;
+mod derivation_path;
 mod error;
 mod queues;
 mod sync_manager;
 mod transaction;
 mod utils;
 mod wallet;
+mod wallet_network;
 mod wallet_peer;
 
 pub use child_kind::*;
 pub use coin_kind::*;
 pub use database::*;
+pub use derivation_path::*;
 pub use error::*;
 pub use queues::*;
 pub use sync_manager::*;
 pub use transaction::*;
 pub use utils::*;
 pub use wallet::*;
+pub use wallet_network::*;
 pub use wallet_peer::*;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 mod test;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 pub use test::*;
@@ -96,6 +96,7 @@ impl CatQueue {
                 icon: Some(format!("{dexie_image_base_url}/{asset_id}.webp")),
                 visible: true,
                 fetched: true,
+                decimals: None,
             })
             .await?;
 
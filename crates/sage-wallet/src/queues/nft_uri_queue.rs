@@ -1,54 +1,140 @@
 use std::time::Duration;
 
-use futures_lite::StreamExt;
-use futures_util::stream::FuturesUnordered;
+use futures_util::{stream, StreamExt};
 use sage_database::{Database, NftData};
 use tokio::{sync::mpsc, time::sleep};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::{compute_nft_info, fetch_uri, SyncEvent, WalletError};
+use crate::{compute_nft_info, fetch_uri, Data, MetadataCache, SyncEvent, UriError, WalletError};
+
+/// The number of URIs that are checked in each batch.
+const BATCH_SIZE: u32 = 30;
 
 #[derive(Debug)]
 pub struct NftUriQueue {
     db: Database,
     sync_sender: mpsc::Sender<SyncEvent>,
+    concurrency: usize,
+    cache: Option<MetadataCache>,
+    prefetch_delay: Option<Duration>,
+    retries: u32,
+    retry_delay: Duration,
 }
 
 impl NftUriQueue {
     pub fn new(db: Database, sync_sender: mpsc::Sender<SyncEvent>) -> Self {
-        Self { db, sync_sender }
+        Self {
+            db,
+            sync_sender,
+            concurrency: BATCH_SIZE as usize,
+            cache: None,
+            prefetch_delay: None,
+            retries: 0,
+            retry_delay: Duration::ZERO,
+        }
+    }
+
+    /// Limits how many URIs are fetched at the same time, to avoid overwhelming gateways.
+    /// By default, the whole batch is fetched at once.
+    #[must_use]
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Checks the cache for data before fetching it from the network, and stores fetched data in it.
+    #[must_use]
+    pub fn with_cache(mut self, cache: Option<MetadataCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Eagerly works through a backlog of URIs, such as the ones discovered during an initial sync,
+    /// by only waiting this long between full batches. Progress is reported after each batch
+    /// with a [`SyncEvent::NftPrefetch`] event.
+    #[must_use]
+    pub fn with_prefetch(mut self, delay: Duration) -> Self {
+        self.prefetch_delay = Some(delay);
+        self
+    }
+
+    /// Fetches a URI that fails up to this many more times before recording the error, waiting
+    /// the given delay before the first retry and twice as long before each one after it.
+    #[must_use]
+    pub fn with_retries(mut self, retries: u32, delay: Duration) -> Self {
+        self.retries = retries;
+        self.retry_delay = delay;
+        self
     }
 
     pub async fn start(self, delay: Duration) -> Result<(), WalletError> {
         loop {
-            self.process_batch().await?;
-            sleep(delay).await;
+            let count = self.process_batch().await?;
+
+            match self.prefetch_delay {
+                Some(prefetch_delay) if count == BATCH_SIZE as usize => sleep(prefetch_delay).await,
+                _ => sleep(delay).await,
+            }
         }
     }
 
-    async fn process_batch(&self) -> Result<(), WalletError> {
-        let batch = self.db.unchecked_nft_uris(30).await?;
+    async fn fetch_with_retries(&self, uri: &str) -> Result<Data, UriError> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+
+        loop {
+            let result = fetch_uri(uri, Duration::from_secs(15), Duration::from_secs(15)).await;
+
+            match result {
+                Err(error) if attempt < self.retries => {
+                    debug!("Retrying in {delay:?} after failed attempt: {error}");
+                    sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Fetches the next batch of unchecked URIs, and returns how many there were.
+    async fn process_batch(&self) -> Result<usize, WalletError> {
+        let batch = self.db.unchecked_nft_uris(BATCH_SIZE).await?;
+        let count = batch.len();
 
         if batch.is_empty() {
-            return Ok(());
+            return Ok(0);
         }
 
         info!("Processing batch of {} NFT URIs", batch.len());
 
-        let mut futures = FuturesUnordered::new();
+        let mut futures = stream::iter(batch)
+            .map(|item| async move {
+                if let Some(cache) = &self.cache {
+                    if let Some(data) = cache.get(item.hash).await {
+                        return (item, Ok(data));
+                    }
+                }
+
+                let result = self.fetch_with_retries(&item.uri).await;
+
+                if let (Some(cache), Ok(data)) = (&self.cache, &result) {
+                    if data.hash == item.hash {
+                        if let Err(error) = cache.insert(data).await {
+                            warn!("Failed to cache NFT data with hash {}: {error}", item.hash);
+                        }
+                    }
+                }
 
-        for item in batch {
-            futures.push(async move {
-                let result =
-                    fetch_uri(&item.uri, Duration::from_secs(15), Duration::from_secs(15)).await;
                 (item, result)
-            });
-        }
+            })
+            .buffer_unordered(self.concurrency);
 
         while let Some((item, result)) = futures.next().await {
             let mut tx = self.db.tx().await?;
 
-            match result {
+            // The reason the metadata couldn't be fetched from this URI, if it couldn't.
+            let error = match result {
                 Ok(data) => {
                     if data.hash == item.hash {
                         if tx.fetch_nft_data(item.hash).await?.is_none() {
@@ -78,25 +164,42 @@ impl NftUriQueue {
                                 tx.insert_nft(nft).await?;
                             }
                         }
+
+                        None
                     } else {
-                        debug!(
+                        let error = format!(
                             "Hash mismatch for URI {} (expected {} but found {})",
                             item.uri, item.hash, data.hash
                         );
+                        debug!("{error}");
+                        Some(error)
                     }
                 }
                 Err(error) => {
                     debug!("{error}");
+                    Some(error.to_string())
                 }
             };
 
-            tx.set_nft_uri_checked(item.uri, item.hash).await?;
+            tx.set_nft_uri_checked(item.uri, item.hash, error).await?;
 
             tx.commit().await?;
         }
 
         self.sync_sender.send(SyncEvent::NftData).await.ok();
 
-        Ok(())
+        if self.prefetch_delay.is_some() {
+            let counts = self.db.nft_metadata_counts().await?;
+
+            self.sync_sender
+                .send(SyncEvent::NftPrefetch {
+                    fetched: counts.fetched,
+                    pending: counts.pending,
+                })
+                .await
+                .ok();
+        }
+
+        Ok(count)
     }
 }
@@ -105,7 +105,10 @@ impl OfferQueue {
                     }
                 };
 
-                if coin_states.iter().all(|cs| cs.spent_height.is_some())
+                // A cancellation may spend every offered coin, which would otherwise look like
+                // the offer was taken.
+                if offer.status != OfferStatus::PendingCancel
+                    && coin_states.iter().all(|cs| cs.spent_height.is_some())
                     && coin_ids
                         .into_iter()
                         .all(|coin_id| coin_states.iter().any(|cs| cs.coin.coin_id() == coin_id))
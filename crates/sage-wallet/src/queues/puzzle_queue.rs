@@ -6,13 +6,13 @@ use sage_database::{CoinKind, Database};
 use tokio::{
     sync::{mpsc, Mutex},
     task::spawn_blocking,
-    time::{sleep, timeout},
+    time::sleep,
 };
 use tracing::{debug, warn};
 
 use crate::{
-    database::insert_puzzle, fetch_nft_did, ChildKind, PeerState, SyncCommand, SyncEvent,
-    WalletError, WalletPeer,
+    database::insert_puzzle, fetch_nft_did, peer_timeout, ChildKind, PeerState, SyncCommand,
+    SyncEvent, WalletError, WalletPeer,
 };
 
 #[derive(Debug)]
@@ -104,10 +104,24 @@ impl PuzzleQueue {
                 Ok((info, minter_did)) => {
                     let subscribe = info.subscribe();
 
-                    let remove = match info.p2_puzzle_hash() {
-                        Some(p2_puzzle_hash) => !self.db.is_p2_puzzle_hash(p2_puzzle_hash).await?,
-                        None => true,
-                    };
+                    let mut remove = true;
+
+                    for p2_puzzle_hash in info.spender_puzzle_hashes() {
+                        if self.db.is_p2_puzzle_hash(p2_puzzle_hash).await? {
+                            remove = false;
+                            break;
+                        }
+                    }
+
+                    // An NFT that the wallet sent to a burn address is kept, so that it's shown as burned.
+                    if let ChildKind::Nft { info, .. } = &info {
+                        if remove
+                            && self.db.is_burn_puzzle_hash(info.p2_puzzle_hash).await?
+                            && self.db.nft_row(info.launcher_id).await?.is_some()
+                        {
+                            remove = false;
+                        }
+                    }
 
                     if remove {
                         self.db.delete_coin_state(coin_state.coin.coin_id()).await?;
@@ -129,7 +143,9 @@ impl PuzzleQueue {
 
                     if matches!(
                         error,
-                        WalletError::Elapsed(..)
+                        WalletError::PeerTimeout
+                            | WalletError::PeerUnavailable(..)
+                            | WalletError::ChainDataMissing(..)
                             | WalletError::PeerMisbehaved
                             | WalletError::Client(..)
                     ) {
@@ -165,11 +181,11 @@ async fn fetch_puzzle(
     genesis_challenge: Bytes32,
     coin: Coin,
 ) -> Result<(ChildKind, Option<Bytes32>), WalletError> {
-    let parent_spend = timeout(
+    let parent_spend = peer_timeout(
         Duration::from_secs(15),
         peer.fetch_coin_spend(coin.parent_coin_info, genesis_challenge),
     )
-    .await??;
+    .await?;
 
     let info = spawn_blocking(move || {
         ChildKind::from_parent(
@@ -12,7 +12,8 @@ use tokio::{
 use tracing::{info, warn};
 
 use crate::{
-    safely_remove_transaction, submit_to_peers, PeerState, Status, SyncEvent, WalletError,
+    revert_offer_cancellations, safely_remove_transaction, submit_to_peers, PeerState, Status,
+    SyncEvent, WalletError,
 };
 
 #[derive(Debug)]
@@ -84,6 +85,10 @@ impl TransactionQueue {
                 return Ok(());
             }
 
+            self.db
+                .increment_transaction_submissions(transaction_id)
+                .await?;
+
             match submit_to_peers(&peers, self.genesis_challenge, spend_bundle).await? {
                 Status::Success => {
                     info!("Transaction {transaction_id} confirmed, removing and confirming coins");
@@ -120,6 +125,8 @@ impl TransactionQueue {
                     );
 
                     let mut tx = self.db.tx().await?;
+                    revert_offer_cancellations(&mut tx, transaction_id).await?;
+                    tx.remove_transaction_history(transaction_id).await?;
                     safely_remove_transaction(&mut tx, transaction_id).await?;
                     tx.commit().await?;
 
@@ -2,7 +2,7 @@ use std::{
     fmt, mem,
     net::{IpAddr, SocketAddr},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use chia::{
@@ -18,10 +18,10 @@ use tokio::{
     time::{sleep, timeout},
 };
 use tracing::{debug, info, warn};
-use wallet_sync::{incremental_sync, sync_wallet};
+use wallet_sync::{subscribe_new_puzzle_hashes, sync_wallet};
 
 use crate::{
-    CatQueue, NftUriQueue, OfferQueue, PuzzleQueue, TransactionQueue, Wallet, WalletError,
+    CatQueue, MetadataWorker, OfferQueue, PuzzleQueue, TransactionQueue, Wallet, WalletError,
 };
 
 mod options;
@@ -29,12 +29,16 @@ mod peer_discovery;
 mod peer_state;
 mod sync_command;
 mod sync_event;
+mod sync_health;
 mod wallet_sync;
 
 pub use options::*;
 pub use peer_state::*;
 pub use sync_command::*;
 pub use sync_event::*;
+pub use sync_health::*;
+
+pub(crate) use wallet_sync::incremental_sync;
 
 pub struct SyncManager {
     options: SyncOptions,
@@ -49,10 +53,12 @@ pub struct SyncManager {
     initial_wallet_sync: InitialWalletSync,
     puzzle_lookup_task: Option<JoinHandle<Result<(), WalletError>>>,
     cat_queue_task: Option<JoinHandle<Result<(), WalletError>>>,
-    nft_uri_queue_task: Option<JoinHandle<Result<(), WalletError>>>,
+    metadata_worker: Option<MetadataWorker>,
     transaction_queue_task: Option<JoinHandle<Result<(), WalletError>>>,
     offer_queue_task: Option<JoinHandle<Result<(), WalletError>>>,
     pending_coin_subscriptions: Vec<Bytes32>,
+    sync_progress: SyncProgress,
+    stall_detector: StallDetector,
 }
 
 impl fmt::Debug for SyncManager {
@@ -67,7 +73,7 @@ enum InitialWalletSync {
     Idle,
     Syncing {
         ip: IpAddr,
-        task: JoinHandle<Result<(), WalletError>>,
+        task: JoinHandle<Result<IpAddr, WalletError>>,
     },
     Subscribed(IpAddr),
 }
@@ -83,8 +89,8 @@ impl Drop for SyncManager {
         if let Some(task) = &mut self.cat_queue_task {
             task.abort();
         }
-        if let Some(task) = &mut self.nft_uri_queue_task {
-            task.abort();
+        if let Some(worker) = &self.metadata_worker {
+            worker.cancel();
         }
         if let Some(task) = &mut self.transaction_queue_task {
             task.abort();
@@ -107,6 +113,8 @@ impl SyncManager {
         let (command_sender, command_receiver) = mpsc::channel(100);
         let (event_sender, event_receiver) = mpsc::channel(100);
 
+        let stall_detector = StallDetector::new(options.timeouts.sync_stall);
+
         let manager = Self {
             options,
             state,
@@ -120,10 +128,12 @@ impl SyncManager {
             initial_wallet_sync: InitialWalletSync::Idle,
             puzzle_lookup_task: None,
             cat_queue_task: None,
-            nft_uri_queue_task: None,
+            metadata_worker: None,
             transaction_queue_task: None,
             offer_queue_task: None,
             pending_coin_subscriptions: Vec::new(),
+            sync_progress: SyncProgress::default(),
+            stall_detector,
         };
 
         (manager, command_sender, event_receiver)
@@ -194,24 +204,40 @@ impl SyncManager {
     }
 
     async fn subscribe(&mut self) {
-        if self.pending_coin_subscriptions.is_empty() {
+        let InitialWalletSync::Subscribed(ip) = self.initial_wallet_sync else {
             return;
+        };
+
+        let Some(peer) = self
+            .state
+            .lock()
+            .await
+            .peer(ip)
+            .map(|info| info.peer.clone())
+        else {
+            return;
+        };
+
+        if !self.pending_coin_subscriptions.is_empty() {
+            // TODO: Handle cases
+            timeout(
+                Duration::from_secs(3),
+                peer.subscribe_coins(
+                    mem::take(&mut self.pending_coin_subscriptions),
+                    None,
+                    self.network.genesis_challenge,
+                ),
+            )
+            .await
+            .map(Result::ok)
+            .ok();
         }
 
-        if let InitialWalletSync::Subscribed(ip) = self.initial_wallet_sync {
-            if let Some(info) = self.state.lock().await.peer(ip) {
-                // TODO: Handle cases
-                timeout(
-                    Duration::from_secs(3),
-                    info.peer.subscribe_coins(
-                        mem::take(&mut self.pending_coin_subscriptions),
-                        None,
-                        self.network.genesis_challenge,
-                    ),
-                )
-                .await
-                .map(Result::ok)
-                .ok();
+        // Only the puzzle hashes that have been derived since the last subscription are sent.
+        if let Some(wallet) = self.wallet.as_ref() {
+            if let Err(error) = subscribe_new_puzzle_hashes(wallet, &peer, &self.event_sender).await
+            {
+                warn!("Failed to subscribe to new puzzle hashes: {error}");
             }
         }
     }
@@ -228,8 +254,8 @@ impl SyncManager {
         if let Some(task) = &mut self.cat_queue_task.take() {
             task.abort();
         }
-        if let Some(task) = &mut self.nft_uri_queue_task.take() {
-            task.abort();
+        if let Some(worker) = self.metadata_worker.take() {
+            worker.cancel();
         }
         if let Some(task) = &mut self.transaction_queue_task.take() {
             task.abort();
@@ -290,9 +316,11 @@ impl SyncManager {
 
                     incremental_sync(wallet, message.items, true, &self.event_sender).await?;
 
+                    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
                     wallet
                         .db
-                        .insert_peak(message.height, message.peak_hash)
+                        .insert_peak(message.height, message.peak_hash, timestamp)
                         .await?;
 
                     info!(
@@ -326,9 +354,76 @@ impl SyncManager {
         }
 
         self.update_tasks().await;
+        self.check_sync_health().await;
         self.poll_tasks().await;
     }
 
+    /// Restarts the wallet sync if nothing has been synced for too long while there are still
+    /// heights to sync. The sync is resumed from the last peak that was synced, and the puzzle
+    /// hashes are subscribed to again. If there's another peer to sync against, the stalled
+    /// peer is banned so that it isn't picked again.
+    async fn check_sync_health(&mut self) {
+        let behind = match &self.initial_wallet_sync {
+            InitialWalletSync::Idle => return,
+            InitialWalletSync::Syncing { .. } => true,
+            InitialWalletSync::Subscribed(ip) => {
+                let state = self.state.lock().await;
+
+                // Once subscribed, updates are pushed by the peer, so it's only behind
+                // if it has fallen behind the peak of the other peers.
+                let behind = state
+                    .peak_of(*ip)
+                    .zip(state.peak())
+                    .is_some_and(|((height, _), (peak, _))| height < peak);
+
+                if !behind {
+                    self.sync_progress.record();
+                }
+
+                behind
+            }
+        };
+
+        let check = self
+            .stall_detector
+            .check(Instant::now(), self.sync_progress.last(), behind);
+
+        if let Some(health) = check.health {
+            self.state.lock().await.set_sync_health(health);
+            self.event_sender
+                .send(SyncEvent::SyncHealth(health))
+                .await
+                .ok();
+        }
+
+        if !check.recover {
+            return;
+        }
+
+        let ip = match mem::take(&mut self.initial_wallet_sync) {
+            InitialWalletSync::Idle => return,
+            InitialWalletSync::Syncing { ip, task } => {
+                task.abort();
+                ip
+            }
+            InitialWalletSync::Subscribed(ip) => ip,
+        };
+
+        let mut state = self.state.lock().await;
+
+        if state.peer_count() > 1 {
+            warn!("Wallet sync against peer {ip} has stalled, restarting it against another peer");
+            state.ban(ip, Duration::from_secs(300), "wallet sync stalled");
+        } else {
+            // Banning the only peer would stop the sync until the ban expires.
+            warn!("Wallet sync against peer {ip} has stalled, restarting it");
+        }
+
+        drop(state);
+
+        self.event_sender.send(SyncEvent::Stop).await.ok();
+    }
+
     async fn update_tasks(&mut self) {
         let state = self.state.lock().await;
 
@@ -342,15 +437,17 @@ impl SyncManager {
                             peer,
                             self.state.clone(),
                             self.event_sender.clone(),
+                            self.options.sync_retries,
+                            self.sync_progress.clone(),
                         ));
+                        self.stall_detector.watch(Instant::now());
                         *sync = InitialWalletSync::Syncing { ip, task };
                         self.event_sender.send(SyncEvent::Start(ip)).await.ok();
                     }
                 }
             }
-            InitialWalletSync::Syncing { ip, task }
-                if !state.is_connected(*ip) || self.wallet.is_none() =>
-            {
+            // Peers that drop out mid-sync are retried by the sync task itself.
+            InitialWalletSync::Syncing { task, .. } if self.wallet.is_none() => {
                 task.abort();
                 self.initial_wallet_sync = InitialWalletSync::Idle;
                 self.event_sender.send(SyncEvent::Stop).await.ok();
@@ -369,7 +466,7 @@ impl SyncManager {
                 let task = tokio::spawn(
                     PuzzleQueue::new(
                         wallet.db.clone(),
-                        wallet.genesis_challenge,
+                        wallet.network.genesis_challenge(),
                         self.state.clone(),
                         self.event_sender.clone(),
                         self.command_sender.clone(),
@@ -393,19 +490,18 @@ impl SyncManager {
                 }
             }
 
-            if self.nft_uri_queue_task.is_none() && !self.options.testing {
-                let task = tokio::spawn(
-                    NftUriQueue::new(wallet.db.clone(), self.event_sender.clone())
-                        .start(self.options.timeouts.nft_uri_delay),
-                );
-                self.nft_uri_queue_task = Some(task);
+            if self.metadata_worker.is_none() {
+                if let Some(options) = self.options.metadata_worker.clone() {
+                    self.metadata_worker =
+                        Some(wallet.spawn_metadata_worker(options, self.event_sender.clone()));
+                }
             }
 
             if self.transaction_queue_task.is_none() {
                 let task = tokio::spawn(
                     TransactionQueue::new(
                         wallet.db.clone(),
-                        wallet.genesis_challenge,
+                        wallet.network.genesis_challenge(),
                         self.state.clone(),
                         self.event_sender.clone(),
                     )
@@ -418,7 +514,7 @@ impl SyncManager {
                 let task = tokio::spawn(
                     OfferQueue::new(
                         wallet.db.clone(),
-                        wallet.genesis_challenge,
+                        wallet.network.genesis_challenge(),
                         self.state.clone(),
                         self.event_sender.clone(),
                     )
@@ -429,7 +525,7 @@ impl SyncManager {
         } else {
             self.puzzle_lookup_task = None;
             self.cat_queue_task = None;
-            self.nft_uri_queue_task = None;
+            self.metadata_worker = None;
             self.transaction_queue_task = None;
             self.offer_queue_task = None;
         }
@@ -439,8 +535,8 @@ impl SyncManager {
         if let InitialWalletSync::Syncing { ip, task } = &mut self.initial_wallet_sync {
             if let Ok(Some(result)) = timeout(Duration::from_secs(1), poll_once(task)).await {
                 match result {
-                    Ok(Ok(())) => {
-                        self.initial_wallet_sync = InitialWalletSync::Subscribed(*ip);
+                    Ok(Ok(subscribed_ip)) => {
+                        self.initial_wallet_sync = InitialWalletSync::Subscribed(subscribed_ip);
                         self.event_sender.send(SyncEvent::Subscribed).await.ok();
                     }
                     Ok(Err(error)) => {
@@ -484,18 +580,18 @@ impl SyncManager {
             }
         }
 
-        if let Some(task) = &mut self.nft_uri_queue_task {
-            match poll_once(task).await {
+        if let Some(worker) = &mut self.metadata_worker {
+            match worker.poll().await {
                 Some(Err(error)) => {
-                    warn!("NFT URI queue failed with panic: {error}");
-                    self.nft_uri_queue_task = None;
+                    warn!("NFT metadata worker failed with panic: {error}");
+                    self.metadata_worker = None;
                 }
                 Some(Ok(Err(error))) => {
-                    warn!("NFT URI queue failed with error: {error}");
-                    self.nft_uri_queue_task = None;
+                    warn!("NFT metadata worker failed with error: {error}");
+                    self.metadata_worker = None;
                 }
                 Some(Ok(Ok(()))) => {
-                    self.nft_uri_queue_task = None;
+                    self.metadata_worker = None;
                 }
                 None => {}
             }
@@ -536,3 +632,69 @@ impl SyncManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_stalled_sync_restarted_against_only_peer() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+        let state = Arc::new(Mutex::new(PeerState::default()));
+
+        let (mut sync_manager, _sender, mut events) = SyncManager::new(
+            SyncOptions {
+                target_peers: 0,
+                discover_peers: false,
+                dns_batch_size: 0,
+                connection_batch_size: 0,
+                max_peer_age_seconds: 0,
+                sync_retries: 0,
+                timeouts: Timeouts {
+                    // Every sync is stalled as soon as it starts.
+                    sync_stall: Duration::ZERO,
+                    ..Default::default()
+                },
+                metadata_worker: None,
+                testing: true,
+            },
+            state.clone(),
+            Some(test.wallet.clone()),
+            "testnet11".to_string(),
+            Network::default_testnet11(),
+            Connector::Plain,
+        );
+
+        let (peer, receiver) = test.sim.connect_raw().await?;
+        let ip = peer.socket_addr().ip();
+        assert!(sync_manager.try_add_peer(peer, receiver, true).await);
+
+        sync_manager.update_tasks().await;
+        assert!(matches!(events.recv().await, Some(SyncEvent::Start(start)) if start == ip));
+
+        sync_manager.check_sync_health().await;
+        assert!(matches!(
+            events.recv().await,
+            Some(SyncEvent::SyncHealth(SyncHealth::Stalled))
+        ));
+        assert!(matches!(events.recv().await, Some(SyncEvent::Stop)));
+
+        // The only peer isn't banned, and the health can be read from the peer state.
+        {
+            let state = state.lock().await;
+            assert!(state.is_connected(ip));
+            assert!(!state.is_banned(ip));
+            assert_eq!(state.sync_health(), SyncHealth::Stalled);
+        }
+
+        // So the sync is restarted against the same peer.
+        sync_manager.update_tasks().await;
+        assert!(matches!(events.recv().await, Some(SyncEvent::Start(start)) if start == ip));
+
+        Ok(())
+    }
+}
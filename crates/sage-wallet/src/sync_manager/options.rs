@@ -1,21 +1,27 @@
 use std::time::Duration;
 
-#[derive(Debug, Clone, Copy)]
+use crate::MetadataWorkerOptions;
+
+#[derive(Debug, Clone)]
 pub struct SyncOptions {
     pub target_peers: usize,
     pub discover_peers: bool,
     pub dns_batch_size: usize,
     pub connection_batch_size: usize,
     pub max_peer_age_seconds: u64,
+    pub sync_retries: usize,
     pub timeouts: Timeouts,
+    /// How NFT metadata is fetched in the background, or `None` if it isn't fetched.
+    pub metadata_worker: Option<MetadataWorkerOptions>,
     pub testing: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Timeouts {
     pub sync_delay: Duration,
+    /// How long the wallet sync can go without syncing anything before it's restarted.
+    pub sync_stall: Duration,
     pub cat_delay: Duration,
-    pub nft_uri_delay: Duration,
     pub puzzle_delay: Duration,
     pub transaction_delay: Duration,
     pub offer_delay: Duration,
@@ -30,8 +36,8 @@ impl Default for Timeouts {
     fn default() -> Self {
         Self {
             sync_delay: Duration::from_secs(1),
+            sync_stall: Duration::from_secs(120),
             cat_delay: Duration::from_secs(1),
-            nft_uri_delay: Duration::from_secs(1),
             puzzle_delay: Duration::from_secs(1),
             transaction_delay: Duration::from_secs(1),
             offer_delay: Duration::from_secs(5),
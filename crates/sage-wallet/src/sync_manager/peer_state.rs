@@ -11,6 +11,8 @@ use tracing::debug;
 
 use crate::WalletPeer;
 
+use super::SyncHealth;
+
 #[derive(Debug)]
 pub struct PeerInfo {
     pub peer: WalletPeer,
@@ -30,6 +32,7 @@ pub struct PeerState {
     peers: HashMap<IpAddr, PeerInfo>,
     banned_peers: HashMap<IpAddr, u64>,
     trusted_peers: HashSet<IpAddr>,
+    sync_health: SyncHealth,
 }
 
 impl PeerState {
@@ -135,6 +138,15 @@ impl PeerState {
         self.peers.insert(state.peer.socket_addr().ip(), state);
     }
 
+    /// The health of the wallet sync, as last reported by the sync manager.
+    pub fn sync_health(&self) -> SyncHealth {
+        self.sync_health
+    }
+
+    pub(super) fn set_sync_health(&mut self, health: SyncHealth) {
+        self.sync_health = health;
+    }
+
     pub fn trusted_peers(&self) -> &HashSet<IpAddr> {
         &self.trusted_peers
     }
@@ -3,6 +3,8 @@ use std::net::IpAddr;
 use chia::protocol::{Bytes32, CoinState};
 use sage_database::OfferStatus;
 
+use super::SyncHealth;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SyncEvent {
     Start(IpAddr),
@@ -11,6 +13,9 @@ pub enum SyncEvent {
     DerivationIndex {
         next_index: u32,
     },
+    DerivationLimitReached {
+        max_index: u32,
+    },
     CoinsUpdated {
         coin_states: Vec<CoinState>,
     },
@@ -26,4 +31,9 @@ pub enum SyncEvent {
     CatInfo,
     DidInfo,
     NftData,
+    NftPrefetch {
+        fetched: u32,
+        pending: u32,
+    },
+    SyncHealth(SyncHealth),
 }
@@ -0,0 +1,152 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Whether the wallet is keeping up with the network, as far as the sync manager can tell.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SyncHealth {
+    #[default]
+    Healthy,
+    /// Nothing has been synced for too long while there were still heights to sync.
+    /// The sync is restarted from the last peak that was synced, against another peer if possible.
+    Stalled,
+}
+
+/// The time that a batch of coin states was last synced, which is shared with the sync task.
+#[derive(Debug, Clone)]
+pub struct SyncProgress(Arc<Mutex<Instant>>);
+
+impl Default for SyncProgress {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+}
+
+impl SyncProgress {
+    pub fn record(&self) {
+        *self.0.lock().expect("poisoned") = Instant::now();
+    }
+
+    pub fn last(&self) -> Instant {
+        *self.0.lock().expect("poisoned")
+    }
+}
+
+/// The outcome of checking for a stall.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StallCheck {
+    /// The new health, if it changed.
+    pub health: Option<SyncHealth>,
+    /// Whether the sync should be restarted.
+    pub recover: bool,
+}
+
+/// Detects when the sync stops making progress, based on the time since the last synced batch.
+#[derive(Debug)]
+pub(crate) struct StallDetector {
+    timeout: Duration,
+    health: SyncHealth,
+    /// When the current sync started, so that it has the full timeout to make progress.
+    watch_since: Instant,
+    stalled_at: Option<Instant>,
+}
+
+impl StallDetector {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            health: SyncHealth::Healthy,
+            watch_since: Instant::now(),
+            stalled_at: None,
+        }
+    }
+
+    /// Starts the timeout over, such as when a new sync is started.
+    pub fn watch(&mut self, now: Instant) {
+        self.watch_since = now;
+    }
+
+    /// Checks whether the sync has stalled, given when the last batch was synced, and whether
+    /// there are still heights to sync. Once stalled, the sync is healthy again as soon as
+    /// another batch is synced.
+    pub fn check(&mut self, now: Instant, last_progress: Instant, behind: bool) -> StallCheck {
+        let mut check = StallCheck::default();
+
+        if self
+            .stalled_at
+            .is_some_and(|stalled_at| last_progress > stalled_at)
+        {
+            self.stalled_at = None;
+            self.set_health(SyncHealth::Healthy, &mut check);
+        }
+
+        let since = last_progress.max(self.watch_since);
+
+        if behind && now.saturating_duration_since(since) >= self.timeout {
+            self.stalled_at = Some(now);
+            self.watch_since = now;
+            self.set_health(SyncHealth::Stalled, &mut check);
+            check.recover = true;
+        }
+
+        check
+    }
+
+    fn set_health(&mut self, health: SyncHealth, check: &mut StallCheck) {
+        if self.health != health {
+            self.health = health;
+            check.health = Some(health);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_detection() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let mut detector = StallDetector::new(Duration::from_secs(60));
+        detector.watch(start);
+
+        // Progress within the timeout is fine, as is being caught up.
+        assert_eq!(detector.check(at(30), start, true), StallCheck::default());
+        assert_eq!(detector.check(at(90), start, false), StallCheck::default());
+
+        // No batch has been synced for the whole timeout, so recovery is triggered.
+        assert_eq!(
+            detector.check(at(90), start, true),
+            StallCheck {
+                health: Some(SyncHealth::Stalled),
+                recover: true,
+            }
+        );
+
+        // The restarted sync gets the full timeout before it's recovered again.
+        assert_eq!(detector.check(at(120), start, true), StallCheck::default());
+        assert_eq!(
+            detector.check(at(150), start, true),
+            StallCheck {
+                health: None,
+                recover: true,
+            }
+        );
+
+        // Once a batch is synced, the sync is healthy again.
+        assert_eq!(
+            detector.check(at(160), at(155), true),
+            StallCheck {
+                health: Some(SyncHealth::Healthy),
+                recover: false,
+            }
+        );
+        assert_eq!(
+            detector.check(at(200), at(190), true),
+            StallCheck::default()
+        );
+    }
+}
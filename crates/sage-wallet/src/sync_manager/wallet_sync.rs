@@ -1,6 +1,8 @@
 use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use chia::{
@@ -12,26 +14,36 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tokio::{
     sync::{mpsc, Mutex},
     task::spawn_blocking,
-    time::{sleep, timeout},
+    time::sleep,
 };
 use tracing::{debug, info, warn};
 
-use crate::{delete_puzzle, upsert_coin, UpsertCounters, Wallet, WalletError, WalletPeer};
+use crate::{
+    delete_puzzle, peer_timeout, upsert_coin, UpsertCounters, Wallet, WalletError, WalletPeer,
+};
 
-use super::{PeerState, SyncEvent};
+use super::{PeerState, SyncEvent, SyncProgress};
 
+/// Syncs the wallet against the given peer, falling back to other connected peers if requests fail.
+/// Returns the IP address of the peer that holds the wallet's subscriptions once finished.
+/// Each batch that is synced is recorded in the progress, so that stalls can be detected.
 pub async fn sync_wallet(
     wallet: Arc<Wallet>,
     peer: WalletPeer,
     state: Arc<Mutex<PeerState>>,
     sync_sender: mpsc::Sender<SyncEvent>,
-) -> Result<(), WalletError> {
+    max_retries: usize,
+    progress: SyncProgress,
+) -> Result<IpAddr, WalletError> {
     info!("Starting sync against peer {}", peer.socket_addr());
 
-    let p2_puzzle_hashes = wallet.db.p2_puzzle_hashes().await?;
+    let mut session = SyncSession::new(peer, state.clone(), max_retries, progress);
+
+    let subscribed = wallet.db.subscribed_p2_puzzle_hashes().await?;
+    let unsubscribed = wallet.db.unsubscribed_p2_puzzle_hashes().await?;
 
     let (start_height, start_header_hash) = wallet.db.latest_peak().await?.map_or_else(
-        || (None, wallet.genesis_challenge),
+        || (None, wallet.network.genesis_challenge()),
         |(peak, header_hash)| (Some(peak), header_hash),
     );
 
@@ -42,37 +54,72 @@ pub async fn sync_wallet(
 
     sync_coin_ids(
         &wallet,
-        &peer,
+        &mut session,
         start_height,
         start_header_hash,
         coin_ids,
-        sync_sender.clone(),
+        &sync_sender,
     )
     .await?;
 
-    let mut derive_more = p2_puzzle_hashes.is_empty();
+    let mut derive_more = subscribed.is_empty() && unsubscribed.is_empty();
 
-    for batch in p2_puzzle_hashes.chunks(500) {
+    // Puzzle hashes that were subscribed to previously have been synced up to the latest peak,
+    // so only what changed since then needs to be fetched.
+    for batch in subscribed.chunks(500) {
         derive_more |= sync_puzzle_hashes(
             &wallet,
-            &peer,
+            &mut session,
             start_height,
             start_header_hash,
             batch,
-            sync_sender.clone(),
+            &sync_sender,
+        )
+        .await?;
+    }
+
+    // Derivations that have never been subscribed to need their full history.
+    for batch in unsubscribed.chunks(500) {
+        derive_more |= sync_puzzle_hashes(
+            &wallet,
+            &mut session,
+            None,
+            wallet.network.genesis_challenge(),
+            batch,
+            &sync_sender,
         )
         .await?;
     }
 
-    let mut start_index = p2_puzzle_hashes.len() as u32;
+    let mut start_index = (subscribed.len() + unsubscribed.len()) as u32;
 
     while derive_more {
         derive_more = false;
 
+        // Deriving stops at the limit rather than failing the sync, so that what's been synced is kept.
+        // The user is notified so that they can raise the limit if the wallet needs more addresses.
+        let end_index = (start_index + 500).min(wallet.options.max_derivation_index);
+
+        if start_index >= end_index {
+            warn!(
+                "Reached the maximum derivation index of {}",
+                wallet.options.max_derivation_index
+            );
+
+            sync_sender
+                .send(SyncEvent::DerivationLimitReached {
+                    max_index: wallet.options.max_derivation_index,
+                })
+                .await
+                .ok();
+
+            break;
+        }
+
         let intermediate_pk = wallet.intermediate_pk;
 
         let new_derivations = spawn_blocking(move || {
-            (start_index..start_index + 500)
+            (start_index..end_index)
                 .into_par_iter()
                 .map(|index| {
                     let synthetic_key = intermediate_pk.derive_unhardened(index).derive_synthetic();
@@ -108,60 +155,242 @@ pub async fn sync_wallet(
         for batch in p2_puzzle_hashes.chunks(500) {
             derive_more |= sync_puzzle_hashes(
                 &wallet,
-                &peer,
+                &mut session,
                 None,
-                wallet.genesis_challenge,
+                wallet.network.genesis_challenge(),
                 batch,
-                sync_sender.clone(),
+                &sync_sender,
             )
             .await?;
         }
     }
 
-    if let Some((height, header_hash)) = state.lock().await.peak_of(peer.socket_addr().ip()) {
+    let ip = session.peer.socket_addr().ip();
+
+    if let Some((height, header_hash)) = state.lock().await.peak_of(ip) {
         // TODO: Maybe look into a better way.
         info!(
             "Updating peak from peer to {} with header hash {}",
             height, header_hash
         );
-        wallet.db.insert_peak(height, header_hash).await?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        wallet
+            .db
+            .insert_peak(height, header_hash, timestamp)
+            .await?;
     } else {
         warn!("No peak found");
     }
 
-    Ok(())
+    Ok(ip)
+}
+
+/// A batch of subscriptions which has already been synced, and the point it was synced up to.
+/// These are replayed against the next peer if the current one fails, so that a retry only
+/// needs to fetch what changed since then rather than starting over.
+#[derive(Debug, Clone)]
+enum Checkpoint {
+    Coins {
+        coin_ids: Vec<Bytes32>,
+        height: Option<u32>,
+        header_hash: Bytes32,
+    },
+    Puzzles {
+        puzzle_hashes: Vec<Bytes32>,
+        height: Option<u32>,
+        header_hash: Bytes32,
+    },
+}
+
+#[derive(Debug)]
+struct SyncSession {
+    peer: WalletPeer,
+    state: Arc<Mutex<PeerState>>,
+    failed_peers: HashSet<IpAddr>,
+    checkpoints: Vec<Checkpoint>,
+    attempts: usize,
+    max_retries: usize,
+    progress: SyncProgress,
+}
+
+impl SyncSession {
+    fn new(
+        peer: WalletPeer,
+        state: Arc<Mutex<PeerState>>,
+        max_retries: usize,
+        progress: SyncProgress,
+    ) -> Self {
+        Self {
+            peer,
+            state,
+            failed_peers: HashSet::new(),
+            checkpoints: Vec::new(),
+            attempts: 0,
+            max_retries,
+            progress,
+        }
+    }
+
+    /// Bans the current peer and moves the session over to the next best one, replaying
+    /// everything that has been synced so far. Fails once the retry limit has been reached
+    /// or there are no other peers left to try.
+    async fn retry(
+        &mut self,
+        wallet: &Wallet,
+        sync_sender: &mpsc::Sender<SyncEvent>,
+        mut error: WalletError,
+    ) -> Result<(), WalletError> {
+        loop {
+            let ip = self.peer.socket_addr().ip();
+
+            warn!("Sync request to peer {ip} failed: {error}");
+
+            self.attempts += 1;
+            self.failed_peers.insert(ip);
+
+            let mut state = self.state.lock().await;
+
+            state.ban(ip, Duration::from_secs(300), "sync request failed");
+
+            if self.attempts > self.max_retries {
+                return Err(WalletError::SyncFailed(self.attempts));
+            }
+
+            let Some(peer) = state
+                .peers_with_heights()
+                .into_iter()
+                .filter(|(peer, _)| !self.failed_peers.contains(&peer.socket_addr().ip()))
+                .max_by_key(|(_, height)| *height)
+                .map(|(peer, _)| peer)
+            else {
+                return Err(WalletError::SyncFailed(self.attempts));
+            };
+
+            drop(state);
+
+            info!(
+                "Retrying sync against peer {} (attempt {})",
+                peer.socket_addr(),
+                self.attempts + 1
+            );
+
+            self.peer = peer;
+
+            match self.replay(wallet, sync_sender).await {
+                Ok(()) => return Ok(()),
+                Err(replay_error) => error = replay_error,
+            }
+        }
+    }
+
+    async fn replay(
+        &mut self,
+        wallet: &Wallet,
+        sync_sender: &mpsc::Sender<SyncEvent>,
+    ) -> Result<(), WalletError> {
+        for checkpoint in &mut self.checkpoints {
+            match checkpoint {
+                Checkpoint::Coins {
+                    coin_ids,
+                    height,
+                    header_hash,
+                } => {
+                    let coin_states = peer_timeout(
+                        Duration::from_secs(10),
+                        self.peer
+                            .subscribe_coins(coin_ids.clone(), *height, *header_hash),
+                    )
+                    .await?;
+
+                    if !coin_states.is_empty() {
+                        incremental_sync(wallet, coin_states, true, sync_sender).await?;
+                    }
+
+                    self.progress.record();
+                }
+                Checkpoint::Puzzles {
+                    puzzle_hashes,
+                    height,
+                    header_hash,
+                } => loop {
+                    let data = peer_timeout(
+                        Duration::from_secs(45),
+                        self.peer.subscribe_puzzles(
+                            puzzle_hashes.clone(),
+                            *height,
+                            *header_hash,
+                            CoinStateFilters::new(true, true, true, 0),
+                        ),
+                    )
+                    .await?;
+
+                    if !data.coin_states.is_empty() {
+                        incremental_sync(wallet, data.coin_states, true, sync_sender).await?;
+                    }
+
+                    self.progress.record();
+
+                    *height = Some(data.height);
+                    *header_hash = data.header_hash;
+
+                    if data.is_finished {
+                        break;
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
 }
 
 async fn sync_coin_ids(
     wallet: &Wallet,
-    peer: &WalletPeer,
+    session: &mut SyncSession,
     start_height: Option<u32>,
     start_header_hash: Bytes32,
     coin_ids: Vec<Bytes32>,
-    sync_sender: mpsc::Sender<SyncEvent>,
+    sync_sender: &mpsc::Sender<SyncEvent>,
 ) -> Result<(), WalletError> {
     for (i, coin_ids) in coin_ids.chunks(10000).enumerate() {
         if i != 0 {
             sleep(Duration::from_millis(500)).await;
         }
 
-        debug!(
-            "Subscribing to {} coins from peer {}",
-            coin_ids.len(),
-            peer.socket_addr()
-        );
+        let coin_states = loop {
+            debug!(
+                "Subscribing to {} coins from peer {}",
+                coin_ids.len(),
+                session.peer.socket_addr()
+            );
+
+            let result = peer_timeout(
+                Duration::from_secs(10),
+                session
+                    .peer
+                    .subscribe_coins(coin_ids.to_vec(), start_height, start_header_hash),
+            )
+            .await;
 
-        let coin_states = timeout(
-            Duration::from_secs(10),
-            peer.subscribe_coins(coin_ids.to_vec(), start_height, start_header_hash),
-        )
-        .await??;
+            match result {
+                Ok(coin_states) => break coin_states,
+                Err(error) => session.retry(wallet, sync_sender, error).await?,
+            }
+        };
 
         debug!("Received {} coin states", coin_states.len());
 
         if !coin_states.is_empty() {
-            incremental_sync(wallet, coin_states, true, &sync_sender).await?;
+            incremental_sync(wallet, coin_states, true, sync_sender).await?;
         }
+
+        session.progress.record();
+
+        session.checkpoints.push(Checkpoint::Coins {
+            coin_ids: coin_ids.to_vec(),
+            height: start_height,
+            header_hash: start_header_hash,
+        });
     }
 
     Ok(())
@@ -169,11 +398,11 @@ async fn sync_coin_ids(
 
 async fn sync_puzzle_hashes(
     wallet: &Wallet,
-    peer: &WalletPeer,
+    session: &mut SyncSession,
     start_height: Option<u32>,
     start_header_hash: Bytes32,
     puzzle_hashes: &[Bytes32],
-    sync_sender: mpsc::Sender<SyncEvent>,
+    sync_sender: &mpsc::Sender<SyncEvent>,
 ) -> Result<bool, WalletError> {
     let mut prev_height = start_height;
     let mut prev_header_hash = start_header_hash;
@@ -184,27 +413,38 @@ async fn sync_puzzle_hashes(
             "Subscribing to puzzles at height {:?} and header hash {} from peer {}",
             prev_height,
             prev_header_hash,
-            peer.socket_addr()
+            session.peer.socket_addr()
         );
 
-        let data = timeout(
+        let result = peer_timeout(
             Duration::from_secs(45),
-            peer.subscribe_puzzles(
+            session.peer.subscribe_puzzles(
                 puzzle_hashes.to_vec(),
                 prev_height,
                 prev_header_hash,
                 CoinStateFilters::new(true, true, true, 0),
             ),
         )
-        .await??;
+        .await;
+
+        // If the request fails, the next peer picks up from the last height that was synced.
+        let data = match result {
+            Ok(data) => data,
+            Err(error) => {
+                session.retry(wallet, sync_sender, error).await?;
+                continue;
+            }
+        };
 
         debug!("Received {} coin states", data.coin_states.len());
 
         if !data.coin_states.is_empty() {
             found_coins = true;
-            incremental_sync(wallet, data.coin_states, true, &sync_sender).await?;
+            incremental_sync(wallet, data.coin_states, true, sync_sender).await?;
         }
 
+        session.progress.record();
+
         prev_height = Some(data.height);
         prev_header_hash = data.header_hash;
 
@@ -213,15 +453,88 @@ async fn sync_puzzle_hashes(
         }
     }
 
+    mark_subscribed(wallet, puzzle_hashes).await?;
+
+    session.checkpoints.push(Checkpoint::Puzzles {
+        puzzle_hashes: puzzle_hashes.to_vec(),
+        height: prev_height,
+        header_hash: prev_header_hash,
+    });
+
     Ok(found_coins)
 }
 
+/// Subscribes to the puzzle hashes which haven't been subscribed to yet, such as derivations
+/// that were added after the initial sync, and syncs their full history. Puzzle hashes that are
+/// already subscribed aren't sent to the peer again. Returns the puzzle hashes that were subscribed.
+pub async fn subscribe_new_puzzle_hashes(
+    wallet: &Wallet,
+    peer: &WalletPeer,
+    sync_sender: &mpsc::Sender<SyncEvent>,
+) -> Result<Vec<Bytes32>, WalletError> {
+    let puzzle_hashes = wallet.db.unsubscribed_p2_puzzle_hashes().await?;
+
+    for batch in puzzle_hashes.chunks(500) {
+        let mut prev_height = None;
+        let mut prev_header_hash = wallet.network.genesis_challenge();
+
+        loop {
+            debug!(
+                "Subscribing to {} new puzzles at height {:?} from peer {}",
+                batch.len(),
+                prev_height,
+                peer.socket_addr()
+            );
+
+            let data = peer_timeout(
+                Duration::from_secs(45),
+                peer.subscribe_puzzles(
+                    batch.to_vec(),
+                    prev_height,
+                    prev_header_hash,
+                    CoinStateFilters::new(true, true, true, 0),
+                ),
+            )
+            .await?;
+
+            if !data.coin_states.is_empty() {
+                incremental_sync(wallet, data.coin_states, true, sync_sender).await?;
+            }
+
+            prev_height = Some(data.height);
+            prev_header_hash = data.header_hash;
+
+            if data.is_finished {
+                break;
+            }
+        }
+
+        mark_subscribed(wallet, batch).await?;
+    }
+
+    Ok(puzzle_hashes)
+}
+
+async fn mark_subscribed(wallet: &Wallet, puzzle_hashes: &[Bytes32]) -> Result<(), WalletError> {
+    let mut tx = wallet.db.tx().await?;
+
+    for &puzzle_hash in puzzle_hashes {
+        tx.mark_subscribed(puzzle_hash).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
 pub async fn incremental_sync(
     wallet: &Wallet,
     coin_states: Vec<CoinState>,
     derive_automatically: bool,
     sync_sender: &mpsc::Sender<SyncEvent>,
 ) -> Result<(), WalletError> {
+    let coin_states = merge_coin_states(coin_states);
+
     let mut tx = wallet.db.tx().await?;
 
     let start = Instant::now();
@@ -246,6 +559,7 @@ pub async fn incremental_sync(
     );
 
     let mut derived = false;
+    let mut limit_reached = false;
 
     let mut next_index = tx.derivation_index(false).await?;
 
@@ -256,12 +570,23 @@ pub async fn incremental_sync(
             .map_or(0, |index| index + 1);
 
         while next_index < max_index + 500 {
+            let end_index = (next_index + 500).min(wallet.options.max_derivation_index);
+
+            if next_index >= end_index {
+                warn!(
+                    "Reached the maximum derivation index of {}",
+                    wallet.options.max_derivation_index
+                );
+                limit_reached = true;
+                break;
+            }
+
             wallet
-                .insert_unhardened_derivations(&mut tx, next_index..next_index + 500)
+                .insert_unhardened_derivations(&mut tx, next_index..end_index)
                 .await?;
 
             derived = true;
-            next_index += 500;
+            next_index = end_index;
         }
     }
 
@@ -279,5 +604,223 @@ pub async fn incremental_sync(
             .ok();
     }
 
+    if limit_reached {
+        sync_sender
+            .send(SyncEvent::DerivationLimitReached {
+                max_index: wallet.options.max_derivation_index,
+            })
+            .await
+            .ok();
+    }
+
     Ok(())
 }
+
+/// Combines updates to the same coin within a batch into a single coin state, so that a coin
+/// which is created and spent in the same batch is recorded as spent regardless of the order
+/// the updates arrived in, rather than being overwritten by a stale unspent state.
+fn merge_coin_states(coin_states: Vec<CoinState>) -> Vec<CoinState> {
+    let mut merged = Vec::with_capacity(coin_states.len());
+    let mut indices = HashMap::new();
+
+    for coin_state in coin_states {
+        let coin_id = coin_state.coin.coin_id();
+
+        if let Some(&index) = indices.get(&coin_id) {
+            let existing: &mut CoinState = &mut merged[index];
+            existing.created_height = existing.created_height.max(coin_state.created_height);
+            existing.spent_height = existing.spent_height.max(coin_state.spent_height);
+        } else {
+            indices.insert(coin_id, merged.len());
+            merged.push(coin_state);
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::Coin;
+    use test_log::test;
+
+    use crate::{TestWallet, WalletOptions};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_coin_created_and_spent_in_same_batch() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let coin = Coin::new(Bytes32::new([1; 32]), test.puzzle_hash, 500);
+        let (sync_sender, _sync_receiver) = mpsc::channel(10);
+
+        // The spent update arrives before the stale unspent one.
+        incremental_sync(
+            &test.wallet,
+            vec![
+                CoinState::new(coin, Some(11), Some(10)),
+                CoinState::new(coin, None, Some(10)),
+            ],
+            false,
+            &sync_sender,
+        )
+        .await?;
+
+        let coin_state = test
+            .wallet
+            .db
+            .coin_state(coin.coin_id())
+            .await?
+            .expect("missing coin");
+
+        assert_eq!(coin_state.created_height, Some(10));
+        assert_eq!(coin_state.spent_height, Some(11));
+        assert!(test.wallet.db.spendable_coins().await?.is_empty());
+        assert_eq!(test.wallet.db.balance().await?, 0);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_only_new_puzzle_hashes_subscribed() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        // Everything derived by the initial sync has already been subscribed to.
+        assert!(test
+            .wallet
+            .db
+            .unsubscribed_p2_puzzle_hashes()
+            .await?
+            .is_empty());
+
+        // A wallet that isn't managed by the sync manager, so that the subscriptions can be made manually.
+        let wallet = test.wallet_with(TestWallet::empty_database().await?, test.wallet.options);
+
+        let mut tx = wallet.db.tx().await?;
+        let puzzle_hashes = wallet.insert_unhardened_derivations(&mut tx, 0..10).await?;
+        tx.commit().await?;
+
+        assert_eq!(
+            wallet.db.unsubscribed_p2_puzzle_hashes().await?,
+            puzzle_hashes
+        );
+
+        // The coin was created before the subscription, so it's found by syncing the history.
+        test.sim.mint_coin(puzzle_hashes[3], 1000).await;
+
+        let peer = test.state.lock().await.acquire_peer().expect("no peer");
+        let (sync_sender, _sync_receiver) = mpsc::channel(10);
+
+        assert_eq!(
+            subscribe_new_puzzle_hashes(&wallet, &peer, &sync_sender).await?,
+            puzzle_hashes
+        );
+        assert_eq!(wallet.db.balance().await?, 1000);
+
+        // Finding the coin derives more puzzle hashes, which are the only ones left to subscribe to.
+        let derived = subscribe_new_puzzle_hashes(&wallet, &peer, &sync_sender).await?;
+        assert_eq!(derived.len(), 500);
+        assert!(derived
+            .iter()
+            .all(|puzzle_hash| !puzzle_hashes.contains(puzzle_hash)));
+
+        assert!(subscribe_new_puzzle_hashes(&wallet, &peer, &sync_sender)
+            .await?
+            .is_empty());
+
+        // Adding derivations only subscribes to the new puzzle hashes.
+        let mut tx = wallet.db.tx().await?;
+        let new_puzzle_hashes = wallet
+            .insert_unhardened_derivations(&mut tx, 510..520)
+            .await?;
+        tx.commit().await?;
+
+        assert_eq!(
+            subscribe_new_puzzle_hashes(&wallet, &peer, &sync_sender).await?,
+            new_puzzle_hashes
+        );
+        assert_eq!(wallet.db.subscribed_p2_puzzle_hashes().await?.len(), 520);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_derivation_stops_at_max_index() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let mut wallet = test.wallet_with(
+            TestWallet::empty_database().await?,
+            WalletOptions {
+                max_derivation_index: 100,
+                ..test.wallet.options
+            },
+        );
+
+        let mut tx = wallet.db.tx().await?;
+        let puzzle_hashes = wallet.insert_unhardened_derivations(&mut tx, 0..10).await?;
+        tx.commit().await?;
+
+        test.sim.mint_coin(puzzle_hashes[3], 1000).await;
+
+        let peer = test.state.lock().await.acquire_peer().expect("no peer");
+        let (sync_sender, mut sync_receiver) = mpsc::channel(10);
+
+        subscribe_new_puzzle_hashes(&wallet, &peer, &sync_sender).await?;
+        assert_eq!(wallet.db.balance().await?, 1000);
+
+        // Finding the coin would normally derive 500 more, but only those up to the limit are derived.
+        assert_eq!(wallet.db.derivation_index(false).await?, 100);
+
+        // The user is told that the limit was reached, so that they can raise it.
+        let mut events = Vec::new();
+        while let Ok(event) = sync_receiver.try_recv() {
+            events.push(event);
+        }
+        assert!(events.contains(&SyncEvent::DerivationLimitReached { max_index: 100 }));
+
+        // Lowering the limit below the derivation index doesn't affect keys that are already derived.
+        wallet.options.max_derivation_index = 50;
+        assert_eq!(wallet.backfill_derivations().await?, 0);
+        let synthetic_key = wallet.derive_synthetic_key(3, false).await?;
+        assert_eq!(
+            wallet
+                .synthetic_key(StandardArgs::curry_tree_hash(synthetic_key).into())
+                .await?,
+            synthetic_key
+        );
+        wallet.options.max_derivation_index = 100;
+
+        // Deriving past the limit has to be allowed explicitly.
+        let mut tx = wallet.db.tx().await?;
+        assert!(matches!(
+            wallet.insert_unhardened_derivations(&mut tx, 95..101).await,
+            Err(WalletError::DerivationLimitReached {
+                index: 100,
+                max: 100
+            })
+        ));
+        drop(tx);
+
+        assert!(matches!(
+            wallet.derive_synthetic_key(100, false).await,
+            Err(WalletError::DerivationLimitReached {
+                index: 100,
+                max: 100
+            })
+        ));
+
+        wallet.options.max_derivation_index = 200;
+        let mut tx = wallet.db.tx().await?;
+        assert_eq!(
+            wallet
+                .insert_unhardened_derivations(&mut tx, 95..101)
+                .await?
+                .len(),
+            1
+        );
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,12 @@
 use std::{sync::Arc, time::Duration};
 
 use chia::{
-    bls::{master_to_wallet_unhardened_intermediate, DerivableKey, SecretKey},
-    protocol::{Bytes32, CoinSpend, SpendBundle},
+    bls::{DerivableKey, SecretKey, Signature},
+    clvm_utils::tree_hash_atom,
+    protocol::{Bytes32, CoinSpend, Program, SpendBundle},
     puzzles::{standard::StandardArgs, DeriveSynthetic},
 };
-use chia_wallet_sdk::{
-    test_secret_key, AggSigConstants, Connector, Network, Peer, PeerSimulator, TESTNET11_CONSTANTS,
-};
+use chia_wallet_sdk::{test_secret_key, AggSigConstants, Connector, Network, Peer, PeerSimulator};
 use sage_database::Database;
 use sqlx::{migrate, SqlitePool};
 use tokio::{
@@ -20,8 +19,8 @@ use tokio::{
 use tracing::debug;
 
 use crate::{
-    insert_transaction, PeerState, SyncCommand, SyncEvent, SyncManager, SyncOptions, Timeouts,
-    Transaction, Wallet,
+    insert_transaction, DerivationPath, MetadataWorkerOptions, PeerState, SyncCommand, SyncEvent,
+    SyncManager, SyncOptions, Timeouts, Transaction, Wallet, WalletNetwork, WalletOptions,
 };
 
 static INDEX: Mutex<u32> = Mutex::const_new(0);
@@ -43,18 +42,24 @@ pub struct TestWallet {
 impl TestWallet {
     pub async fn new(balance: u64) -> anyhow::Result<Self> {
         let sim = PeerSimulator::new().await?;
-        Self::with_sim(Arc::new(sim), balance, 0).await
+        Self::with_sim(Arc::new(sim), balance, 0, None).await
+    }
+
+    /// A wallet whose sync manager also runs the NFT metadata worker with the given options.
+    pub async fn with_metadata_worker(
+        balance: u64,
+        options: MetadataWorkerOptions,
+    ) -> anyhow::Result<Self> {
+        let sim = PeerSimulator::new().await?;
+        Self::with_sim(Arc::new(sim), balance, 0, Some(options)).await
     }
 
     pub async fn next(&self, balance: u64) -> anyhow::Result<Self> {
-        Self::with_sim(self.sim.clone(), balance, self.index + 1).await
+        Self::with_sim(self.sim.clone(), balance, self.index + 1, None).await
     }
 
-    async fn with_sim(
-        sim: Arc<PeerSimulator>,
-        balance: u64,
-        key_index: u32,
-    ) -> anyhow::Result<Self> {
+    /// A migrated in-memory database which isn't shared with any other test.
+    pub async fn empty_database() -> anyhow::Result<Database> {
         let db_index = {
             let mut lock = INDEX.lock().await;
             let index = *lock;
@@ -64,29 +69,55 @@ impl TestWallet {
         let pool =
             SqlitePool::connect(&format!("file:testdb{db_index}?mode=memory&cache=shared")).await?;
         migrate!("../../migrations").run(&pool).await?;
-        let db = Database::new(pool);
+        Ok(Database::new(pool))
+    }
+
+    /// The same wallet on the given database and with the given options. It isn't synced by the
+    /// sync manager, so it only changes when the test changes it.
+    pub fn wallet_with(&self, db: Database, options: WalletOptions) -> Wallet {
+        Wallet::new(
+            db,
+            self.wallet.fingerprint,
+            &self.master_sk.public_key(),
+            self.wallet.derivation_path.clone(),
+            self.wallet.network.clone(),
+            options,
+        )
+    }
+
+    async fn with_sim(
+        sim: Arc<PeerSimulator>,
+        balance: u64,
+        key_index: u32,
+        metadata_worker: Option<MetadataWorkerOptions>,
+    ) -> anyhow::Result<Self> {
+        let db = Self::empty_database().await?;
 
         let sk = test_secret_key()?.derive_unhardened(key_index);
         let pk = sk.public_key();
         let fingerprint = pk.get_fingerprint();
-        let intermediate_pk = master_to_wallet_unhardened_intermediate(&pk);
-        let genesis_challenge = TESTNET11_CONSTANTS.genesis_challenge;
-
-        let puzzle_hash =
-            StandardArgs::curry_tree_hash(intermediate_pk.derive_unhardened(0).derive_synthetic());
-
-        if balance > 0 {
-            sim.mint_coin(puzzle_hash.into(), balance).await;
-        }
 
         let state = Arc::new(Mutex::new(PeerState::default()));
         let wallet = Arc::new(Wallet::new(
             db,
             fingerprint,
-            intermediate_pk,
-            genesis_challenge,
+            &pk,
+            DerivationPath::default(),
+            WalletNetwork::simulator(),
+            WalletOptions::default(),
         ));
 
+        let puzzle_hash = StandardArgs::curry_tree_hash(
+            wallet
+                .intermediate_pk
+                .derive_unhardened(0)
+                .derive_synthetic(),
+        );
+
+        if balance > 0 {
+            sim.mint_coin(puzzle_hash.into(), balance).await;
+        }
+
         let (mut sync_manager, sender, events) = SyncManager::new(
             SyncOptions {
                 target_peers: 0,
@@ -94,15 +125,16 @@ impl TestWallet {
                 dns_batch_size: 0,
                 connection_batch_size: 0,
                 max_peer_age_seconds: 0,
+                sync_retries: 0,
                 timeouts: Timeouts {
                     sync_delay: Duration::from_millis(100),
-                    nft_uri_delay: Duration::from_millis(100),
                     cat_delay: Duration::from_millis(100),
                     puzzle_delay: Duration::from_millis(100),
                     transaction_delay: Duration::from_millis(100),
                     offer_delay: Duration::from_millis(100),
                     ..Default::default()
                 },
+                metadata_worker,
                 testing: true,
             },
             state.clone(),
@@ -124,7 +156,7 @@ impl TestWallet {
 
         let mut test = TestWallet {
             sim,
-            agg_sig: AggSigConstants::new(TESTNET11_CONSTANTS.agg_sig_me_additional_data),
+            agg_sig: WalletNetwork::simulator().agg_sig_constants(),
             peer,
             wallet,
             master_sk: sk,
@@ -159,7 +191,7 @@ impl TestWallet {
         let subscriptions = insert_transaction(
             &self.wallet.db,
             &peer,
-            TESTNET11_CONSTANTS.genesis_challenge,
+            self.wallet.network.genesis_challenge(),
             spend_bundle.name(),
             Transaction::from_coin_spends(spend_bundle.coin_spends)?,
             spend_bundle.aggregated_signature,
@@ -175,6 +207,29 @@ impl TestWallet {
         Ok(())
     }
 
+    /// Advances the simulator by one block, by spending a coin that doesn't belong to any wallet.
+    /// Every connected peer is notified of the new peak.
+    pub async fn advance_height(&self) -> anyhow::Result<()> {
+        // The puzzle `1` returns its solution, which is nil, so the spend has no conditions.
+        let coin = self.sim.mint_coin(tree_hash_atom(&[1]).into(), 0).await;
+
+        let ack = self
+            .peer
+            .send_transaction(SpendBundle::new(
+                vec![CoinSpend::new(
+                    coin,
+                    Program::from(vec![1]),
+                    Program::from(vec![0x80]),
+                )],
+                Signature::default(),
+            ))
+            .await?;
+
+        anyhow::ensure!(ack.status == 1, "transaction rejected: {:?}", ack.error);
+
+        Ok(())
+    }
+
     pub async fn consume_until(&mut self, f: impl Fn(SyncEvent) -> bool) {
         loop {
             let next = timeout(Duration::from_secs(10), self.events.recv())
@@ -199,4 +254,76 @@ impl TestWallet {
         self.consume_until(|event| matches!(event, SyncEvent::PuzzleBatchSynced))
             .await;
     }
+
+    /// Switches to the same wallet with an empty database, as if it had been restored from the
+    /// mnemonic, and waits for it to sync.
+    pub async fn resync_from_scratch(&mut self) -> anyhow::Result<()> {
+        let wallet = Arc::new(self.wallet_with(Self::empty_database().await?, self.wallet.options));
+
+        self.sender
+            .send(SyncCommand::SwitchWallet {
+                wallet: Some(wallet.clone()),
+            })
+            .await?;
+
+        self.wallet = wallet;
+        self.wait_for_puzzles().await;
+
+        Ok(())
+    }
+}
+
+/// An offer of a freshly issued CAT, which is locked in the settlement puzzle by the eve spend,
+/// for a payment of XCH. It isn't signed, so it can be encoded but never taken.
+#[cfg(test)]
+pub(crate) fn test_offer() -> anyhow::Result<chia_wallet_sdk::Offer> {
+    use chia::puzzles::offer::{Payment, SETTLEMENT_PAYMENTS_PUZZLE_HASH};
+    use chia_wallet_sdk::{Cat, Conditions, OfferBuilder, SpendContext};
+
+    let mut ctx = SpendContext::new();
+
+    let (_conditions, _cat) = Cat::single_issuance_eve(
+        &mut ctx,
+        Bytes32::new([1; 32]),
+        1000,
+        Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, None),
+    )?;
+    let coin_spends = ctx.take();
+
+    let settlement = ctx.settlement_payments_puzzle()?;
+
+    let (_assertions, builder) = OfferBuilder::new(Bytes32::default())
+        .request(
+            &mut ctx,
+            &settlement,
+            vec![Payment::new(Bytes32::new([2; 32]), 500)],
+        )?
+        .finish();
+
+    Ok(builder.bundle(
+        &mut ctx,
+        SpendBundle::new(coin_spends, Signature::default()),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_advance_height() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let height = test.sim.height().await;
+
+        test.advance_height().await?;
+        test.advance_height().await?;
+
+        assert_eq!(test.sim.height().await, height + 2);
+        assert_eq!(test.wallet.db.balance().await?, 1000);
+
+        Ok(())
+    }
 }
@@ -1,11 +1,25 @@
 mod fetch_nft_did;
 mod fetch_nft_offer_details;
 mod fetch_uri;
+mod known_cats;
+mod metadata_cache;
+mod nft_ext;
 mod offchain_metadata;
+mod offer_chunks;
+mod offer_encoding;
+mod offer_ext;
+mod onchain_metadata;
 mod submit;
 
 pub use fetch_nft_did::*;
 pub use fetch_nft_offer_details::*;
 pub use fetch_uri::*;
+pub use known_cats::*;
+pub use metadata_cache::*;
+pub use nft_ext::*;
 pub use offchain_metadata::*;
+pub use offer_chunks::*;
+pub use offer_encoding::*;
+pub use offer_ext::*;
+pub use onchain_metadata::*;
 pub use submit::*;
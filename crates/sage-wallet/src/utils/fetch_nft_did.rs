@@ -11,9 +11,9 @@ use chia::{
 };
 use chia_wallet_sdk::{run_puzzle, Condition, Conditions, DidInfo, HashedPtr, NftInfo, Puzzle};
 use clvmr::{Allocator, NodePtr};
-use tokio::time::{sleep, timeout};
+use tokio::time::sleep;
 
-use crate::{WalletError, WalletPeer};
+use crate::{peer_timeout, WalletError, WalletPeer};
 
 pub async fn fetch_nft_did(
     peer: &WalletPeer,
@@ -29,11 +29,11 @@ pub async fn fetch_nft_did(
             if let Some(coin_spend) = pending_coin_spends.get(&parent_id).cloned() {
                 (false, coin_spend)
             } else {
-                let Some(parent_spend) = timeout(
+                let Some(parent_spend) = peer_timeout(
                     Duration::from_secs(15),
                     peer.fetch_optional_coin_spend(parent_id, genesis_challenge),
                 )
-                .await??
+                .await?
                 else {
                     break;
                 };
@@ -68,7 +68,7 @@ pub async fn fetch_nft_did(
         {
             coin_spend
         } else {
-            let child = timeout(Duration::from_secs(5), peer.fetch_child(launcher_id)).await??;
+            let child = peer_timeout(Duration::from_secs(5), peer.fetch_child(launcher_id)).await?;
             let spent_height = child.spent_height.ok_or(WalletError::PeerMisbehaved)?;
             let (puzzle_reveal, solution) = peer
                 .fetch_puzzle_solution(child.coin.coin_id(), spent_height)
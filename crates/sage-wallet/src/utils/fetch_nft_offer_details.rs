@@ -6,9 +6,9 @@ use chia::{
 };
 use chia_wallet_sdk::{HashedPtr, Nft, Puzzle};
 use clvmr::Allocator;
-use tokio::time::{sleep, timeout};
+use tokio::time::sleep;
 
-use crate::{RequestedNft, WalletError, WalletPeer};
+use crate::{peer_timeout, RequestedNft, WalletError, WalletPeer};
 
 pub async fn fetch_nft_offer_details(
     peer: &WalletPeer,
@@ -20,7 +20,7 @@ pub async fn fetch_nft_offer_details(
 
     loop {
         let Some(child) =
-            timeout(Duration::from_secs(5), peer.try_fetch_child(current_id)).await??
+            peer_timeout(Duration::from_secs(5), peer.try_fetch_child(current_id)).await?
         else {
             return Ok(None);
         };
@@ -34,14 +34,14 @@ pub async fn fetch_nft_offer_details(
 
         let parent = parent.expect("parent not found");
 
-        let (parent_puzzle_reveal, parent_solution) = timeout(
+        let (parent_puzzle_reveal, parent_solution) = peer_timeout(
             Duration::from_secs(15),
             peer.fetch_puzzle_solution(
                 parent.coin.coin_id(),
                 parent.spent_height.ok_or(WalletError::PeerMisbehaved)?,
             ),
         )
-        .await??;
+        .await?;
 
         let mut allocator = Allocator::new();
         let parent_puzzle = parent_puzzle_reveal.to_clvm(&mut allocator)?;
@@ -62,6 +62,7 @@ pub async fn fetch_nft_offer_details(
                 metadata_updater_puzzle_hash: nft.info.metadata_updater_puzzle_hash,
                 royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
                 royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
+                memos: Vec::new(),
             });
             break;
         }
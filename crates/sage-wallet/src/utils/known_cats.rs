@@ -0,0 +1,166 @@
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::timeout;
+
+use crate::WalletError;
+
+/// The token list that's bundled with the wallet, so that known CATs can be named offline.
+const BUNDLED_KNOWN_CATS: &str = include_str!("known_cats.json");
+
+/// The number of CATs fetched when refreshing the token list.
+const REFRESH_PAGE_SIZE: usize = 100;
+
+/// A CAT from a token list, with enough information to display it by name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownCat {
+    pub asset_id: String,
+    pub name: String,
+    pub ticker: String,
+    pub decimals: u8,
+    pub icon: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    assets: Vec<AssetData>,
+}
+
+#[derive(Deserialize)]
+struct AssetData {
+    id: String,
+    name: Option<String>,
+    code: Option<String>,
+}
+
+/// Parses a token list, sorted by name.
+pub fn parse_known_cats(json: &str) -> Result<Vec<KnownCat>, WalletError> {
+    let mut cats: Vec<KnownCat> = serde_json::from_str(json)?;
+    sort_known_cats(&mut cats);
+    Ok(cats)
+}
+
+/// The mainnet token list that's bundled with the wallet, sorted by name.
+pub fn bundled_known_cats() -> Vec<KnownCat> {
+    parse_known_cats(BUNDLED_KNOWN_CATS).expect("invalid bundled token list")
+}
+
+/// Fetches the most traded CATs from dexie.space, sorted by name.
+/// CATs that don't have both a name and a ticker are skipped. The token list doesn't include
+/// decimals, so they're taken from the bundled token list, or are the default if the CAT isn't in it.
+pub async fn fetch_known_cats(
+    testnet: bool,
+    default_decimals: u8,
+) -> Result<Vec<KnownCat>, WalletError> {
+    let (dexie_base_url, dexie_image_base_url) = if testnet {
+        (
+            "https://api-testnet.dexie.space/v1",
+            "https://icons-testnet.dexie.space",
+        )
+    } else {
+        ("https://api.dexie.space/v1", "https://icons.dexie.space")
+    };
+
+    let response = timeout(
+        Duration::from_secs(10),
+        reqwest::get(format!(
+            "{dexie_base_url}/assets?page_size={REFRESH_PAGE_SIZE}&page=1&type=cat"
+        )),
+    )
+    .await??
+    .json::<Response>()
+    .await?;
+
+    Ok(known_cats_from_assets(
+        response.assets,
+        dexie_image_base_url,
+        default_decimals,
+    ))
+}
+
+fn known_cats_from_assets(
+    assets: Vec<AssetData>,
+    image_base_url: &str,
+    default_decimals: u8,
+) -> Vec<KnownCat> {
+    let bundled_decimals: HashMap<String, u8> = bundled_known_cats()
+        .into_iter()
+        .map(|cat| (cat.asset_id, cat.decimals))
+        .collect();
+
+    let mut cats: Vec<KnownCat> = assets
+        .into_iter()
+        .filter_map(|asset| {
+            Some(KnownCat {
+                icon: Some(format!("{image_base_url}/{}.webp", asset.id)),
+                decimals: bundled_decimals
+                    .get(&asset.id)
+                    .copied()
+                    .unwrap_or(default_decimals),
+                asset_id: asset.id,
+                name: asset.name?,
+                ticker: asset.code?,
+            })
+        })
+        .collect();
+
+    sort_known_cats(&mut cats);
+
+    cats
+}
+
+fn sort_known_cats(cats: &mut [KnownCat]) {
+    cats.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.asset_id.cmp(&b.asset_id))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_known_cats() {
+        let cats = bundled_known_cats();
+        assert!(!cats.is_empty());
+
+        for cat in &cats {
+            assert_eq!(cat.asset_id.len(), 64);
+            assert!(cat.asset_id.chars().all(|c| c.is_ascii_hexdigit()));
+            assert!(!cat.name.is_empty());
+            assert!(!cat.ticker.is_empty());
+        }
+
+        assert!(cats.windows(2).all(|pair| pair[0].name <= pair[1].name));
+    }
+
+    #[test]
+    fn test_fetched_cat_decimals() {
+        let bundled = bundled_known_cats()
+            .into_iter()
+            .find(|cat| cat.decimals != 6)
+            .expect("no bundled CAT");
+        let unknown = "00".repeat(32);
+
+        let assets = [bundled.asset_id.clone(), unknown.clone()]
+            .into_iter()
+            .map(|id| AssetData {
+                id,
+                name: Some("Name".to_string()),
+                code: Some("CODE".to_string()),
+            })
+            .collect();
+
+        let cats = known_cats_from_assets(assets, "https://icons.dexie.space", 6);
+        let decimals = |asset_id: &str| {
+            cats.iter()
+                .find(|cat| cat.asset_id == asset_id)
+                .map(|cat| cat.decimals)
+        };
+
+        assert_eq!(decimals(&bundled.asset_id), Some(bundled.decimals));
+        assert_eq!(decimals(&unknown), Some(6));
+    }
+}
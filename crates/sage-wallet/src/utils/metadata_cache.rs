@@ -0,0 +1,68 @@
+use std::{io, path::PathBuf};
+
+use chia::protocol::Bytes32;
+use clvmr::sha2::Sha256;
+use tokio::fs;
+use tracing::debug;
+
+use crate::Data;
+
+/// A directory of previously fetched NFT data, keyed by the hash of its content.
+///
+/// Each item is stored as a file named after its hash, with the mime type in a file next to it.
+/// Content is hashed again when it's read, so a corrupted or tampered file is never served.
+#[derive(Debug, Clone)]
+pub struct MetadataCache {
+    dir: PathBuf,
+}
+
+impl MetadataCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Reads the cached data with the given hash, if it exists and matches the hash.
+    pub async fn get(&self, hash: Bytes32) -> Option<Data> {
+        let (blob_path, mime_path) = self.paths(hash);
+
+        let blob = fs::read(&blob_path).await.ok()?;
+        let mime_type = fs::read_to_string(&mime_path).await.ok()?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(&blob);
+        let found = Bytes32::new(hasher.finalize());
+
+        if found != hash {
+            debug!(
+                "Removing cached NFT data with hash mismatch, expected {hash} but found {found}"
+            );
+            fs::remove_file(&blob_path).await.ok();
+            fs::remove_file(&mime_path).await.ok();
+            return None;
+        }
+
+        Some(Data {
+            blob,
+            mime_type,
+            hash,
+        })
+    }
+
+    /// Writes the data to the cache, creating the directory if needed.
+    pub async fn insert(&self, data: &Data) -> io::Result<()> {
+        let (blob_path, mime_path) = self.paths(data.hash);
+
+        fs::create_dir_all(&self.dir).await?;
+        fs::write(&mime_path, &data.mime_type).await?;
+        fs::write(&blob_path, &data.blob).await?;
+
+        Ok(())
+    }
+
+    fn paths(&self, hash: Bytes32) -> (PathBuf, PathBuf) {
+        (
+            self.dir.join(hash.to_string()),
+            self.dir.join(format!("{hash}.mime")),
+        )
+    }
+}
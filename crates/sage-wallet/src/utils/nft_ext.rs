@@ -0,0 +1,79 @@
+use chia::clvm_traits::ToClvm;
+use chia_wallet_sdk::{HashedPtr, Nft, SpendContext};
+use clvmr::Allocator;
+
+use crate::WalletError;
+
+pub trait NftExt {
+    /// Allocates the metadata in the spend context, so that the NFT can be spent with it.
+    /// The tree hash is computed once up front, rather than each time the puzzle is curried.
+    fn hashed(&self, ctx: &mut SpendContext) -> Result<Nft<HashedPtr>, WalletError>;
+}
+
+impl<M> NftExt for Nft<M>
+where
+    M: Clone + ToClvm<Allocator>,
+{
+    fn hashed(&self, ctx: &mut SpendContext) -> Result<Nft<HashedPtr>, WalletError> {
+        let metadata_ptr = ctx.alloc(&self.info.metadata)?;
+        let metadata = HashedPtr::from_ptr(&ctx.allocator, metadata_ptr);
+        Ok(self.clone().with_metadata(metadata))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::{
+        clvm_traits::FromClvm,
+        clvm_utils::tree_hash,
+        protocol::{Bytes32, Coin, Program},
+        puzzles::{nft::NftMetadata, EveProof, Proof},
+    };
+    use chia_wallet_sdk::NftInfo;
+
+    use super::*;
+
+    #[test]
+    fn test_hashed_metadata_matches() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let original = NftMetadata {
+            data_uris: vec!["https://example.com/image.png".to_string()],
+            edition_number: 3,
+            edition_total: 10,
+            ..Default::default()
+        };
+        let metadata_ptr = ctx.alloc(&original)?;
+        let expected_hash = tree_hash(&ctx.allocator, metadata_ptr);
+        let metadata = Program::from_clvm(&ctx.allocator, metadata_ptr)?;
+
+        let nft = Nft::new(
+            Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1),
+            Proof::Eve(EveProof {
+                parent_parent_coin_info: Bytes32::new([3; 32]),
+                parent_amount: 1,
+            }),
+            NftInfo::new(
+                Bytes32::new([4; 32]),
+                metadata,
+                Bytes32::new([5; 32]),
+                None,
+                Bytes32::new([6; 32]),
+                300,
+                Bytes32::new([7; 32]),
+            ),
+        );
+
+        let hashed = nft.hashed(&mut ctx)?;
+
+        assert_eq!(hashed.info.metadata.tree_hash(), expected_hash);
+        assert_eq!(
+            NftMetadata::from_clvm(&ctx.allocator, hashed.info.metadata.ptr())?,
+            original
+        );
+        assert_eq!(hashed.coin, nft.coin);
+        assert_eq!(hashed.info.launcher_id, nft.info.launcher_id);
+
+        Ok(())
+    }
+}
@@ -57,17 +57,26 @@ pub fn compute_nft_info(did_id: Option<Bytes32>, blob: Option<&[u8]>) -> Compute
         }),
     ) = (did_id, json.collection)
     {
+        let attributes = attributes.unwrap_or_default();
+
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find_map(|item| match (item.key.as_deref(), &item.value) {
+                    (Some(item_key), Some(Value::String(value))) if item_key == key => {
+                        Some(value.clone())
+                    }
+                    _ => None,
+                })
+        };
+
         Some(CollectionRow {
             collection_id: calculate_collection_id(did_id, &metadata_collection_id),
             did_id,
             metadata_collection_id,
             name,
-            icon: attributes.unwrap_or_default().into_iter().find_map(|item| {
-                match (item.key.as_deref(), item.value) {
-                    (Some("icon"), Some(Value::String(icon))) => Some(icon),
-                    _ => None,
-                }
-            }),
+            icon: attribute("icon"),
+            banner: attribute("banner"),
             visible: true,
         })
     } else {
@@ -88,6 +97,33 @@ pub fn compute_nft_info(did_id: Option<Bytes32>, blob: Option<&[u8]>) -> Compute
     }
 }
 
+/// Extracts the traits of an NFT from its off-chain metadata, as pairs of trait type and value.
+/// Attributes that are malformed or have a non-scalar value are skipped.
+pub fn nft_traits(blob: Option<&[u8]>) -> Vec<(String, String)> {
+    let Some(Value::Array(attributes)) = blob
+        .and_then(|blob| serde_json::from_slice::<Value>(blob).ok())
+        .and_then(|mut json| json.get_mut("attributes").map(Value::take))
+    else {
+        return Vec::new();
+    };
+
+    attributes
+        .into_iter()
+        .filter_map(|attribute| {
+            let trait_type = attribute.get("trait_type")?.as_str()?.to_string();
+
+            let value = match attribute.get("value")? {
+                Value::String(value) => value.clone(),
+                Value::Number(value) => value.to_string(),
+                Value::Bool(value) => value.to_string(),
+                _ => return None,
+            };
+
+            Some((trait_type, value))
+        })
+        .collect()
+}
+
 fn offchain_metadata(blob: Option<&[u8]>) -> Option<OffchainMetadata> {
     serde_json::from_slice(blob?).ok()
 }
@@ -0,0 +1,155 @@
+use chia_wallet_sdk::Offer;
+
+use crate::{decode_offer, WalletError};
+
+/// The most chunks that an offer can be split into. The total comes from scanned input,
+/// so it's limited before anything is allocated for the chunks.
+pub const MAX_OFFER_CHUNKS: usize = 1000;
+
+/// Splits an encoded offer into chunks of at most `chunk_size` characters, each prefixed with a
+/// header of the form `index/total:`, starting at 1. This allows offers too large for a single QR code
+/// to be transferred as a sequence of them.
+pub fn split_offer(encoded: &str, chunk_size: usize) -> Vec<String> {
+    let chunk_size = chunk_size.max(1);
+    let data = encoded.as_bytes().chunks(chunk_size).collect::<Vec<_>>();
+    let total = data.len();
+
+    data.into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            // Bech32m strings are ASCII, so the chunks are always on character boundaries.
+            format!("{}/{total}:{}", index + 1, String::from_utf8_lossy(chunk))
+        })
+        .collect()
+}
+
+/// Parses the header of a chunk, returning its index, the total number of chunks, and its data.
+fn parse_chunk(chunk: &str) -> Result<(usize, usize, &str), WalletError> {
+    let invalid = || WalletError::InvalidOfferChunk(chunk.to_string());
+
+    let (header, data) = chunk.trim().split_once(':').ok_or_else(invalid)?;
+    let (index, total) = header.split_once('/').ok_or_else(invalid)?;
+    let index: usize = index.parse().map_err(|_| invalid())?;
+    let total: usize = total.parse().map_err(|_| invalid())?;
+
+    if index == 0 || index > total || total > MAX_OFFER_CHUNKS || data.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok((index, total, data))
+}
+
+/// Reassembles the chunks of an offer into its encoded form. The chunks can be given in any order,
+/// such as the order they were scanned in, but every one of them must be present exactly once,
+/// and all of them must agree on the total.
+pub fn join_offer_chunks(chunks: &[String]) -> Result<String, WalletError> {
+    let mut parts: Vec<Option<&str>> = Vec::new();
+
+    for chunk in chunks {
+        let (index, total, data) = parse_chunk(chunk)?;
+
+        if parts.is_empty() {
+            parts = vec![None; total];
+        } else if parts.len() != total {
+            return Err(WalletError::InvalidOfferChunk(chunk.clone()));
+        }
+
+        if parts[index - 1].replace(data).is_some() {
+            return Err(WalletError::DuplicateOfferChunk { index });
+        }
+    }
+
+    if parts.is_empty() {
+        return Err(WalletError::MissingOfferChunk { index: 1 });
+    }
+
+    let mut encoded = String::new();
+
+    for (index, part) in parts.into_iter().enumerate() {
+        let Some(part) = part else {
+            return Err(WalletError::MissingOfferChunk { index: index + 1 });
+        };
+
+        encoded.push_str(part);
+    }
+
+    Ok(encoded)
+}
+
+/// Reassembles the chunks of an offer, and decodes it.
+pub fn decode_offer_chunks(chunks: &[String]) -> Result<Offer, WalletError> {
+    decode_offer(&join_offer_chunks(chunks)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_offer, OfferExt};
+
+    use super::*;
+
+    #[test]
+    fn test_split_and_join_offer() -> anyhow::Result<()> {
+        let offer = test_offer()?;
+        let encoded = offer.encode()?;
+
+        let mut chunks = split_offer(&encoded, 100);
+        let total = encoded.len().div_ceil(100);
+        assert_eq!(chunks.len(), total);
+        assert!(chunks[0].starts_with(&format!("1/{total}:offer1")));
+
+        // The chunks can be scanned in any order.
+        chunks.reverse();
+        assert_eq!(join_offer_chunks(&chunks)?, encoded);
+        assert_eq!(decode_offer_chunks(&chunks)?.offer_id(), offer.offer_id());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_offer_chunks_invalid() -> anyhow::Result<()> {
+        let encoded = test_offer()?.encode()?;
+        let chunks = split_offer(&encoded, 100);
+        assert!(chunks.len() > 2);
+
+        let mut missing = chunks.clone();
+        missing.remove(1);
+        assert!(matches!(
+            join_offer_chunks(&missing),
+            Err(WalletError::MissingOfferChunk { index: 2 })
+        ));
+
+        let mut duplicate = chunks.clone();
+        duplicate.push(chunks[0].clone());
+        assert!(matches!(
+            join_offer_chunks(&duplicate),
+            Err(WalletError::DuplicateOfferChunk { index: 1 })
+        ));
+
+        let mut mismatched = chunks.clone();
+        mismatched[0] = format!("1/{}:offer1", chunks.len() + 1);
+        assert!(matches!(
+            join_offer_chunks(&mismatched),
+            Err(WalletError::InvalidOfferChunk(..))
+        ));
+
+        assert!(matches!(
+            join_offer_chunks(&["1/18446744073709551615:offer1".to_string()]),
+            Err(WalletError::InvalidOfferChunk(..))
+        ));
+        assert!(matches!(
+            join_offer_chunks(&[format!("1/{}:offer1", MAX_OFFER_CHUNKS + 1)]),
+            Err(WalletError::InvalidOfferChunk(..))
+        ));
+
+        assert!(matches!(
+            join_offer_chunks(&[encoded]),
+            Err(WalletError::InvalidOfferChunk(..))
+        ));
+        assert!(matches!(
+            join_offer_chunks(&[]),
+            Err(WalletError::MissingOfferChunk { index: 1 })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::Read;
+
+use chia::{
+    protocol::SpendBundle,
+    puzzles::{
+        cat::{CAT_PUZZLE, CAT_PUZZLE_V1},
+        nft::{
+            NFT_METADATA_UPDATER_PUZZLE, NFT_OWNERSHIP_LAYER_PUZZLE, NFT_ROYALTY_TRANSFER_PUZZLE,
+            NFT_STATE_LAYER_PUZZLE,
+        },
+        offer::{SETTLEMENT_PAYMENTS_PUZZLE, SETTLEMENT_PAYMENTS_PUZZLE_V1},
+        singleton::SINGLETON_TOP_LAYER_PUZZLE,
+        standard::STANDARD_PUZZLE,
+    },
+    traits::Streamable,
+};
+use chia_wallet_sdk::{decode_offer_data, Offer};
+use flate2::{read::ZlibDecoder, Decompress, FlushDecompress};
+
+use crate::WalletError;
+
+/// The latest compression version, which is the one that the SDK encodes offers with.
+const LATEST_VERSION: u16 = 6;
+
+/// Each compression version adds puzzles to the end of the dictionary used by the previous one.
+/// The last version intentionally didn't add anything, to break compatibility with older wallets.
+fn compression_dictionary(version: u16) -> Vec<u8> {
+    let additions: [&[&[u8]]; 6] = [
+        &[&STANDARD_PUZZLE, &CAT_PUZZLE_V1],
+        &[&SETTLEMENT_PAYMENTS_PUZZLE_V1],
+        &[
+            &SINGLETON_TOP_LAYER_PUZZLE,
+            &NFT_STATE_LAYER_PUZZLE,
+            &NFT_OWNERSHIP_LAYER_PUZZLE,
+            &NFT_METADATA_UPDATER_PUZZLE,
+            &NFT_ROYALTY_TRANSFER_PUZZLE,
+        ],
+        &[&CAT_PUZZLE],
+        &[&SETTLEMENT_PAYMENTS_PUZZLE],
+        &[],
+    ];
+
+    additions[..usize::from(version)].concat().concat()
+}
+
+/// The largest spend bundle that an offer can decompress to. A spend bundle can't be larger than this
+/// and still fit in a block, since every byte adds to its cost, so anything beyond it is rejected rather
+/// than decompressed without bound.
+const MAX_DECOMPRESSED_OFFER_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Decompresses a zlib stream, providing the dictionary if the stream was compressed with one.
+/// Returns `None` if it decompresses to more than [`MAX_DECOMPRESSED_OFFER_SIZE`] bytes.
+fn zlib_decompress(input: &[u8], dictionary: &[u8]) -> Option<Vec<u8>> {
+    let mut decompress = Decompress::new(true);
+
+    if let Err(error) = decompress.decompress(input, &mut [], FlushDecompress::None) {
+        error.needs_dictionary()?;
+        decompress.set_dictionary(dictionary).ok()?;
+    }
+
+    let offset = usize::try_from(decompress.total_in()).ok()?;
+    let mut output = Vec::new();
+
+    ZlibDecoder::new_with_decompress(&input[offset..], decompress)
+        .take(MAX_DECOMPRESSED_OFFER_SIZE + 1)
+        .read_to_end(&mut output)
+        .ok()?;
+
+    if output.len() as u64 > MAX_DECOMPRESSED_OFFER_SIZE {
+        return None;
+    }
+
+    Some(output)
+}
+
+/// Decompresses the offer's spend bundle, using the dictionary of the version it was compressed with.
+fn decompress_offer(bytes: &[u8]) -> Option<Offer> {
+    let version = u16::from_be_bytes(bytes.get(..2)?.try_into().ok()?);
+
+    if version > LATEST_VERSION {
+        return None;
+    }
+
+    let decompressed = zlib_decompress(&bytes[2..], &compression_dictionary(version))?;
+
+    SpendBundle::from_bytes(&decompressed).ok().map(Offer::from)
+}
+
+/// Decodes an offer string, whether it was compressed with any version of the puzzle dictionary, or
+/// is the uncompressed spend bundle that older wallets produced. Both result in the same offer.
+pub fn decode_offer(text: &str) -> Result<Offer, WalletError> {
+    let bytes = decode_offer_data(text.trim())?;
+
+    if let Some(offer) = decompress_offer(&bytes) {
+        return Ok(offer);
+    }
+
+    SpendBundle::from_bytes(&bytes)
+        .map(Offer::from)
+        .map_err(|_| WalletError::UnknownOfferFormat)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_wallet_sdk::encode_offer_data;
+    use flate2::{read::ZlibEncoder, Compress, Compression};
+
+    use crate::{test_offer, OfferExt};
+
+    use super::*;
+
+    /// Compresses with the same settings as the reference wallet, to produce offers the way it would.
+    fn zlib_compress(input: &[u8], dictionary: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut compress = Compress::new(Compression::new(6), true);
+        compress.set_dictionary(dictionary)?;
+
+        let mut output = Vec::new();
+        ZlibEncoder::new_with_compress(input, compress).read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    const COMPRESSED_OFFER: &str = include_str!("./test_data/compressed.offer");
+    const DECOMPRESSED_OFFER: &str = include_str!("./test_data/decompressed.offer");
+
+    #[test]
+    fn test_decode_reference_offer() -> anyhow::Result<()> {
+        // An offer made by the reference wallet, along with its compressed form.
+        let offer = Offer::from(SpendBundle::from_bytes(&hex::decode(
+            DECOMPRESSED_OFFER.trim(),
+        )?)?);
+        let encoded = encode_offer_data(&hex::decode(COMPRESSED_OFFER.trim())?)?;
+
+        assert_eq!(decode_offer(&encoded)?.offer_id(), offer.offer_id());
+        assert_eq!(offer.encode()?, encoded);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_modern_offer() -> anyhow::Result<()> {
+        let offer = test_offer()?;
+
+        let decoded = decode_offer(&offer.encode()?)?;
+        assert_eq!(decoded.offer_id(), offer.offer_id());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_legacy_offer() -> anyhow::Result<()> {
+        let offer = test_offer()?;
+        let bytes = offer.to_bytes()?;
+
+        // Offers from before compression was introduced are the spend bundle on its own.
+        let uncompressed = encode_offer_data(&bytes)?;
+        assert_eq!(decode_offer(&uncompressed)?.offer_id(), offer.offer_id());
+
+        // An offer compressed with the smaller dictionary of an older version.
+        let mut compressed = 2u16.to_be_bytes().to_vec();
+        compressed.extend(zlib_compress(&bytes, &compression_dictionary(2))?);
+
+        let legacy = encode_offer_data(&compressed)?;
+        assert_eq!(decode_offer(&legacy)?.offer_id(), offer.offer_id());
+
+        // The current decoder only understands the latest dictionary.
+        assert!(Offer::decode(&legacy).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decompressed_size_limit() -> anyhow::Result<()> {
+        let dictionary = compression_dictionary(LATEST_VERSION);
+        let size = usize::try_from(MAX_DECOMPRESSED_OFFER_SIZE)?;
+
+        let compressed = zlib_compress(&vec![0; size], &dictionary)?;
+        assert_eq!(
+            zlib_decompress(&compressed, &dictionary).map(|output| output.len()),
+            Some(size)
+        );
+
+        // A few kilobytes that expand to just beyond the limit aren't decompressed.
+        let compressed = zlib_compress(&vec![0; size + 1], &dictionary)?;
+        assert!(compressed.len() < 10_000);
+        assert!(zlib_decompress(&compressed, &dictionary).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_unknown_offer_format() -> anyhow::Result<()> {
+        let mut bytes = LATEST_VERSION.to_be_bytes().to_vec();
+        bytes.extend_from_slice(b"not an offer");
+
+        assert!(matches!(
+            decode_offer(&encode_offer_data(&bytes)?),
+            Err(WalletError::UnknownOfferFormat)
+        ));
+
+        Ok(())
+    }
+}
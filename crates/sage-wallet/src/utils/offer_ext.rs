@@ -0,0 +1,56 @@
+use chia::protocol::{Bytes32, SpendBundle};
+use chia_wallet_sdk::Offer;
+
+pub trait OfferExt {
+    /// The canonical id of the offer, which is the hash of its spend bundle. This is the same as the
+    /// offer's name in the reference wallet, and is what marketplaces such as Dexie identify it by.
+    fn offer_id(&self) -> Bytes32;
+}
+
+impl OfferExt for Offer {
+    fn offer_id(&self) -> Bytes32 {
+        SpendBundle::from(self.clone()).name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::traits::Streamable;
+    use hex_literal::hex;
+
+    use super::*;
+
+    #[test]
+    fn test_offer_id() -> anyhow::Result<()> {
+        // A spend bundle serialized by hand in the streamable format: one coin spend of a coin with
+        // parent 0x0101.., puzzle hash 0x0202.. and amount 1000, whose puzzle is `1` and solution is
+        // `()`, followed by the infinity signature.
+        let bytes = [
+            hex!("00000001").as_slice(),
+            &[1; 32],
+            &[2; 32],
+            &hex!("00000000000003e8 01 80 c0"),
+            &[0; 95],
+        ]
+        .concat();
+
+        let offer = Offer::from(SpendBundle::from_bytes(&bytes)?);
+
+        // The reference wallet names an offer `std_hash(bytes(spend_bundle))`, which is the SHA-256
+        // of the bytes above, computed independently of this implementation.
+        assert_eq!(
+            offer.offer_id(),
+            Bytes32::new(hex!(
+                "3d22fe61cf2448f25163d340e633a44c4a1356693ef0b6a84f50fdc0783d76ab"
+            ))
+        );
+
+        // The id survives encoding and decoding the offer.
+        assert_eq!(
+            Offer::decode(&offer.encode()?)?.offer_id(),
+            offer.offer_id()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use chia::{
+    clvm_traits::{FromClvm, ToClvm},
+    protocol::{Bytes, Program},
+};
+use clvmr::{Allocator, NodePtr, SExp};
+use serde_json::{json, Value};
+
+/// The keys of the standard NFT metadata format, which point to the off-chain content.
+const STANDARD_KEYS: [&str; 8] = ["u", "h", "mu", "mh", "lu", "lh", "sn", "st"];
+
+/// Extracts the name and attributes that are stored directly in an NFT's on-chain metadata, as a
+/// JSON document in the off-chain metadata format. Returns `None` if there's neither.
+///
+/// The metadata is expected to be a list of key value pairs. The keys of the standard format are
+/// skipped, a `name` key is used as the name, and any other key with a UTF-8 value is an attribute.
+pub fn onchain_metadata_json(metadata: &Program) -> Option<Vec<u8>> {
+    let mut allocator = Allocator::new();
+    let ptr = metadata.to_clvm(&mut allocator).ok()?;
+    let pairs = Vec::<(Bytes, NodePtr)>::from_clvm(&allocator, ptr).ok()?;
+
+    let mut name = None;
+    let mut attributes = Vec::new();
+
+    for (key, value) in pairs {
+        let Ok(key) = std::str::from_utf8(key.as_ref()) else {
+            continue;
+        };
+
+        if STANDARD_KEYS.contains(&key) {
+            continue;
+        }
+
+        let SExp::Atom = allocator.sexp(value) else {
+            continue;
+        };
+
+        let Ok(value) = String::from_utf8(allocator.atom(value).as_ref().to_vec()) else {
+            continue;
+        };
+
+        if key == "name" {
+            name = Some(value);
+        } else {
+            attributes.push(json!({ "trait_type": key, "value": value }));
+        }
+    }
+
+    if name.is_none() && attributes.is_empty() {
+        return None;
+    }
+
+    serde_json::to_vec(&json!({ "name": name, "attributes": Value::Array(attributes) })).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::puzzles::nft::NftMetadata;
+
+    use crate::{compute_nft_info, nft_traits};
+
+    use super::*;
+
+    fn program(value: impl ToClvm<Allocator>) -> anyhow::Result<Program> {
+        let mut allocator = Allocator::new();
+        let ptr = value.to_clvm(&mut allocator)?;
+        Ok(Program::from_clvm(&allocator, ptr)?)
+    }
+
+    #[test]
+    fn test_onchain_metadata_json() -> anyhow::Result<()> {
+        let metadata = program(vec![
+            ("name", "On-chain NFT"),
+            ("Background", "Blue"),
+            ("sn", "1"),
+        ])?;
+
+        let blob = onchain_metadata_json(&metadata).expect("no metadata");
+
+        assert_eq!(
+            compute_nft_info(None, Some(&blob)).name.as_deref(),
+            Some("On-chain NFT")
+        );
+        assert_eq!(
+            nft_traits(Some(&blob)),
+            vec![("Background".to_string(), "Blue".to_string())]
+        );
+
+        // Standard metadata only points to off-chain content.
+        let standard = program(NftMetadata::default())?;
+        assert!(onchain_metadata_json(&standard).is_none());
+
+        Ok(())
+    }
+}
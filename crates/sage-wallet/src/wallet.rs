@@ -1,43 +1,131 @@
-use chia::{bls::PublicKey, protocol::Bytes32};
+use chia::bls::PublicKey;
+use chia_wallet_sdk::MAINNET_CONSTANTS;
 use sage_database::Database;
+use tokio::sync::Mutex;
 
+use crate::{DerivationPath, WalletNetwork};
+
+mod address_sync;
+mod balances;
 mod cat_coin_management;
 mod cat_spends;
 mod cats;
+mod clawbacks;
+mod coin_cache;
 mod coin_selection;
+mod custom_spends;
 mod derivations;
 mod did_assign;
 mod dids;
+mod fees;
+mod height_estimator;
+mod metadata_health;
+mod metadata_worker;
+mod nft_provenance;
 mod nfts;
 mod offer;
+mod offer_stats;
 mod p2_coin_management;
 mod p2_send;
 mod p2_spends;
+mod puzzle_kind;
+mod rarity;
 mod signing;
+mod spend_context_cache;
+mod sweep;
+mod throttle;
+mod transaction_export;
 
+pub use balances::*;
+pub use coin_cache::SpendableCoinCache;
+pub use coin_selection::CoinSelectionMode;
+pub use derivations::DEFAULT_MAX_DERIVATION_INDEX;
+pub use metadata_health::*;
+pub use metadata_worker::*;
+pub use nft_provenance::*;
 pub use nfts::WalletNftMint;
 pub use offer::*;
+pub use offer_stats::*;
+pub use puzzle_kind::*;
+pub use signing::UnverifiedSignature;
+pub use spend_context_cache::*;
+
+/// The configurable behavior of a wallet, which is set from its config when it's logged into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalletOptions {
+    /// The maximum cost of each transaction, when an operation splits its spends across several.
+    pub max_bundle_cost: u64,
+    /// The maximum number of coin spends in an offer made by this wallet, since the offer
+    /// and the taker's spends must fit within a single transaction.
+    pub max_offer_spends: usize,
+    /// Coins worth less than this are dust, which is left out of coin selection and the balance.
+    /// It's still spent when sweeping the wallet.
+    pub dust_threshold: u64,
+    /// Coins must be created at least this many blocks below the peak before they're selected,
+    /// so that they're less likely to be undone by a reorg.
+    pub min_confirmations: u32,
+    /// How coins are chosen when spending part of the balance.
+    pub coin_selection_mode: CoinSelectionMode,
+    /// Limits how many unhardened keys are derived, to bound resource use if derivation runs away.
+    /// Deriving up to or past the limit fails, and the sync stops deriving once it's reached.
+    pub max_derivation_index: u32,
+    /// Makes operations that select coins wait for each other, when holding the guard returned
+    /// by [`Wallet::transaction_guard`]. This trades throughput for safety, since selected coins
+    /// aren't excluded from coin selection until the transaction spending them has been recorded.
+    pub serialize_transactions: bool,
+}
+
+impl Default for WalletOptions {
+    fn default() -> Self {
+        Self {
+            max_bundle_cost: MAINNET_CONSTANTS.max_block_cost_clvm / 2,
+            max_offer_spends: 50,
+            dust_threshold: 0,
+            min_confirmations: 0,
+            coin_selection_mode: CoinSelectionMode::default(),
+            max_derivation_index: DEFAULT_MAX_DERIVATION_INDEX,
+            serialize_transactions: false,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Wallet {
     pub db: Database,
     pub fingerprint: u32,
     pub intermediate_pk: PublicKey,
-    pub genesis_challenge: Bytes32,
+    /// The path that the intermediate public key was derived along from the master public key.
+    pub derivation_path: DerivationPath,
+    /// The network that the wallet's addresses, signatures and cost estimates are for.
+    pub network: WalletNetwork,
+    /// The limits and preferences that the wallet builds transactions with.
+    pub options: WalletOptions,
+    /// The spendable coins, kept between selections until the database changes.
+    pub spendable_coin_cache: SpendableCoinCache,
+    /// The spend context that's reused between offers and bulk operations.
+    pub spend_context_cache: SpendContextCache,
+    transaction_lock: Mutex<()>,
 }
 
 impl Wallet {
     pub fn new(
         db: Database,
         fingerprint: u32,
-        intermediate_pk: PublicKey,
-        genesis_challenge: Bytes32,
+        master_pk: &PublicKey,
+        derivation_path: DerivationPath,
+        network: WalletNetwork,
+        options: WalletOptions,
     ) -> Self {
         Self {
             db,
             fingerprint,
-            intermediate_pk,
-            genesis_challenge,
+            intermediate_pk: derivation_path.intermediate_pk(master_pk),
+            derivation_path,
+            network,
+            options,
+            spendable_coin_cache: SpendableCoinCache::default(),
+            spend_context_cache: SpendContextCache::default(),
+            transaction_lock: Mutex::new(()),
         }
     }
 }
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use chia::protocol::{Bytes32, CoinStateFilters};
+use tokio::sync::mpsc;
+
+use crate::{peer_timeout, sync_manager::incremental_sync, SyncEvent, WalletError, WalletPeer};
+
+use super::Wallet;
+
+impl Wallet {
+    /// Subscribes to and syncs the full history of only the given puzzle hashes, rather than every
+    /// derivation in the wallet. The coin states are merged into the database the same way as they
+    /// are during a full sync, including the [`SyncEvent::CoinsUpdated`] event, so this can be used
+    /// to quickly check the balance of an address. Returns the number of coin states that were
+    /// received.
+    pub async fn sync_addresses(
+        &self,
+        peer: &WalletPeer,
+        puzzle_hashes: &[Bytes32],
+        sync_sender: &mpsc::Sender<SyncEvent>,
+    ) -> Result<usize, WalletError> {
+        for &puzzle_hash in puzzle_hashes {
+            if !self.db.is_p2_puzzle_hash(puzzle_hash).await? {
+                return Err(WalletError::MissingDerivation(puzzle_hash));
+            }
+        }
+
+        let mut count = 0;
+
+        for batch in puzzle_hashes.chunks(500) {
+            let mut prev_height = None;
+            let mut prev_header_hash = self.network.genesis_challenge();
+
+            loop {
+                let data = peer_timeout(
+                    Duration::from_secs(45),
+                    peer.subscribe_puzzles(
+                        batch.to_vec(),
+                        prev_height,
+                        prev_header_hash,
+                        CoinStateFilters::new(true, true, true, 0),
+                    ),
+                )
+                .await?;
+
+                count += data.coin_states.len();
+
+                if !data.coin_states.is_empty() {
+                    incremental_sync(self, data.coin_states, true, sync_sender).await?;
+                }
+
+                prev_height = Some(data.height);
+                prev_header_hash = data.header_hash;
+
+                if data.is_finished {
+                    break;
+                }
+            }
+        }
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_sync_single_address() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        // A wallet that isn't synced by the sync manager, so only the chosen addresses are synced.
+        let wallet = test.wallet_with(TestWallet::empty_database().await?, test.wallet.options);
+
+        let mut tx = wallet.db.tx().await?;
+        let puzzle_hashes = wallet.insert_unhardened_derivations(&mut tx, 0..10).await?;
+        tx.commit().await?;
+
+        test.sim.mint_coin(puzzle_hashes[2], 500).await;
+        test.sim.mint_coin(puzzle_hashes[7], 300).await;
+
+        let peer = test.state.lock().await.acquire_peer().expect("no peer");
+        let (sender, mut receiver) = mpsc::channel(10);
+
+        assert_eq!(
+            wallet
+                .sync_addresses(&peer, &[puzzle_hashes[2]], &sender)
+                .await?,
+            1
+        );
+        assert_eq!(wallet.db.balance().await?, 500);
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(SyncEvent::CoinsUpdated { coin_states }) if coin_states.len() == 1
+        ));
+
+        // Syncing another address is merged with the coins that are already known.
+        assert_eq!(
+            wallet
+                .sync_addresses(&peer, &[puzzle_hashes[7]], &sender)
+                .await?,
+            1
+        );
+        assert_eq!(wallet.db.balance().await?, 800);
+        assert_eq!(wallet.db.spendable_coins().await?.len(), 2);
+
+        let unknown = Bytes32::new([42; 32]);
+        assert!(matches!(
+            wallet.sync_addresses(&peer, &[unknown], &sender).await,
+            Err(WalletError::MissingDerivation(puzzle_hash)) if puzzle_hash == unknown
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use chia::protocol::Bytes32;
+
+use crate::WalletError;
+
+use super::Wallet;
+
+/// The balance of an asset, divided by whether each coin can be spent right now.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Balances {
+    /// Coins that can be selected to spend, including dust.
+    pub spendable: u128,
+    /// Coins that are frozen, or offered by an active offer.
+    pub locked: u128,
+    /// Coins created by a transaction that hasn't been confirmed yet,
+    /// or that don't have enough confirmations to be selected.
+    pub pending: u128,
+}
+
+impl Balances {
+    /// The total balance, which is the same as the balance reported by the database.
+    pub fn total(&self) -> u128 {
+        self.spendable + self.locked + self.pending
+    }
+}
+
+impl Wallet {
+    /// Divides the balance of XCH, or the CAT with the given asset id, by whether it can be spent.
+    /// Coins being spent by a pending transaction aren't part of the balance at all.
+    pub async fn balances(&self, asset_id: Option<Bytes32>) -> Result<Balances, WalletError> {
+        let coins = if let Some(asset_id) = asset_id {
+            self.db.cat_balance_coins(asset_id).await?
+        } else {
+            self.db.p2_balance_coins().await?
+        };
+
+        let unconfirmed = self.unconfirmed_coin_ids().await?;
+        let mut balances = Balances::default();
+
+        for coin in coins {
+            let amount = coin.amount as u128;
+
+            if coin.pending {
+                balances.pending += amount;
+            } else if coin.locked {
+                balances.locked += amount;
+            } else if unconfirmed.contains(&coin.coin_id) {
+                balances.pending += amount;
+            } else {
+                balances.spendable += amount;
+            }
+        }
+
+        Ok(balances)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::bls::Signature;
+    use sage_database::{OfferRow, OfferStatus};
+    use test_log::test;
+
+    use crate::{TestWallet, WalletOptions};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_balances() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1500).await?;
+
+        let (coin_spends, asset_id) = test.wallet.issue_cat(500, 0, None, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let coins = test.wallet.db.spendable_coins().await?;
+        let coin_spends = test.wallet.split_xch(&coins, 4, 0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let coins = test.wallet.db.spendable_coins().await?;
+        assert_eq!(coins.len(), 4);
+
+        // One coin is frozen, one is offered, and one was created by a pending transaction.
+        test.wallet.db.freeze_coin(coins[0].coin_id()).await?;
+
+        let offer_id = Bytes32::new([1; 32]);
+        let transaction_id = Bytes32::new([2; 32]);
+        let pending = test
+            .wallet
+            .db
+            .coin_state(coins[2].coin_id())
+            .await?
+            .expect("missing coin");
+
+        let mut tx = test.wallet.db.tx().await?;
+        tx.insert_offer(OfferRow {
+            offer_id,
+            encoded_offer: String::new(),
+            expiration_height: None,
+            expiration_timestamp: None,
+            fee: 0,
+            status: OfferStatus::Active,
+            inserted_timestamp: 0,
+        })
+        .await?;
+        tx.insert_offered_coin(offer_id, coins[1].coin_id()).await?;
+        tx.insert_pending_transaction(transaction_id, Signature::default(), 0)
+            .await?;
+        tx.update_coin_state(
+            coins[2].coin_id(),
+            pending.created_height,
+            None,
+            Some(transaction_id),
+        )
+        .await?;
+        tx.commit().await?;
+
+        let balances = test.wallet.balances(None).await?;
+        assert_eq!(
+            balances,
+            Balances {
+                spendable: 250,
+                locked: 500,
+                pending: 250,
+            }
+        );
+        assert_eq!(balances.total(), test.wallet.db.balance().await?);
+
+        let cat_balances = test.wallet.balances(Some(asset_id)).await?;
+        assert_eq!(cat_balances.spendable, 500);
+        assert_eq!(
+            cat_balances.total(),
+            test.wallet.db.cat_balance(asset_id).await?
+        );
+
+        // Without enough confirmations, the spendable coin is pending as well.
+        let wallet = test.wallet_with(
+            test.wallet.db.clone(),
+            WalletOptions {
+                min_confirmations: 1000,
+                ..test.wallet.options
+            },
+        );
+
+        assert_eq!(
+            wallet.balances(None).await?,
+            Balances {
+                spendable: 0,
+                locked: 500,
+                pending: 500,
+            }
+        );
+
+        Ok(())
+    }
+}
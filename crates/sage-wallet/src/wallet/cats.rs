@@ -137,6 +137,7 @@ impl Wallet {
 
 #[cfg(test)]
 mod tests {
+    use chia::protocol::Bytes;
     use test_log::test;
 
     use crate::TestWallet;
@@ -190,6 +191,40 @@ mod tests {
         assert_eq!(test.wallet.db.cat_balance(asset_id).await?, 1000);
         assert_eq!(test.wallet.db.spendable_cat_coins(asset_id).await?.len(), 1);
 
+        Ok(())
+    }
+    #[test(tokio::test)]
+    async fn test_cat_balances_by_memo() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1000).await?;
+
+        let (coin_spends, asset_id) = test.wallet.issue_cat(1000, 0, None, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let memo = Bytes::new(b"savings".to_vec());
+
+        let coin_spends = test
+            .wallet
+            .send_cat(
+                asset_id,
+                test.puzzle_hash,
+                300,
+                0,
+                vec![memo.clone()],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        // The change only has a hint, so it's grouped with the coins that have no memo.
+        assert_eq!(
+            test.wallet.db.cat_balances_by_memo(asset_id).await?,
+            vec![(None, 700), (Some(memo), 300)]
+        );
+        assert_eq!(test.wallet.db.cat_balance(asset_id).await?, 1000);
+
         Ok(())
     }
 }
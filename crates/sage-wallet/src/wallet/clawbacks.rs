@@ -0,0 +1,220 @@
+use std::num::NonZeroU64;
+
+use chia::{
+    clvm_utils::ToTreeHash,
+    protocol::{Bytes32, CoinSpend},
+};
+use chia_wallet_sdk::{
+    Clawback, Conditions, Memos, SpendContext, SpendWithConditions, StandardLayer,
+};
+
+use crate::WalletError;
+
+use super::Wallet;
+
+impl Wallet {
+    /// Sends XCH to a clawback puzzle, which the recipient can claim once the timelock has passed.
+    /// Until it's claimed, the sender can claw the funds back at any time.
+    pub async fn send_with_clawback(
+        &self,
+        recipient: Bytes32,
+        amount: u64,
+        clawback_seconds: u64,
+        fee: u64,
+        hardened: bool,
+        reuse: bool,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        let Some(timelock) = NonZeroU64::new(clawback_seconds) else {
+            return Err(WalletError::InvalidClawbackTimelock);
+        };
+
+        let total = amount as u128 + fee as u128;
+        let coins = self.select_p2_coins(total).await?;
+        let selected: u128 = coins.iter().map(|coin| coin.amount as u128).sum();
+
+        let sender_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
+
+        let change: u64 = (selected - total)
+            .try_into()
+            .map_err(|_| WalletError::AmountOverflow { asset_id: None })?;
+
+        let clawback = Clawback {
+            timelock,
+            sender_puzzle_hash,
+            recipient_puzzle_hash: recipient,
+        };
+
+        let mut ctx = SpendContext::new();
+
+        // The memos allow both parties to identify the clawback coin while syncing.
+        // The recipient puzzle hash comes first, so that it's used as the hint.
+        let memos = ctx.alloc(&(recipient, (sender_puzzle_hash, (timelock.get(), ()))))?;
+
+        let mut conditions = Conditions::new().create_coin(
+            clawback.to_layer().tree_hash().into(),
+            amount,
+            Some(Memos::new(memos)),
+        );
+
+        if fee > 0 {
+            conditions = conditions.reserve_fee(fee);
+        }
+
+        if change > 0 {
+            conditions = conditions.create_coin(sender_puzzle_hash, change, None);
+        }
+
+        self.spend_p2_coins(&mut ctx, coins, conditions).await?;
+
+        Ok(ctx.take())
+    }
+
+    /// Returns a clawback coin to the sender, before it has been claimed by the recipient.
+    pub async fn claw_back(
+        &self,
+        coin_id: Bytes32,
+        fee: u64,
+        hardened: bool,
+        reuse: bool,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        self.spend_clawback(coin_id, fee, hardened, reuse, false)
+            .await
+    }
+
+    /// Claims a clawback coin as the recipient, after the timelock has passed.
+    pub async fn claim_clawback(
+        &self,
+        coin_id: Bytes32,
+        fee: u64,
+        hardened: bool,
+        reuse: bool,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        self.spend_clawback(coin_id, fee, hardened, reuse, true)
+            .await
+    }
+
+    async fn spend_clawback(
+        &self,
+        coin_id: Bytes32,
+        fee: u64,
+        hardened: bool,
+        reuse: bool,
+        claim: bool,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        let Some(row) = self.db.spendable_clawback_coin(coin_id).await? else {
+            return Err(WalletError::MissingCoin(coin_id));
+        };
+
+        let inner_puzzle_hash = if claim {
+            row.clawback.recipient_puzzle_hash
+        } else {
+            row.clawback.sender_puzzle_hash
+        };
+
+        if !self.db.is_p2_puzzle_hash(inner_puzzle_hash).await? {
+            return Err(WalletError::UnspendableCoin(coin_id));
+        }
+
+        let Some(amount) = row.coin.amount.checked_sub(fee) else {
+            return Err(WalletError::InsufficientFunds);
+        };
+
+        let p2_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
+
+        let mut ctx = SpendContext::new();
+
+        let mut conditions = Conditions::new();
+
+        if amount > 0 {
+            conditions = conditions.create_coin(p2_puzzle_hash, amount, None);
+        }
+
+        if fee > 0 {
+            conditions = conditions.reserve_fee(fee);
+        }
+
+        // The inner puzzle of either spend path is the standard puzzle of the spender.
+        let synthetic_key = self.synthetic_key(inner_puzzle_hash).await?;
+        let inner_spend =
+            StandardLayer::new(synthetic_key).spend_with_conditions(&mut ctx, conditions)?;
+
+        let spend = if claim {
+            row.clawback.claim_spend(&mut ctx, inner_spend)?
+        } else {
+            row.clawback.clawback_spend(&mut ctx, inner_spend)?
+        };
+
+        ctx.spend(row.coin, spend)?;
+
+        Ok(ctx.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    #[test(tokio::test)]
+    async fn test_claw_back() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_with_clawback(bob.puzzle_hash, 750, 3600, 250, false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 0);
+
+        let clawbacks = alice.wallet.db.clawback_coins().await?;
+        assert_eq!(clawbacks.len(), 1);
+        assert_eq!(clawbacks[0].coin.amount, 750);
+        assert_eq!(clawbacks[0].clawback.recipient_puzzle_hash, bob.puzzle_hash);
+
+        let coin_spends = alice
+            .wallet
+            .claw_back(clawbacks[0].coin.coin_id(), 0, false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 750);
+        assert!(alice.wallet.db.clawback_coins().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_claim_clawback() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(0).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_with_clawback(bob.puzzle_hash, 1000, 1, 0, false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+
+        bob.wait_for_puzzles().await;
+
+        let clawbacks = bob.wallet.db.clawback_coins().await?;
+        assert_eq!(clawbacks.len(), 1);
+        assert_eq!(clawbacks[0].clawback.sender_puzzle_hash, alice.puzzle_hash);
+
+        let coin_spends = bob
+            .wallet
+            .claim_clawback(clawbacks[0].coin.coin_id(), 0, false, true)
+            .await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        assert_eq!(bob.wallet.db.balance().await?, 1000);
+        assert!(bob.wallet.db.clawback_coins().await?.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use chia::protocol::{Bytes32, Coin};
+use sage_database::CatCoinRow;
+
+use crate::WalletError;
+
+use super::Wallet;
+
+/// An in-memory copy of the spendable coins in the database, so that selecting coins repeatedly
+/// doesn't query the database each time. The coins are keyed on the database's change count, so
+/// they're discarded as soon as anything is written to the database after they were read.
+#[derive(Debug, Default, Clone)]
+pub struct SpendableCoinCache(Arc<Mutex<CachedCoins>>);
+
+#[derive(Debug, Default)]
+struct CachedCoins {
+    /// The database change count that the cached coins were read at.
+    changes: u64,
+    p2: Option<Vec<Coin>>,
+    cats: HashMap<Bytes32, Vec<CatCoinRow>>,
+}
+
+impl CachedCoins {
+    /// Discards the coins if they were read before the given change count, and returns whether
+    /// coins read at that change count can be cached.
+    fn update(&mut self, changes: u64) -> bool {
+        if changes > self.changes {
+            self.changes = changes;
+            self.p2 = None;
+            self.cats.clear();
+        }
+
+        changes == self.changes
+    }
+}
+
+impl SpendableCoinCache {
+    /// Clears the cache, so that the next selection reads the coins from the database again.
+    pub fn invalidate(&self) {
+        let mut cached = self.0.lock().expect("poisoned");
+        cached.p2 = None;
+        cached.cats.clear();
+    }
+
+    fn p2(&self, changes: u64) -> Option<Vec<Coin>> {
+        let mut cached = self.0.lock().expect("poisoned");
+
+        if !cached.update(changes) {
+            return None;
+        }
+
+        cached.p2.clone()
+    }
+
+    fn cats(&self, changes: u64, asset_id: Bytes32) -> Option<Vec<CatCoinRow>> {
+        let mut cached = self.0.lock().expect("poisoned");
+
+        if !cached.update(changes) {
+            return None;
+        }
+
+        cached.cats.get(&asset_id).cloned()
+    }
+
+    fn insert_p2(&self, changes: u64, coins: Vec<Coin>) {
+        let mut cached = self.0.lock().expect("poisoned");
+
+        if cached.update(changes) {
+            cached.p2 = Some(coins);
+        }
+    }
+
+    fn insert_cats(&self, changes: u64, asset_id: Bytes32, coins: Vec<CatCoinRow>) {
+        let mut cached = self.0.lock().expect("poisoned");
+
+        if cached.update(changes) {
+            cached.cats.insert(asset_id, coins);
+        }
+    }
+}
+
+impl Wallet {
+    /// Forces the spendable coins to be read from the database the next time coins are selected,
+    /// for when the database has been changed by something other than this wallet.
+    pub fn refresh_spendable_coins(&self) {
+        self.spendable_coin_cache.invalidate();
+    }
+
+    /// The unspent p2 coins that aren't locked by a transaction or offer, from the cache if possible.
+    pub(crate) async fn cached_spendable_coins(&self) -> Result<Vec<Coin>, WalletError> {
+        let changes = self.db.change_count();
+
+        if let Some(coins) = self.spendable_coin_cache.p2(changes) {
+            return Ok(coins);
+        }
+
+        let coins = self.db.spendable_coins().await?;
+        self.spendable_coin_cache.insert_p2(changes, coins.clone());
+
+        Ok(coins)
+    }
+
+    /// The unspent coins of the CAT that aren't locked by a transaction or offer, from the cache if possible.
+    pub(crate) async fn cached_spendable_cat_coins(
+        &self,
+        asset_id: Bytes32,
+    ) -> Result<Vec<CatCoinRow>, WalletError> {
+        let changes = self.db.change_count();
+
+        if let Some(coins) = self.spendable_coin_cache.cats(changes, asset_id) {
+            return Ok(coins);
+        }
+
+        let coins = self.db.spendable_cat_coins(asset_id).await?;
+        self.spendable_coin_cache
+            .insert_cats(changes, asset_id, coins.clone());
+
+        Ok(coins)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    #[test(tokio::test)]
+    async fn test_spendable_coin_cache_invalidated_on_spend() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coins = alice.wallet.select_p2_coins(1000).await?;
+        assert_eq!(coins.len(), 1);
+
+        // The spend is pushed to the peer directly rather than recorded as a transaction,
+        // so the coin is only removed from the cache once the spend is synced.
+        let coin_spends = alice
+            .wallet
+            .send_xch(bob.puzzle_hash, 1000, 0, Vec::new(), false, true)
+            .await?;
+        let spend_bundle = alice
+            .wallet
+            .sign_transaction(coin_spends, &alice.agg_sig, alice.master_sk.clone(), false)
+            .await?;
+        alice.peer.send_transaction(spend_bundle).await?;
+        alice.wait_for_coins().await;
+
+        assert!(alice.wallet.select_p2_coins(1).await.is_err());
+        assert!(alice.wallet.cached_spendable_coins().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_spendable_coin_cache_invalidated_on_write() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let coin = alice.wallet.cached_spendable_coins().await?[0];
+
+        // Freezing the coin is a write outside of a transaction, which must still be seen.
+        alice.wallet.db.freeze_coin(coin.coin_id()).await?;
+        assert!(alice.wallet.cached_spendable_coins().await?.is_empty());
+
+        alice.wallet.db.unfreeze_coin(coin.coin_id()).await?;
+        assert_eq!(alice.wallet.cached_spendable_coins().await?, vec![coin]);
+
+        // As is a change to the coin's state made in a transaction.
+        let coin_state = alice
+            .wallet
+            .db
+            .coin_state(coin.coin_id())
+            .await?
+            .expect("missing coin");
+
+        let mut tx = alice.wallet.db.tx().await?;
+        tx.update_coin_state(coin.coin_id(), coin_state.created_height, Some(1), None)
+            .await?;
+        tx.commit().await?;
+
+        assert!(alice.wallet.cached_spendable_coins().await?.is_empty());
+
+        Ok(())
+    }
+}
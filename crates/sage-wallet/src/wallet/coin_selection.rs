@@ -1,17 +1,119 @@
-use std::collections::HashMap;
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
 
 use chia::protocol::{Bytes32, Coin};
-use chia_wallet_sdk::{select_coins, Cat};
+use chia_wallet_sdk::{select_coins, Cat, CoinSelectionError};
+use indexmap::IndexMap;
+use sage_database::BalanceCoin;
 
 use crate::WalletError;
 
 use super::Wallet;
 
+/// The most coins that are selected at once, which is the same limit as the default selection.
+const MAX_SELECTED_COINS: usize = 500;
+
+/// How coins are chosen to spend part of the balance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CoinSelectionMode {
+    /// Chooses the coins that best fit the amount, to keep the number of coins and the change small.
+    #[default]
+    Knapsack,
+    /// Spends the coins created at the lowest height first, like first in, first out accounting,
+    /// so that recently received coins are only spent once the older ones aren't enough.
+    Oldest,
+}
+
 impl Wallet {
-    /// Selects one or more unspent p2 coins from the database.
+    /// Previews which coins would be selected to spend the given amount, without spending them.
+    /// If no asset id is given, XCH coins are selected. The coins are sorted by amount descending,
+    /// and each is returned with its p2 puzzle hash, which is the inner puzzle hash for CATs.
+    pub async fn preview_coin_selection(
+        &self,
+        asset_id: Option<Bytes32>,
+        amount: u128,
+    ) -> Result<Vec<(Coin, Bytes32)>, WalletError> {
+        let mut coins: Vec<(Coin, Bytes32)> = if let Some(asset_id) = asset_id {
+            self.select_cat_coins(asset_id, amount)
+                .await?
+                .into_iter()
+                .map(|cat| (cat.coin, cat.p2_puzzle_hash))
+                .collect()
+        } else {
+            self.select_p2_coins(amount)
+                .await?
+                .into_iter()
+                .map(|coin| (coin, coin.puzzle_hash))
+                .collect()
+        };
+
+        coins.sort_by(|(a, _), (b, _)| {
+            b.amount
+                .cmp(&a.amount)
+                .then_with(|| a.coin_id().cmp(&b.coin_id()))
+        });
+
+        Ok(coins)
+    }
+
+    /// Selects one or more unspent p2 coins from the database, leaving out dust.
     pub(crate) async fn select_p2_coins(&self, amount: u128) -> Result<Vec<Coin>, WalletError> {
-        let spendable_coins = self.db.spendable_coins().await?;
-        Ok(select_coins(spendable_coins, amount)?)
+        let spendable_coins = self.selectable_p2_coins().await?;
+
+        match self.options.coin_selection_mode {
+            CoinSelectionMode::Knapsack => Ok(select_coins(spendable_coins, amount)?),
+            CoinSelectionMode::Oldest => {
+                let coins = self.db.p2_balance_coins().await?;
+                Ok(select_oldest_coins(spendable_coins, &coins, amount)?)
+            }
+        }
+    }
+
+    /// The total amount of the spendable coins that are below the dust threshold.
+    pub async fn dust_balance(&self) -> Result<u128, WalletError> {
+        if self.options.dust_threshold == 0 {
+            return Ok(0);
+        }
+
+        Ok(self
+            .db
+            .spendable_coins()
+            .await?
+            .into_iter()
+            .filter(|coin| coin.amount < self.options.dust_threshold)
+            .map(|coin| coin.amount as u128)
+            .sum())
+    }
+
+    async fn selectable_p2_coins(&self) -> Result<Vec<Coin>, WalletError> {
+        let unconfirmed = self.unconfirmed_coin_ids().await?;
+        let mut coins = self.cached_spendable_coins().await?;
+        coins.retain(|coin| {
+            coin.amount >= self.options.dust_threshold && !unconfirmed.contains(&coin.coin_id())
+        });
+        Ok(coins)
+    }
+
+    /// The ids of unspent coins that don't have enough confirmations to be selected yet.
+    pub(crate) async fn unconfirmed_coin_ids(&self) -> Result<HashSet<Bytes32>, WalletError> {
+        if self.options.min_confirmations == 0 {
+            return Ok(HashSet::new());
+        }
+
+        let Some((peak_height, _header_hash)) = self.db.latest_peak().await? else {
+            return Ok(HashSet::new());
+        };
+
+        let min_height = (peak_height + 1).saturating_sub(self.options.min_confirmations);
+
+        Ok(self
+            .db
+            .unspent_coin_ids_since(min_height)
+            .await?
+            .into_iter()
+            .collect())
     }
 
     /// Selects one or more unspent CAT coins from the database.
@@ -20,12 +122,55 @@ impl Wallet {
         asset_id: Bytes32,
         amount: u128,
     ) -> Result<Vec<Cat>, WalletError> {
-        let cat_coins = self.db.spendable_cat_coins(asset_id).await?;
+        let cats = self.spendable_cats(asset_id).await?;
+        let spendable_coins = cats.keys().copied().collect();
+
+        let selected = match self.options.coin_selection_mode {
+            CoinSelectionMode::Knapsack => select_coins(spendable_coins, amount)?,
+            CoinSelectionMode::Oldest => {
+                let coins = self.db.cat_balance_coins(asset_id).await?;
+                select_oldest_coins(spendable_coins, &coins, amount)?
+            }
+        };
+
+        Ok(selected.into_iter().map(|coin| cats[&coin]).collect())
+    }
+
+    /// Selects unspent p2 coins that add up to exactly the given amount, so that no change is needed.
+    pub(crate) async fn select_exact_p2_coins(
+        &self,
+        amount: u128,
+    ) -> Result<Vec<Coin>, WalletError> {
+        let spendable_coins = self.selectable_p2_coins().await?;
+        select_exact_coins(spendable_coins, amount).ok_or(WalletError::NoExactCoins)
+    }
+
+    /// Selects unspent CAT coins that add up to exactly the given amount, so that no change is needed.
+    pub(crate) async fn select_exact_cat_coins(
+        &self,
+        asset_id: Bytes32,
+        amount: u128,
+    ) -> Result<Vec<Cat>, WalletError> {
+        let cats = self.spendable_cats(asset_id).await?;
+
+        Ok(select_exact_coins(cats.keys().copied().collect(), amount)
+            .ok_or(WalletError::NoExactCoins)?
+            .into_iter()
+            .map(|coin| cats[&coin])
+            .collect())
+    }
 
-        let mut cats = HashMap::with_capacity(cat_coins.len());
-        let mut spendable_coins = Vec::with_capacity(cat_coins.len());
+    async fn spendable_cats(&self, asset_id: Bytes32) -> Result<IndexMap<Coin, Cat>, WalletError> {
+        let unconfirmed = self.unconfirmed_coin_ids().await?;
+        let cat_coins = self.cached_spendable_cat_coins(asset_id).await?;
+
+        let mut cats = IndexMap::with_capacity(cat_coins.len());
+
+        for cat in cat_coins {
+            if unconfirmed.contains(&cat.coin.coin_id()) {
+                continue;
+            }
 
-        for cat in &cat_coins {
             cats.insert(
                 cat.coin,
                 Cat {
@@ -35,12 +180,383 @@ impl Wallet {
                     p2_puzzle_hash: cat.p2_puzzle_hash,
                 },
             );
-            spendable_coins.push(cat.coin);
         }
 
-        Ok(select_coins(spendable_coins, amount)?
+        Ok(cats)
+    }
+}
+
+/// Selects coins in order of the height they were created at, until they add up to the amount.
+/// Coins created at the same height are selected largest first.
+fn select_oldest_coins(
+    mut spendable_coins: Vec<Coin>,
+    balance_coins: &[BalanceCoin],
+    amount: u128,
+) -> Result<Vec<Coin>, CoinSelectionError> {
+    if spendable_coins.is_empty() {
+        return Err(CoinSelectionError::NoSpendableCoins);
+    }
+
+    let spendable_amount: u128 = spendable_coins.iter().map(|coin| coin.amount as u128).sum();
+
+    if spendable_amount < amount {
+        return Err(CoinSelectionError::InsufficientBalance(spendable_amount));
+    }
+
+    let heights: HashMap<Bytes32, u32> = balance_coins
+        .iter()
+        .filter_map(|coin| Some((coin.coin_id, coin.created_height?)))
+        .collect();
+
+    spendable_coins.sort_by_key(|coin| {
+        let coin_id = coin.coin_id();
+
+        (
+            heights.get(&coin_id).copied().unwrap_or(u32::MAX),
+            Reverse(coin.amount),
+            coin_id,
+        )
+    });
+
+    let mut selected = Vec::new();
+    let mut selected_amount = 0;
+
+    for coin in spendable_coins {
+        selected.push(coin);
+        selected_amount += coin.amount as u128;
+
+        if selected_amount >= amount {
+            break;
+        }
+    }
+
+    if selected.len() > MAX_SELECTED_COINS {
+        return Err(CoinSelectionError::ExceededMaxCoins);
+    }
+
+    Ok(selected)
+}
+
+/// Finds a set of coins that adds up to exactly the given amount, trying larger coins first.
+/// Returns `None` if there isn't one, or if the search gives up before finding one.
+fn select_exact_coins(mut coins: Vec<Coin>, amount: u128) -> Option<Vec<Coin>> {
+    coins.sort_by(|a, b| {
+        b.amount
+            .cmp(&a.amount)
+            .then_with(|| a.coin_id().cmp(&b.coin_id()))
+    });
+
+    // The total amount of each coin and every coin after it, used to prune branches
+    // that can no longer reach the amount.
+    let mut remaining = vec![0; coins.len() + 1];
+
+    for (index, coin) in coins.iter().enumerate().rev() {
+        remaining[index] = remaining[index + 1] + coin.amount as u128;
+    }
+
+    let mut search = ExactSearch {
+        coins: &coins,
+        remaining: &remaining,
+        selected: Vec::new(),
+        steps: 0,
+    };
+
+    if !search.find(0, amount) {
+        return None;
+    }
+
+    Some(
+        search
+            .selected
             .into_iter()
-            .map(|coin| cats[&coin])
-            .collect())
+            .map(|index| coins[index])
+            .collect(),
+    )
+}
+
+/// The maximum number of branches explored when searching for an exact set of coins.
+const MAX_EXACT_SEARCH_STEPS: usize = 100_000;
+
+#[derive(Debug)]
+struct ExactSearch<'a> {
+    coins: &'a [Coin],
+    remaining: &'a [u128],
+    selected: Vec<usize>,
+    steps: usize,
+}
+
+/// A branch of the exact search that's still to be explored.
+#[derive(Debug, Clone, Copy)]
+enum SearchStep {
+    /// Looks for coins from this index onward that add up to the amount.
+    Find { index: usize, amount: u128 },
+    /// Removes the last selected coin, once the branch that includes it has been explored.
+    Unselect,
+}
+
+impl ExactSearch<'_> {
+    /// Searches depth-first, including each coin before trying without it. The branches are kept on
+    /// an explicit stack rather than recursing, since there can be one level for each coin.
+    fn find(&mut self, index: usize, amount: u128) -> bool {
+        let mut stack = vec![SearchStep::Find { index, amount }];
+
+        while let Some(step) = stack.pop() {
+            let (index, amount) = match step {
+                SearchStep::Find { index, amount } => (index, amount),
+                SearchStep::Unselect => {
+                    self.selected.pop();
+                    continue;
+                }
+            };
+
+            if amount == 0 {
+                return true;
+            }
+
+            if index == self.coins.len()
+                || self.remaining[index] < amount
+                || self.steps >= MAX_EXACT_SEARCH_STEPS
+            {
+                continue;
+            }
+
+            self.steps += 1;
+
+            let coin_amount = self.coins[index].amount as u128;
+
+            // The branch without the coin is explored last.
+            stack.push(SearchStep::Find {
+                index: index + 1,
+                amount,
+            });
+
+            if coin_amount <= amount {
+                self.selected.push(index);
+                stack.push(SearchStep::Unselect);
+                stack.push(SearchStep::Find {
+                    index: index + 1,
+                    amount: amount - coin_amount,
+                });
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::{Bytes32, Coin};
+    use test_log::test;
+
+    use crate::{TestWallet, WalletError, WalletOptions};
+
+    use super::{select_exact_coins, CoinSelectionMode};
+
+    #[test(tokio::test)]
+    async fn test_preview_coin_selection() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coins = test.wallet.preview_coin_selection(None, 250).await?;
+        assert_eq!(coins.len(), 1);
+        assert_eq!(coins[0].0.amount, 1000);
+        assert_eq!(coins[0].1, test.puzzle_hash);
+
+        // Previewing doesn't spend or lock anything.
+        assert_eq!(test.wallet.db.spendable_coins().await?.len(), 1);
+
+        assert!(test
+            .wallet
+            .preview_coin_selection(None, 1001)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_select_oldest_coins() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1000).await?;
+
+        let coins = test.wallet.db.spendable_coins().await?;
+        let coin_spends = test.wallet.split_xch(&coins, 2, 0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        // Splits one of the coins into newer coins of 200 and 300.
+        let coin_spends = test
+            .wallet
+            .send_xch(test.puzzle_hash, 200, 0, Vec::new(), false, true)
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let wallet = test.wallet_with(
+            test.wallet.db.clone(),
+            WalletOptions {
+                coin_selection_mode: CoinSelectionMode::Oldest,
+                ..test.wallet.options
+            },
+        );
+
+        // The default selection prefers the coin that matches the amount exactly.
+        let coins = test.wallet.preview_coin_selection(None, 200).await?;
+        assert_eq!(
+            coins
+                .iter()
+                .map(|(coin, _)| coin.amount)
+                .collect::<Vec<_>>(),
+            [200]
+        );
+
+        // The oldest coin is spent first, followed by the larger of the newer coins.
+        let coins = wallet.preview_coin_selection(None, 200).await?;
+        assert_eq!(
+            coins
+                .iter()
+                .map(|(coin, _)| coin.amount)
+                .collect::<Vec<_>>(),
+            [500]
+        );
+
+        let coins = wallet.preview_coin_selection(None, 600).await?;
+        assert_eq!(
+            coins
+                .iter()
+                .map(|(coin, _)| coin.amount)
+                .collect::<Vec<_>>(),
+            [500, 300]
+        );
+
+        assert!(matches!(
+            wallet.preview_coin_selection(None, 1001).await,
+            Err(WalletError::CoinSelection(..))
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_frozen_coin_not_selected() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1000).await?;
+
+        let coins = test.wallet.db.spendable_coins().await?;
+        let coin_spends = test.wallet.split_xch(&coins, 2, 0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let coins = test.wallet.db.spendable_coins().await?;
+        assert_eq!(coins.len(), 2);
+
+        let frozen = coins[0];
+        test.wallet.db.freeze_coin(frozen.coin_id()).await?;
+        assert_eq!(test.wallet.db.frozen_coins().await?.len(), 1);
+        assert!(!test.wallet.db.is_coin_unlocked(frozen.coin_id()).await?);
+
+        // Only the other coin can be selected, even when it isn't enough.
+        let selected = test.wallet.select_p2_coins(1).await?;
+        assert_eq!(selected, vec![coins[1]]);
+
+        assert!(matches!(
+            test.wallet.select_p2_coins(501).await,
+            Err(WalletError::CoinSelection(..))
+        ));
+
+        test.wallet.db.unfreeze_coin(frozen.coin_id()).await?;
+        assert!(test.wallet.db.frozen_coins().await?.is_empty());
+        assert_eq!(test.wallet.select_p2_coins(1000).await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_unconfirmed_coin_not_selected() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coin = test.wallet.db.spendable_coins().await?[0];
+        let created_height = test
+            .wallet
+            .db
+            .coin_state(coin.coin_id())
+            .await?
+            .and_then(|coin_state| coin_state.created_height)
+            .expect("coin isn't confirmed");
+        let peak_height = test
+            .wallet
+            .db
+            .latest_peak()
+            .await?
+            .map_or(created_height, |(height, _)| height.max(created_height));
+
+        // The coin is one confirmation short of being selected.
+        let wallet = test.wallet_with(
+            test.wallet.db.clone(),
+            WalletOptions {
+                min_confirmations: peak_height - created_height + 1,
+                ..test.wallet.options
+            },
+        );
+
+        assert!(matches!(
+            wallet.select_p2_coins(1).await,
+            Err(WalletError::CoinSelection(..))
+        ));
+        assert_eq!(test.wallet.select_p2_coins(1).await?, vec![coin]);
+
+        test.wallet
+            .db
+            .insert_peak(peak_height + 1, Bytes32::default(), 0)
+            .await?;
+
+        assert_eq!(wallet.select_p2_coins(1).await?, vec![coin]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_exact_coins() {
+        let coins: Vec<Coin> = [500, 300, 200, 70]
+            .into_iter()
+            .enumerate()
+            .map(|(i, amount)| Coin::new(Bytes32::new([i as u8; 32]), Bytes32::default(), amount))
+            .collect();
+
+        let amounts = |coins: Option<Vec<Coin>>| {
+            coins.map(|coins| coins.iter().map(|coin| coin.amount).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            amounts(select_exact_coins(coins.clone(), 500)),
+            Some(vec![500])
+        );
+        assert_eq!(
+            amounts(select_exact_coins(coins.clone(), 570)),
+            Some(vec![500, 70])
+        );
+        assert_eq!(
+            amounts(select_exact_coins(coins.clone(), 270)),
+            Some(vec![200, 70])
+        );
+        assert_eq!(
+            amounts(select_exact_coins(coins.clone(), 1070)),
+            Some(vec![500, 300, 200, 70])
+        );
+        assert_eq!(amounts(select_exact_coins(coins.clone(), 600)), None);
+        assert_eq!(amounts(select_exact_coins(coins, 1071)), None);
+    }
+
+    #[test]
+    fn test_select_exact_coins_deep_search() {
+        // Every coin has to be selected, so the search is as deep as the number of coins.
+        let coins: Vec<Coin> = (0..100_000u32)
+            .map(|i| {
+                let mut parent = [0; 32];
+                parent[..4].copy_from_slice(&i.to_be_bytes());
+                Coin::new(Bytes32::new(parent), Bytes32::default(), 1)
+            })
+            .collect();
+
+        let selected = select_exact_coins(coins, 100_000).expect("no exact selection");
+        assert_eq!(selected.len(), 100_000);
     }
 }
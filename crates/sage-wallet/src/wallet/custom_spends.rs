@@ -0,0 +1,140 @@
+use std::collections::{HashMap, HashSet};
+
+use chia::protocol::{Bytes32, CoinSpend};
+use chia_wallet_sdk::{Conditions, SpendContext};
+
+use crate::WalletError;
+
+use super::Wallet;
+
+impl Wallet {
+    /// Spends the given p2 coins with arbitrary conditions, keyed by coin id.
+    /// The first coin also reserves the fee, and the other coins assert that it is spent
+    /// within the transaction. No outputs are created automatically, so the conditions
+    /// must account for any change. Each coin can only be given once.
+    pub async fn spend(
+        &self,
+        coin_ids: Vec<Bytes32>,
+        mut conditions: HashMap<Bytes32, Conditions>,
+        fee: u64,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        let Some(&first_coin_id) = coin_ids.first() else {
+            return Err(WalletError::EmptySpend);
+        };
+
+        let mut coins = Vec::with_capacity(coin_ids.len());
+        let mut seen = HashSet::with_capacity(coin_ids.len());
+
+        for coin_id in coin_ids {
+            if !seen.insert(coin_id) {
+                return Err(WalletError::DuplicateCoin(coin_id));
+            }
+
+            let Some(coin_state) = self.db.coin_state(coin_id).await? else {
+                return Err(WalletError::MissingCoin(coin_id));
+            };
+
+            if coin_state.spent_height.is_some()
+                || self.db.is_p2_coin(coin_id).await? != Some(true)
+                || !self.db.is_coin_unlocked(coin_id).await?
+            {
+                return Err(WalletError::UnspendableCoin(coin_id));
+            }
+
+            let mut coin_conditions = conditions.remove(&coin_id).unwrap_or_default();
+
+            if coin_id == first_coin_id {
+                if fee > 0 {
+                    coin_conditions = coin_conditions.reserve_fee(fee);
+                }
+            } else {
+                coin_conditions = coin_conditions.assert_concurrent_spend(first_coin_id);
+            }
+
+            coins.push((coin_state.coin, coin_conditions));
+        }
+
+        if let Some(&coin_id) = conditions.keys().next() {
+            return Err(WalletError::UnexpectedConditions(coin_id));
+        }
+
+        let mut ctx = SpendContext::new();
+
+        self.spend_p2_coins_separately(&mut ctx, coins.into_iter())
+            .await?;
+
+        Ok(ctx.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::Bytes;
+    use chia_wallet_sdk::announcement_id;
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_spend_with_custom_announcement() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1000).await?;
+
+        let coin = test.wallet.db.spendable_coins().await?[0];
+        let message = Bytes::new(b"hello".to_vec());
+
+        let coin_spends = test
+            .wallet
+            .spend(
+                vec![coin.coin_id()],
+                HashMap::from([(
+                    coin.coin_id(),
+                    Conditions::new()
+                        .create_coin(test.puzzle_hash, 900, None)
+                        .create_coin_announcement(message.clone())
+                        .assert_coin_announcement(announcement_id(coin.coin_id(), message)),
+                )]),
+                100,
+            )
+            .await?;
+
+        assert_eq!(coin_spends.len(), 1);
+
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        assert_eq!(test.wallet.db.balance().await?, 900);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_spend_unknown_coin() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coin_id = Bytes32::new([1; 32]);
+
+        let result = test.wallet.spend(vec![coin_id], HashMap::new(), 0).await;
+
+        assert!(matches!(result, Err(WalletError::MissingCoin(id)) if id == coin_id));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_spend_duplicate_coin() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coin_id = test.wallet.db.spendable_coins().await?[0].coin_id();
+
+        let result = test
+            .wallet
+            .spend(vec![coin_id, coin_id], HashMap::new(), 0)
+            .await;
+
+        assert!(matches!(result, Err(WalletError::DuplicateCoin(id)) if id == coin_id));
+
+        Ok(())
+    }
+}
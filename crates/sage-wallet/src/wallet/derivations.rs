@@ -1,7 +1,7 @@
-use std::ops::Range;
+use std::{collections::HashSet, ops::Range};
 
 use chia::{
-    bls::DerivableKey,
+    bls::{DerivableKey, PublicKey},
     protocol::Bytes32,
     puzzles::{standard::StandardArgs, DeriveSynthetic},
 };
@@ -11,16 +11,44 @@ use crate::WalletError;
 
 use super::Wallet;
 
+/// The default limit on how many unhardened keys are derived, which is far more than
+/// a wallet needs in normal use.
+pub const DEFAULT_MAX_DERIVATION_INDEX: u32 = 10_000;
+
 impl Wallet {
-    /// Inserts a range of unhardened derivations to the database.
+    /// Fails if deriving the unhardened key at the given index would exceed the maximum.
+    /// Indices below the current derivation index have already been derived, so they're
+    /// always allowed, even if the maximum has since been lowered.
+    pub fn check_derivation_index(&self, index: u32, next_index: u32) -> Result<(), WalletError> {
+        if index >= next_index && index >= self.options.max_derivation_index {
+            return Err(WalletError::DerivationLimitReached {
+                index,
+                max: self.options.max_derivation_index,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a range of unhardened derivations to the database, and returns the new puzzle hashes.
+    /// Indices that have already been derived are skipped, so this is safe to call repeatedly.
     pub async fn insert_unhardened_derivations(
         &self,
         tx: &mut DatabaseTx<'_>,
         range: Range<u32>,
     ) -> Result<Vec<Bytes32>, WalletError> {
+        if let Some(last) = range.clone().last() {
+            let next_index = tx.derivation_index(false).await?;
+            self.check_derivation_index(last, next_index)?;
+        }
+
         let mut puzzle_hashes = Vec::new();
 
         for index in range {
+            if tx.has_derivation(index, false).await? {
+                continue;
+            }
+
             let synthetic_key = self
                 .intermediate_pk
                 .derive_unhardened(index)
@@ -37,6 +65,82 @@ impl Wallet {
         Ok(puzzle_hashes)
     }
 
+    /// Recomputes the synthetic key at the given index from the wallet's public key, and inserts the
+    /// derivation if it's missing. Hardened keys can't be derived without the secret key.
+    pub async fn derive_synthetic_key(
+        &self,
+        index: u32,
+        hardened: bool,
+    ) -> Result<PublicKey, WalletError> {
+        if hardened {
+            return Err(WalletError::HardenedDerivation(index));
+        }
+
+        let next_index = self.db.derivation_index(false).await?;
+        self.check_derivation_index(index, next_index)?;
+
+        let synthetic_key = self
+            .intermediate_pk
+            .derive_unhardened(index)
+            .derive_synthetic();
+
+        let p2_puzzle_hash = StandardArgs::curry_tree_hash(synthetic_key).into();
+
+        let mut tx = self.db.tx().await?;
+        tx.insert_derivation(p2_puzzle_hash, index, false, synthetic_key)
+            .await?;
+        tx.commit().await?;
+
+        Ok(synthetic_key)
+    }
+
+    /// Looks up the synthetic key for a p2 puzzle hash. If the derivation is missing, for example
+    /// after a partial import, any gaps in the unhardened derivations are filled in first.
+    pub async fn synthetic_key(&self, p2_puzzle_hash: Bytes32) -> Result<PublicKey, WalletError> {
+        if !self.db.is_p2_puzzle_hash(p2_puzzle_hash).await? {
+            self.backfill_derivations().await?;
+
+            if !self.db.is_p2_puzzle_hash(p2_puzzle_hash).await? {
+                return Err(WalletError::MissingDerivation(p2_puzzle_hash));
+            }
+        }
+
+        Ok(self.db.synthetic_key(p2_puzzle_hash).await?)
+    }
+
+    /// Inserts any unhardened derivations that are missing below the current derivation index,
+    /// and returns how many were inserted. Nothing is written if there are no gaps.
+    pub async fn backfill_derivations(&self) -> Result<usize, WalletError> {
+        let mut tx = self.db.tx().await?;
+
+        let next_index = tx.derivation_index(false).await?;
+
+        if tx.derivation_count(false).await? >= next_index {
+            return Ok(0);
+        }
+
+        let existing: HashSet<u32> = tx.derivation_indices(false).await?.into_iter().collect();
+        let missing: Vec<u32> = (0..next_index)
+            .filter(|index| !existing.contains(index))
+            .collect();
+
+        for &index in &missing {
+            let synthetic_key = self
+                .intermediate_pk
+                .derive_unhardened(index)
+                .derive_synthetic();
+
+            let p2_puzzle_hash = StandardArgs::curry_tree_hash(synthetic_key).into();
+
+            tx.insert_derivation(p2_puzzle_hash, index, false, synthetic_key)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(missing.len())
+    }
+
     pub async fn p2_puzzle_hashes(
         &self,
         count: u32,
@@ -90,3 +194,92 @@ impl Wallet {
         Ok(self.p2_puzzle_hashes(1, hardened, reuse).await?[0])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_insert_derivations_twice() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let next_index = test.wallet.db.derivation_index(false).await?;
+        let range = next_index..next_index + 10;
+
+        let mut tx = test.wallet.db.tx().await?;
+        let first = test
+            .wallet
+            .insert_unhardened_derivations(&mut tx, range.clone())
+            .await?;
+        let second = test
+            .wallet
+            .insert_unhardened_derivations(&mut tx, range.clone())
+            .await?;
+        tx.commit().await?;
+
+        assert_eq!(first.len(), 10);
+        assert!(second.is_empty());
+
+        // Overlapping ranges only derive the indices that are missing.
+        let mut tx = test.wallet.db.tx().await?;
+        let third = test
+            .wallet
+            .insert_unhardened_derivations(&mut tx, next_index + 5..next_index + 15)
+            .await?;
+        tx.commit().await?;
+
+        assert_eq!(third.len(), 5);
+        assert_eq!(
+            test.wallet.db.derivation_index(false).await?,
+            next_index + 15
+        );
+
+        let derivations = test.wallet.db.unhardened_derivations(u32::MAX, 0).await?;
+        assert_eq!(derivations.len(), (next_index + 15) as usize);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_backfill_missing_derivation() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let wallet = test.wallet_with(TestWallet::empty_database().await?, test.wallet.options);
+
+        // Simulate a partial import, which is missing the derivation at index 3.
+        let mut tx = wallet.db.tx().await?;
+        wallet.insert_unhardened_derivations(&mut tx, 0..3).await?;
+        wallet.insert_unhardened_derivations(&mut tx, 4..10).await?;
+        tx.commit().await?;
+
+        let synthetic_key = wallet
+            .intermediate_pk
+            .derive_unhardened(3)
+            .derive_synthetic();
+        let p2_puzzle_hash = StandardArgs::curry_tree_hash(synthetic_key).into();
+        assert!(!wallet.db.is_p2_puzzle_hash(p2_puzzle_hash).await?);
+
+        assert_eq!(wallet.synthetic_key(p2_puzzle_hash).await?, synthetic_key);
+        assert!(wallet.db.is_p2_puzzle_hash(p2_puzzle_hash).await?);
+        assert_eq!(wallet.backfill_derivations().await?, 0);
+
+        // Puzzle hashes that aren't derived from the wallet's key are still an error.
+        let unknown = Bytes32::new([42; 32]);
+        assert!(matches!(
+            wallet.synthetic_key(unknown).await,
+            Err(WalletError::MissingDerivation(puzzle_hash)) if puzzle_hash == unknown
+        ));
+
+        assert_eq!(wallet.derive_synthetic_key(3, false).await?, synthetic_key);
+        assert!(matches!(
+            wallet.derive_synthetic_key(3, true).await,
+            Err(WalletError::HardenedDerivation(3))
+        ));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use chia::protocol::{Bytes32, CoinSpend};
 use chia_wallet_sdk::{Conditions, DidOwner, HashedPtr, SpendContext, StandardLayer};
 
-use crate::WalletError;
+use crate::{NftExt, WalletError};
 
 use super::Wallet;
 
@@ -70,10 +70,9 @@ impl Wallet {
         let mut did_conditions = Conditions::new();
 
         for (i, nft) in nfts.into_iter().enumerate() {
-            let nft_metadata_ptr = ctx.alloc(&nft.info.metadata)?;
-            let nft = nft.with_metadata(HashedPtr::from_ptr(&ctx.allocator, nft_metadata_ptr));
+            let nft = nft.hashed(&mut ctx)?;
 
-            let synthetic_key = self.db.synthetic_key(nft.info.p2_puzzle_hash).await?;
+            let synthetic_key = self.synthetic_key(nft.info.p2_puzzle_hash).await?;
             let p2 = StandardLayer::new(synthetic_key);
 
             let conditions = if nft_coin_ids.len() == 1 {
@@ -102,7 +101,7 @@ impl Wallet {
         let did_coin_id = did.as_ref().map(|did| did.coin.coin_id());
 
         if let Some(did) = did {
-            let synthetic_key = self.db.synthetic_key(did.info.p2_puzzle_hash).await?;
+            let synthetic_key = self.synthetic_key(did.info.p2_puzzle_hash).await?;
             let p2 = StandardLayer::new(synthetic_key);
             let _did = did.update(&mut ctx, &p2, did_conditions)?;
         }
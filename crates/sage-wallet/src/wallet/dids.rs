@@ -24,7 +24,7 @@ impl Wallet {
 
         let mut ctx = SpendContext::new();
 
-        let synthetic_key = self.db.synthetic_key(coins[0].puzzle_hash).await?;
+        let synthetic_key = self.synthetic_key(coins[0].puzzle_hash).await?;
         let p2 = StandardLayer::new(synthetic_key);
         let (mut conditions, did) =
             Launcher::new(coins[0].coin_id(), 1).create_simple_did(&mut ctx, &p2)?;
@@ -88,7 +88,7 @@ impl Wallet {
             let did_metadata_ptr = ctx.alloc(&did.info.metadata)?;
             let did = did.with_metadata(HashedPtr::from_ptr(&ctx.allocator, did_metadata_ptr));
 
-            let synthetic_key = self.db.synthetic_key(did.info.p2_puzzle_hash).await?;
+            let synthetic_key = self.synthetic_key(did.info.p2_puzzle_hash).await?;
             let p2 = StandardLayer::new(synthetic_key);
 
             let conditions = if did_coin_ids.len() == 1 {
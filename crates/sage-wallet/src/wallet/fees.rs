@@ -0,0 +1,91 @@
+use chia::{
+    bls::Signature,
+    consensus::spendbundle_conditions::get_conditions_from_spendbundle,
+    protocol::{CoinSpend, SpendBundle},
+};
+use clvmr::Allocator;
+
+use crate::WalletError;
+
+use super::Wallet;
+
+impl Wallet {
+    /// Estimates the cost of the coin spends the same way the mempool would, including execution,
+    /// conditions, and the size of the spends. Signatures aren't validated, so the spends can be unsigned.
+    pub fn estimate_cost(&self, coin_spends: &[CoinSpend]) -> Result<u64, WalletError> {
+        let spend_bundle = SpendBundle::new(coin_spends.to_vec(), Signature::default());
+
+        let conditions = get_conditions_from_spendbundle(
+            &mut Allocator::new(),
+            &spend_bundle,
+            self.network.constants.max_block_cost_clvm,
+            u32::MAX,
+            &self.network.constants,
+        )
+        .map_err(|error| WalletError::InvalidSpend(error.1))?;
+
+        Ok(conditions.cost)
+    }
+
+    /// Fails if the coin spends cost more than `max_bundle_cost`, for bulk operations that have
+    /// to fit in a single transaction, such as those that spend the same DID for every NFT.
+    pub fn check_bundle_cost(&self, coin_spends: &[CoinSpend]) -> Result<(), WalletError> {
+        let cost = self.estimate_cost(coin_spends)?;
+
+        if cost > self.options.max_bundle_cost {
+            return Err(WalletError::BundleCostExceeded {
+                cost,
+                max: self.options.max_bundle_cost,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Calculates the fee needed to pay the given rate for the coin spends, in mojos per unit of cost.
+    ///
+    /// Paying the fee adds to the cost of the transaction, so the spends should be built with
+    /// a placeholder fee first, to make sure the fee coins are accounted for.
+    pub fn fee_for_rate(
+        &self,
+        coin_spends: &[CoinSpend],
+        mojos_per_cost: u64,
+    ) -> Result<u64, WalletError> {
+        self.estimate_cost(coin_spends)?
+            .checked_mul(mojos_per_cost)
+            .ok_or(WalletError::FeeOverflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    #[test(tokio::test)]
+    async fn test_fee_for_rate() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coin_spends = test
+            .wallet
+            .send_xch(test.puzzle_hash, 100, 1, Vec::new(), false, true)
+            .await?;
+
+        // A standard spend with a payment, change, and a fee.
+        let cost = test.wallet.estimate_cost(&coin_spends)?;
+        assert!(cost > 0 && cost < test.wallet.network.constants.max_block_cost_clvm);
+
+        // Each condition adds to the cost.
+        let without_fee = test
+            .wallet
+            .send_xch(test.puzzle_hash, 100, 0, Vec::new(), false, true)
+            .await?;
+        assert!(test.wallet.estimate_cost(&without_fee)? < cost);
+
+        assert_eq!(test.wallet.fee_for_rate(&coin_spends, 5)?, cost * 5);
+        assert_eq!(test.wallet.fee_for_rate(&coin_spends, 0)?, 0);
+
+        Ok(())
+    }
+}
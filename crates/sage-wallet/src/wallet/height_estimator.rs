@@ -0,0 +1,93 @@
+use sage_database::{extrapolate_height, extrapolate_time};
+
+use crate::WalletError;
+
+use super::Wallet;
+
+impl Wallet {
+    /// Estimates the block height at the given unix timestamp, in seconds.
+    ///
+    /// This is an approximation, extrapolated from the closest recorded peak that was
+    /// observed at or before the timestamp using the average block time. Actual block
+    /// times vary, so the estimate becomes less accurate the further it is from a known peak.
+    /// Peaks are timed with the local clock when the wallet receives them, rather than with the
+    /// block's own timestamp, so the estimate is also off by however late the peak was seen.
+    pub async fn estimate_height_at(&self, timestamp: u64) -> Result<u32, WalletError> {
+        let reference = match self.db.timed_peak_before(timestamp).await? {
+            Some(peak) => peak,
+            None => self
+                .db
+                .earliest_timed_peak()
+                .await?
+                .ok_or(WalletError::MissingTimedPeak)?,
+        };
+
+        Ok(extrapolate_height(reference, timestamp))
+    }
+
+    /// Estimates the unix timestamp, in seconds, at which the given block height was or will be reached.
+    ///
+    /// Like [`Wallet::estimate_height_at`], this is only an approximation based on the
+    /// closest recorded peak at or below the height and the average block time.
+    pub async fn estimate_time_at(&self, height: u32) -> Result<u64, WalletError> {
+        let reference = match self.db.timed_peak_below(height).await? {
+            Some(peak) => peak,
+            None => self
+                .db
+                .earliest_timed_peak()
+                .await?
+                .ok_or(WalletError::MissingTimedPeak)?,
+        };
+
+        Ok(extrapolate_time(reference, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::Bytes32;
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_estimate_height_and_time() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        // A separate database, so that the peaks recorded by syncing don't interfere.
+        let wallet = test.wallet_with(TestWallet::empty_database().await?, test.wallet.options);
+
+        assert!(matches!(
+            wallet.estimate_height_at(1_000_000).await,
+            Err(WalletError::MissingTimedPeak)
+        ));
+
+        // The second peak was reached a bit slower than the average block time.
+        wallet
+            .db
+            .insert_peak(1000, Bytes32::default(), 1_000_000)
+            .await?;
+        wallet
+            .db
+            .insert_peak(2000, Bytes32::default(), 1_020_000)
+            .await?;
+
+        // Exact peaks and extrapolation past the latest one, at 4 blocks every 75 seconds.
+        assert_eq!(wallet.estimate_height_at(1_000_000).await?, 1000);
+        assert_eq!(wallet.estimate_height_at(1_020_750).await?, 2040);
+        assert_eq!(wallet.estimate_time_at(2000).await?, 1_020_000);
+        assert_eq!(wallet.estimate_time_at(2040).await?, 1_020_750);
+
+        // Historical estimates use the closest earlier peak rather than the latest one.
+        assert_eq!(wallet.estimate_height_at(1_000_075).await?, 1004);
+        assert_eq!(wallet.estimate_time_at(1004).await?, 1_000_075);
+
+        // Before the earliest peak, the estimate is extrapolated backwards.
+        assert_eq!(wallet.estimate_height_at(999_250).await?, 960);
+        assert_eq!(wallet.estimate_time_at(960).await?, 999_250);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use chia::protocol::Bytes32;
+
+use crate::WalletError;
+
+use super::Wallet;
+
+/// The most NFTs with broken metadata that are included in a report.
+const MAX_METADATA_FAILURES: u32 = 20;
+
+/// An owned NFT whose off-chain metadata couldn't be fetched from any of its URIs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataFailure {
+    pub launcher_id: Bytes32,
+    /// The last error from one of the URIs, if any of them were tried.
+    pub error: Option<String>,
+}
+
+/// A summary of how many owned NFTs have had their metadata fetched, are still waiting
+/// for it to be fetched, or have broken or unreachable metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataHealthReport {
+    pub fetched: u32,
+    pub pending: u32,
+    pub failed: u32,
+    /// A sample of the NFTs that failed, so that they can be investigated.
+    pub failures: Vec<MetadataFailure>,
+}
+
+impl Wallet {
+    /// Reports on the off-chain metadata of the NFTs in the wallet. Only NFTs that commit to a
+    /// metadata hash are counted, since there's nothing to fetch or verify otherwise.
+    pub async fn metadata_health(&self) -> Result<MetadataHealthReport, WalletError> {
+        let counts = self.db.nft_metadata_counts().await?;
+
+        let failures = self
+            .db
+            .failed_nft_metadata(MAX_METADATA_FAILURES)
+            .await?
+            .into_iter()
+            .map(|(launcher_id, error)| MetadataFailure { launcher_id, error })
+            .collect();
+
+        Ok(MetadataHealthReport {
+            fetched: counts.fetched,
+            pending: counts.pending,
+            failed: counts.failed,
+            failures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::puzzles::nft::NftMetadata;
+    use sage_database::NftData;
+    use test_log::test;
+
+    use crate::{TestWallet, WalletNftMint};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_metadata_health() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(3).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let fetched_hash = Bytes32::new([1; 32]);
+        let failed_hash = Bytes32::new([2; 32]);
+
+        let mut nft_ids = Vec::new();
+
+        for metadata_hash in [fetched_hash, failed_hash] {
+            let (coin_spends, nfts, _did) = test
+                .wallet
+                .bulk_mint_nfts(
+                    0,
+                    did.info.launcher_id,
+                    vec![WalletNftMint {
+                        metadata: NftMetadata {
+                            metadata_uris: vec!["https://example.com".to_string()],
+                            metadata_hash: Some(metadata_hash),
+                            ..Default::default()
+                        },
+                        royalty_puzzle_hash: Some(Bytes32::default()),
+                        royalty_ten_thousandths: 300,
+                    }],
+                    false,
+                    true,
+                )
+                .await?;
+            test.transact(coin_spends).await?;
+            test.wait_for_coins().await;
+
+            nft_ids.extend(nfts.iter().map(|nft| nft.info.launcher_id));
+        }
+
+        // Neither NFT's metadata has been fetched yet.
+        let report = test.wallet.metadata_health().await?;
+        assert_eq!((report.fetched, report.pending, report.failed), (0, 2, 0));
+        assert!(report.failures.is_empty());
+
+        let mut tx = test.wallet.db.tx().await?;
+        tx.insert_nft_data(
+            fetched_hash,
+            NftData {
+                blob: b"{}".to_vec(),
+                mime_type: "application/json".to_string(),
+            },
+        )
+        .await?;
+        for uri in ["https://mirror.example.com", "https://backup.example.com"] {
+            tx.insert_nft_uri(uri.to_string(), failed_hash).await?;
+        }
+        tx.commit().await?;
+
+        // The URIs fail in a different order than they were added, and the last error is reported.
+        for (uri, error) in [
+            ("https://example.com", "not found"),
+            ("https://backup.example.com", "bad gateway"),
+            ("https://mirror.example.com", "timeout"),
+        ] {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+            let mut tx = test.wallet.db.tx().await?;
+            tx.set_nft_uri_checked(uri.to_string(), failed_hash, Some(error.to_string()))
+                .await?;
+            tx.commit().await?;
+        }
+
+        let report = test.wallet.metadata_health().await?;
+        assert_eq!((report.fetched, report.pending, report.failed), (1, 0, 1));
+        assert_eq!(
+            report.failures,
+            vec![MetadataFailure {
+                launcher_id: nft_ids[1],
+                error: Some("timeout".to_string()),
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_onchain_only_metadata() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(2).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        // The metadata commits to a hash, but there's nowhere to fetch it from.
+        let (coin_spends, nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata {
+                        metadata_hash: Some(Bytes32::new([1; 32])),
+                        ..Default::default()
+                    },
+                    royalty_puzzle_hash: Some(Bytes32::default()),
+                    royalty_ten_thousandths: 300,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let row = test
+            .wallet
+            .db
+            .nft_row(nfts[0].info.launcher_id)
+            .await?
+            .expect("missing nft");
+        assert_eq!(row.metadata_hash, None);
+
+        let report = test.wallet.metadata_health().await?;
+        assert_eq!((report.fetched, report.pending, report.failed), (0, 0, 0));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use std::{path::PathBuf, time::Duration};
+
+use futures_lite::future::poll_once;
+use tokio::{
+    sync::mpsc,
+    task::{JoinError, JoinHandle},
+};
+
+use crate::{MetadataCache, NftUriQueue, SyncEvent, WalletError};
+
+use super::Wallet;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataWorkerOptions {
+    /// How long to wait between batches of URIs.
+    pub delay: Duration,
+    /// The maximum number of URIs that are fetched at the same time.
+    pub concurrency: usize,
+    /// A directory where fetched metadata is cached by hash, and checked before fetching.
+    pub cache_dir: Option<PathBuf>,
+    /// If set, a backlog of URIs is fetched eagerly by only waiting this long between full batches,
+    /// and [`SyncEvent::NftPrefetch`] events report the progress.
+    pub prefetch_delay: Option<Duration>,
+    /// How many more times a URI that fails to fetch is tried before the error is recorded.
+    pub retries: u32,
+    /// How long to wait before the first retry, which doubles before each retry after it.
+    pub retry_delay: Duration,
+}
+
+impl Default for MetadataWorkerOptions {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_secs(1),
+            concurrency: 10,
+            cache_dir: None,
+            prefetch_delay: None,
+            retries: 3,
+            retry_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// A handle to a task that fetches NFT metadata in the background.
+/// The task is cancelled when the handle is dropped.
+#[derive(Debug)]
+pub struct MetadataWorker {
+    task: JoinHandle<Result<(), WalletError>>,
+}
+
+impl MetadataWorker {
+    /// Stops the worker. A batch that's in progress is abandoned, and will be fetched again by
+    /// the next worker, since URIs are only marked as checked once they have been fetched.
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Returns how the worker stopped if it has, without waiting for it.
+    pub async fn poll(&mut self) -> Option<Result<Result<(), WalletError>, JoinError>> {
+        poll_once(&mut self.task).await
+    }
+}
+
+impl Drop for MetadataWorker {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Wallet {
+    /// Spawns a task that fetches the metadata of NFTs from URIs that haven't been checked yet,
+    /// and stores it in the database. An [`SyncEvent::NftData`] event is sent after each batch.
+    pub fn spawn_metadata_worker(
+        &self,
+        options: MetadataWorkerOptions,
+        sync_sender: mpsc::Sender<SyncEvent>,
+    ) -> MetadataWorker {
+        let mut queue = NftUriQueue::new(self.db.clone(), sync_sender)
+            .with_concurrency(options.concurrency)
+            .with_cache(options.cache_dir.map(MetadataCache::new))
+            .with_retries(options.retries, options.retry_delay);
+
+        if let Some(prefetch_delay) = options.prefetch_delay {
+            queue = queue.with_prefetch(prefetch_delay);
+        }
+
+        MetadataWorker {
+            task: tokio::spawn(queue.start(options.delay)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::{protocol::Bytes32, sha2::Sha256};
+    use test_log::test;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use chia::puzzles::nft::NftMetadata;
+
+    use crate::{Data, TestWallet, WalletNftMint};
+
+    use super::*;
+
+    /// Serves the same JSON document in response to every request.
+    async fn serve_json(body: &'static str) -> anyhow::Result<String> {
+        serve_json_after_failures(body, 0).await
+    }
+
+    /// Closes the first connections without a response, then serves the JSON document.
+    async fn serve_json_after_failures(
+        body: &'static str,
+        mut failures: usize,
+    ) -> anyhow::Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let uri = format!("http://{}/metadata.json", listener.local_addr()?);
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                if failures > 0 {
+                    failures -= 1;
+                    continue;
+                }
+
+                let (reader, mut writer) = stream.split();
+
+                // Skip the request line and headers.
+                let mut lines = BufReader::new(reader).lines();
+
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line.is_empty() {
+                        break;
+                    }
+                }
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                writer.write_all(response.as_bytes()).await.ok();
+            }
+        });
+
+        Ok(uri)
+    }
+
+    #[test(tokio::test)]
+    async fn test_metadata_worker() -> anyhow::Result<()> {
+        let mut test = TestWallet::with_metadata_worker(
+            0,
+            MetadataWorkerOptions {
+                delay: Duration::from_millis(100),
+                concurrency: 1,
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let body = r#"{"name":"Test NFT"}"#;
+        let uri = serve_json(body).await?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(body.as_bytes());
+        let hash = Bytes32::new(hasher.finalize());
+
+        let mut tx = test.wallet.db.tx().await?;
+        tx.insert_nft_uri(uri, hash).await?;
+        tx.commit().await?;
+
+        test.consume_until(|event| matches!(event, SyncEvent::NftData))
+            .await;
+
+        let data = test.wallet.db.fetch_nft_data(hash).await?.expect("no data");
+        assert_eq!(data.blob, body.as_bytes());
+        assert_eq!(data.mime_type, "application/json");
+        assert!(test.wallet.db.unchecked_nft_uris(10).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_metadata_worker_retries() -> anyhow::Result<()> {
+        let mut test = TestWallet::with_metadata_worker(
+            0,
+            MetadataWorkerOptions {
+                delay: Duration::from_millis(100),
+                concurrency: 1,
+                retries: 2,
+                retry_delay: Duration::from_millis(10),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let body = r#"{"name":"Flaky NFT"}"#;
+        let uri = serve_json_after_failures(body, 2).await?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(body.as_bytes());
+        let hash = Bytes32::new(hasher.finalize());
+
+        let mut tx = test.wallet.db.tx().await?;
+        tx.insert_nft_uri(uri, hash).await?;
+        tx.commit().await?;
+
+        test.consume_until(|event| matches!(event, SyncEvent::NftData))
+            .await;
+
+        // The first two attempts failed, but the data was fetched on the last retry.
+        let data = test.wallet.db.fetch_nft_data(hash).await?.expect("no data");
+        assert_eq!(data.blob, body.as_bytes());
+        assert!(test.wallet.db.unchecked_nft_uris(10).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_metadata_worker_cache() -> anyhow::Result<()> {
+        let body = r#"{"name":"Cached NFT"}"#;
+
+        let mut hasher = Sha256::new();
+        hasher.update(body.as_bytes());
+        let hash = Bytes32::new(hasher.finalize());
+
+        let cache_dir = std::env::temp_dir().join(format!("sage-metadata-cache-{hash}"));
+        let cache = MetadataCache::new(&cache_dir);
+        cache
+            .insert(&Data {
+                blob: body.as_bytes().to_vec(),
+                mime_type: "application/json".to_string(),
+                hash,
+            })
+            .await?;
+
+        let mut test = TestWallet::with_metadata_worker(
+            0,
+            MetadataWorkerOptions {
+                delay: Duration::from_millis(100),
+                concurrency: 1,
+                cache_dir: Some(cache_dir.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        // Nothing is listening on this port, so the data can only come from the cache.
+        let mut tx = test.wallet.db.tx().await?;
+        tx.insert_nft_uri("http://127.0.0.1:1/metadata.json".to_string(), hash)
+            .await?;
+        tx.commit().await?;
+
+        test.consume_until(|event| matches!(event, SyncEvent::NftData))
+            .await;
+
+        let data = test.wallet.db.fetch_nft_data(hash).await?.expect("no data");
+        assert_eq!(data.blob, body.as_bytes());
+        assert_eq!(data.mime_type, "application/json");
+
+        // Content that no longer matches its hash isn't served.
+        tokio::fs::write(cache_dir.join(hash.to_string()), b"tampered").await?;
+        assert!(cache.get(hash).await.is_none());
+
+        tokio::fs::remove_dir_all(&cache_dir).await.ok();
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_metadata_prefetch() -> anyhow::Result<()> {
+        let mut test = TestWallet::with_metadata_worker(
+            2,
+            MetadataWorkerOptions {
+                delay: Duration::from_millis(100),
+                concurrency: 1,
+                prefetch_delay: Some(Duration::from_millis(10)),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let body = r#"{"name":"Prefetched NFT"}"#;
+        let uri = serve_json(body).await?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(body.as_bytes());
+        let hash = Bytes32::new(hasher.finalize());
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let (coin_spends, _nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata {
+                        metadata_uris: vec![uri],
+                        metadata_hash: Some(hash),
+                        ..Default::default()
+                    },
+                    royalty_puzzle_hash: Some(Bytes32::default()),
+                    royalty_ten_thousandths: 300,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+
+        // The URI of the discovered NFT is fetched by the worker that the sync manager spawned.
+        test.consume_until(|event| {
+            event
+                == SyncEvent::NftPrefetch {
+                    fetched: 1,
+                    pending: 0,
+                }
+        })
+        .await;
+
+        let data = test.wallet.db.fetch_nft_data(hash).await?.expect("no data");
+        assert_eq!(data.blob, body.as_bytes());
+        assert!(test.wallet.db.unchecked_nft_uris(10).await?.is_empty());
+
+        Ok(())
+    }
+}
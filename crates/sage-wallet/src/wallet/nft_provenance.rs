@@ -0,0 +1,172 @@
+use chia::{protocol::Bytes32, puzzles::Proof};
+
+use crate::WalletError;
+
+use super::Wallet;
+
+/// A coin in the lineage of an NFT, and the puzzle hash that owned it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProvenanceEntry {
+    pub coin_id: Bytes32,
+    pub owner_puzzle_hash: Bytes32,
+    pub created_height: u32,
+    pub spent_height: Option<u32>,
+}
+
+/// The known ownership history of an NFT, starting with the coin created when it was minted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftProvenance {
+    pub entries: Vec<ProvenanceEntry>,
+    /// Whether every coin from the mint to the current one is known. Coins owned by other
+    /// wallets aren't synced, so there can be gaps in the history, or it can end early.
+    pub complete: bool,
+}
+
+impl Wallet {
+    /// Walks the lineage of the NFT through its synced coins, from the mint to the latest coin.
+    ///
+    /// The mint entry is the first coin owned after the launch. If the eve coin was spent by
+    /// the mint transaction, it's recognized by its lineage proof, since the eve coin is the
+    /// child of the launcher. The parent of every other coin is stored with its coin state,
+    /// so no extra requests are needed to check that the history has no gaps.
+    pub async fn nft_provenance(&self, nft_id: Bytes32) -> Result<NftProvenance, WalletError> {
+        let coin_states = self.db.nft_coin_states(nft_id).await?;
+
+        if coin_states.is_empty() {
+            return Err(WalletError::MissingNft(nft_id));
+        }
+
+        let mut entries: Vec<ProvenanceEntry> = Vec::with_capacity(coin_states.len());
+        let mut complete = true;
+
+        for row in coin_states {
+            let coin = row.coin_state.coin;
+
+            let Some(nft) = self.db.nft_by_coin_id(coin.coin_id()).await? else {
+                continue;
+            };
+
+            let is_linked = if let Some(previous) = entries.last() {
+                coin.parent_coin_info == previous.coin_id
+            } else {
+                coin.parent_coin_info == nft_id
+                    || matches!(nft.proof, Proof::Lineage(proof) if proof.parent_parent_coin_info == nft_id)
+            };
+
+            if !is_linked {
+                complete = false;
+            }
+
+            entries.push(ProvenanceEntry {
+                coin_id: coin.coin_id(),
+                owner_puzzle_hash: nft.info.p2_puzzle_hash,
+                created_height: row.coin_state.created_height.unwrap_or_default(),
+                spent_height: row.coin_state.spent_height,
+            });
+        }
+
+        // If the latest known coin has been spent, the NFT has moved on to a coin that wasn't synced.
+        if entries
+            .last()
+            .is_some_and(|entry| entry.spent_height.is_some())
+        {
+            complete = false;
+        }
+
+        Ok(NftProvenance { entries, complete })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::puzzles::nft::NftMetadata;
+    use test_log::test;
+
+    use crate::{TestWallet, WalletNftMint};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_nft_provenance() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(2).await?;
+        let mut bob = alice.next(0).await?;
+
+        let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = alice
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: None,
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let nft_id = nfts.remove(0).info.launcher_id;
+        let puzzle_hash = alice.wallet.p2_puzzle_hash(false, false).await?;
+
+        let coin_spends = alice
+            .wallet
+            .transfer_nfts(vec![nft_id], puzzle_hash, None, 0, false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        // Every coin since the mint has been owned by Alice.
+        let provenance = alice.wallet.nft_provenance(nft_id).await?;
+        assert!(provenance.complete);
+        assert_eq!(provenance.entries.len(), 2);
+
+        let coin_spends = alice
+            .wallet
+            .transfer_nfts(vec![nft_id], bob.puzzle_hash, None, 0, false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+        bob.wait_for_puzzles().await;
+
+        // Alice has seen every coin up to the one she sent to Bob, but not Bob's coin.
+        let provenance = alice.wallet.nft_provenance(nft_id).await?;
+        assert!(!provenance.complete);
+        assert_eq!(
+            provenance
+                .entries
+                .iter()
+                .map(|entry| entry.owner_puzzle_hash)
+                .collect::<Vec<_>>(),
+            vec![alice.puzzle_hash, puzzle_hash]
+        );
+        assert_eq!(
+            provenance.entries[1].created_height,
+            provenance.entries[0].spent_height.expect("spent")
+        );
+
+        // Bob never saw the mint, so his history has a gap.
+        let provenance = bob.wallet.nft_provenance(nft_id).await?;
+        assert!(!provenance.complete);
+        assert_eq!(
+            provenance
+                .entries
+                .last()
+                .map(|entry| entry.owner_puzzle_hash),
+            Some(bob.puzzle_hash)
+        );
+
+        assert!(matches!(
+            alice.wallet.nft_provenance(Bytes32::default()).await,
+            Err(WalletError::MissingNft(..))
+        ));
+
+        Ok(())
+    }
+}
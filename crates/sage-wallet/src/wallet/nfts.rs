@@ -7,7 +7,7 @@ use chia_wallet_sdk::{
     StandardLayer,
 };
 
-use crate::WalletError;
+use crate::{NftExt, WalletError};
 
 use super::Wallet;
 
@@ -19,6 +19,8 @@ pub struct WalletNftMint {
 }
 
 impl Wallet {
+    /// Mints the NFTs with the given DID as their owner. Every NFT is minted by the same DID spend,
+    /// so the mints can't be split across transactions, and fail if they exceed `max_bundle_cost`.
     pub async fn bulk_mint_nfts(
         &self,
         fee: u64,
@@ -41,12 +43,12 @@ impl Wallet {
 
         let p2_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
 
-        let mut ctx = SpendContext::new();
+        let mut ctx = self.spend_context();
 
         let did_metadata_ptr = ctx.alloc(&did.info.metadata)?;
         let did = did.with_metadata(HashedPtr::from_ptr(&ctx.allocator, did_metadata_ptr));
 
-        let synthetic_key = self.db.synthetic_key(did.info.p2_puzzle_hash).await?;
+        let synthetic_key = self.synthetic_key(did.info.p2_puzzle_hash).await?;
         let p2 = StandardLayer::new(synthetic_key);
 
         let mut did_conditions = Conditions::new();
@@ -86,13 +88,24 @@ impl Wallet {
 
         let new_did = new_did.with_metadata(ctx.serialize(&new_did.info.metadata)?);
 
-        Ok((ctx.take(), nfts, new_did))
+        let coin_spends = ctx.take();
+        self.reuse_spend_context(ctx);
+
+        self.check_bundle_cost(&coin_spends)?;
+
+        Ok((coin_spends, nfts, new_did))
     }
 
+    /// Transfers the NFTs to the given puzzle hash. If `new_owner` is set, the owner DID of each NFT
+    /// is reassigned during the transfer, which requires spending that DID as well. Passing the
+    /// zero id instead clears the owner, without spending any DID.
+    ///
+    /// The NFTs are transferred together, so the transfer fails if it exceeds `max_bundle_cost`.
     pub async fn transfer_nfts(
         &self,
         nft_ids: Vec<Bytes32>,
         puzzle_hash: Bytes32,
+        new_owner: Option<Bytes32>,
         fee: u64,
         hardened: bool,
         reuse: bool,
@@ -111,6 +124,16 @@ impl Wallet {
             nfts.push(nft);
         }
 
+        let did = match new_owner {
+            Some(did_id) if did_id != Bytes32::default() => Some(
+                self.db
+                    .spendable_did(did_id)
+                    .await?
+                    .ok_or(WalletError::MissingDid(did_id))?,
+            ),
+            _ => None,
+        };
+
         let coins = if fee > 0 {
             self.select_p2_coins(fee as u128).await?
         } else {
@@ -124,18 +147,26 @@ impl Wallet {
 
         let change_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
 
-        let mut ctx = SpendContext::new();
+        let mut ctx = self.spend_context();
+
+        let did = if let Some(did) = did {
+            let did_metadata_ptr = ctx.alloc(&did.info.metadata)?;
+            Some(did.with_metadata(HashedPtr::from_ptr(&ctx.allocator, did_metadata_ptr)))
+        } else {
+            None
+        };
 
         let nft_coin_ids = nfts
             .iter()
             .map(|nft| nft.coin.coin_id())
             .collect::<Vec<_>>();
 
+        let mut did_conditions = Conditions::new();
+
         for (i, nft) in nfts.into_iter().enumerate() {
-            let nft_metadata_ptr = ctx.alloc(&nft.info.metadata)?;
-            let nft = nft.with_metadata(HashedPtr::from_ptr(&ctx.allocator, nft_metadata_ptr));
+            let nft = nft.hashed(&mut ctx)?;
 
-            let synthetic_key = self.db.synthetic_key(nft.info.p2_puzzle_hash).await?;
+            let synthetic_key = self.synthetic_key(nft.info.p2_puzzle_hash).await?;
             let p2 = StandardLayer::new(synthetic_key);
 
             let conditions = if nft_coin_ids.len() == 1 {
@@ -150,7 +181,27 @@ impl Wallet {
                 )
             };
 
-            let _nft = nft.transfer(&mut ctx, &p2, puzzle_hash, conditions)?;
+            if new_owner.is_some() {
+                let (parent_conditions, _nft) = nft.transfer_to_did(
+                    &mut ctx,
+                    &p2,
+                    puzzle_hash,
+                    did.as_ref().map(|did| DidOwner::from_did_info(&did.info)),
+                    conditions,
+                )?;
+
+                did_conditions = did_conditions.extend(parent_conditions);
+            } else {
+                let _nft = nft.transfer(&mut ctx, &p2, puzzle_hash, conditions)?;
+            }
+        }
+
+        let did_coin_id = did.as_ref().map(|did| did.coin.coin_id());
+
+        if let Some(did) = did {
+            let synthetic_key = self.synthetic_key(did.info.p2_puzzle_hash).await?;
+            let p2 = StandardLayer::new(synthetic_key);
+            let _did = did.update(&mut ctx, &p2, did_conditions)?;
         }
 
         if fee > 0 {
@@ -162,10 +213,19 @@ impl Wallet {
                 conditions = conditions.create_coin(change_puzzle_hash, change, None);
             }
 
+            if let Some(did_coin_id) = did_coin_id {
+                conditions = conditions.assert_concurrent_spend(did_coin_id);
+            }
+
             self.spend_p2_coins(&mut ctx, coins, conditions).await?;
         }
 
-        Ok(ctx.take())
+        let coin_spends = ctx.take();
+        self.reuse_spend_context(ctx);
+
+        self.check_bundle_cost(&coin_spends)?;
+
+        Ok(coin_spends)
     }
 
     pub async fn add_nft_uri(
@@ -180,6 +240,13 @@ impl Wallet {
             return Err(WalletError::MissingNft(nft_id));
         };
 
+        // The update spend is only understood by the standard metadata updater.
+        if nft.info.metadata_updater_puzzle_hash != NFT_METADATA_UPDATER_PUZZLE_HASH.into() {
+            return Err(WalletError::UnsupportedMetadataUpdater(
+                nft.info.metadata_updater_puzzle_hash,
+            ));
+        }
+
         let coins = if fee > 0 {
             self.select_p2_coins(fee as u128).await?
         } else {
@@ -195,10 +262,9 @@ impl Wallet {
 
         let mut ctx = SpendContext::new();
 
-        let nft_metadata_ptr = ctx.alloc(&nft.info.metadata)?;
-        let nft = nft.with_metadata(HashedPtr::from_ptr(&ctx.allocator, nft_metadata_ptr));
+        let nft = nft.hashed(&mut ctx)?;
 
-        let synthetic_key = self.db.synthetic_key(nft.info.p2_puzzle_hash).await?;
+        let synthetic_key = self.synthetic_key(nft.info.p2_puzzle_hash).await?;
         let p2 = StandardLayer::new(synthetic_key);
 
         let update_spend = uri.spend(&mut ctx)?;
@@ -232,7 +298,7 @@ impl Wallet {
 mod tests {
     use test_log::test;
 
-    use crate::TestWallet;
+    use crate::{TestWallet, WalletOptions};
 
     use super::*;
 
@@ -281,10 +347,360 @@ mod tests {
         for _ in 0..2 {
             let coin_spends = test
                 .wallet
-                .transfer_nfts(vec![nft.info.launcher_id], puzzle_hash, 0, false, true)
+                .transfer_nfts(
+                    vec![nft.info.launcher_id],
+                    puzzle_hash,
+                    None,
+                    0,
+                    false,
+                    true,
+                )
+                .await?;
+            test.transact(coin_spends).await?;
+            test.wait_for_coins().await;
+        }
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_bulk_nfts_max_bundle_cost() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(3).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let mints = vec![WalletNftMint {
+            metadata: NftMetadata::default(),
+            royalty_puzzle_hash: None,
+            royalty_ten_thousandths: 0,
+        }];
+
+        let (coin_spends, mut nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(0, did.info.launcher_id, mints.clone(), false, true)
+            .await?;
+        let mint_cost = test.wallet.estimate_cost(&coin_spends)?;
+
+        // A wallet on the same database which only allows transactions up to the given cost.
+        let limited = |test: &TestWallet, max_bundle_cost| {
+            test.wallet_with(
+                test.wallet.db.clone(),
+                WalletOptions {
+                    max_bundle_cost,
+                    ..test.wallet.options
+                },
+            )
+        };
+
+        assert!(matches!(
+            limited(&test, mint_cost - 1)
+                .bulk_mint_nfts(0, did.info.launcher_id, mints.clone(), false, true)
+                .await,
+            Err(WalletError::BundleCostExceeded { cost, max }) if cost == mint_cost && max == mint_cost - 1
+        ));
+
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let (coin_spends, more_nfts, _did) = limited(&test, mint_cost)
+            .bulk_mint_nfts(0, did.info.launcher_id, mints, false, true)
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        nfts.extend(more_nfts);
+
+        let nft_ids: Vec<Bytes32> = nfts.iter().map(|nft| nft.info.launcher_id).collect();
+
+        let coin_spends = test
+            .wallet
+            .transfer_nfts(nft_ids.clone(), test.puzzle_hash, None, 0, false, true)
+            .await?;
+        let transfer_cost = test.wallet.estimate_cost(&coin_spends)?;
+
+        assert!(matches!(
+            limited(&test, transfer_cost - 1)
+                .transfer_nfts(nft_ids.clone(), test.puzzle_hash, None, 0, false, true)
+                .await,
+            Err(WalletError::BundleCostExceeded { .. })
+        ));
+
+        let coin_spends = limited(&test, transfer_cost)
+            .transfer_nfts(nft_ids, test.puzzle_hash, None, 0, false, true)
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_add_nft_uri() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(3).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata {
+                        data_uris: vec!["https://example.com/nft.png".to_string()],
+                        ..Default::default()
+                    },
+                    royalty_puzzle_hash: None,
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let launcher_id = nfts.remove(0).info.launcher_id;
+
+        let (coin_spends, _nft) = test
+            .wallet
+            .add_nft_uri(
+                launcher_id,
+                1,
+                MetadataUpdate::NewDataUri("https://mirror.example.com/nft.png".to_string()),
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let nft = test
+            .wallet
+            .db
+            .spendable_nft(launcher_id)
+            .await?
+            .expect("missing nft");
+
+        let mut ctx = SpendContext::new();
+        let metadata_ptr = ctx.alloc(&nft.info.metadata)?;
+        let metadata: NftMetadata = ctx.extract(metadata_ptr)?;
+
+        assert_eq!(
+            metadata.data_uris,
+            [
+                "https://mirror.example.com/nft.png".to_string(),
+                "https://example.com/nft.png".to_string()
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_nft_groups_by_metadata_hash() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(4).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let edition_hash = Bytes32::new([1; 32]);
+
+        let mint = |edition_number, metadata_hash| WalletNftMint {
+            metadata: NftMetadata {
+                edition_number,
+                metadata_uris: vec!["https://example.com/metadata.json".to_string()],
+                metadata_hash,
+                ..Default::default()
+            },
+            royalty_puzzle_hash: None,
+            royalty_ten_thousandths: 0,
+        };
+
+        let mut nfts = Vec::new();
+
+        for mint in [
+            mint(1, Some(edition_hash)),
+            mint(1, None),
+            mint(2, Some(edition_hash)),
+        ] {
+            let (coin_spends, minted, _did) = test
+                .wallet
+                .bulk_mint_nfts(0, did.info.launcher_id, vec![mint], false, true)
+                .await?;
+            test.transact(coin_spends).await?;
+            test.wait_for_coins().await;
+
+            nfts.extend(minted);
+        }
+
+        let groups = test.wallet.db.nft_groups_by_metadata_hash().await?;
+        assert_eq!(groups.len(), 2);
+
+        // Both editions are grouped together, and the NFT with unique metadata is on its own.
+        let mut editions = vec![nfts[0].info.launcher_id, nfts[2].info.launcher_id];
+        editions.sort();
+
+        assert_eq!(groups[0].metadata_hash, Some(edition_hash));
+        assert_eq!(groups[0].launcher_ids, editions);
+        assert_eq!(groups[0].count, 2);
+
+        assert_eq!(groups[1].metadata_hash, None);
+        assert_eq!(groups[1].launcher_ids, vec![nfts[1].info.launcher_id]);
+        assert_eq!(groups[1].count, 1);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_burn_nft() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(2).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: None,
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let launcher_id = nfts.remove(0).info.launcher_id;
+
+        // Burn addresses can be added alongside the default one, and removed again.
+        let burn_puzzle_hash = Bytes32::new([0xbb; 32]);
+        test.wallet
+            .db
+            .insert_burn_puzzle_hash(burn_puzzle_hash)
+            .await?;
+        assert_eq!(test.wallet.db.burn_puzzle_hashes().await?.len(), 2);
+
+        let removed_puzzle_hash = Bytes32::new([0xcc; 32]);
+        test.wallet
+            .db
+            .insert_burn_puzzle_hash(removed_puzzle_hash)
+            .await?;
+        test.wallet
+            .db
+            .remove_burn_puzzle_hash(removed_puzzle_hash)
+            .await?;
+        assert!(
+            !test
+                .wallet
+                .db
+                .is_burn_puzzle_hash(removed_puzzle_hash)
+                .await?
+        );
+
+        let coin_spends = test
+            .wallet
+            .transfer_nfts(vec![launcher_id], burn_puzzle_hash, None, 0, false, true)
+            .await?;
+        test.transact(coin_spends).await?;
+
+        // The transfer alone doesn't mark the NFT as burned until it's confirmed.
+        let row = test
+            .wallet
+            .db
+            .nft_row(launcher_id)
+            .await?
+            .expect("missing nft");
+        assert!(!row.is_burned);
+
+        test.wait_for_coins().await;
+        test.wait_for_puzzles().await;
+
+        let row = test
+            .wallet
+            .db
+            .nft_row(launcher_id)
+            .await?
+            .expect("missing nft");
+
+        assert!(row.is_burned);
+        assert!(!row.is_owned);
+        assert!(test.wallet.db.spendable_nft(launcher_id).await?.is_none());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_transfer_nft_with_new_owner() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(3).await?;
+
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let (coin_spends, other_did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: None,
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let launcher_id = nfts.remove(0).info.launcher_id;
+        let puzzle_hash = test.wallet.p2_puzzle_hash(false, false).await?;
+
+        for (new_owner, expected) in [
+            (Bytes32::default(), None),
+            (did.info.launcher_id, Some(did.info.launcher_id)),
+            (other_did.info.launcher_id, Some(other_did.info.launcher_id)),
+        ] {
+            let coin_spends = test
+                .wallet
+                .transfer_nfts(
+                    vec![launcher_id],
+                    puzzle_hash,
+                    Some(new_owner),
+                    0,
+                    false,
+                    true,
+                )
                 .await?;
             test.transact(coin_spends).await?;
             test.wait_for_coins().await;
+
+            let nft = test
+                .wallet
+                .db
+                .spendable_nft(launcher_id)
+                .await?
+                .expect("missing nft");
+
+            assert_eq!(nft.info.p2_puzzle_hash, puzzle_hash);
+            assert_eq!(nft.info.current_owner, expected);
         }
 
         Ok(())
@@ -1,14 +1,27 @@
+mod alternative_offers;
+mod bump_offer_fee;
+mod cancel_offer;
+mod incoming_settlements;
 mod lock_assets;
 mod make_offer;
+mod marketplace_offer;
+mod notarized_payments;
 mod offer_coins;
+mod offer_price;
 mod parse_offer;
 mod royalties;
 mod take_offer;
 mod unlock_assets;
 
+pub use bump_offer_fee::*;
+pub use cancel_offer::*;
+pub use incoming_settlements::*;
 pub use lock_assets::*;
 pub use make_offer::*;
+pub use marketplace_offer::*;
+pub use notarized_payments::*;
 pub use offer_coins::*;
+pub use offer_price::*;
 pub use parse_offer::*;
 pub use royalties::*;
 pub use take_offer::*;
@@ -16,43 +29,1929 @@ pub use unlock_assets::*;
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Ordering;
+
     use chia::{
+        bls::Signature,
         clvm_traits::{FromClvm, ToClvm},
-        protocol::{Bytes32, Program},
-        puzzles::nft::NftMetadata,
+        protocol::{Bytes, Bytes32, Program, SpendBundle},
+        puzzles::{
+            nft::NftMetadata,
+            offer::{Memos, Payment, SettlementPaymentsSolution, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
+        },
     };
+    use chia_wallet_sdk::{Conditions, Offer, OfferBuilder, SpendContext};
     use clvmr::Allocator;
     use indexmap::{indexmap, IndexMap};
+    use sage_database::{OfferRow, OfferStatus};
     use test_log::test;
 
-    use crate::{MakerSide, RequestedNft, TakerSide, TestWallet, WalletNftMint};
+    use crate::{
+        calculate_royalties, parse_asserted_announcements, parse_locked_coins,
+        parse_offer_payments, BindingMode, MakerSide, NftRoyaltyInfo, OfferAmounts, OfferExt,
+        OfferPrice, OfferSpend, OfferTerms, OverfillPolicy, RequestedNft, Royalties, TakeOptions,
+        TakerSide, TestWallet, UnsignedMakeOffer, WalletError, WalletNftMint, WalletOptions,
+        MAX_NFT_MEMO_SIZE, MAX_OFFER_MEMO_SIZE,
+    };
+
+    #[test(tokio::test)]
+    async fn test_offer_xch_for_cat() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        // Issue CAT
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // Create offer
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 750,
+                    fee: 250,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Take offer
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        // We need to wait for both wallets to sync in this case
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        // Check balances
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.balance().await?, 750);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_fee_paid_by_taker() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1250).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // The maker doesn't include a fee, so the offer doesn't reserve one.
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 750,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let mut allocator = Allocator::new();
+        let parsed = offer.clone().parse(&mut allocator)?;
+        let (locked_coins, _original_coin_ids) = parse_locked_coins(&mut allocator, &parsed)?;
+        assert_eq!(locked_coins.fee, 0);
+
+        // The taker pays the whole fee instead.
+        let offer = bob
+            .wallet
+            .take_offer(offer, 250, false, false, true)
+            .await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 250);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.balance().await?, 750);
+        assert_eq!(bob.wallet.db.cat_balance(asset_id).await?, 0);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_xch_change_boundaries() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // The first offer leaves a single mojo of change, and the second offers exactly that coin.
+        for (xch, change) in [(999, 1), (1, 0)] {
+            let offer = alice
+                .wallet
+                .make_offer(
+                    MakerSide {
+                        xch,
+                        ..Default::default()
+                    },
+                    TakerSide {
+                        cats: indexmap! { asset_id => 500 },
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                    false,
+                    true,
+                )
+                .await?;
+            assert_eq!(offer.coin_spends.len(), 1);
+            assert_eq!(offer.coin_spends[0].coin.amount, xch + change);
+
+            let offer = alice
+                .wallet
+                .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+                .await?;
+
+            let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+            let spend_bundle = bob
+                .wallet
+                .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+                .await?;
+            bob.push_bundle(spend_bundle).await?;
+
+            bob.wait_for_coins().await;
+            alice.wait_for_puzzles().await;
+
+            let coins = alice.wallet.db.spendable_coins().await?;
+            assert_eq!(
+                coins.iter().map(|coin| coin.amount).collect::<Vec<_>>(),
+                if change > 0 { vec![change] } else { Vec::new() }
+            );
+        }
+
+        assert_eq!(alice.wallet.db.balance().await?, 0);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.balance().await?, 1000);
+        assert_eq!(bob.wallet.db.cat_balance(asset_id).await?, 0);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_cancel_offer() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1500).await?;
+        let bob = alice.next(0).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(1000, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let coin_id = alice.wallet.db.spendable_coins().await?[0].coin_id();
+        let cat_coin_id = alice.wallet.db.spendable_cat_coins(asset_id).await?[0]
+            .coin
+            .coin_id();
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 300,
+                    cats: indexmap! { asset_id => 1000 },
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { Bytes32::new([1; 32]) => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let mut tx = alice.wallet.db.tx().await?;
+        tx.insert_offer(OfferRow {
+            offer_id: offer.offer_id(),
+            encoded_offer: offer.encode()?,
+            expiration_height: None,
+            expiration_timestamp: None,
+            fee: 0,
+            status: OfferStatus::Active,
+            inserted_timestamp: 0,
+        })
+        .await?;
+        tx.insert_offered_coin(offer.offer_id(), coin_id).await?;
+        tx.insert_offered_coin(offer.offer_id(), cat_coin_id)
+            .await?;
+        tx.commit().await?;
+
+        // Only the maker has the coins needed to cancel the offer
+        assert!(matches!(
+            bob.wallet.cancel_offer(offer.clone(), 0, false, true).await,
+            Err(WalletError::OfferNotOwned)
+        ));
+
+        // The CAT coin asserts that the XCH coin is spent with it, so only the XCH coin is spent.
+        let coin_spends = alice
+            .wallet
+            .cancel_offer(offer.clone(), 100, false, true)
+            .await?;
+        assert_eq!(coin_spends.len(), 1);
+        assert_eq!(coin_spends[0].coin.coin_id(), coin_id);
+
+        // The offer stays active until the cancellation is submitted.
+        assert_eq!(
+            alice
+                .wallet
+                .db
+                .get_offer(offer.offer_id())
+                .await?
+                .map(|row| row.status),
+            Some(OfferStatus::Active)
+        );
+
+        alice.transact(coin_spends).await?;
+
+        assert_eq!(
+            alice
+                .wallet
+                .db
+                .get_offer(offer.offer_id())
+                .await?
+                .map(|row| row.status),
+            Some(OfferStatus::PendingCancel)
+        );
+        alice.wait_for_coins().await;
+
+        let coin_state = alice.wallet.db.coin_state(coin_id).await?;
+        assert!(coin_state.is_some_and(|coin_state| coin_state.spent_height.is_some()));
+        let coin_state = alice.wallet.db.coin_state(cat_coin_id).await?;
+        assert!(coin_state.is_some_and(|coin_state| coin_state.spent_height.is_none()));
+
+        assert_eq!(alice.wallet.db.balance().await?, 400);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_cancel_unbound_offer() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_xch(alice.puzzle_hash, 400, 0, Vec::new(), false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let coins = alice
+            .wallet
+            .fetch_offer_coins(
+                &OfferAmounts {
+                    xch: 1000,
+                    cats: IndexMap::new(),
+                },
+                Vec::new(),
+                Vec::new(),
+                true,
+            )
+            .await?;
+        let p2_puzzle_hash = alice.wallet.p2_puzzle_hash(false, true).await?;
+
+        let mut ctx = SpendContext::new();
+        let settlement = ctx.settlement_payments_puzzle()?;
+
+        let (assertions, builder) = OfferBuilder::new(coins.nonce())
+            .request(
+                &mut ctx,
+                &settlement,
+                vec![Payment::with_memos(
+                    p2_puzzle_hash,
+                    1000,
+                    vec![p2_puzzle_hash.into()],
+                )],
+            )?
+            .finish();
+
+        // Each coin is spent on its own, so none of them depend on each other.
+        alice
+            .wallet
+            .spend_p2_coins_separately(
+                &mut ctx,
+                coins.xch.iter().map(|&coin| {
+                    let conditions = Conditions::new()
+                        .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), coin.amount, None)
+                        .extend(assertions.clone());
+                    (coin, conditions)
+                }),
+            )
+            .await?;
+
+        let coin_spends = ctx.take();
+        let offer = alice
+            .wallet
+            .sign_make_offer(
+                UnsignedMakeOffer {
+                    ctx,
+                    coin_spends,
+                    builder,
+                    terms: OfferTerms::default(),
+                },
+                &alice.agg_sig,
+                alice.master_sk.clone(),
+            )
+            .await?;
+
+        let coin_spends = alice.wallet.cancel_offer(offer, 0, false, true).await?;
+        assert_eq!(coin_spends.len(), 2);
+
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        for coin in coins.xch {
+            let coin_state = alice.wallet.db.coin_state(coin.coin_id()).await?;
+            assert!(coin_state.is_some_and(|coin_state| coin_state.spent_height.is_some()));
+        }
+
+        assert_eq!(alice.wallet.db.balance().await?, 1000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_alternative_offers() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let coin_id = alice.wallet.db.spendable_coins().await?[0].coin_id();
+
+        // Request either an NFT or the CAT in exchange for the same XCH.
+        let [primary, alternative] = alice
+            .wallet
+            .make_alternative_offers(
+                MakerSide {
+                    xch: 750,
+                    ..Default::default()
+                },
+                TakerSide {
+                    nfts: indexmap! {
+                        Bytes32::new([1; 32]) => RequestedNft {
+                            metadata: Program::default(),
+                            metadata_updater_puzzle_hash: Bytes32::default(),
+                            royalty_puzzle_hash: Bytes32::default(),
+                            royalty_ten_thousandths: 0,
+                            memos: Vec::new(),
+                        },
+                    },
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+
+        for offer in [&primary, &alternative] {
+            assert!(offer
+                .coin_spends
+                .iter()
+                .any(|coin_spend| coin_spend.coin.coin_id() == coin_id));
+        }
+
+        let primary = alice
+            .wallet
+            .sign_make_offer(primary, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+        let alternative = alice
+            .wallet
+            .sign_make_offer(alternative, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Take the alternative offer
+        let offer = bob
+            .wallet
+            .take_offer(alternative, 0, false, false, true)
+            .await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+
+        // The shared coin has been spent, so the primary offer is void.
+        assert!(alice
+            .sim
+            .coin_state(coin_id)
+            .await
+            .is_some_and(|coin_state| coin_state.spent_height.is_some()));
+        assert!(matches!(
+            alice.wallet.cancel_offer(primary, 0, false, true).await,
+            Err(WalletError::CoinAlreadySpent { coin_id: id }) if id == coin_id
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_selected_coin() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        // Issue CAT
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // Split XCH
+        let coins = alice.wallet.db.spendable_coins().await?;
+        let coin_spends = alice.wallet.split_xch(&coins, 2, 0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let coin = alice.wallet.db.spendable_coins().await?[0];
+
+        let maker = |xch| MakerSide {
+            xch,
+            coin_ids: vec![coin.coin_id()],
+            ..Default::default()
+        };
+        let taker = || TakerSide {
+            cats: indexmap! { asset_id => 1000 },
+            ..Default::default()
+        };
+
+        // The selected coin alone can't cover more than its own amount
+        let result = alice
+            .wallet
+            .make_offer(maker(coin.amount + 1), taker(), None, None, false, true)
+            .await;
+        assert!(matches!(result, Err(WalletError::InsufficientFunds)));
+
+        // Create offer
+        let offer = alice
+            .wallet
+            .make_offer(maker(250), taker(), None, None, false, true)
+            .await?;
+        assert_eq!(offer.coin_spends.len(), 1);
+        assert_eq!(offer.coin_spends[0].coin, coin);
+
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Take offer
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        // Check balances
+        assert_eq!(alice.wallet.db.balance().await?, 750);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.balance().await?, 250);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_start_time() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let maker = MakerSide {
+            xch: 1000,
+            ..Default::default()
+        };
+
+        let taker = TakerSide {
+            cats: indexmap! { asset_id => 1000 },
+            ..Default::default()
+        };
+
+        // The simulator uses the block height as the timestamp.
+        let starts_at = u64::from(alice.sim.height().await) + 3;
+
+        let result = alice
+            .wallet
+            .make_offer(
+                maker.clone(),
+                taker.clone(),
+                Some(starts_at),
+                Some(starts_at),
+                false,
+                true,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(WalletError::InvalidOfferWindow { .. })
+        ));
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                maker,
+                taker,
+                Some(starts_at),
+                Some(starts_at + 10),
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+
+        // The offer can't be taken before the start time.
+        let ack = bob.peer.send_transaction(spend_bundle.clone()).await?;
+        assert_eq!(ack.status, 3);
+
+        while u64::from(bob.sim.height().await) < starts_at {
+            bob.advance_height().await?;
+        }
+
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.balance().await?, 1000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_cat_for_cat() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        // Issue CATs
+        let (coin_spends, alice_asset_id) =
+            alice.wallet.issue_cat(1000, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let (coin_spends, bob_asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // Create offer
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    cats: indexmap! { alice_asset_id => 400 },
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { bob_asset_id => 600 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Take offer
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        // Check balances
+        assert_eq!(alice.wallet.db.cat_balance(alice_asset_id).await?, 600);
+        assert_eq!(alice.wallet.db.cat_balance(bob_asset_id).await?, 600);
+        assert_eq!(bob.wallet.db.cat_balance(alice_asset_id).await?, 400);
+        assert_eq!(bob.wallet.db.cat_balance(bob_asset_id).await?, 400);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_announcement_binding() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, alice_asset_id) =
+            alice.wallet.issue_cat(500, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let (coin_spends, bob_asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // Both the XCH and the CAT are primary coins, which are bound with announcements.
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 100,
+                    cats: indexmap! { alice_asset_id => 400 },
+                    binding_mode: BindingMode::Announcements,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { bob_asset_id => 600 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob
+            .wallet
+            .take_offer_with_options(
+                offer,
+                TakeOptions {
+                    binding_mode: BindingMode::Linear,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 400);
+        assert_eq!(alice.wallet.db.cat_balance(alice_asset_id).await?, 100);
+        assert_eq!(alice.wallet.db.cat_balance(bob_asset_id).await?, 600);
+        assert_eq!(bob.wallet.db.cat_balance(alice_asset_id).await?, 400);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_cat_change_found_on_resync() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(1000, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        // Only part of the CAT coin is offered, so the rest is sent back as hinted change.
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    cats: indexmap! { asset_id => 400 },
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 300,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 600);
+
+        // Recover the wallet into an empty database, as if it had been restored from the mnemonic.
+        alice.resync_from_scratch().await?;
+
+        // The change coin can only be found by its hint, since it's locked in the CAT puzzle.
+        let cat_coins = alice.wallet.db.spendable_cat_coins(asset_id).await?;
+        assert_eq!(cat_coins.len(), 1);
+        assert_eq!(cat_coins[0].coin.amount, 600);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 600);
+        assert_eq!(alice.wallet.db.balance().await?, 300);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_requested_cat_found_on_resync() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 300,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 400 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 400);
+
+        // Recover the wallet into an empty database, as if it had been restored from the mnemonic.
+        alice.resync_from_scratch().await?;
+
+        // The received CAT was paid out of the settlement puzzle, so it can only be found by its hint.
+        let cat_coins = alice.wallet.db.spendable_cat_coins(asset_id).await?;
+        assert_eq!(cat_coins.len(), 1);
+        assert_eq!(cat_coins[0].coin.amount, 400);
+        assert_eq!(cat_coins[0].p2_puzzle_hash, alice.puzzle_hash);
+        assert_eq!(alice.wallet.db.balance().await?, 700);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_cat_for_same_cat() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(1000, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    cats: indexmap! { asset_id => 500 },
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 600 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(result, Err(WalletError::SelfTrade(Some(id))) if id == asset_id));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_empty_offer() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    fee: 100,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { Bytes32::new([1; 32]) => 0 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(result, Err(WalletError::EmptyOffer)));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_xch_for_xch() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 500,
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 600,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(result, Err(WalletError::SelfTrade(None))));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_take_own_offer() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(500, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    cats: indexmap! { asset_id => 200 },
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 100,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let result = alice
+            .wallet
+            .take_offer(offer.clone(), 0, false, false, true)
+            .await;
+        assert!(matches!(result, Err(WalletError::CannotTakeOwnOffer)));
+
+        // Another wallet can still take it.
+        let bob = alice.next(1000).await?;
+        bob.wallet.take_offer(offer, 0, false, false, true).await?;
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_nft_for_same_nft() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let nft_id = Bytes32::new([1; 32]);
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    nfts: vec![nft_id],
+                    ..Default::default()
+                },
+                TakerSide {
+                    nfts: indexmap! {
+                        nft_id => RequestedNft {
+                            metadata: Program::default(),
+                            metadata_updater_puzzle_hash: Bytes32::default(),
+                            royalty_puzzle_hash: Bytes32::default(),
+                            royalty_ten_thousandths: 0,
+                            memos: Vec::new(),
+                        },
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(result, Err(WalletError::SelfTrade(Some(id))) if id == nft_id));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_too_large() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+
+        let coins = alice.wallet.db.spendable_coins().await?;
+        let coin_spends = alice.wallet.split_xch(&coins, 3, 0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let wallet = alice.wallet_with(
+            alice.wallet.db.clone(),
+            WalletOptions {
+                max_offer_spends: 2,
+                ..alice.wallet.options
+            },
+        );
+
+        // Offering the whole balance requires spending all three coins.
+        let result = wallet
+            .make_offer(
+                MakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { Bytes32::new([1; 32]) => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(WalletError::OfferTooLarge { spends: 3, max: 2 })
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_without_change() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+
+        let coins = alice.wallet.db.spendable_coins().await?;
+        let coin_spends = alice.wallet.split_xch(&coins, 4, 0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let maker = |xch| MakerSide {
+            xch,
+            fee: 100,
+            allow_change: false,
+            ..Default::default()
+        };
+
+        let taker = TakerSide {
+            cats: indexmap! { Bytes32::new([1; 32]) => 1000 },
+            ..Default::default()
+        };
+
+        // Two of the 250 mojo coins cover the offered amount and fee exactly.
+        let offer = alice
+            .wallet
+            .make_offer(maker(400), taker.clone(), None, None, false, true)
+            .await?;
+
+        assert_eq!(offer.coin_spends.len(), 2);
+        assert!(offer
+            .coin_spends
+            .iter()
+            .all(|coin_spend| coin_spend.coin.amount == 250));
+
+        // No combination of the coins adds up to 600.
+        let result = alice
+            .wallet
+            .make_offer(maker(500), taker, None, None, false, true)
+            .await;
+
+        assert!(matches!(result, Err(WalletError::NoExactCoins)));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_cat_amount_overflow() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let asset_id = Bytes32::new([1; 32]);
+        let nft_id = Bytes32::new([2; 32]);
+
+        // The royalty owed on the requested NFT is added to the offered amount, which overflows.
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    cats: indexmap! { asset_id => u64::MAX },
+                    ..Default::default()
+                },
+                TakerSide {
+                    nfts: indexmap! {
+                        nft_id => RequestedNft {
+                            metadata: Program::default(),
+                            metadata_updater_puzzle_hash: Bytes32::default(),
+                            royalty_puzzle_hash: Bytes32::default(),
+                            royalty_ten_thousandths: 300,
+                            memos: Vec::new(),
+                        },
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(WalletError::AmountOverflow { asset_id: Some(id) }) if id == asset_id
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_royalty_total_overflow() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        // Each royalty fits in a coin amount on its own, but together they add up to more.
+        let nfts = (1..=10)
+            .map(|i| {
+                (
+                    Bytes32::new([i; 32]),
+                    RequestedNft {
+                        metadata: Program::default(),
+                        metadata_updater_puzzle_hash: Bytes32::default(),
+                        royalty_puzzle_hash: Bytes32::default(),
+                        royalty_ten_thousandths: u16::MAX,
+                        memos: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: u64::MAX / 4,
+                    ..Default::default()
+                },
+                TakerSide {
+                    nfts,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(result, Err(WalletError::RoyaltyOverflow)));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_request_cat_to_custom_puzzle_hash() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let asset_id = Bytes32::new([1; 32]);
+        let puzzle_hash = Bytes32::new([2; 32]);
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 500 },
+                    cat_puzzle_hashes: indexmap! { asset_id => puzzle_hash },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let mut ctx = SpendContext::new();
+        let mut builder = offer.parse(&mut ctx.allocator)?.take();
+        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+        let payments = &requested_payments.cats[&asset_id];
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].payments.len(), 1);
+        assert_eq!(payments[0].payments[0].puzzle_hash, puzzle_hash);
+        assert_eq!(payments[0].payments[0].amount, 500);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_request_assets_to_recipient() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+        let mut carol = bob.next(0).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // Alice pays for the CAT, but it's delivered to Carol.
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 750,
+                    fee: 250,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 1000 },
+                    recipient_override: Some(carol.puzzle_hash),
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        carol.wait_for_puzzles().await;
+
+        assert_eq!(carol.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 0);
+        assert_eq!(bob.wallet.db.balance().await?, 750);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_request_nft_with_custom_memo() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let nft_id = Bytes32::new([1; 32]);
+        let memo = Bytes::from(b"order-42".to_vec());
+
+        let taker = |memos: Vec<Bytes>| TakerSide {
+            nfts: indexmap! {
+                nft_id => RequestedNft {
+                    metadata: Program::default(),
+                    metadata_updater_puzzle_hash: Bytes32::default(),
+                    royalty_puzzle_hash: Bytes32::default(),
+                    royalty_ten_thousandths: 0,
+                    memos,
+                },
+            },
+            ..Default::default()
+        };
+
+        let maker = MakerSide {
+            xch: 1000,
+            ..Default::default()
+        };
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                maker.clone(),
+                taker(vec![memo.clone()]),
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let mut ctx = SpendContext::new();
+        let mut builder = offer.parse(&mut ctx.allocator)?.take();
+        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+        // The hint is still included first, followed by the custom memo.
+        let p2_puzzle_hash = alice.wallet.p2_puzzle_hash(false, true).await?;
+        let (_info, payments) = &requested_payments.nfts[&nft_id];
+        assert_eq!(payments.len(), 1);
+        assert_eq!(
+            payments[0].payments[0].memos,
+            Some(Memos(vec![p2_puzzle_hash.into(), memo]))
+        );
+
+        let result = alice
+            .wallet
+            .make_offer(
+                maker,
+                taker(vec![Bytes::from(vec![0; MAX_NFT_MEMO_SIZE + 1])]),
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(WalletError::MemoTooLarge {
+                max: MAX_NFT_MEMO_SIZE,
+                ..
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_compare_offer_prices() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+
+        let mut prices = Vec::new();
+
+        for (xch, fee, asset_id) in [
+            (500, 0, Bytes32::default()),
+            (250, 0, Bytes32::default()),
+            (250, 0, Bytes32::new([1; 32])),
+            (250, 250, Bytes32::default()),
+        ] {
+            let offer = alice
+                .wallet
+                .make_offer(
+                    MakerSide {
+                        xch,
+                        fee,
+                        ..Default::default()
+                    },
+                    TakerSide {
+                        cats: indexmap! { asset_id => 1000 },
+                        ..Default::default()
+                    },
+                    None,
+                    None,
+                    false,
+                    true,
+                )
+                .await?;
+            let offer = alice
+                .wallet
+                .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+                .await?;
+
+            prices.push(OfferPrice::from_offer(offer)?.expect("offer should be priced"));
+        }
+
+        assert_eq!(prices[0].offered_amount, 500);
+        assert_eq!(prices[0].requested_amount, 1000);
+        assert_eq!(prices[0].compare(&prices[1]), Some(Ordering::Less));
+        assert_eq!(prices[1].compare(&prices[0]), Some(Ordering::Greater));
+        assert_eq!(prices[1].compare(&prices[2]), None);
+
+        // The maker's fee is paid to the farmer, so it doesn't change the price.
+        assert_eq!(prices[0].fee, 0);
+        assert_eq!(prices[3].offered_amount, 250);
+        assert_eq!(prices[3].fee, 250);
+        assert_eq!(prices[3].compare(&prices[1]), Some(Ordering::Equal));
+        assert_eq!(prices[3].compare(&prices[0]), Some(Ordering::Greater));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_xch_for_nft() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1030).await?;
+        let mut bob = alice.next(2).await?;
+
+        let (coin_spends, did) = bob.wallet.create_did(0, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = bob
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: Some(Bytes32::default()),
+                    royalty_ten_thousandths: 300,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let nft = nfts.remove(0);
+
+        let mut allocator = Allocator::new();
+        let metadata = nft.info.metadata.to_clvm(&mut allocator)?;
+        let metadata = Program::from_clvm(&allocator, metadata)?;
+
+        // Create offer
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                TakerSide {
+                    nfts: indexmap! {
+                        nft.info.launcher_id => RequestedNft {
+                            metadata,
+                            metadata_updater_puzzle_hash: nft.info.metadata_updater_puzzle_hash,
+                            royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
+                            royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
+                            memos: Vec::new(),
+                        },
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Take offer
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        // We need to wait for both wallets to sync in this case
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        // Check balances
+        assert_ne!(
+            alice.wallet.db.spendable_nft(nft.info.launcher_id).await?,
+            None
+        );
+        assert_eq!(bob.wallet.db.balance().await?, 1000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_take_offer_underpaid_royalty() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1030).await?;
+        let mut bob = alice.next(2).await?;
+
+        let (coin_spends, did) = bob.wallet.create_did(0, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = bob
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: Some(Bytes32::default()),
+                    royalty_ten_thousandths: 300,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let nft = nfts.remove(0);
+
+        let mut allocator = Allocator::new();
+        let metadata = nft.info.metadata.to_clvm(&mut allocator)?;
+        let metadata = Program::from_clvm(&allocator, metadata)?;
+
+        // Create offer
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                TakerSide {
+                    nfts: indexmap! {
+                        nft.info.launcher_id => RequestedNft {
+                            metadata,
+                            metadata_updater_puzzle_hash: nft.info.metadata_updater_puzzle_hash,
+                            royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
+                            royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
+                            memos: Vec::new(),
+                        },
+                    },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Lower the royalty paid by the maker's settlement spend
+        let mut spend_bundle = SpendBundle::from(offer);
+
+        for coin_spend in &mut spend_bundle.coin_spends {
+            if coin_spend.coin.puzzle_hash != SETTLEMENT_PAYMENTS_PUZZLE_HASH.into() {
+                continue;
+            }
+
+            let solution = coin_spend.solution.to_clvm(&mut allocator)?;
+            let mut solution = SettlementPaymentsSolution::from_clvm(&allocator, solution)?;
+
+            for notarized_payment in &mut solution.notarized_payments {
+                if notarized_payment.nonce == nft.info.launcher_id {
+                    notarized_payment.payments[0].amount -= 1;
+                }
+            }
+
+            let solution = solution.to_clvm(&mut allocator)?;
+            coin_spend.solution = Program::from_clvm(&allocator, solution)?;
+        }
+
+        // Take offer
+        let result = bob
+            .wallet
+            .take_offer(Offer::from(spend_bundle), 0, false, false, true)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(WalletError::RoyaltyUnderpaid { nft_id, required: 30, paid: 29 })
+                if nft_id == nft.info.launcher_id
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_stale_nft() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(2).await?;
+
+        let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = alice
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: Some(Bytes32::default()),
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let nft = nfts.remove(0);
+        let nft_id = nft.info.launcher_id;
+
+        // Simulate the NFT coin being spent without the wallet having synced the new coin.
+        let coin_state = alice
+            .wallet
+            .db
+            .coin_state(nft.coin.coin_id())
+            .await?
+            .expect("missing nft coin state");
+
+        let mut tx = alice.wallet.db.tx().await?;
+        tx.update_coin_state(
+            nft.coin.coin_id(),
+            coin_state.created_height,
+            coin_state.created_height,
+            None,
+        )
+        .await?;
+        tx.commit().await?;
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    nfts: vec![nft_id],
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        assert!(matches!(result, Err(WalletError::StaleNft { nft_id: id }) if id == nft_id));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_pending_nft() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(2).await?;
+
+        let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let (coin_spends, mut nfts, _did) = alice
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: Some(Bytes32::default()),
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let nft = nfts.remove(0);
+        let nft_id = nft.info.launcher_id;
+
+        // Lock the NFT coin with a pending transaction which hasn't been submitted yet.
+        let coin_spend = alice
+            .wallet
+            .transfer_nfts(vec![nft_id], alice.puzzle_hash, None, 0, false, true)
+            .await?
+            .into_iter()
+            .find(|coin_spend| coin_spend.coin == nft.coin)
+            .expect("missing nft spend");
+
+        let transaction_id = Bytes32::new([42; 32]);
+
+        let mut tx = alice.wallet.db.tx().await?;
+        tx.insert_pending_transaction(transaction_id, Signature::default(), 0)
+            .await?;
+        tx.insert_transaction_spend(transaction_id, coin_spend, 0)
+            .await?;
+        tx.commit().await?;
+
+        let result = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    nfts: vec![nft_id],
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+
+        // The NFT's coin hasn't been spent, so it isn't reported as out of date.
+        assert!(matches!(result, Err(WalletError::MissingNft(id)) if id == nft_id));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_nfts_with_shared_royalty_for_xch() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(4).await?;
+        let mut bob = alice.next(1030).await?;
+
+        let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let royalty_puzzle_hash = Bytes32::new([42; 32]);
+
+        let mut nft_ids = Vec::new();
+
+        for edition_number in 1..=3 {
+            let (coin_spends, nfts, _did) = alice
+                .wallet
+                .bulk_mint_nfts(
+                    0,
+                    did.info.launcher_id,
+                    vec![WalletNftMint {
+                        metadata: NftMetadata {
+                            edition_number,
+                            ..Default::default()
+                        },
+                        royalty_puzzle_hash: Some(royalty_puzzle_hash),
+                        royalty_ten_thousandths: 300,
+                    }],
+                    false,
+                    true,
+                )
+                .await?;
+            alice.transact(coin_spends).await?;
+            alice.wait_for_coins().await;
+
+            nft_ids.extend(nfts.iter().map(|nft| nft.info.launcher_id));
+        }
+
+        // Create offer
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    nfts: nft_ids.clone(),
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // Take offer
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        alice.wait_for_coins().await;
+        bob.wait_for_coins().await;
+
+        // Check balances
+        assert_eq!(alice.wallet.db.balance().await?, 1000);
+
+        // Each NFT has a 3% royalty on a third of the price, paid to the same address.
+        assert_eq!(bob.wallet.db.balance().await?, 3);
+
+        for nft_id in nft_ids {
+            assert_ne!(bob.wallet.db.spendable_nft(nft_id).await?, None);
+        }
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_xch_for_nfts_with_distinct_royalties() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(3085).await?;
+        let mut bob = alice.next(4).await?;
+
+        let (coin_spends, did) = bob.wallet.create_did(0, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        // Two of the NFTs share a royalty address, but with different percentages.
+        let royalty_configs = [
+            (Bytes32::new([1; 32]), 100),
+            (Bytes32::new([1; 32]), 250),
+            (Bytes32::new([2; 32]), 500),
+        ];
+
+        let mut requested_nfts = IndexMap::new();
+        let mut allocator = Allocator::new();
+
+        for (edition_number, (royalty_puzzle_hash, royalty_ten_thousandths)) in
+            (1..).zip(royalty_configs)
+        {
+            let (coin_spends, nfts, _did) = bob
+                .wallet
+                .bulk_mint_nfts(
+                    0,
+                    did.info.launcher_id,
+                    vec![WalletNftMint {
+                        metadata: NftMetadata {
+                            edition_number,
+                            ..Default::default()
+                        },
+                        royalty_puzzle_hash: Some(royalty_puzzle_hash),
+                        royalty_ten_thousandths,
+                    }],
+                    false,
+                    true,
+                )
+                .await?;
+            bob.transact(coin_spends).await?;
+            bob.wait_for_coins().await;
+
+            for nft in nfts {
+                let metadata = nft.info.metadata.to_clvm(&mut allocator)?;
+
+                requested_nfts.insert(
+                    nft.info.launcher_id,
+                    RequestedNft {
+                        metadata: Program::from_clvm(&allocator, metadata)?,
+                        metadata_updater_puzzle_hash: nft.info.metadata_updater_puzzle_hash,
+                        royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
+                        royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
+                        memos: Vec::new(),
+                    },
+                );
+            }
+        }
 
-    #[test(tokio::test)]
-    async fn test_offer_xch_for_cat() -> anyhow::Result<()> {
-        let mut alice = TestWallet::new(1000).await?;
-        let mut bob = alice.next(1000).await?;
+        let royalties = calculate_royalties(
+            &OfferAmounts {
+                xch: 3000,
+                cats: IndexMap::new(),
+            },
+            &requested_nfts
+                .iter()
+                .map(|(&launcher_id, nft)| NftRoyaltyInfo {
+                    launcher_id,
+                    royalty_puzzle_hash: nft.royalty_puzzle_hash,
+                    royalty_ten_thousandths: nft.royalty_ten_thousandths,
+                })
+                .collect::<Vec<_>>(),
+        )?;
 
-        // Issue CAT
-        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
-        bob.transact(coin_spends).await?;
-        bob.wait_for_coins().await;
+        // Each NFT has a trade price of 1000, so the royalties are 10, 25, and 50.
+        assert_eq!(
+            royalties
+                .xch
+                .iter()
+                .map(|royalty| royalty.amount)
+                .collect::<Vec<_>>(),
+            vec![10, 25, 50]
+        );
 
         // Create offer
         let offer = alice
             .wallet
             .make_offer(
                 MakerSide {
-                    xch: 750,
-                    cats: IndexMap::new(),
-                    nfts: Vec::new(),
-                    fee: 250,
+                    xch: 3000,
+                    ..Default::default()
                 },
                 TakerSide {
-                    xch: 0,
-                    cats: indexmap! { asset_id => 1000 },
-                    nfts: IndexMap::new(),
+                    nfts: requested_nfts.clone(),
+                    ..Default::default()
                 },
                 None,
+                None,
                 false,
                 true,
             )
@@ -62,35 +1961,49 @@ mod tests {
             .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
             .await?;
 
+        // Every royalty payment must be asserted separately, including the ones to the same address.
+        let parsed_offer = offer.clone().parse(&mut allocator)?;
+        let asserted_announcements = parse_asserted_announcements(&mut allocator, &parsed_offer)?;
+
+        let assertions = royalties.assertions();
+        assert_eq!(assertions.len(), 3);
+
+        for assertion in assertions {
+            assert!(asserted_announcements.contains(&assertion.announcement_id));
+        }
+
         // Take offer
-        let offer = bob.wallet.take_offer(offer, 0, false, true).await?;
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
         let spend_bundle = bob
             .wallet
             .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
             .await?;
         bob.push_bundle(spend_bundle).await?;
 
-        // We need to wait for both wallets to sync in this case
         bob.wait_for_coins().await;
         alice.wait_for_puzzles().await;
 
         // Check balances
-        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
-        assert_eq!(bob.wallet.db.balance().await?, 750);
+        assert_eq!(alice.wallet.db.balance().await?, 0);
+        assert_eq!(bob.wallet.db.balance().await?, 3000);
+
+        for nft_id in requested_nfts.keys() {
+            assert_ne!(alice.wallet.db.spendable_nft(*nft_id).await?, None);
+        }
 
         Ok(())
     }
 
     #[test(tokio::test)]
-    async fn test_xch_for_nft() -> anyhow::Result<()> {
-        let mut alice = TestWallet::new(1030).await?;
-        let mut bob = alice.next(2).await?;
+    async fn test_nft_for_xch() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(2).await?;
+        let mut bob = alice.next(1030).await?;
 
-        let (coin_spends, did) = bob.wallet.create_did(0, false, true).await?;
-        bob.transact(coin_spends).await?;
-        bob.wait_for_coins().await;
+        let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
 
-        let (coin_spends, mut nfts, _did) = bob
+        let (coin_spends, mut nfts, _did) = alice
             .wallet
             .bulk_mint_nfts(
                 0,
@@ -104,38 +2017,25 @@ mod tests {
                 true,
             )
             .await?;
-        bob.transact(coin_spends).await?;
-        bob.wait_for_coins().await;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
 
         let nft = nfts.remove(0);
 
-        let mut allocator = Allocator::new();
-        let metadata = nft.info.metadata.to_clvm(&mut allocator)?;
-        let metadata = Program::from_clvm(&allocator, metadata)?;
-
         // Create offer
         let offer = alice
             .wallet
             .make_offer(
                 MakerSide {
-                    xch: 1000,
-                    cats: IndexMap::new(),
-                    nfts: Vec::new(),
-                    fee: 0,
+                    nfts: vec![nft.info.launcher_id],
+                    ..Default::default()
                 },
                 TakerSide {
-                    xch: 0,
-                    cats: IndexMap::new(),
-                    nfts: indexmap! {
-                        nft.info.launcher_id => RequestedNft {
-                            metadata,
-                            metadata_updater_puzzle_hash: nft.info.metadata_updater_puzzle_hash,
-                            royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
-                            royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
-                        },
-                    },
+                    xch: 1000,
+                    ..Default::default()
                 },
                 None,
+                None,
                 false,
                 true,
             )
@@ -146,7 +2046,7 @@ mod tests {
             .await?;
 
         // Take offer
-        let offer = bob.wallet.take_offer(offer, 0, false, true).await?;
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
         let spend_bundle = bob
             .wallet
             .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
@@ -154,22 +2054,22 @@ mod tests {
         bob.push_bundle(spend_bundle).await?;
 
         // We need to wait for both wallets to sync in this case
+        alice.wait_for_coins().await;
         bob.wait_for_coins().await;
-        alice.wait_for_puzzles().await;
 
         // Check balances
+        assert_eq!(alice.wallet.db.balance().await?, 1000);
         assert_ne!(
-            alice.wallet.db.spendable_nft(nft.info.launcher_id).await?,
+            bob.wallet.db.spendable_nft(nft.info.launcher_id).await?,
             None
         );
-        assert_eq!(bob.wallet.db.balance().await?, 1000);
 
         Ok(())
     }
 
     #[test(tokio::test)]
-    async fn test_nft_for_xch() -> anyhow::Result<()> {
-        let mut alice = TestWallet::new(2).await?;
+    async fn test_offer_did_owned_nft() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(3).await?;
         let mut bob = alice.next(1030).await?;
 
         let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
@@ -193,24 +2093,30 @@ mod tests {
         alice.transact(coin_spends).await?;
         alice.wait_for_coins().await;
 
-        let nft = nfts.remove(0);
+        let nft_id = nfts.remove(0).info.launcher_id;
+
+        let nft = alice
+            .wallet
+            .db
+            .spendable_nft(nft_id)
+            .await?
+            .expect("missing nft");
+        assert_eq!(nft.info.current_owner, Some(did.info.launcher_id));
 
         // Create offer
         let offer = alice
             .wallet
             .make_offer(
                 MakerSide {
-                    xch: 0,
-                    cats: IndexMap::new(),
-                    nfts: vec![nft.info.launcher_id],
-                    fee: 0,
+                    nfts: vec![nft_id],
+                    ..Default::default()
                 },
                 TakerSide {
                     xch: 1000,
-                    cats: IndexMap::new(),
-                    nfts: IndexMap::new(),
+                    ..Default::default()
                 },
                 None,
+                None,
                 false,
                 true,
             )
@@ -221,24 +2127,363 @@ mod tests {
             .await?;
 
         // Take offer
-        let offer = bob.wallet.take_offer(offer, 0, false, true).await?;
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
         let spend_bundle = bob
             .wallet
             .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
             .await?;
         bob.push_bundle(spend_bundle).await?;
 
-        // We need to wait for both wallets to sync in this case
         alice.wait_for_coins().await;
         bob.wait_for_coins().await;
 
-        // Check balances
-        assert_eq!(alice.wallet.db.balance().await?, 1000);
+        // The taker receives the NFT without an owner, and the DID is left untouched.
+        let nft = bob
+            .wallet
+            .db
+            .spendable_nft(nft_id)
+            .await?
+            .expect("missing nft");
+        assert_eq!(nft.info.current_owner, None);
         assert_ne!(
-            bob.wallet.db.spendable_nft(nft.info.launcher_id).await?,
+            alice.wallet.db.spendable_did(did.info.launcher_id).await?,
             None
         );
 
+        // The NFT is valid, so the taker can transfer it again.
+        let coin_spends = bob
+            .wallet
+            .transfer_nfts(vec![nft_id], alice.puzzle_hash, None, 0, false, true)
+            .await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        assert_eq!(bob.wallet.db.spendable_nft(nft_id).await?, None);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_royalty_waiver_only_for_non_enforcing_offers() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(3).await?;
+        let mut bob = alice.next(2060).await?;
+
+        let (coin_spends, did) = alice.wallet.create_did(0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let mut nft_ids = Vec::new();
+
+        for edition_number in 1..=2 {
+            let (coin_spends, nfts, _did) = alice
+                .wallet
+                .bulk_mint_nfts(
+                    0,
+                    did.info.launcher_id,
+                    vec![WalletNftMint {
+                        metadata: NftMetadata {
+                            edition_number,
+                            ..Default::default()
+                        },
+                        royalty_puzzle_hash: Some(Bytes32::new([42; 32])),
+                        royalty_ten_thousandths: 300,
+                    }],
+                    false,
+                    true,
+                )
+                .await?;
+            alice.transact(coin_spends).await?;
+            alice.wait_for_coins().await;
+
+            nft_ids.extend(nfts.iter().map(|nft| nft.info.launcher_id));
+        }
+
+        // The first offer enforces royalties, so they're paid despite the waiver.
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    nfts: vec![nft_ids[0]],
+                    ..Default::default()
+                },
+                TakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, true, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        alice.wait_for_coins().await;
+        bob.wait_for_coins().await;
+
+        assert_eq!(bob.wallet.db.balance().await?, 1030);
+
+        // The second offer is made without trade prices, so the NFT doesn't assert any royalties.
+        let coins = alice
+            .wallet
+            .fetch_offer_coins(&OfferAmounts::default(), Vec::new(), vec![nft_ids[1]], true)
+            .await?;
+        let p2_puzzle_hash = alice.wallet.p2_puzzle_hash(false, true).await?;
+
+        let mut ctx = SpendContext::new();
+        let settlement = ctx.settlement_payments_puzzle()?;
+
+        let (assertions, builder) = OfferBuilder::new(coins.nonce())
+            .request(
+                &mut ctx,
+                &settlement,
+                vec![Payment::with_memos(
+                    p2_puzzle_hash,
+                    1000,
+                    vec![p2_puzzle_hash.into()],
+                )],
+            )?
+            .finish();
+
+        alice
+            .wallet
+            .lock_assets(
+                &mut ctx,
+                OfferSpend {
+                    amounts: OfferAmounts::default(),
+                    coins,
+                    royalties: Royalties::default(),
+                    trade_prices: Vec::new(),
+                    fee: 0,
+                    change_puzzle_hash: p2_puzzle_hash,
+                    extra_conditions: Conditions::new().extend(assertions),
+                    binding_mode: BindingMode::default(),
+                },
+            )
+            .await?;
+
+        let coin_spends = ctx.take();
+        let offer = alice
+            .wallet
+            .sign_make_offer(
+                UnsignedMakeOffer {
+                    ctx,
+                    coin_spends,
+                    builder,
+                    terms: OfferTerms::default(),
+                },
+                &alice.agg_sig,
+                alice.master_sk.clone(),
+            )
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, true, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        alice.wait_for_coins().await;
+        bob.wait_for_coins().await;
+
+        assert_eq!(bob.wallet.db.balance().await?, 30);
+        assert_eq!(alice.wallet.db.balance().await?, 2000);
+
+        for nft_id in nft_ids {
+            assert_ne!(bob.wallet.db.spendable_nft(nft_id).await?, None);
+        }
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_memo() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(500, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let maker = MakerSide {
+            cats: indexmap! { asset_id => 500 },
+            ..Default::default()
+        };
+        let taker = |xch, offer_memo| TakerSide {
+            xch,
+            offer_memo,
+            ..Default::default()
+        };
+
+        let memo = Bytes::new(b"order-1234".to_vec());
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                maker.clone(),
+                taker(100, Some(memo.clone())),
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // The memo survives encoding and decoding the offer.
+        let offer = Offer::decode(&offer.encode()?)?;
+
+        let mut ctx = SpendContext::new();
+        let mut builder = offer.parse(&mut ctx.allocator)?.take();
+        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+        assert_eq!(requested_payments.offer_memo(), Some(memo));
+
+        // There's nothing for the memo to be attached to if only assets are being offered.
+        let result = alice
+            .wallet
+            .make_offer(
+                maker.clone(),
+                taker(0, Some(Bytes::new(b"order".to_vec()))),
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+        assert!(matches!(result, Err(WalletError::OfferMemoWithoutPayment)));
+
+        let result = alice
+            .wallet
+            .make_offer(
+                maker,
+                taker(100, Some(Bytes::new(vec![0; MAX_OFFER_MEMO_SIZE + 1]))),
+                None,
+                None,
+                false,
+                true,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(WalletError::MemoTooLarge { size, max: MAX_OFFER_MEMO_SIZE })
+                if size == MAX_OFFER_MEMO_SIZE + 1
+        ));
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_overfill() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 1000,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 999 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = alice
+            .wallet
+            .sign_make_offer(offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        // The taker pays slightly more than requested, such as from rounding a price.
+        let overfill = OfferAmounts {
+            xch: 0,
+            cats: indexmap! { asset_id => 1 },
+        };
+
+        let result = bob
+            .wallet
+            .take_offer_with_options(
+                offer.clone(),
+                TakeOptions {
+                    overfill: overfill.clone(),
+                    policy: OverfillPolicy::Reject,
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(WalletError::OfferOverfill { asset_id: Some(id) }) if id == asset_id
+        ));
+
+        // XCH isn't requested, so there's nobody to pay extra XCH to.
+        let result = bob
+            .wallet
+            .take_offer_with_options(
+                offer.clone(),
+                TakeOptions {
+                    overfill: OfferAmounts {
+                        xch: 1,
+                        cats: IndexMap::new(),
+                    },
+                    policy: OverfillPolicy::PayMaker,
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(WalletError::OverfillNotRequested { asset_id: None })
+        ));
+
+        let offer = bob
+            .wallet
+            .take_offer_with_options(
+                offer,
+                TakeOptions {
+                    overfill,
+                    policy: OverfillPolicy::PayMaker,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        // The excess goes to the maker, rather than being left in the settlement coin.
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.cat_balance(asset_id).await?, 0);
+        assert_eq!(bob.wallet.db.balance().await?, 1000);
+
         Ok(())
     }
 }
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use chia::protocol::Bytes32;
+
+use crate::{Wallet, WalletError};
+
+use super::{MakerSide, TakerSide, UnsignedMakeOffer};
+
+impl Wallet {
+    /// Makes two offers for the same assets, which each request something different, such as an NFT
+    /// or its equivalent in XCH. The XCH coins are selected for the primary offer and spent by both
+    /// of them, so they share the same nonce. Once either offer is taken, the other can no longer be,
+    /// since the coins it spends have already been spent.
+    ///
+    /// This only approximates a single offer with alternatives. They are still two separate offers,
+    /// which have to be shared and can be taken independently, and nothing links them together other
+    /// than the contention over their coins. The primary offer should be the one that requests more
+    /// royalties, since the alternative can't spend any coins that the primary offer doesn't.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn make_alternative_offers(
+        &self,
+        mut maker: MakerSide,
+        primary: TakerSide,
+        alternative: TakerSide,
+        starts_at: Option<u64>,
+        expires_at: Option<u64>,
+        hardened: bool,
+        reuse: bool,
+    ) -> Result<[UnsignedMakeOffer; 2], WalletError> {
+        let primary = self
+            .make_offer(
+                maker.clone(),
+                primary,
+                starts_at,
+                expires_at,
+                hardened,
+                reuse,
+            )
+            .await?;
+
+        let primary_coin_ids: HashSet<Bytes32> = primary
+            .coin_spends
+            .iter()
+            .map(|coin_spend| coin_spend.coin.coin_id())
+            .collect();
+
+        if maker.coin_ids.is_empty() {
+            maker.coin_ids = self.maker_p2_coin_ids(&primary.coin_spends).await?;
+        }
+
+        let alternative = self
+            .make_offer(maker, alternative, starts_at, expires_at, hardened, reuse)
+            .await?;
+
+        // Without a coin in common, both offers could be taken.
+        if !alternative
+            .coin_spends
+            .iter()
+            .any(|coin_spend| primary_coin_ids.contains(&coin_spend.coin.coin_id()))
+        {
+            return Err(WalletError::NoSharedOfferCoins);
+        }
+
+        Ok([primary, alternative])
+    }
+}
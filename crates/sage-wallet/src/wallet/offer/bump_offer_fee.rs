@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use chia::protocol::Bytes32;
+
+use crate::{Wallet, WalletError};
+
+use super::{MakerSide, OfferTerms, UnsignedMakeOffer};
+
+/// An offer that has been rebuilt with a different fee.
+#[derive(Debug)]
+pub struct BumpedOffer {
+    pub offer: UnsignedMakeOffer,
+    /// Whether different coins had to be spent to cover the new fee. If so, the nonce that the
+    /// requested payments are notarized with has changed, so anything derived from the original
+    /// offer's nonce, such as a reference given to a marketplace, no longer applies.
+    pub nonce_changed: bool,
+}
+
+impl Wallet {
+    /// Rebuilds an unsigned offer with a new maker fee, on the same terms the offer was originally
+    /// made with. The XCH coins the offer already spends are reused if they can cover the new fee,
+    /// so that the nonce stays the same. Otherwise, coins are selected again.
+    ///
+    /// Coins that were chosen explicitly by the maker side are never replaced.
+    pub async fn bump_offer_fee(
+        &self,
+        offer: UnsignedMakeOffer,
+        fee: u64,
+    ) -> Result<BumpedOffer, WalletError> {
+        let OfferTerms {
+            maker,
+            taker,
+            starts_at,
+            expires_at,
+            hardened,
+            reuse,
+        } = offer.terms.clone();
+
+        let maker = MakerSide { fee, ..maker };
+
+        // The reused coins are only chosen for this build, so the offer keeps the original terms
+        // and can be bumped again.
+        let terms = OfferTerms {
+            maker: maker.clone(),
+            ..offer.terms.clone()
+        };
+
+        let previous_coin_ids: HashSet<Bytes32> = offer
+            .coin_spends
+            .iter()
+            .map(|coin_spend| coin_spend.coin.coin_id())
+            .collect();
+
+        let bumped = |mut offer: UnsignedMakeOffer| {
+            offer.terms = terms;
+
+            let coin_ids: HashSet<Bytes32> = offer
+                .coin_spends
+                .iter()
+                .map(|coin_spend| coin_spend.coin.coin_id())
+                .collect();
+
+            BumpedOffer {
+                nonce_changed: coin_ids != previous_coin_ids,
+                offer,
+            }
+        };
+
+        if maker.coin_ids.is_empty() {
+            let coin_ids = self.maker_p2_coin_ids(&offer.coin_spends).await?;
+
+            if !coin_ids.is_empty() {
+                let reused = MakerSide {
+                    coin_ids,
+                    ..maker.clone()
+                };
+
+                match self
+                    .make_offer(
+                        reused,
+                        taker.clone(),
+                        starts_at,
+                        expires_at,
+                        hardened,
+                        reuse,
+                    )
+                    .await
+                {
+                    Ok(offer) => return Ok(bumped(offer)),
+                    Err(WalletError::InsufficientFunds | WalletError::NoExactCoins) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+
+        let offer = self
+            .make_offer(maker, taker, starts_at, expires_at, hardened, reuse)
+            .await?;
+
+        Ok(bumped(offer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use test_log::test;
+
+    use crate::{TakerSide, TestWallet};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_bump_offer_fee() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(1000).await?;
+
+        let (coin_spends, asset_id) = bob.wallet.issue_cat(1000, 0, None, false, true).await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+
+        let coins = alice.wallet.db.spendable_coins().await?;
+        let coin_spends = alice.wallet.split_xch(&coins, 2, 0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let offer = alice
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 100,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { asset_id => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        assert_eq!(offer.coin_spends.len(), 1);
+
+        // The coin that's already spent can cover a higher fee, so the nonce doesn't change.
+        let bumped = alice.wallet.bump_offer_fee(offer, 100).await?;
+        assert!(!bumped.nonce_changed);
+        assert_eq!(bumped.offer.coin_spends.len(), 1);
+
+        // Covering this fee requires another coin.
+        let bumped = alice.wallet.bump_offer_fee(bumped.offer, 450).await?;
+        assert!(bumped.nonce_changed);
+        assert_eq!(bumped.offer.coin_spends.len(), 2);
+
+        let offer = alice
+            .wallet
+            .sign_make_offer(bumped.offer, &alice.agg_sig, alice.master_sk.clone())
+            .await?;
+
+        let offer = bob.wallet.take_offer(offer, 0, false, false, true).await?;
+        let spend_bundle = bob
+            .wallet
+            .sign_take_offer(offer, &bob.agg_sig, bob.master_sk.clone())
+            .await?;
+        bob.push_bundle(spend_bundle).await?;
+
+        bob.wait_for_coins().await;
+        alice.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 450);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 1000);
+        assert_eq!(bob.wallet.db.balance().await?, 100);
+
+        Ok(())
+    }
+}
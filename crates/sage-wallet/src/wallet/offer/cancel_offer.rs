@@ -0,0 +1,263 @@
+use std::collections::{HashMap, HashSet};
+
+use chia::{
+    clvm_traits::{FromClvm, ToClvm},
+    protocol::{Bytes32, Coin, CoinSpend, Program},
+};
+use chia_wallet_sdk::{
+    announcement_id, run_puzzle, Cat, Condition, Conditions, Nft, Offer, ParsedOffer, SpendContext,
+    StandardLayer,
+};
+use clvmr::{Allocator, NodePtr};
+use indexmap::IndexMap;
+
+use crate::{NftExt, Wallet, WalletError};
+
+use super::parse_locked_coins;
+
+/// How an offer made by this wallet is cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancellationMethod {
+    /// Every offered coin asserts, directly or through other offered coins, that this coin is
+    /// spent alongside it. Spending only this coin invalidates the whole offer.
+    Announcement(Bytes32),
+    /// The offered coins aren't all bound to one of the wallet's coins, so each of them is spent.
+    SpendCoins,
+}
+
+#[derive(Debug, Clone)]
+enum OwnedCoin {
+    Xch(Coin),
+    Cat(Cat),
+    Nft(Nft<Program>),
+}
+
+impl Wallet {
+    /// Cancels an offer made by this wallet by spending the coins it offers back to the wallet.
+    ///
+    /// The settlement puzzle has no way to cancel an offer directly, so the only way to invalidate
+    /// it is to spend an original coin before a taker does. If the offered coins are bound together
+    /// with concurrent spend or coin announcement assertions, spending the coin they all depend on
+    /// is enough. Otherwise every owned coin is spent. The cost, and therefore the fee needed, is
+    /// roughly proportional to the number of coins spent.
+    ///
+    /// This is a regular transaction, and the fee is paid from the offered XCH first, selecting
+    /// more coins if needed. If the offer is saved, it's marked as pending cancellation once the
+    /// transaction is submitted.
+    pub async fn cancel_offer(
+        &self,
+        offer: Offer,
+        fee: u64,
+        hardened: bool,
+        reuse: bool,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        let mut ctx = SpendContext::new();
+
+        let offer = offer.parse(&mut ctx.allocator)?;
+        let (_locked_coins, original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+
+        let mut owned = self.owned_offer_coins(&original_coin_ids).await?;
+
+        if owned.is_empty() {
+            return Err(WalletError::OfferNotOwned);
+        }
+
+        let method = cancellation_method(
+            &mut ctx.allocator,
+            &offer,
+            &original_coin_ids,
+            owned.keys().copied(),
+        )?;
+
+        if let CancellationMethod::Announcement(coin_id) = method {
+            owned.retain(|&owned_coin_id, _| owned_coin_id == coin_id);
+        }
+
+        let mut xch = Vec::new();
+        let mut cats = IndexMap::<Bytes32, Vec<Cat>>::new();
+        let mut nfts = Vec::new();
+
+        for coin in owned.into_values() {
+            match coin {
+                OwnedCoin::Xch(coin) => xch.push(coin),
+                OwnedCoin::Cat(cat) => cats.entry(cat.asset_id).or_default().push(cat),
+                OwnedCoin::Nft(nft) => nfts.push(nft),
+            }
+        }
+
+        let p2_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
+
+        let total: u128 = xch.iter().map(|coin| coin.amount as u128).sum();
+
+        if total < fee as u128 {
+            let fee_coins: Vec<Coin> = self.select_p2_coins(fee as u128 - total).await?;
+            xch.extend(fee_coins);
+        }
+
+        if !xch.is_empty() {
+            let total: u128 = xch.iter().map(|coin| coin.amount as u128).sum();
+            let change: u64 = (total - fee as u128)
+                .try_into()
+                .map_err(|_| WalletError::AmountOverflow { asset_id: None })?;
+
+            let mut conditions = Conditions::new();
+
+            if fee > 0 {
+                conditions = conditions.reserve_fee(fee);
+            }
+
+            if change > 0 {
+                conditions = conditions.create_coin(p2_puzzle_hash, change, None);
+            }
+
+            self.spend_p2_coins(&mut ctx, xch, conditions).await?;
+        }
+
+        // Each CAT has its own ring, since coins of different assets can't be spent together.
+        let hint = ctx.hint(p2_puzzle_hash)?;
+
+        for cats in cats.into_values() {
+            self.spend_cat_coins(
+                &mut ctx,
+                cats.into_iter().map(|cat| {
+                    let conditions =
+                        Conditions::new().create_coin(p2_puzzle_hash, cat.coin.amount, Some(hint));
+                    (cat, conditions)
+                }),
+            )
+            .await?;
+        }
+
+        for nft in nfts {
+            let nft = nft.hashed(&mut ctx)?;
+
+            let synthetic_key = self.synthetic_key(nft.info.p2_puzzle_hash).await?;
+            let p2 = StandardLayer::new(synthetic_key);
+
+            let _nft = nft.transfer(&mut ctx, &p2, p2_puzzle_hash, Conditions::new())?;
+        }
+
+        Ok(ctx.take())
+    }
+
+    /// The original coins of an offer which belong to this wallet, with XCH first, then CATs, then NFTs.
+    async fn owned_offer_coins(
+        &self,
+        original_coin_ids: &[Bytes32],
+    ) -> Result<IndexMap<Bytes32, OwnedCoin>, WalletError> {
+        let mut xch = IndexMap::new();
+        let mut cats = IndexMap::new();
+        let mut nfts = IndexMap::new();
+
+        for &coin_id in original_coin_ids {
+            if self.db.is_p2_coin(coin_id).await? == Some(true) {
+                let Some(coin_state) = self.db.coin_state(coin_id).await? else {
+                    return Err(WalletError::MissingCoin(coin_id));
+                };
+                xch.insert(coin_id, OwnedCoin::Xch(coin_state.coin));
+            } else if let Some(cat) = self.db.cat_coin(coin_id).await? {
+                cats.insert(coin_id, OwnedCoin::Cat(cat));
+            } else if let Some(nft) = self.db.nft_by_coin_id(coin_id).await? {
+                if !self.db.is_p2_puzzle_hash(nft.info.p2_puzzle_hash).await? {
+                    continue;
+                }
+                nfts.insert(coin_id, OwnedCoin::Nft(nft));
+            } else {
+                continue;
+            }
+
+            let spent = self
+                .db
+                .coin_state(coin_id)
+                .await?
+                .is_some_and(|coin_state| coin_state.spent_height.is_some());
+
+            if spent {
+                return Err(WalletError::CoinAlreadySpent { coin_id });
+            }
+        }
+
+        xch.extend(cats);
+        xch.extend(nfts);
+
+        Ok(xch)
+    }
+}
+
+/// Finds the first of the candidate coins which every original coin of the offer depends on being
+/// spent in the same transaction, following concurrent spend and coin announcement assertions.
+pub fn cancellation_method(
+    allocator: &mut Allocator,
+    offer: &ParsedOffer,
+    original_coin_ids: &[Bytes32],
+    candidates: impl IntoIterator<Item = Bytes32>,
+) -> Result<CancellationMethod, WalletError> {
+    let mut dependencies = HashMap::<Bytes32, Vec<Bytes32>>::new();
+    let mut announcements = HashMap::new();
+    let mut asserted_announcements = Vec::new();
+
+    for coin_spend in &offer.coin_spends {
+        let coin_id = coin_spend.coin.coin_id();
+
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+
+        let output = run_puzzle(allocator, puzzle, solution)?;
+        let conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+        for condition in conditions {
+            match condition {
+                Condition::CreateCoinAnnouncement(cond) => {
+                    announcements.insert(announcement_id(coin_id, cond.message), coin_id);
+                }
+                Condition::AssertCoinAnnouncement(cond) => {
+                    asserted_announcements.push((coin_id, cond.announcement_id));
+                }
+                Condition::AssertConcurrentSpend(cond) => {
+                    dependencies.entry(coin_id).or_default().push(cond.coin_id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for (coin_id, announcement_id) in asserted_announcements {
+        if let Some(&announcer) = announcements.get(&announcement_id) {
+            dependencies.entry(coin_id).or_default().push(announcer);
+        }
+    }
+
+    for candidate in candidates {
+        if original_coin_ids
+            .iter()
+            .all(|&coin_id| depends_on(&dependencies, coin_id, candidate))
+        {
+            return Ok(CancellationMethod::Announcement(candidate));
+        }
+    }
+
+    Ok(CancellationMethod::SpendCoins)
+}
+
+fn depends_on(
+    dependencies: &HashMap<Bytes32, Vec<Bytes32>>,
+    coin_id: Bytes32,
+    target: Bytes32,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack = vec![coin_id];
+
+    while let Some(coin_id) = stack.pop() {
+        if coin_id == target {
+            return true;
+        }
+
+        if visited.insert(coin_id) {
+            if let Some(next) = dependencies.get(&coin_id) {
+                stack.extend(next);
+            }
+        }
+    }
+
+    false
+}
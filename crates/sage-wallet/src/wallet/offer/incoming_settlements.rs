@@ -0,0 +1,108 @@
+use chia::protocol::{Bytes32, Coin};
+
+use crate::{Wallet, WalletError};
+
+/// A payment to the wallet that's waiting in the settlement payments puzzle to be claimed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncomingPayment {
+    pub coin: Coin,
+    /// The puzzle hash of the wallet that the settlement coin was hinted to.
+    pub p2_puzzle_hash: Bytes32,
+    pub created_height: Option<u32>,
+}
+
+impl Wallet {
+    /// Lists the XCH settlement coins hinted to the wallet's puzzle hashes that haven't been claimed yet,
+    /// such as payments left behind by offers that were made to this wallet.
+    ///
+    /// The settlement puzzle can be spent by anyone, so these are only claimable until someone else
+    /// spends them first. Coins that have been spent or are being spent by a pending transaction are left out.
+    pub async fn incoming_settlements(&self) -> Result<Vec<IncomingPayment>, WalletError> {
+        let mut payments = Vec::new();
+
+        for row in self.db.unclaimed_settlement_coins().await? {
+            if !self.db.is_p2_puzzle_hash(row.p2_puzzle_hash).await? {
+                continue;
+            }
+
+            payments.push(IncomingPayment {
+                coin: row.coin,
+                p2_puzzle_hash: row.p2_puzzle_hash,
+                created_height: row.created_height,
+            });
+        }
+
+        Ok(payments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::puzzles::offer::{
+        NotarizedPayment, Payment, SettlementPaymentsSolution, SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+    };
+    use chia_wallet_sdk::{Conditions, Layer, SettlementLayer, SpendContext};
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_incoming_settlements() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(0).await?;
+
+        // Lock some XCH in the settlement puzzle, hinted to Bob.
+        let mut ctx = SpendContext::new();
+        let hint = ctx.hint(bob.puzzle_hash)?;
+        let coins = alice.wallet.db.spendable_coins().await?;
+
+        alice
+            .wallet
+            .spend_p2_coins(
+                &mut ctx,
+                coins,
+                Conditions::new()
+                    .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 300, Some(hint))
+                    .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 200, None)
+                    .create_coin(alice.puzzle_hash, 500, None),
+            )
+            .await?;
+
+        alice.transact(ctx.take()).await?;
+        bob.wait_for_puzzles().await;
+
+        // Only the hinted coin is found, and it isn't counted towards the balance.
+        let payments = bob.wallet.incoming_settlements().await?;
+        assert_eq!(payments.len(), 1);
+        assert_eq!(payments[0].coin.amount, 300);
+        assert_eq!(payments[0].p2_puzzle_hash, bob.puzzle_hash);
+        assert_eq!(bob.wallet.db.balance().await?, 0);
+
+        assert!(alice.wallet.incoming_settlements().await?.is_empty());
+
+        // Once the payment has been claimed, it's no longer listed.
+        let coin_spend = SettlementLayer.construct_coin_spend(
+            &mut ctx,
+            payments[0].coin,
+            SettlementPaymentsSolution {
+                notarized_payments: vec![NotarizedPayment {
+                    nonce: Bytes32::default(),
+                    payments: vec![Payment::with_memos(
+                        bob.puzzle_hash,
+                        300,
+                        vec![bob.puzzle_hash.into()],
+                    )],
+                }],
+            },
+        )?;
+        bob.transact(vec![coin_spend]).await?;
+        bob.wait_for_coins().await;
+
+        assert!(bob.wallet.incoming_settlements().await?.is_empty());
+        assert_eq!(bob.wallet.db.balance().await?, 300);
+
+        Ok(())
+    }
+}
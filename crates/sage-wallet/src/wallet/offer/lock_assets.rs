@@ -2,18 +2,16 @@ use std::{collections::HashMap, mem};
 
 use chia::{
     protocol::{Bytes32, Coin},
-    puzzles::offer::{
-        NotarizedPayment, Payment, SettlementPaymentsSolution, SETTLEMENT_PAYMENTS_PUZZLE_HASH,
-    },
+    puzzles::offer::{SettlementPaymentsSolution, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
 };
 use chia_wallet_sdk::{
-    Cat, CatSpend, Conditions, HashedPtr, Layer, SettlementLayer, SpendContext, StandardLayer,
-    TradePrice,
+    announcement_id, Cat, CatSpend, Conditions, Layer, SettlementLayer, SpendContext,
+    StandardLayer, TradePrice,
 };
 
-use crate::{Wallet, WalletError};
+use crate::{NftExt, Wallet, WalletError};
 
-use super::{LockedCoins, OfferAmounts, OfferCoins, Royalties};
+use super::{royalty_settlements, LockedCoins, OfferAmounts, OfferCoins, Royalties};
 
 #[derive(Debug, Clone)]
 pub struct OfferSpend {
@@ -24,6 +22,22 @@ pub struct OfferSpend {
     pub fee: u64,
     pub change_puzzle_hash: Bytes32,
     pub extra_conditions: Conditions,
+    pub binding_mode: BindingMode,
+}
+
+/// How the primary coins of each asset are tied together, when more than one asset is spent.
+/// The extra conditions are only added to the first primary coin.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BindingMode {
+    /// Each primary coin asserts that the previous one is spent, and the first asserts the last,
+    /// so that none of them can be spent without the others.
+    #[default]
+    Ring,
+    /// Each primary coin except the first asserts that the previous one is spent.
+    /// The first coin can be spent on its own, but none of the others can be spent without it.
+    Linear,
+    /// Like [`BindingMode::Ring`], but with coin announcements rather than concurrent spend assertions.
+    Announcements,
 }
 
 impl Wallet {
@@ -37,30 +51,13 @@ impl Wallet {
             trade_prices,
             fee,
             change_puzzle_hash,
-            mut extra_conditions,
+            extra_conditions,
+            binding_mode,
         }: OfferSpend,
     ) -> Result<LockedCoins, WalletError> {
-        let primary_coins = coins.primary_coin_ids();
-
         // Calculate conditions for each primary coin.
-        let mut primary_conditions = HashMap::new();
-
-        if primary_coins.len() == 1 {
-            primary_conditions.insert(primary_coins[0], extra_conditions);
-        } else {
-            for (i, &coin_id) in primary_coins.iter().enumerate() {
-                let relation = if i == 0 {
-                    *primary_coins.last().expect("empty primary coins")
-                } else {
-                    primary_coins[i - 1]
-                };
-
-                primary_conditions.insert(
-                    coin_id,
-                    mem::take(&mut extra_conditions).assert_concurrent_spend(relation),
-                );
-            }
-        }
+        let mut primary_conditions =
+            bind_primary_coins(&coins.primary_coin_ids(), extra_conditions, binding_mode);
 
         // Keep track of the coins that are locked.
         let mut locked = LockedCoins {
@@ -89,17 +86,21 @@ impl Wallet {
             }
 
             // Handle royalties.
-            let royalty_amount = royalties.xch_amount();
-
-            if royalty_amount > 0 {
-                conditions = conditions.create_coin(
-                    SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
-                    royalty_amount,
-                    None,
-                );
+            let mut parent_coin_id = primary_xch_coin.coin_id();
+
+            for (i, (royalty_amount, notarized_payments)) in
+                royalty_settlements(&royalties.xch)?.into_iter().enumerate()
+            {
+                if i == 0 {
+                    conditions = conditions.create_coin(
+                        SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                        royalty_amount,
+                        None,
+                    );
+                }
 
                 let royalty_coin = Coin::new(
-                    primary_xch_coin.coin_id(),
+                    parent_coin_id,
                     SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
                     royalty_amount,
                 );
@@ -107,27 +108,19 @@ impl Wallet {
                 let coin_spend = SettlementLayer.construct_coin_spend(
                     ctx,
                     royalty_coin,
-                    SettlementPaymentsSolution {
-                        notarized_payments: royalties
-                            .xch
-                            .iter()
-                            .map(|royalty| NotarizedPayment {
-                                nonce: royalty.nft_id,
-                                payments: vec![Payment::with_memos(
-                                    royalty.p2_puzzle_hash,
-                                    royalty.amount,
-                                    vec![royalty.p2_puzzle_hash.into()],
-                                )],
-                            })
-                            .collect(),
-                    },
+                    SettlementPaymentsSolution { notarized_payments },
                 )?;
 
                 ctx.insert(coin_spend);
+
+                parent_coin_id = royalty_coin.coin_id();
             }
 
-            let total_amount = coins.xch.iter().map(|coin| coin.amount).sum::<u64>();
-            let change = total_amount - amounts.xch - fee - royalties.xch_amount();
+            let change = change_amount(
+                coins.xch.iter().map(|coin| coin.amount),
+                [amounts.xch, fee, royalties.xch_amount()?],
+                None,
+            )?;
 
             if change > 0 {
                 conditions = conditions.create_coin(change_puzzle_hash, change, None);
@@ -147,8 +140,11 @@ impl Wallet {
             };
 
             let amount = amounts.cats.get(&asset_id).copied().unwrap_or(0);
-            let total_amount = cat_coins.iter().map(|cat| cat.coin.amount).sum::<u64>();
-            let change = total_amount - amount - royalties.cat_amount(asset_id);
+            let change = change_amount(
+                cat_coins.iter().map(|cat| cat.coin.amount),
+                [amount, royalties.cat_amount(asset_id)?],
+                Some(asset_id),
+            )?;
 
             let settlement_hint = ctx.hint(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into())?;
 
@@ -173,36 +169,35 @@ impl Wallet {
             }
 
             // Handle royalties.
-            let royalty_amount = royalties.cat_amount(asset_id);
-
-            if royalty_amount > 0 {
-                conditions = conditions.create_coin(
-                    SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
-                    royalty_amount,
-                    Some(settlement_hint),
-                );
-
-                let royalty_cat = primary_cat
-                    .wrapped_child(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), royalty_amount);
+            let mut royalty_parent = None;
+
+            for (royalty_amount, notarized_payments) in
+                royalty_settlements(royalties.cats.get(&asset_id).map_or(&[], Vec::as_slice))?
+            {
+                let royalty_cat = match royalty_parent {
+                    None => {
+                        conditions = conditions.create_coin(
+                            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                            royalty_amount,
+                            Some(settlement_hint),
+                        );
+
+                        primary_cat
+                            .wrapped_child(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), royalty_amount)
+                    }
+                    Some(parent) => Cat::wrapped_child(
+                        &parent,
+                        SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                        royalty_amount,
+                    ),
+                };
 
-                let inner_spend = SettlementLayer.construct_spend(
-                    ctx,
-                    SettlementPaymentsSolution {
-                        notarized_payments: royalties.cats[&asset_id]
-                            .iter()
-                            .map(|royalty| NotarizedPayment {
-                                nonce: royalty.nft_id,
-                                payments: vec![Payment::with_memos(
-                                    royalty.p2_puzzle_hash,
-                                    royalty.amount,
-                                    vec![royalty.p2_puzzle_hash.into()],
-                                )],
-                            })
-                            .collect(),
-                    },
-                )?;
+                let inner_spend = SettlementLayer
+                    .construct_spend(ctx, SettlementPaymentsSolution { notarized_payments })?;
 
                 Cat::spend_all(ctx, &[CatSpend::new(royalty_cat, inner_spend)])?;
+
+                royalty_parent = Some(royalty_cat);
             }
 
             self.spend_cat_coins(
@@ -214,12 +209,12 @@ impl Wallet {
             .await?;
         }
 
-        // Spend the NFTs.
+        // Spend the NFTs. Locking them in the settlement clears any DID that owns them, since the
+        // taker has no way to prove ownership of it. This doesn't require spending the DID.
         for nft in coins.nfts.into_values() {
-            let metadata_ptr = ctx.alloc(&nft.info.metadata)?;
-            let nft = nft.with_metadata(HashedPtr::from_ptr(&ctx.allocator, metadata_ptr));
+            let nft = nft.hashed(ctx)?;
 
-            let synthetic_key = self.db.synthetic_key(nft.info.p2_puzzle_hash).await?;
+            let synthetic_key = self.synthetic_key(nft.info.p2_puzzle_hash).await?;
             let p2 = StandardLayer::new(synthetic_key);
 
             let conditions = primary_conditions
@@ -234,3 +229,161 @@ impl Wallet {
         Ok(locked)
     }
 }
+
+/// The message of the coin announcement each primary coin makes in [`BindingMode::Announcements`].
+const BINDING_MESSAGE: &[u8] = b"offer";
+
+fn bind_primary_coins(
+    primary_coins: &[Bytes32],
+    mut extra_conditions: Conditions,
+    binding_mode: BindingMode,
+) -> HashMap<Bytes32, Conditions> {
+    let mut primary_conditions = HashMap::new();
+
+    if primary_coins.len() == 1 {
+        primary_conditions.insert(primary_coins[0], extra_conditions);
+        return primary_conditions;
+    }
+
+    for (i, &coin_id) in primary_coins.iter().enumerate() {
+        let previous = if i == 0 {
+            primary_coins
+                .last()
+                .copied()
+                .filter(|_| binding_mode != BindingMode::Linear)
+        } else {
+            Some(primary_coins[i - 1])
+        };
+
+        let mut conditions = mem::take(&mut extra_conditions);
+
+        if binding_mode == BindingMode::Announcements {
+            conditions = conditions.create_coin_announcement(BINDING_MESSAGE.to_vec().into());
+        }
+
+        if let Some(previous) = previous {
+            conditions = match binding_mode {
+                BindingMode::Ring | BindingMode::Linear => {
+                    conditions.assert_concurrent_spend(previous)
+                }
+                BindingMode::Announcements => {
+                    conditions.assert_coin_announcement(announcement_id(previous, BINDING_MESSAGE))
+                }
+            };
+        }
+
+        primary_conditions.insert(coin_id, conditions);
+    }
+
+    primary_conditions
+}
+
+/// Calculates the change left over from spending the coins, after the amounts are taken out of them.
+fn change_amount(
+    coin_amounts: impl Iterator<Item = u64>,
+    amounts: impl IntoIterator<Item = u64>,
+    asset_id: Option<Bytes32>,
+) -> Result<u64, WalletError> {
+    let total = coin_amounts.map(u128::from).sum::<u128>();
+    let spent = amounts.into_iter().map(u128::from).sum::<u128>();
+
+    let Some(change) = total.checked_sub(spent) else {
+        return Err(WalletError::InsufficientFunds);
+    };
+
+    change
+        .try_into()
+        .map_err(|_| WalletError::AmountOverflow { asset_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_wallet_sdk::Condition;
+
+    use super::*;
+
+    fn assertions(conditions: &Conditions) -> Vec<Condition> {
+        conditions
+            .as_ref()
+            .iter()
+            .filter(|condition| {
+                matches!(
+                    condition,
+                    Condition::AssertConcurrentSpend(..) | Condition::AssertCoinAnnouncement(..)
+                )
+            })
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_ring_binding() {
+        let coins = [
+            Bytes32::new([1; 32]),
+            Bytes32::new([2; 32]),
+            Bytes32::new([3; 32]),
+        ];
+        let conditions = bind_primary_coins(&coins, Conditions::new(), BindingMode::Ring);
+
+        for (i, previous) in [(0, 2), (1, 0), (2, 1)] {
+            assert_eq!(
+                assertions(&conditions[&coins[i]]),
+                Conditions::new()
+                    .assert_concurrent_spend(coins[previous])
+                    .into_iter()
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_linear_binding() {
+        let coins = [
+            Bytes32::new([1; 32]),
+            Bytes32::new([2; 32]),
+            Bytes32::new([3; 32]),
+        ];
+        let conditions = bind_primary_coins(&coins, Conditions::new(), BindingMode::Linear);
+
+        // The first coin doesn't close the ring.
+        assert!(assertions(&conditions[&coins[0]]).is_empty());
+
+        for (i, previous) in [(1, 0), (2, 1)] {
+            assert_eq!(
+                assertions(&conditions[&coins[i]]),
+                Conditions::new()
+                    .assert_concurrent_spend(coins[previous])
+                    .into_iter()
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_announcement_binding() {
+        let coins = [Bytes32::new([1; 32]), Bytes32::new([2; 32])];
+        let extra = Conditions::new().reserve_fee(1);
+        let conditions = bind_primary_coins(&coins, extra.clone(), BindingMode::Announcements);
+
+        let announcement = Conditions::new()
+            .create_coin_announcement(BINDING_MESSAGE.to_vec().into())
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        for (i, previous) in [(0, 1), (1, 0)] {
+            let expected = Conditions::new()
+                .assert_coin_announcement(announcement_id(coins[previous], BINDING_MESSAGE));
+
+            assert_eq!(
+                assertions(&conditions[&coins[i]]),
+                expected.into_iter().collect::<Vec<_>>()
+            );
+            assert!(conditions[&coins[i]].as_ref().contains(&announcement[0]));
+        }
+
+        // The extra conditions are only added to the first coin.
+        let fee = extra.into_iter().collect::<Vec<_>>();
+        assert!(conditions[&coins[0]].as_ref().contains(&fee[0]));
+        assert!(!conditions[&coins[1]].as_ref().contains(&fee[0]));
+    }
+}
@@ -1,5 +1,5 @@
 use chia::{
-    protocol::{Bytes32, CoinSpend, Program},
+    protocol::{Bytes, Bytes32, CoinSpend, Program},
     puzzles::{
         cat::CatArgs,
         offer::{Payment, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
@@ -11,15 +11,34 @@ use indexmap::IndexMap;
 use crate::{Wallet, WalletError};
 
 use super::{
-    calculate_royalties, calculate_trade_prices, lock_assets::OfferSpend, NftRoyaltyInfo,
-    OfferAmounts,
+    calculate_royalties, calculate_trade_prices,
+    lock_assets::{BindingMode, OfferSpend},
+    NftRoyaltyInfo, OfferAmounts,
 };
 
+/// The maximum size of each custom memo attached to a requested NFT payment, in bytes.
+pub const MAX_NFT_MEMO_SIZE: usize = 1024;
+
+/// The maximum size of the memo attached to the whole offer, in bytes.
+pub const MAX_OFFER_MEMO_SIZE: usize = 1024;
+
 #[derive(Debug)]
 pub struct UnsignedMakeOffer {
     pub ctx: SpendContext,
     pub coin_spends: Vec<CoinSpend>,
     pub builder: OfferBuilder<Partial>,
+    pub terms: OfferTerms,
+}
+
+/// What an offer was made with, so that it can be made again with a different fee.
+#[derive(Debug, Default, Clone)]
+pub struct OfferTerms {
+    pub maker: MakerSide,
+    pub taker: TakerSide,
+    pub starts_at: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub hardened: bool,
+    pub reuse: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -27,14 +46,62 @@ pub struct MakerSide {
     pub xch: u64,
     pub cats: IndexMap<Bytes32, u64>,
     pub nfts: Vec<Bytes32>,
+    /// The network fee paid by the maker. If it's zero, no fee is reserved by the offer,
+    /// and the taker supplies the whole fee when taking it instead.
     pub fee: u64,
+    /// Spends exactly these XCH coins, rather than selecting them automatically.
+    /// They must cover the offered amount, fee, and royalties, with any excess returned as change.
+    pub coin_ids: Vec<Bytes32>,
+    /// Whether the selected coins can add up to more than is needed, with the excess returned as change.
+    /// If not, only coins that add up to exactly the offered amount, fee, and royalties are spent.
+    pub allow_change: bool,
+    /// How the primary coins of the offered assets are tied together.
+    pub binding_mode: BindingMode,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct TakerSide {
     pub xch: u64,
     pub cats: IndexMap<Bytes32, u64>,
     pub nfts: IndexMap<Bytes32, RequestedNft>,
+    /// Overrides the inner puzzle hash that requested CATs are paid to, by asset id.
+    /// CATs that aren't listed here are paid to the recipient.
+    pub cat_puzzle_hashes: IndexMap<Bytes32, Bytes32>,
+    /// Pays the requested assets to this puzzle hash instead of the wallet's own p2 puzzle hash.
+    /// Change is still returned to the wallet.
+    pub recipient_override: Option<Bytes32>,
+    /// A reference such as a marketplace order id, attached to the first requested XCH or CAT
+    /// payment after its hint, so that it's part of the offer and the taker's payment.
+    pub offer_memo: Option<Bytes>,
+}
+
+impl Default for MakerSide {
+    /// Offers nothing, with change allowed.
+    fn default() -> Self {
+        Self {
+            xch: 0,
+            cats: IndexMap::new(),
+            nfts: Vec::new(),
+            fee: 0,
+            coin_ids: Vec::new(),
+            allow_change: true,
+            binding_mode: BindingMode::default(),
+        }
+    }
+}
+
+impl MakerSide {
+    /// Whether nothing is being offered. The fee alone doesn't count as an offered asset.
+    pub fn is_empty(&self) -> bool {
+        self.xch == 0 && self.cats.values().all(|&amount| amount == 0) && self.nfts.is_empty()
+    }
+}
+
+impl TakerSide {
+    /// Whether nothing is being requested.
+    pub fn is_empty(&self) -> bool {
+        self.xch == 0 && self.cats.values().all(|&amount| amount == 0) && self.nfts.is_empty()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +110,8 @@ pub struct RequestedNft {
     pub metadata_updater_puzzle_hash: Bytes32,
     pub royalty_puzzle_hash: Bytes32,
     pub royalty_ten_thousandths: u16,
+    /// Memos attached to the payment of this NFT, after the hint that's always included first.
+    pub memos: Vec<Bytes>,
 }
 
 impl Wallet {
@@ -50,10 +119,88 @@ impl Wallet {
         &self,
         maker: MakerSide,
         taker: TakerSide,
+        starts_at: Option<u64>,
         expires_at: Option<u64>,
         hardened: bool,
         reuse: bool,
     ) -> Result<UnsignedMakeOffer, WalletError> {
+        if maker.is_empty() && taker.is_empty() {
+            return Err(WalletError::EmptyOffer);
+        }
+
+        let terms = OfferTerms {
+            maker: maker.clone(),
+            taker: taker.clone(),
+            starts_at,
+            expires_at,
+            hardened,
+            reuse,
+        };
+
+        // The offer could never be taken if it expired before it became valid.
+        if let (Some(starts_at), Some(expires_at)) = (starts_at, expires_at) {
+            if starts_at >= expires_at {
+                return Err(WalletError::InvalidOfferWindow {
+                    starts_at,
+                    expires_at,
+                });
+            }
+        }
+
+        // Trading an asset for itself is nonsensical, since the offer would pay itself.
+        if maker.xch > 0 && taker.xch > 0 {
+            return Err(WalletError::SelfTrade(None));
+        }
+
+        for asset_id in maker.cats.keys() {
+            if taker.cats.contains_key(asset_id) {
+                return Err(WalletError::SelfTrade(Some(*asset_id)));
+            }
+        }
+
+        for nft_id in &maker.nfts {
+            if taker.nfts.contains_key(nft_id) {
+                return Err(WalletError::SelfTrade(Some(*nft_id)));
+            }
+        }
+
+        // Burned NFTs can never be spent again, so an offer involving one could never complete.
+        for &nft_id in maker.nfts.iter().chain(taker.nfts.keys()) {
+            if self
+                .db
+                .nft_row(nft_id)
+                .await?
+                .is_some_and(|row| row.is_burned)
+            {
+                return Err(WalletError::BurnedNft(nft_id));
+            }
+        }
+
+        for requested_nft in taker.nfts.values() {
+            for memo in &requested_nft.memos {
+                if memo.len() > MAX_NFT_MEMO_SIZE {
+                    return Err(WalletError::MemoTooLarge {
+                        size: memo.len(),
+                        max: MAX_NFT_MEMO_SIZE,
+                    });
+                }
+            }
+        }
+
+        if let Some(memo) = &taker.offer_memo {
+            if memo.len() > MAX_OFFER_MEMO_SIZE {
+                return Err(WalletError::MemoTooLarge {
+                    size: memo.len(),
+                    max: MAX_OFFER_MEMO_SIZE,
+                });
+            }
+
+            // NFT payments can have custom memos of their own, so the offer memo isn't attached to them.
+            if taker.xch == 0 && taker.cats.values().all(|&amount| amount == 0) {
+                return Err(WalletError::OfferMemoWithoutPayment);
+            }
+        }
+
         let maker_amounts = OfferAmounts {
             xch: maker.xch,
             cats: maker.cats,
@@ -72,31 +219,53 @@ impl Wallet {
                 .collect::<Vec<_>>(),
         )?;
 
-        let total_amounts = maker_amounts.clone()
-            + maker_royalties.amounts()
-            + OfferAmounts {
+        let total_amounts = maker_amounts
+            .clone()
+            .checked_add(maker_royalties.amounts()?)?
+            .checked_add(OfferAmounts {
                 xch: maker.fee,
                 cats: IndexMap::new(),
-            };
+            })?;
         let maker_coins = self
-            .fetch_offer_coins(&total_amounts, maker.nfts.clone())
+            .fetch_offer_coins(
+                &total_amounts,
+                maker.coin_ids.clone(),
+                maker.nfts.clone(),
+                maker.allow_change,
+            )
             .await?;
+        self.check_offer_coins_unspent(&maker_coins).await?;
+
+        let spends = maker_coins.spend_count() + maker_royalties.settlement_count()?;
+
+        if spends > self.options.max_offer_spends {
+            return Err(WalletError::OfferTooLarge {
+                spends,
+                max: self.options.max_offer_spends,
+            });
+        }
+
         let p2_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
+        let recipient = taker.recipient_override.unwrap_or(p2_puzzle_hash);
 
         let mut builder = OfferBuilder::new(maker_coins.nonce());
-        let mut ctx = SpendContext::new();
+        // The spend context caches puzzles by tree hash, and is reused once the offer is signed,
+        // so this and the CAT puzzles curried below are only deserialized by the first offer.
+        let mut ctx = self.spend_context();
+
         let settlement = ctx.settlement_payments_puzzle()?;
 
+        let mut offer_memo = taker.offer_memo;
+
         // Add requested XCH payments.
         if taker.xch > 0 {
+            let mut memos = vec![recipient.into()];
+            memos.extend(offer_memo.take());
+
             builder = builder.request(
                 &mut ctx,
                 &settlement,
-                vec![Payment::with_memos(
-                    p2_puzzle_hash,
-                    taker.xch,
-                    vec![p2_puzzle_hash.into()],
-                )],
+                vec![Payment::with_memos(recipient, taker.xch, memos)],
             )?;
         }
 
@@ -104,39 +273,49 @@ impl Wallet {
         for (&asset_id, &amount) in &taker.cats {
             let cat_puzzle = ctx.curry(CatArgs::new(asset_id, settlement))?;
 
+            let puzzle_hash = taker
+                .cat_puzzle_hashes
+                .get(&asset_id)
+                .copied()
+                .unwrap_or(recipient);
+
+            // The received coin's puzzle hash is the CAT puzzle curried with the inner puzzle hash,
+            // so it can't be looked up directly. The first memo of a CAT payment is its hint, which
+            // is the inner puzzle hash, and is what lets the recipient find the coin when syncing.
+            let mut memos = vec![puzzle_hash.into()];
+
+            if amount > 0 {
+                memos.extend(offer_memo.take());
+            }
+
             builder = builder.request(
                 &mut ctx,
                 &cat_puzzle,
-                vec![Payment::with_memos(
-                    p2_puzzle_hash,
-                    amount,
-                    vec![p2_puzzle_hash.into()],
-                )],
+                vec![Payment::with_memos(puzzle_hash, amount, memos)],
             )?;
         }
 
         // Add requested NFT payments.
-        for (nft_id, info) in taker.nfts {
+        for (nft_id, requested_nft) in taker.nfts {
             let info = NftInfo {
                 launcher_id: nft_id,
-                metadata: info.metadata,
-                metadata_updater_puzzle_hash: info.metadata_updater_puzzle_hash,
+                metadata: requested_nft.metadata,
+                metadata_updater_puzzle_hash: requested_nft.metadata_updater_puzzle_hash,
                 current_owner: None,
-                royalty_puzzle_hash: info.royalty_puzzle_hash,
-                royalty_ten_thousandths: info.royalty_ten_thousandths,
+                royalty_puzzle_hash: requested_nft.royalty_puzzle_hash,
+                royalty_ten_thousandths: requested_nft.royalty_ten_thousandths,
                 p2_puzzle_hash: SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
             };
 
             let layers = info.into_layers(settlement).construct_puzzle(&mut ctx)?;
 
+            let mut memos = vec![recipient.into()];
+            memos.extend(requested_nft.memos);
+
             builder = builder.request(
                 &mut ctx,
                 &layers,
-                vec![Payment::with_memos(
-                    p2_puzzle_hash,
-                    1,
-                    vec![p2_puzzle_hash.into()],
-                )],
+                vec![Payment::with_memos(recipient, 1, memos)],
             )?;
         }
 
@@ -153,6 +332,10 @@ impl Wallet {
             .extend(assertions)
             .extend(maker_royalties.assertions());
 
+        if let Some(starts_at) = starts_at {
+            extra_conditions = extra_conditions.assert_seconds_absolute(starts_at);
+        }
+
         if let Some(expires_at) = expires_at {
             extra_conditions = extra_conditions.assert_before_seconds_absolute(expires_at);
         }
@@ -168,6 +351,7 @@ impl Wallet {
                 fee: maker.fee,
                 change_puzzle_hash: p2_puzzle_hash,
                 extra_conditions,
+                binding_mode: maker.binding_mode,
             },
         )
         .await?;
@@ -178,6 +362,7 @@ impl Wallet {
             ctx,
             coin_spends,
             builder,
+            terms,
         })
     }
 }
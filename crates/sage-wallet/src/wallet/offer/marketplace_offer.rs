@@ -0,0 +1,148 @@
+use chia_wallet_sdk::{Offer, SpendContext};
+use serde::Serialize;
+
+use crate::WalletError;
+
+use super::{parse_locked_coins, parse_offer_payments};
+
+/// An asset on one side of an offer, as it's reported to marketplaces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MarketplaceAsset {
+    Xch { amount: u64 },
+    Cat { asset_id: String, amount: u64 },
+    Nft { launcher_id: String },
+}
+
+/// The payload submitted to offer aggregators such as Dexie when posting an offer.
+///
+/// The encoded offer is the canonical field, and is all that an aggregator strictly needs.
+/// The summary is derived from it, so that clients can display or index the offer
+/// without decoding it themselves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MarketplaceOffer {
+    pub offer: String,
+    pub offered: Vec<MarketplaceAsset>,
+    pub requested: Vec<MarketplaceAsset>,
+    pub fee: u64,
+}
+
+impl MarketplaceOffer {
+    pub fn from_offer(offer: Offer) -> Result<Self, WalletError> {
+        let encoded = offer.encode()?;
+
+        let mut ctx = SpendContext::new();
+
+        let offer = offer.parse(&mut ctx.allocator)?;
+        let (locked_coins, _original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+        let maker_amounts = locked_coins.amounts();
+
+        let mut builder = offer.take();
+        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+        let taker_amounts = requested_payments.amounts();
+
+        let mut offered = Vec::new();
+
+        if maker_amounts.xch > 0 {
+            offered.push(MarketplaceAsset::Xch {
+                amount: maker_amounts.xch,
+            });
+        }
+
+        for (asset_id, amount) in maker_amounts.cats {
+            offered.push(MarketplaceAsset::Cat {
+                asset_id: asset_id.to_string(),
+                amount,
+            });
+        }
+
+        for launcher_id in locked_coins.nfts.keys() {
+            offered.push(MarketplaceAsset::Nft {
+                launcher_id: launcher_id.to_string(),
+            });
+        }
+
+        let mut requested = Vec::new();
+
+        if taker_amounts.xch > 0 {
+            requested.push(MarketplaceAsset::Xch {
+                amount: taker_amounts.xch,
+            });
+        }
+
+        for (asset_id, amount) in taker_amounts.cats {
+            requested.push(MarketplaceAsset::Cat {
+                asset_id: asset_id.to_string(),
+                amount,
+            });
+        }
+
+        for launcher_id in requested_payments.nfts.keys() {
+            requested.push(MarketplaceAsset::Nft {
+                launcher_id: launcher_id.to_string(),
+            });
+        }
+
+        Ok(Self {
+            offer: encoded,
+            offered,
+            requested,
+            fee: locked_coins.fee,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::{
+        bls::Signature,
+        protocol::{Bytes32, SpendBundle},
+        puzzles::offer::{Payment, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
+    };
+    use chia_wallet_sdk::{Cat, Conditions, OfferBuilder};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_marketplace_offer_json() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        // Offer a freshly issued CAT, which is locked in the settlement puzzle by the eve spend.
+        let (_conditions, cat) = Cat::single_issuance_eve(
+            &mut ctx,
+            Bytes32::new([1; 32]),
+            1000,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, None),
+        )?;
+        let coin_spends = ctx.take();
+
+        let puzzle_hash = Bytes32::new([2; 32]);
+        let settlement = ctx.settlement_payments_puzzle()?;
+
+        let (_assertions, builder) = OfferBuilder::new(Bytes32::default())
+            .request(&mut ctx, &settlement, vec![Payment::new(puzzle_hash, 500)])?
+            .finish();
+
+        let offer = builder.bundle(
+            &mut ctx,
+            SpendBundle::new(coin_spends, Signature::default()),
+        )?;
+        let encoded = offer.encode()?;
+
+        let json = serde_json::to_value(MarketplaceOffer::from_offer(offer)?)?;
+
+        assert!(encoded.starts_with("offer1"));
+        assert_eq!(
+            json,
+            json!({
+                "offer": encoded,
+                "offered": [{ "type": "cat", "asset_id": cat.asset_id.to_string(), "amount": 1000 }],
+                "requested": [{ "type": "xch", "amount": 500 }],
+                "fee": 0,
+            })
+        );
+
+        Ok(())
+    }
+}
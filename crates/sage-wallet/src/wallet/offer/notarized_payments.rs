@@ -0,0 +1,232 @@
+use chia::{
+    protocol::Bytes32,
+    puzzles::offer::{
+        NotarizedPayment, SETTLEMENT_PAYMENTS_PUZZLE_HASH, SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1,
+    },
+};
+use chia_wallet_sdk::{CatLayer, HashedPtr, Layer, NftInfo, Offer, Puzzle, SpendContext};
+
+use crate::WalletError;
+
+/// The asset held by the settlement coins that a group of notarized payments is made from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettlementAsset {
+    Xch,
+    Cat {
+        asset_id: Bytes32,
+    },
+    Nft {
+        launcher_id: Bytes32,
+    },
+    /// A settlement puzzle that isn't recognized, such as one wrapped in an unsupported outer puzzle.
+    Unknown,
+}
+
+/// The notarized payments that the taker must make from coins with a given settlement puzzle.
+#[derive(Debug, Clone)]
+pub struct SettlementPayments {
+    pub asset: SettlementAsset,
+    /// The full puzzle hash of the settlement coins, including any outer puzzles.
+    pub puzzle_hash: Bytes32,
+    pub notarized_payments: Vec<NotarizedPayment>,
+}
+
+/// Lists every notarized payment that the offer requests, with its nonce, puzzle hash, amount, and memos.
+/// Unlike the summary, nothing is combined or left out, so this shows exactly what taking the offer pays,
+/// grouped by the settlement puzzle that it's paid from, in the order the payments appear in the offer.
+pub fn offer_notarized_payments(offer: Offer) -> Result<Vec<SettlementPayments>, WalletError> {
+    let mut ctx = SpendContext::new();
+    let mut builder = offer.take(&mut ctx.allocator)?;
+
+    let mut groups: Vec<SettlementPayments> = Vec::new();
+
+    while let Some((puzzle, notarized_payments)) = builder.fulfill() {
+        let puzzle_hash: Bytes32 = puzzle.curried_puzzle_hash().into();
+
+        if let Some(group) = groups
+            .iter_mut()
+            .find(|group| group.puzzle_hash == puzzle_hash)
+        {
+            group.notarized_payments.extend(notarized_payments);
+            continue;
+        }
+
+        groups.push(SettlementPayments {
+            asset: settlement_asset(&ctx, puzzle)?,
+            puzzle_hash,
+            notarized_payments,
+        });
+    }
+
+    Ok(groups)
+}
+
+fn settlement_asset(ctx: &SpendContext, puzzle: Puzzle) -> Result<SettlementAsset, WalletError> {
+    if let Some(cat) = CatLayer::<Puzzle>::parse_puzzle(&ctx.allocator, puzzle)? {
+        return Ok(SettlementAsset::Cat {
+            asset_id: cat.asset_id,
+        });
+    }
+
+    if let Some((info, _inner_puzzle)) = NftInfo::<HashedPtr>::parse(&ctx.allocator, puzzle)? {
+        return Ok(SettlementAsset::Nft {
+            launcher_id: info.launcher_id,
+        });
+    }
+
+    // Offers made by older wallets still use the original settlement puzzle.
+    let puzzle_hash = puzzle.curried_puzzle_hash();
+
+    if puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH
+        || puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1
+    {
+        return Ok(SettlementAsset::Xch);
+    }
+
+    Ok(SettlementAsset::Unknown)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::{
+        bls::Signature,
+        protocol::{Bytes, SpendBundle},
+        puzzles::{
+            cat::CatArgs,
+            nft::NFT_METADATA_UPDATER_PUZZLE_HASH,
+            offer::{Memos, Payment, SETTLEMENT_PAYMENTS_PUZZLE_V1},
+        },
+    };
+    use chia_wallet_sdk::OfferBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_offer_notarized_payments() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let nonce = Bytes32::new([1; 32]);
+        let recipient = Bytes32::new([2; 32]);
+        let asset_id = Bytes32::new([3; 32]);
+        let launcher_id = Bytes32::new([4; 32]);
+
+        let settlement = ctx.settlement_payments_puzzle()?;
+        let cat_puzzle = ctx.curry(CatArgs::new(asset_id, settlement))?;
+        let nft_puzzle = NftInfo::new(
+            launcher_id,
+            HashedPtr::NIL,
+            NFT_METADATA_UPDATER_PUZZLE_HASH.into(),
+            None,
+            Bytes32::default(),
+            0,
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+        )
+        .into_layers(settlement)
+        .construct_puzzle(&mut ctx)?;
+
+        let memos = vec![
+            Bytes::from(recipient.to_vec()),
+            Bytes::new(b"order".to_vec()),
+        ];
+
+        let (_assertions, builder) = OfferBuilder::new(nonce)
+            .request(
+                &mut ctx,
+                &settlement,
+                vec![Payment::with_memos(recipient, 1000, memos.clone())],
+            )?
+            .request(
+                &mut ctx,
+                &cat_puzzle,
+                vec![
+                    Payment::with_memos(recipient, 300, vec![recipient.into()]),
+                    Payment::new(Bytes32::new([5; 32]), 200),
+                ],
+            )?
+            .request(
+                &mut ctx,
+                &nft_puzzle,
+                vec![Payment::with_memos(recipient, 1, vec![recipient.into()])],
+            )?
+            .finish();
+
+        let offer = builder.bundle(&mut ctx, SpendBundle::new(Vec::new(), Signature::default()))?;
+
+        let groups = offer_notarized_payments(offer)?;
+
+        let summary: Vec<(SettlementAsset, Vec<(Bytes32, u64)>)> = groups
+            .iter()
+            .map(|group| {
+                (
+                    group.asset,
+                    group
+                        .notarized_payments
+                        .iter()
+                        .flat_map(|item| &item.payments)
+                        .map(|payment| (payment.puzzle_hash, payment.amount))
+                        .collect(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (SettlementAsset::Xch, vec![(recipient, 1000)]),
+                (
+                    SettlementAsset::Cat { asset_id },
+                    vec![(recipient, 300), (Bytes32::new([5; 32]), 200)]
+                ),
+                (SettlementAsset::Nft { launcher_id }, vec![(recipient, 1)]),
+            ]
+        );
+
+        // Every payment is notarized with the offer's nonce, and keeps its memos.
+        assert!(groups
+            .iter()
+            .flat_map(|group| &group.notarized_payments)
+            .all(|item| item.nonce == nonce));
+        assert_eq!(
+            groups[0].notarized_payments[0].payments[0].memos,
+            Some(Memos(memos))
+        );
+        assert_eq!(
+            groups[0].puzzle_hash,
+            Bytes32::from(SETTLEMENT_PAYMENTS_PUZZLE_HASH)
+        );
+        assert_eq!(groups[1].notarized_payments[0].payments[1].memos, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_v1_settlement_payments() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let nonce = Bytes32::new([1; 32]);
+        let recipient = Bytes32::new([2; 32]);
+
+        let settlement = ctx.puzzle(
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1,
+            &SETTLEMENT_PAYMENTS_PUZZLE_V1,
+        )?;
+
+        let (_assertions, builder) = OfferBuilder::new(nonce)
+            .request(&mut ctx, &settlement, vec![Payment::new(recipient, 1000)])?
+            .finish();
+
+        let offer = builder.bundle(&mut ctx, SpendBundle::new(Vec::new(), Signature::default()))?;
+
+        let groups = offer_notarized_payments(offer)?;
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].asset, SettlementAsset::Xch);
+        assert_eq!(
+            groups[0].puzzle_hash,
+            Bytes32::from(SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1)
+        );
+        assert_eq!(groups[0].notarized_payments[0].payments[0].amount, 1000);
+
+        Ok(())
+    }
+}
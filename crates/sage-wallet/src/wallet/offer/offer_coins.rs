@@ -1,8 +1,6 @@
-use std::ops::Add;
-
-use chia::protocol::{Bytes32, Coin, Program};
+use chia::protocol::{Bytes32, Coin, CoinSpend, Program};
 use chia_wallet_sdk::{Cat, Nft, Offer};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 
 use crate::{Wallet, WalletError};
 
@@ -12,20 +10,28 @@ pub struct OfferAmounts {
     pub cats: IndexMap<Bytes32, u64>,
 }
 
-impl Add for OfferAmounts {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
+impl OfferAmounts {
+    /// Adds the amounts of each asset together, failing if any of the totals would overflow.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, WalletError> {
         let mut cats = self.cats;
 
         for (asset_id, amount) in rhs.cats {
-            *cats.entry(asset_id).or_insert(0) += amount;
+            let total = cats.entry(asset_id).or_insert(0);
+
+            *total = total
+                .checked_add(amount)
+                .ok_or(WalletError::AmountOverflow {
+                    asset_id: Some(asset_id),
+                })?;
         }
 
-        Self {
-            xch: self.xch + rhs.xch,
+        Ok(Self {
+            xch: self
+                .xch
+                .checked_add(rhs.xch)
+                .ok_or(WalletError::AmountOverflow { asset_id: None })?,
             cats,
-        }
+        })
     }
 }
 
@@ -37,7 +43,8 @@ pub struct OfferCoins {
 }
 
 impl OfferCoins {
-    pub fn nonce(&self) -> Bytes32 {
+    /// The ids of every coin being offered.
+    pub fn coin_ids(&self) -> Vec<Bytes32> {
         let mut coin_ids = Vec::new();
 
         for coin in &self.xch {
@@ -54,7 +61,21 @@ impl OfferCoins {
             coin_ids.push(nft.coin.coin_id());
         }
 
-        Offer::nonce(coin_ids)
+        coin_ids
+    }
+
+    /// Computes the offer nonce from the ids of every coin being offered.
+    /// The ids are deduplicated first, and [`Offer::nonce`] sorts them, so the nonce is canonical
+    /// regardless of selection order or a coin appearing in more than one asset category.
+    pub fn nonce(&self) -> Bytes32 {
+        let coin_ids: IndexSet<Bytes32> = self.coin_ids().into_iter().collect();
+
+        Offer::nonce(coin_ids.into_iter().collect())
+    }
+
+    /// The number of coin spends needed to offer these coins.
+    pub fn spend_count(&self) -> usize {
+        self.xch.len() + self.cats.values().map(Vec::len).sum::<usize>() + self.nfts.len()
     }
 
     pub fn primary_coin_ids(&self) -> Vec<Bytes32> {
@@ -79,18 +100,39 @@ impl OfferCoins {
 }
 
 impl Wallet {
+    /// Fetches the coins needed to cover the total amounts. If change isn't allowed,
+    /// the coins must add up to exactly the total amount of each asset.
     pub async fn fetch_offer_coins(
         &self,
         total_amounts: &OfferAmounts,
+        xch_coin_ids: Vec<Bytes32>,
         nft_ids: Vec<Bytes32>,
+        allow_change: bool,
     ) -> Result<OfferCoins, WalletError> {
-        // Select XCH coins.
-        let xch = if total_amounts.xch > 0 {
+        // Select XCH coins, unless specific coins were chosen to be spent instead.
+        let xch = if !xch_coin_ids.is_empty() {
+            self.fetch_p2_coins(xch_coin_ids).await?
+        } else if total_amounts.xch > 0 && allow_change {
             self.select_p2_coins(total_amounts.xch as u128).await?
+        } else if total_amounts.xch > 0 {
+            self.select_exact_p2_coins(total_amounts.xch as u128)
+                .await?
         } else {
             Vec::new()
         };
 
+        let selected = xch.iter().map(|coin| coin.amount as u128).sum::<u128>();
+
+        // The settlement and change amounts are derived from the selected total,
+        // so it must cover what's being offered.
+        if selected < total_amounts.xch as u128 {
+            return Err(WalletError::InsufficientFunds);
+        }
+
+        if !allow_change && selected > total_amounts.xch as u128 {
+            return Err(WalletError::NoExactCoins);
+        }
+
         // Select CAT coins.
         let mut cats = IndexMap::new();
 
@@ -99,10 +141,23 @@ impl Wallet {
                 continue;
             }
 
-            cats.insert(
-                asset_id,
-                self.select_cat_coins(asset_id, amount as u128).await?,
-            );
+            let cat_coins = if allow_change {
+                self.select_cat_coins(asset_id, amount as u128).await?
+            } else {
+                self.select_exact_cat_coins(asset_id, amount as u128)
+                    .await?
+            };
+
+            if cat_coins
+                .iter()
+                .map(|cat| cat.coin.amount as u128)
+                .sum::<u128>()
+                < amount as u128
+            {
+                return Err(WalletError::InsufficientFunds);
+            }
+
+            cats.insert(asset_id, cat_coins);
         }
 
         // Fetch NFT coins.
@@ -110,6 +165,21 @@ impl Wallet {
 
         for nft_id in nft_ids {
             let Some(nft) = self.db.spendable_nft(nft_id).await? else {
+                // If the NFT's latest coin has been spent but the NFT hasn't been synced since,
+                // the offer would be built against a stale coin. Otherwise it's not spendable
+                // because it's locked by a pending transaction or offer, or isn't owned.
+                if let Some(row) = self.db.nft_row(nft_id).await? {
+                    let spent = self
+                        .db
+                        .coin_state(row.coin_id)
+                        .await?
+                        .is_some_and(|coin_state| coin_state.spent_height.is_some());
+
+                    if row.is_owned && spent {
+                        return Err(WalletError::StaleNft { nft_id });
+                    }
+                }
+
                 return Err(WalletError::MissingNft(nft_id));
             };
 
@@ -118,4 +188,191 @@ impl Wallet {
 
         Ok(OfferCoins { xch, cats, nfts })
     }
+
+    /// Checks that none of the offered coins have been spent since they were selected. Coins are
+    /// selected from the local database, so a spend that hasn't been synced yet would otherwise
+    /// go unnoticed and result in an offer that can never be taken.
+    pub async fn check_offer_coins_unspent(&self, coins: &OfferCoins) -> Result<(), WalletError> {
+        for coin_id in coins.coin_ids() {
+            let Some(coin_state) = self.db.coin_state(coin_id).await? else {
+                return Err(WalletError::MissingCoin(coin_id));
+            };
+
+            if coin_state.spent_height.is_some() {
+                return Err(WalletError::CoinAlreadySpent { coin_id });
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_p2_coins(&self, mut coin_ids: Vec<Bytes32>) -> Result<Vec<Coin>, WalletError> {
+        coin_ids.sort();
+        coin_ids.dedup();
+
+        let mut coins = Vec::with_capacity(coin_ids.len());
+
+        for coin_id in coin_ids {
+            let Some(coin_state) = self.db.coin_state(coin_id).await? else {
+                return Err(WalletError::MissingCoin(coin_id));
+            };
+
+            if coin_state.spent_height.is_some()
+                || self.db.is_p2_coin(coin_id).await? != Some(true)
+                || !self.db.is_coin_unlocked(coin_id).await?
+            {
+                return Err(WalletError::UnspendableCoin(coin_id));
+            }
+
+            coins.push(coin_state.coin);
+        }
+
+        Ok(coins)
+    }
+
+    /// The ids of the coins spent by an offer that are locked by the wallet's own p2 puzzles, such
+    /// as the XCH coins selected for it, so that they can be spent again when the offer is rebuilt.
+    pub(crate) async fn maker_p2_coin_ids(
+        &self,
+        coin_spends: &[CoinSpend],
+    ) -> Result<Vec<Bytes32>, WalletError> {
+        let mut coin_ids = Vec::new();
+
+        for coin_spend in coin_spends {
+            if self
+                .db
+                .is_p2_puzzle_hash(coin_spend.coin.puzzle_hash)
+                .await?
+            {
+                coin_ids.push(coin_spend.coin.coin_id());
+            }
+        }
+
+        Ok(coin_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_fetch_offer_coins_exact_balance() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let amounts = |xch| OfferAmounts {
+            xch,
+            cats: IndexMap::new(),
+        };
+
+        let coins = test
+            .wallet
+            .fetch_offer_coins(&amounts(1000), Vec::new(), Vec::new(), true)
+            .await?;
+        assert_eq!(coins.xch.iter().map(|coin| coin.amount).sum::<u64>(), 1000);
+
+        let result = test
+            .wallet
+            .fetch_offer_coins(&amounts(1001), Vec::new(), Vec::new(), true)
+            .await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_coin_spent_after_selection() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coins = test
+            .wallet
+            .fetch_offer_coins(
+                &OfferAmounts {
+                    xch: 1000,
+                    cats: IndexMap::new(),
+                },
+                Vec::new(),
+                Vec::new(),
+                true,
+            )
+            .await?;
+        test.wallet.check_offer_coins_unspent(&coins).await?;
+
+        // Simulate the coin being spent by a sync that happened after it was selected.
+        let coin_id = coins.xch[0].coin_id();
+        let coin_state = test
+            .wallet
+            .db
+            .coin_state(coin_id)
+            .await?
+            .expect("missing coin");
+
+        let mut tx = test.wallet.db.tx().await?;
+        tx.update_coin_state(coin_id, coin_state.created_height, Some(1), None)
+            .await?;
+        tx.commit().await?;
+
+        let result = test.wallet.check_offer_coins_unspent(&coins).await;
+
+        assert!(matches!(
+            result,
+            Err(WalletError::CoinAlreadySpent { coin_id: id }) if id == coin_id
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_checked_add_overflow() {
+        let asset_id = Bytes32::new([1; 32]);
+
+        let amounts = |xch, amount| OfferAmounts {
+            xch,
+            cats: indexmap! { asset_id => amount },
+        };
+
+        let total = amounts(1, u64::MAX - 1)
+            .checked_add(amounts(0, 1))
+            .expect("no overflow");
+        assert_eq!(total.cats[&asset_id], u64::MAX);
+
+        assert!(matches!(
+            amounts(0, u64::MAX).checked_add(amounts(0, 1)),
+            Err(WalletError::AmountOverflow { asset_id: Some(id) }) if id == asset_id
+        ));
+        assert!(matches!(
+            amounts(u64::MAX, 0).checked_add(amounts(1, 0)),
+            Err(WalletError::AmountOverflow { asset_id: None })
+        ));
+    }
+
+    #[test]
+    fn test_nonce_ignores_duplicate_coin_ids() {
+        let a = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 100);
+        let b = Coin::new(Bytes32::new([3; 32]), Bytes32::new([4; 32]), 200);
+
+        let coins = |xch: Vec<Coin>| OfferCoins {
+            xch,
+            cats: IndexMap::new(),
+            nfts: IndexMap::new(),
+        };
+
+        let expected = Offer::nonce(vec![a.coin_id(), b.coin_id()]);
+
+        assert_eq!(coins(vec![a, b]).nonce(), expected);
+        assert_eq!(coins(vec![b, a]).nonce(), expected);
+
+        // The duplicates aren't next to each other, and would change the nonce if they were kept.
+        assert_eq!(coins(vec![a, b, a]).nonce(), expected);
+        assert_eq!(coins(vec![b, a, b]).nonce(), expected);
+        assert_ne!(
+            Offer::nonce(vec![a.coin_id(), b.coin_id(), a.coin_id()]),
+            expected
+        );
+    }
 }
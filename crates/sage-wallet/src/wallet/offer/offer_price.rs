@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+
+use chia::protocol::Bytes32;
+use chia_wallet_sdk::{Offer, SpendContext};
+use indexmap::IndexMap;
+
+use crate::WalletError;
+
+use super::{calculate_royalties, parse_locked_coins, parse_offer_payments, NftRoyaltyInfo};
+
+/// The asset on one side of an offer, for the purpose of pricing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PricedAsset {
+    Xch,
+    Cat(Bytes32),
+    /// A bundle of NFTs, sorted by launcher id.
+    Nfts(Vec<Bytes32>),
+}
+
+/// The effective price of an offer from the point of view of the taker.
+///
+/// The requested amount includes any royalties the taker would have to pay
+/// for the NFTs being offered, since those are part of the cost of taking it.
+/// The maker's fee goes to the farmer rather than the taker, so it's reported
+/// separately and doesn't affect the price.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OfferPrice {
+    pub offered: PricedAsset,
+    pub offered_amount: u128,
+    pub requested: PricedAsset,
+    pub requested_amount: u128,
+    pub fee: u64,
+}
+
+impl OfferPrice {
+    /// Returns `None` if either side of the offer consists of more than one kind of asset,
+    /// or of fungible assets mixed with NFTs, since there is no single price in that case.
+    pub fn from_offer(offer: Offer) -> Result<Option<Self>, WalletError> {
+        let mut ctx = SpendContext::new();
+
+        let offer = offer.parse(&mut ctx.allocator)?;
+        let (locked_coins, _original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+        let maker_amounts = locked_coins.amounts();
+
+        let mut builder = offer.take();
+        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+        // The value of an unknown asset can't be priced.
+        if !requested_payments.unknown.is_empty() {
+            return Ok(None);
+        }
+        let taker_amounts = requested_payments.amounts();
+
+        let taker_royalties = calculate_royalties(
+            &taker_amounts,
+            &locked_coins
+                .nfts
+                .values()
+                .map(|nft| NftRoyaltyInfo {
+                    launcher_id: nft.info.launcher_id,
+                    royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
+                    royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
+                })
+                .collect::<Vec<_>>(),
+        )?
+        .amounts()?;
+
+        let Some((offered, offered_amount)) = priced_side(
+            u128::from(maker_amounts.xch),
+            maker_amounts
+                .cats
+                .into_iter()
+                .map(|(asset_id, amount)| (asset_id, u128::from(amount)))
+                .collect(),
+            locked_coins.nfts.keys().copied().collect(),
+        ) else {
+            return Ok(None);
+        };
+
+        let Some((requested, requested_amount)) = priced_side(
+            u128::from(taker_amounts.xch) + u128::from(taker_royalties.xch),
+            taker_amounts
+                .cats
+                .into_iter()
+                .map(|(asset_id, amount)| {
+                    let royalty = taker_royalties.cats.get(&asset_id).copied().unwrap_or(0);
+                    (asset_id, u128::from(amount) + u128::from(royalty))
+                })
+                .collect(),
+            requested_payments.nfts.keys().copied().collect(),
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            offered,
+            offered_amount,
+            requested,
+            requested_amount,
+            fee: locked_coins.fee,
+        }))
+    }
+
+    /// The amount of the requested asset paid per unit of the offered asset.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn price(&self) -> f64 {
+        self.requested_amount as f64 / self.offered_amount as f64
+    }
+
+    /// Compares the price of two offers for the same asset pair, where a lower price is a better deal
+    /// for the taker. Returns `None` if the offers don't trade the same assets.
+    pub fn compare(&self, other: &Self) -> Option<Ordering> {
+        if self.offered != other.offered || self.requested != other.requested {
+            return None;
+        }
+
+        // Cross multiply to compare the ratios exactly when possible.
+        match (
+            self.requested_amount.checked_mul(other.offered_amount),
+            other.requested_amount.checked_mul(self.offered_amount),
+        ) {
+            (Some(lhs), Some(rhs)) => Some(lhs.cmp(&rhs)),
+            _ => self.price().partial_cmp(&other.price()),
+        }
+    }
+}
+
+fn priced_side(
+    xch: u128,
+    cats: IndexMap<Bytes32, u128>,
+    mut nfts: Vec<Bytes32>,
+) -> Option<(PricedAsset, u128)> {
+    let cats: Vec<(Bytes32, u128)> = cats.into_iter().filter(|(_, amount)| *amount > 0).collect();
+
+    if !nfts.is_empty() {
+        if xch > 0 || !cats.is_empty() {
+            return None;
+        }
+
+        nfts.sort();
+        let count = nfts.len() as u128;
+
+        return Some((PricedAsset::Nfts(nfts), count));
+    }
+
+    match (xch, cats.as_slice()) {
+        (0, [(asset_id, amount)]) => Some((PricedAsset::Cat(*asset_id), *amount)),
+        (amount, []) if amount > 0 => Some((PricedAsset::Xch, amount)),
+        _ => None,
+    }
+}
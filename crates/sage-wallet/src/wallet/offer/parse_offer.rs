@@ -2,19 +2,23 @@ use std::collections::HashSet;
 
 use chia::{
     clvm_traits::{FromClvm, ToClvm},
-    protocol::{Bytes32, Coin},
-    puzzles::offer::{NotarizedPayment, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
+    clvm_utils::tree_hash,
+    protocol::{Bytes, Bytes32, Coin, Program},
+    puzzles::offer::{
+        NotarizedPayment, SettlementPaymentsSolution, SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+    },
 };
 use chia_wallet_sdk::{
-    run_puzzle, Cat, CatLayer, Condition, Conditions, HashedPtr, Layer, Nft, NftInfo, OfferBuilder,
-    ParsedOffer, Puzzle, SpendContext, Take,
+    run_puzzle, Cat, CatLayer, Condition, Conditions, DidInfo, HashedPtr, Layer, Nft, NftInfo,
+    OfferBuilder, ParsedOffer, Puzzle, SettlementLayer, SpendContext, Take,
 };
 use clvmr::{Allocator, NodePtr};
 use indexmap::IndexMap;
+use tracing::warn;
 
 use crate::WalletError;
 
-use super::OfferAmounts;
+use super::{OfferAmounts, Royalties, RoyaltyPayment};
 
 #[derive(Debug, Default, Clone)]
 pub struct LockedCoins {
@@ -53,6 +57,9 @@ pub struct RequestedPayments {
     pub xch: Vec<NotarizedPayment>,
     pub cats: IndexMap<Bytes32, Vec<NotarizedPayment>>,
     pub nfts: IndexMap<Bytes32, (NftInfo<HashedPtr>, Vec<NotarizedPayment>)>,
+    /// Payments to puzzles that aren't recognized, by puzzle hash. These can't be fulfilled,
+    /// but are kept so that the rest of the offer can still be displayed.
+    pub unknown: IndexMap<Bytes32, Vec<NotarizedPayment>>,
 }
 
 impl RequestedPayments {
@@ -81,6 +88,16 @@ impl RequestedPayments {
 
         OfferAmounts { xch, cats }
     }
+
+    /// The memo attached to the whole offer, which is the first memo after the hint
+    /// of a requested XCH or CAT payment.
+    pub fn offer_memo(&self) -> Option<Bytes> {
+        self.xch
+            .iter()
+            .chain(self.cats.values().flatten())
+            .flat_map(|item| &item.payments)
+            .find_map(|payment| payment.memos.as_ref()?.0.get(1).cloned())
+    }
 }
 
 pub fn parse_locked_coins(
@@ -171,6 +188,156 @@ pub fn parse_locked_coins(
     ))
 }
 
+/// Finds the p2 puzzle hash that owns each of the given coins spent by the offer, which is the
+/// inner puzzle hash of CATs, NFTs, and DIDs, and the puzzle hash of any other coin.
+pub fn parse_offered_p2_puzzle_hashes(
+    allocator: &mut Allocator,
+    offer: &ParsedOffer,
+    coin_ids: &[Bytes32],
+) -> Result<Vec<Bytes32>, WalletError> {
+    let mut p2_puzzle_hashes = Vec::new();
+
+    for coin_spend in &offer.coin_spends {
+        if !coin_ids.contains(&coin_spend.coin.coin_id()) {
+            continue;
+        }
+
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+
+        let p2_puzzle_hash = if let Some(cat) = CatLayer::<Puzzle>::parse_puzzle(allocator, puzzle)?
+        {
+            cat.inner_puzzle.curried_puzzle_hash().into()
+        } else if let Some((nft, _p2_puzzle)) = NftInfo::<HashedPtr>::parse(allocator, puzzle)? {
+            nft.p2_puzzle_hash
+        } else if let Some((did, _p2_puzzle)) = DidInfo::<HashedPtr>::parse(allocator, puzzle)? {
+            did.p2_puzzle_hash
+        } else {
+            coin_spend.coin.puzzle_hash
+        };
+
+        p2_puzzle_hashes.push(p2_puzzle_hash);
+    }
+
+    Ok(p2_puzzle_hashes)
+}
+
+/// Collects the payments made by settlement coins that are spent within the offer itself.
+/// The maker pays royalties for requested NFTs this way, with the launcher id as the nonce.
+pub fn parse_royalty_payments(
+    allocator: &mut Allocator,
+    offer: &ParsedOffer,
+) -> Result<Royalties, WalletError> {
+    let mut royalties = Royalties::default();
+
+    for coin_spend in &offer.coin_spends {
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+
+        let (payments, notarized_payments) = if SettlementLayer::parse_puzzle(allocator, puzzle)?
+            .is_some()
+        {
+            (
+                &mut royalties.xch,
+                SettlementLayer::parse_solution(allocator, solution)?.notarized_payments,
+            )
+        } else if let Some(cat) = CatLayer::<SettlementLayer>::parse_puzzle(allocator, puzzle)? {
+            let solution = CatLayer::<SettlementLayer>::parse_solution(allocator, solution)?;
+            let SettlementPaymentsSolution { notarized_payments } = solution.inner_puzzle_solution;
+
+            (
+                royalties.cats.entry(cat.asset_id).or_default(),
+                notarized_payments,
+            )
+        } else {
+            continue;
+        };
+
+        for notarized_payment in notarized_payments {
+            for payment in notarized_payment.payments {
+                payments.push(RoyaltyPayment {
+                    nft_id: notarized_payment.nonce,
+                    p2_puzzle_hash: payment.puzzle_hash,
+                    amount: payment.amount,
+                });
+            }
+        }
+    }
+
+    Ok(royalties)
+}
+
+/// Collects the ids of the puzzle announcements asserted by the offer's coin spends, which the taker
+/// must create for the offer to be valid. Offered NFTs assert their royalty payments this way.
+pub fn parse_asserted_announcements(
+    allocator: &mut Allocator,
+    offer: &ParsedOffer,
+) -> Result<HashSet<Bytes32>, WalletError> {
+    let mut announcement_ids = HashSet::new();
+
+    for coin_spend in &offer.coin_spends {
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+
+        let output = run_puzzle(allocator, puzzle, solution)?;
+        let conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+        for condition in conditions {
+            if let Condition::AssertPuzzleAnnouncement(cond) = condition {
+                announcement_ids.insert(cond.announcement_id);
+            }
+        }
+    }
+
+    Ok(announcement_ids)
+}
+
+/// Collects the asset id of every CAT spent by the offer, along with the TAIL program if it's
+/// revealed by one of the spends, such as when the CAT is issued within the offer itself.
+pub fn parse_cat_tails(
+    allocator: &mut Allocator,
+    offer: &ParsedOffer,
+) -> Result<IndexMap<Bytes32, Option<Program>>, WalletError> {
+    let mut tails = IndexMap::new();
+
+    for coin_spend in &offer.coin_spends {
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+
+        let Some(cat) = CatLayer::<Puzzle>::parse_puzzle(allocator, puzzle)? else {
+            continue;
+        };
+
+        let tail = tails.entry(cat.asset_id).or_insert(None);
+
+        if tail.is_some() {
+            continue;
+        }
+
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+        let solution = CatLayer::<Puzzle>::parse_solution(allocator, solution)?;
+
+        let output = run_puzzle(
+            allocator,
+            cat.inner_puzzle.ptr(),
+            solution.inner_puzzle_solution,
+        )?;
+        let conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+        for condition in conditions {
+            if let Condition::RunCatTail(cond) = condition {
+                if tree_hash(allocator, cond.program) == cat.asset_id.into() {
+                    *tail = Some(Program::from_clvm(allocator, cond.program)?);
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(tails)
+}
+
 pub fn parse_offer_payments(
     ctx: &mut SpendContext,
     builder: &mut OfferBuilder<Take>,
@@ -178,6 +345,7 @@ pub fn parse_offer_payments(
     let mut xch_payments = Vec::new();
     let mut cat_payments = IndexMap::new();
     let mut nft_payments = IndexMap::new();
+    let mut unknown_payments = IndexMap::new();
 
     while let Some((puzzle, payments)) = builder.fulfill() {
         if let Some(cat) = CatLayer::<Puzzle>::parse_puzzle(&ctx.allocator, puzzle)? {
@@ -207,9 +375,17 @@ pub fn parse_offer_payments(
         } else if puzzle.curried_puzzle_hash() == SETTLEMENT_PAYMENTS_PUZZLE_HASH {
             xch_payments.extend(payments);
         } else {
-            return Err(WalletError::UnknownRequestedPayment(
-                puzzle.mod_hash().into(),
-            ));
+            let puzzle_hash = puzzle.curried_puzzle_hash().into();
+
+            warn!(
+                "Unrecognized requested payment puzzle hash {puzzle_hash} with mod hash {}",
+                puzzle.mod_hash()
+            );
+
+            unknown_payments
+                .entry(puzzle_hash)
+                .or_insert_with(Vec::new)
+                .extend(payments);
         }
     }
 
@@ -217,5 +393,73 @@ pub fn parse_offer_payments(
         xch: xch_payments,
         cats: cat_payments,
         nfts: nft_payments,
+        unknown: unknown_payments,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use chia::{bls::Signature, protocol::SpendBundle, puzzles::offer::Payment};
+    use chia_wallet_sdk::Offer;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_unknown_requested_payment() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let puzzle_hash = Bytes32::new([1; 32]);
+        let settlement = ctx.settlement_payments_puzzle()?;
+        let unknown = ctx.alloc(&(1, "unknown layer"))?;
+        let unknown_puzzle_hash: Bytes32 = tree_hash(&ctx.allocator, unknown).into();
+
+        let (_assertions, builder) = OfferBuilder::new(Bytes32::default())
+            .request(&mut ctx, &settlement, vec![Payment::new(puzzle_hash, 1000)])?
+            .request(&mut ctx, &unknown, vec![Payment::new(puzzle_hash, 1)])?
+            .finish();
+
+        let offer = builder.bundle(&mut ctx, SpendBundle::new(Vec::new(), Signature::default()))?;
+
+        let mut builder = offer.parse(&mut ctx.allocator)?.take();
+        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+        assert_eq!(requested_payments.amounts().xch, 1000);
+        assert_eq!(
+            requested_payments
+                .unknown
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![unknown_puzzle_hash]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cat_tail_reveal() -> anyhow::Result<()> {
+        let mut ctx = SpendContext::new();
+
+        let (_conditions, cat) = Cat::single_issuance_eve(
+            &mut ctx,
+            Bytes32::new([1; 32]),
+            1000,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, None),
+        )?;
+
+        let offer = Offer::from(SpendBundle::new(ctx.take(), Signature::default()));
+        let offer = offer.parse(&mut ctx.allocator)?;
+
+        let tails = parse_cat_tails(&mut ctx.allocator, &offer)?;
+        assert_eq!(tails.len(), 1);
+
+        let tail = tails[&cat.asset_id].as_ref().expect("tail not revealed");
+        let tail = tail.to_clvm(&mut ctx.allocator)?;
+        assert_eq!(tree_hash(&ctx.allocator, tail), cat.asset_id.into());
+
+        let (locked_coins, _original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+        assert_eq!(locked_coins.amounts().cats[&cat.asset_id], 1000);
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use chia::{
     protocol::Bytes32,
     puzzles::{
@@ -22,27 +24,41 @@ pub struct Royalties {
 }
 
 impl Royalties {
-    pub fn xch_amount(&self) -> u64 {
-        self.xch.iter().map(|royalty| royalty.amount).sum()
+    /// The total XCH royalty amount. Royalties can add up to more than the trade price,
+    /// so this fails rather than wrapping if the total doesn't fit in a coin amount.
+    pub fn xch_amount(&self) -> Result<u64, WalletError> {
+        total_royalty_amount(&self.xch)
     }
 
-    pub fn cat_amount(&self, asset_id: Bytes32) -> u64 {
-        self.cats.get(&asset_id).map_or(0, |royalties| {
-            royalties.iter().map(|royalty| royalty.amount).sum()
-        })
+    /// The total royalty amount for the given CAT, which fails if it would overflow.
+    pub fn cat_amount(&self, asset_id: Bytes32) -> Result<u64, WalletError> {
+        self.cats
+            .get(&asset_id)
+            .map_or(Ok(0), |royalties| total_royalty_amount(royalties))
     }
 
-    pub fn amounts(&self) -> OfferAmounts {
+    pub fn amounts(&self) -> Result<OfferAmounts, WalletError> {
         let mut amounts = OfferAmounts {
-            xch: self.xch_amount(),
+            xch: self.xch_amount()?,
             ..Default::default()
         };
 
         for &asset_id in self.cats.keys() {
-            amounts.cats.insert(asset_id, self.cat_amount(asset_id));
+            amounts.cats.insert(asset_id, self.cat_amount(asset_id)?);
+        }
+
+        Ok(amounts)
+    }
+
+    /// The number of settlement coins that are spent to pay the royalties.
+    pub fn settlement_count(&self) -> Result<usize, WalletError> {
+        let mut count = royalty_settlements(&self.xch)?.len();
+
+        for royalties in self.cats.values() {
+            count += royalty_settlements(royalties)?.len();
         }
 
-        amounts
+        Ok(count)
     }
 
     pub fn assertions(&self) -> Vec<AssertPuzzleAnnouncement> {
@@ -66,6 +82,80 @@ impl Royalties {
 
         assertions
     }
+
+    /// Removes the royalties whose payments aren't asserted by any of the given announcement ids,
+    /// which leaves only the ones that the offer enforces.
+    pub fn retain_asserted(&mut self, announcement_ids: &HashSet<Bytes32>) {
+        let settlement_puzzle_hash: Bytes32 = SETTLEMENT_PAYMENTS_PUZZLE_HASH.into();
+
+        self.xch.retain(|royalty| {
+            announcement_ids.contains(
+                &payment_assertion(settlement_puzzle_hash, &royalty.notarized_payment())
+                    .announcement_id,
+            )
+        });
+
+        for (&asset_id, royalties) in &mut self.cats {
+            let puzzle_hash = CatArgs::curry_tree_hash(asset_id, SETTLEMENT_PAYMENTS_PUZZLE_HASH);
+
+            royalties.retain(|royalty| {
+                announcement_ids.contains(
+                    &payment_assertion(puzzle_hash.into(), &royalty.notarized_payment())
+                        .announcement_id,
+                )
+            });
+        }
+
+        self.cats.retain(|_, royalties| !royalties.is_empty());
+    }
+
+    /// Checks that every royalty in `self` is covered by the payments in `paid`, which are matched
+    /// by asset, NFT id, and royalty puzzle hash. Returns the first royalty that's underpaid.
+    pub fn check_paid(&self, paid: &Self) -> Result<(), WalletError> {
+        let check = |required: &[RoyaltyPayment], paid: &[RoyaltyPayment]| {
+            for royalty in required {
+                let amount: u128 = paid
+                    .iter()
+                    .filter(|payment| {
+                        payment.nft_id == royalty.nft_id
+                            && payment.p2_puzzle_hash == royalty.p2_puzzle_hash
+                    })
+                    .map(|payment| payment.amount as u128)
+                    .sum();
+
+                if amount < royalty.amount as u128 {
+                    return Err(WalletError::RoyaltyUnderpaid {
+                        nft_id: royalty.nft_id,
+                        required: royalty.amount,
+                        paid: amount
+                            .try_into()
+                            .expect("paid amount is less than required"),
+                    });
+                }
+            }
+
+            Ok(())
+        };
+
+        check(&self.xch, &paid.xch)?;
+
+        for (asset_id, royalties) in &self.cats {
+            check(
+                royalties,
+                paid.cats.get(asset_id).map_or(&[], Vec::as_slice),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn total_royalty_amount(royalties: &[RoyaltyPayment]) -> Result<u64, WalletError> {
+    royalties.iter().try_fold(0u64, |total, royalty| {
+        total
+            .checked_add(royalty.amount)
+            .ok_or(WalletError::RoyaltyOverflow)
+    })
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -88,6 +178,65 @@ impl RoyaltyPayment {
     }
 }
 
+/// The royalty payments for a single asset, split into the settlement coins they're paid from.
+///
+/// Royalties are paid out of a single settlement coin per asset rather than one per NFT, which saves
+/// a coin creation and spend for each additional NFT. The payments themselves can't be merged by
+/// recipient, since each NFT asserts a notarized payment with its own launcher id as the nonce.
+///
+/// However, a coin can't create two outputs with the same puzzle hash and amount, which happens when
+/// multiple NFTs with the same royalty address and percentage are traded together. In that case, the
+/// duplicate payments are moved to another settlement coin, which is created by the previous one.
+/// Each item in the list is the amount of the settlement coin and the payments made from it.
+pub fn royalty_settlements(
+    royalties: &[RoyaltyPayment],
+) -> Result<Vec<(u64, Vec<NotarizedPayment>)>, WalletError> {
+    let mut batches: Vec<Vec<RoyaltyPayment>> = Vec::new();
+    let mut occurrences = HashMap::new();
+
+    for &royalty in royalties {
+        let index = occurrences
+            .entry((royalty.p2_puzzle_hash, royalty.amount))
+            .or_insert(0);
+
+        if *index == batches.len() {
+            batches.push(Vec::new());
+        }
+
+        batches[*index].push(royalty);
+        *index += 1;
+    }
+
+    let mut remaining = total_royalty_amount(royalties)?;
+    let mut settlements = Vec::with_capacity(batches.len());
+
+    for (i, batch) in batches.iter().enumerate() {
+        let amount = remaining;
+        remaining = remaining
+            .checked_sub(total_royalty_amount(batch)?)
+            .ok_or(WalletError::RoyaltyOverflow)?;
+
+        let mut notarized_payments: Vec<NotarizedPayment> = batch
+            .iter()
+            .map(RoyaltyPayment::notarized_payment)
+            .collect();
+
+        if i + 1 < batches.len() {
+            notarized_payments.push(NotarizedPayment {
+                nonce: Bytes32::default(),
+                payments: vec![Payment::new(
+                    SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+                    remaining,
+                )],
+            });
+        }
+
+        settlements.push((amount, notarized_payments));
+    }
+
+    Ok(settlements)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NftRoyaltyInfo {
     pub launcher_id: Bytes32,
@@ -184,3 +333,26 @@ pub fn calculate_royalties(
 
     Ok(royalties)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_royalty_settlements_overflow() {
+        let royalty = RoyaltyPayment {
+            nft_id: Bytes32::default(),
+            p2_puzzle_hash: Bytes32::default(),
+            amount: u64::MAX,
+        };
+
+        assert!(matches!(
+            royalty_settlements(&[royalty]),
+            Ok(settlements) if settlements.len() == 1 && settlements[0].0 == u64::MAX
+        ));
+        assert!(matches!(
+            royalty_settlements(&[royalty, royalty]),
+            Err(WalletError::RoyaltyOverflow)
+        ));
+    }
+}
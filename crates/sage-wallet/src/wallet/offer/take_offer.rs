@@ -1,11 +1,15 @@
-use chia::protocol::CoinSpend;
+use chia::{
+    protocol::{Bytes32, CoinSpend},
+    puzzles::offer::{NotarizedPayment, Payment},
+};
 use chia_wallet_sdk::{Conditions, Offer, OfferBuilder, SpendContext, Take};
 use indexmap::IndexMap;
 
 use crate::{
-    calculate_royalties, calculate_trade_prices, complete_requested_payments, parse_locked_coins,
-    parse_offer_payments, unlock_assets, NftRoyaltyInfo, OfferAmounts, OfferSpend, Wallet,
-    WalletError,
+    calculate_royalties, calculate_trade_prices, complete_requested_payments,
+    parse_asserted_announcements, parse_locked_coins, parse_offer_payments,
+    parse_offered_p2_puzzle_hashes, parse_royalty_payments, unlock_assets, BindingMode,
+    NftRoyaltyInfo, OfferAmounts, OfferSpend, RequestedPayments, Wallet, WalletError,
 };
 
 #[derive(Debug)]
@@ -14,25 +18,154 @@ pub struct UnsignedTakeOffer {
     pub builder: OfferBuilder<Take>,
 }
 
+/// What to do when the taker pays more for requested XCH or CATs than the offer asks for.
+///
+/// The requested amounts are a minimum, since the maker only asserts that its own payments were made.
+/// Anything paid beyond them would otherwise be left in the settlement coin, where excess XCH becomes
+/// part of the fee and excess CATs can't be spent at all.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverfillPolicy {
+    /// Refuse to take the offer if anything is paid beyond the requested amounts.
+    #[default]
+    Reject,
+    /// Pay the excess to the maker, as an additional payment to the puzzle hash of the first requested
+    /// payment of the same asset. Royalties are still based on the requested amounts.
+    PayMaker,
+}
+
+/// How an offer is taken, other than the offer itself.
+#[derive(Debug, Clone)]
+pub struct TakeOptions {
+    /// The fee reserved by the taker's spends, on top of any fee included in the offer.
+    pub fee: u64,
+    /// Paid on top of the requested amounts of each asset, and handled according to the policy.
+    pub overfill: OfferAmounts,
+    pub policy: OverfillPolicy,
+    /// Whether royalties are only paid if the offer enforces them.
+    pub allow_royalty_waiver: bool,
+    pub hardened: bool,
+    pub reuse: bool,
+    /// How the taker's primary coins are tied together.
+    pub binding_mode: BindingMode,
+}
+
+impl Default for TakeOptions {
+    /// Takes the offer without a fee or overfill, receiving the offered assets at a reused address.
+    fn default() -> Self {
+        Self {
+            fee: 0,
+            overfill: OfferAmounts::default(),
+            policy: OverfillPolicy::default(),
+            allow_royalty_waiver: false,
+            hardened: false,
+            reuse: true,
+            binding_mode: BindingMode::default(),
+        }
+    }
+}
+
 impl Wallet {
+    /// Takes the offer, paying royalties for each offered NFT based on the requested amounts.
+    ///
+    /// The fee is reserved by the taker's spends, on top of any fee included in the offer.
+    /// For offers made without a fee, this is the only fee paid for the transaction.
+    ///
+    /// If `allow_royalty_waiver` is set, royalties are only paid if the offer enforces them. This is
+    /// for offers that were deliberately made without royalties, but it also means that a maker who
+    /// omits them can cause the creator to go unpaid. It should be left unset unless the user has
+    /// explicitly agreed to skip royalties.
     pub async fn take_offer(
         &self,
         offer: Offer,
         fee: u64,
+        allow_royalty_waiver: bool,
         hardened: bool,
         reuse: bool,
     ) -> Result<UnsignedTakeOffer, WalletError> {
+        self.take_offer_with_options(
+            offer,
+            TakeOptions {
+                fee,
+                allow_royalty_waiver,
+                hardened,
+                reuse,
+                ..Default::default()
+            },
+        )
+        .await
+    }
+
+    /// Takes the offer as [`Wallet::take_offer`] does, paying the overfill on top of the requested
+    /// amounts of each asset, which is handled according to the policy.
+    pub async fn take_offer_with_options(
+        &self,
+        offer: Offer,
+        options: TakeOptions,
+    ) -> Result<UnsignedTakeOffer, WalletError> {
+        let TakeOptions {
+            fee,
+            overfill,
+            policy,
+            allow_royalty_waiver,
+            hardened,
+            reuse,
+            binding_mode,
+        } = options;
+
         let mut ctx = SpendContext::new();
 
         let offer = offer.parse(&mut ctx.allocator)?;
-        let (locked_coins, _original_coins) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+        let (locked_coins, original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+
+        // If every offered coin is this wallet's own, the offer was made by this wallet.
+        let offered_p2_puzzle_hashes =
+            parse_offered_p2_puzzle_hashes(&mut ctx.allocator, &offer, &original_coin_ids)?;
+
+        if !offered_p2_puzzle_hashes.is_empty() {
+            let mut own = true;
+
+            for p2_puzzle_hash in offered_p2_puzzle_hashes {
+                if !self.db.is_p2_puzzle_hash(p2_puzzle_hash).await? {
+                    own = false;
+                    break;
+                }
+            }
+
+            if own {
+                return Err(WalletError::CannotTakeOwnOffer);
+            }
+        }
+
         let maker_amounts = locked_coins.amounts();
+        let maker_royalty_payments = parse_royalty_payments(&mut ctx.allocator, &offer)?;
+        let asserted_announcements = parse_asserted_announcements(&mut ctx.allocator, &offer)?;
 
         let mut builder = offer.take();
-        let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+        let mut requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+        if let Some(&puzzle_hash) = requested_payments.unknown.keys().next() {
+            return Err(WalletError::UnknownRequestedPayment(puzzle_hash));
+        }
         let taker_amounts = requested_payments.amounts();
+        pay_overfill(&mut requested_payments, &overfill, policy)?;
+
+        // The NFTs that the taker gives up assert their royalties when spent, so if the maker
+        // didn't pay enough, the network would reject the bundle.
+        let maker_royalties = calculate_royalties(
+            &maker_amounts,
+            &requested_payments
+                .nfts
+                .values()
+                .map(|(info, _)| NftRoyaltyInfo {
+                    launcher_id: info.launcher_id,
+                    royalty_puzzle_hash: info.royalty_puzzle_hash,
+                    royalty_ten_thousandths: info.royalty_ten_thousandths,
+                })
+                .collect::<Vec<_>>(),
+        )?;
+        maker_royalties.check_paid(&maker_royalty_payments)?;
 
-        let taker_royalties = calculate_royalties(
+        let mut taker_royalties = calculate_royalties(
             &taker_amounts,
             &locked_coins
                 .nfts
@@ -45,16 +178,24 @@ impl Wallet {
                 .collect::<Vec<_>>(),
         )?;
 
-        let total_amounts = taker_amounts.clone()
-            + taker_royalties.amounts()
-            + OfferAmounts {
+        if allow_royalty_waiver {
+            taker_royalties.retain_asserted(&asserted_announcements);
+        }
+
+        let total_amounts = taker_amounts
+            .clone()
+            .checked_add(overfill.clone())?
+            .checked_add(taker_royalties.amounts()?)?
+            .checked_add(OfferAmounts {
                 xch: fee,
                 cats: IndexMap::new(),
-            };
+            })?;
         let taker_coins = self
             .fetch_offer_coins(
                 &total_amounts,
+                Vec::new(),
                 requested_payments.nfts.keys().copied().collect(),
+                true,
             )
             .await?;
         let p2_puzzle_hash = self.p2_puzzle_hash(hardened, reuse).await?;
@@ -73,13 +214,14 @@ impl Wallet {
             .lock_assets(
                 &mut ctx,
                 OfferSpend {
-                    amounts: taker_amounts,
+                    amounts: taker_amounts.checked_add(overfill)?,
                     coins: taker_coins,
                     royalties: taker_royalties,
                     trade_prices,
                     fee,
                     change_puzzle_hash: p2_puzzle_hash,
                     extra_conditions,
+                    binding_mode,
                 },
             )
             .await?;
@@ -94,3 +236,68 @@ impl Wallet {
         })
     }
 }
+
+/// Adds a payment of the overfill of each asset to the maker, or rejects it if that isn't allowed.
+fn pay_overfill(
+    requested: &mut RequestedPayments,
+    overfill: &OfferAmounts,
+    policy: OverfillPolicy,
+) -> Result<(), WalletError> {
+    if overfill.xch > 0 {
+        add_overfill_payment(&mut requested.xch, overfill.xch, None, policy)?;
+    }
+
+    for (&asset_id, &amount) in &overfill.cats {
+        if amount == 0 {
+            continue;
+        }
+
+        let payments =
+            requested
+                .cats
+                .get_mut(&asset_id)
+                .ok_or(WalletError::OverfillNotRequested {
+                    asset_id: Some(asset_id),
+                })?;
+
+        add_overfill_payment(payments, amount, Some(asset_id), policy)?;
+    }
+
+    Ok(())
+}
+
+fn add_overfill_payment(
+    payments: &mut Vec<NotarizedPayment>,
+    amount: u64,
+    asset_id: Option<Bytes32>,
+    policy: OverfillPolicy,
+) -> Result<(), WalletError> {
+    let Some((nonce, payment)) = payments
+        .iter()
+        .find_map(|item| Some((item.nonce, item.payments.first()?)))
+    else {
+        return Err(WalletError::OverfillNotRequested { asset_id });
+    };
+
+    if policy == OverfillPolicy::Reject {
+        return Err(WalletError::OfferOverfill { asset_id });
+    }
+
+    // CATs are hinted to the inner puzzle hash, so that the maker's wallet can find them.
+    let payment = if asset_id.is_some() {
+        Payment::with_memos(
+            payment.puzzle_hash,
+            amount,
+            vec![payment.puzzle_hash.into()],
+        )
+    } else {
+        Payment::new(payment.puzzle_hash, amount)
+    };
+
+    payments.push(NotarizedPayment {
+        nonce,
+        payments: vec![payment],
+    });
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use chia::protocol::Bytes32;
+use indexmap::IndexMap;
+use sage_database::OfferStatus;
+
+use crate::WalletError;
+
+use super::Wallet;
+
+/// A summary of the offers that have been saved to the wallet.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OfferStats {
+    pub active: u32,
+    pub completed: u32,
+    pub cancelled: u32,
+    pub expired: u32,
+    pub pending_cancel: u32,
+    /// The total amount of the unspent XCH coins that are locked up in active offers.
+    pub locked_xch: u128,
+    /// The royalties on the assets offered in completed offers, which were paid by this wallet.
+    pub xch_royalties_paid: u128,
+    pub cat_royalties_paid: IndexMap<Bytes32, u128>,
+}
+
+impl Wallet {
+    /// Summarizes the saved offers. The locked XCH is the current amount of the offered coins,
+    /// rather than the amount the offer was made for, since any excess is returned as change.
+    pub async fn offer_statistics(&self) -> Result<OfferStats, WalletError> {
+        let mut stats = OfferStats::default();
+
+        for offer in self.db.get_offers().await? {
+            match offer.status {
+                OfferStatus::Active => {
+                    stats.active += 1;
+
+                    for coin_id in self.db.offer_coin_ids(offer.offer_id).await? {
+                        let Some(coin_state) = self.db.coin_state(coin_id).await? else {
+                            continue;
+                        };
+
+                        if coin_state.spent_height.is_none()
+                            && self.db.is_p2_coin(coin_id).await? == Some(true)
+                        {
+                            stats.locked_xch += coin_state.coin.amount as u128;
+                        }
+                    }
+                }
+                OfferStatus::Completed => {
+                    stats.completed += 1;
+
+                    for xch in self.db.offer_xch(offer.offer_id).await? {
+                        if !xch.requested {
+                            stats.xch_royalties_paid += xch.royalty as u128;
+                        }
+                    }
+
+                    for cat in self.db.offer_cats(offer.offer_id).await? {
+                        if !cat.requested {
+                            *stats.cat_royalties_paid.entry(cat.asset_id).or_insert(0) +=
+                                cat.royalty as u128;
+                        }
+                    }
+                }
+                OfferStatus::Cancelled => stats.cancelled += 1,
+                OfferStatus::Expired => stats.expired += 1,
+                OfferStatus::PendingCancel => stats.pending_cancel += 1,
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sage_database::{OfferCatRow, OfferRow, OfferXchRow};
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    fn offer_row(offer_id: Bytes32, status: OfferStatus) -> OfferRow {
+        OfferRow {
+            offer_id,
+            encoded_offer: String::new(),
+            expiration_height: None,
+            expiration_timestamp: None,
+            fee: 0,
+            status,
+            inserted_timestamp: 0,
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_offer_statistics() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        assert_eq!(test.wallet.offer_statistics().await?, OfferStats::default());
+
+        let coin = test.wallet.db.spendable_coins().await?[0];
+        let asset_id = Bytes32::new([1; 32]);
+
+        let active_id = Bytes32::new([2; 32]);
+        let completed_id = Bytes32::new([3; 32]);
+
+        let mut tx = test.wallet.db.tx().await?;
+
+        tx.insert_offer(offer_row(active_id, OfferStatus::Active))
+            .await?;
+        tx.insert_offered_coin(active_id, coin.coin_id()).await?;
+
+        tx.insert_offer(offer_row(completed_id, OfferStatus::Completed))
+            .await?;
+        tx.insert_offer_xch(OfferXchRow {
+            offer_id: completed_id,
+            requested: false,
+            amount: 500,
+            royalty: 15,
+        })
+        .await?;
+        tx.insert_offer_cat(OfferCatRow {
+            offer_id: completed_id,
+            requested: false,
+            asset_id,
+            amount: 100,
+            royalty: 3,
+            name: None,
+            ticker: None,
+            icon: None,
+        })
+        .await?;
+
+        // Royalties on the requested side are paid by the taker.
+        tx.insert_offer_xch(OfferXchRow {
+            offer_id: completed_id,
+            requested: true,
+            amount: 200,
+            royalty: 6,
+        })
+        .await?;
+
+        tx.insert_offer(offer_row(Bytes32::new([4; 32]), OfferStatus::Expired))
+            .await?;
+
+        tx.commit().await?;
+
+        let stats = test.wallet.offer_statistics().await?;
+
+        assert_eq!(stats.active, 1);
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.cancelled, 0);
+        assert_eq!(stats.expired, 1);
+        assert_eq!(stats.locked_xch, 1000);
+        assert_eq!(stats.xch_royalties_paid, 15);
+        assert_eq!(stats.cat_royalties_paid, IndexMap::from([(asset_id, 3)]));
+
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use chia::protocol::{Bytes, Bytes32, CoinSpend};
-use chia_wallet_sdk::{Conditions, Memos, SpendContext};
+use chia_wallet_sdk::{Condition, Conditions, Memos, SpendContext};
 
 use crate::WalletError;
 
@@ -16,6 +16,42 @@ impl Wallet {
         hardened: bool,
         reuse: bool,
     ) -> Result<Vec<CoinSpend>, WalletError> {
+        self.send_xch_with_conditions(
+            puzzle_hash,
+            amount,
+            fee,
+            memos,
+            Conditions::new(),
+            hardened,
+            reuse,
+        )
+        .await
+    }
+
+    /// Sends XCH like [`Wallet::send_xch`], with extra conditions added to the spend of the first coin,
+    /// such as announcements that bind the send to spends outside of this transaction.
+    /// The payment, fee, and change are computed automatically, so the extra conditions can't create
+    /// coins or reserve fees of their own.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_xch_with_conditions(
+        &self,
+        puzzle_hash: Bytes32,
+        amount: u64,
+        fee: u64,
+        memos: Vec<Bytes>,
+        extra_conditions: Conditions,
+        hardened: bool,
+        reuse: bool,
+    ) -> Result<Vec<CoinSpend>, WalletError> {
+        if extra_conditions.as_ref().iter().any(|condition| {
+            matches!(
+                condition,
+                Condition::CreateCoin(..) | Condition::ReserveFee(..)
+            )
+        }) {
+            return Err(WalletError::ConflictingConditions);
+        }
+
         let total = amount as u128 + fee as u128;
         let coins = self.select_p2_coins(total).await?;
         let selected: u128 = coins.iter().map(|coin| coin.amount as u128).sum();
@@ -42,6 +78,8 @@ impl Wallet {
             conditions = conditions.create_coin(change_puzzle_hash, change, None);
         }
 
+        conditions = conditions.extend(extra_conditions);
+
         self.spend_p2_coins(&mut ctx, coins, conditions).await?;
 
         Ok(ctx.take())
@@ -50,9 +88,13 @@ impl Wallet {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use chia::protocol::{Bytes, SpendBundle};
+    use chia_wallet_sdk::{announcement_id, Conditions};
     use test_log::test;
 
-    use crate::TestWallet;
+    use crate::{TestWallet, WalletError};
 
     #[test(tokio::test)]
     async fn test_send_xch() -> anyhow::Result<()> {
@@ -93,4 +135,90 @@ mod tests {
 
         Ok(())
     }
+
+    #[test(tokio::test)]
+    async fn test_send_xch_bound_to_external_announcement() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(500).await?;
+
+        // Bob's coin announces a message, which is external to Alice's transaction.
+        let bob_coin = bob.wallet.db.spendable_coins().await?[0];
+        let message = Bytes::new(b"bound".to_vec());
+
+        let bob_coin_spends = bob
+            .wallet
+            .spend(
+                vec![bob_coin.coin_id()],
+                HashMap::from([(
+                    bob_coin.coin_id(),
+                    Conditions::new()
+                        .create_coin(bob.puzzle_hash, 500, None)
+                        .create_coin_announcement(message.clone()),
+                )]),
+                0,
+            )
+            .await?;
+        let bob_bundle = bob
+            .wallet
+            .sign_transaction(bob_coin_spends, &bob.agg_sig, bob.master_sk.clone(), false)
+            .await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_xch_with_conditions(
+                bob.puzzle_hash,
+                250,
+                0,
+                Vec::new(),
+                Conditions::new()
+                    .assert_coin_announcement(announcement_id(bob_coin.coin_id(), message)),
+                false,
+                true,
+            )
+            .await?;
+        let alice_bundle = alice
+            .wallet
+            .sign_transaction(coin_spends, &alice.agg_sig, alice.master_sk.clone(), false)
+            .await?;
+
+        // The send can't be included without the spend that makes the announcement.
+        let ack = alice.peer.send_transaction(alice_bundle.clone()).await?;
+        assert_ne!(ack.status, 1);
+
+        alice
+            .push_bundle(SpendBundle::aggregate(&[alice_bundle, bob_bundle]))
+            .await?;
+        alice.wait_for_coins().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 750);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_send_xch_conflicting_conditions() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        for conditions in [
+            Conditions::new().create_coin(test.puzzle_hash, 100, None),
+            Conditions::new().reserve_fee(100),
+        ] {
+            let result = test
+                .wallet
+                .send_xch_with_conditions(
+                    test.puzzle_hash,
+                    250,
+                    0,
+                    Vec::new(),
+                    conditions,
+                    false,
+                    true,
+                )
+                .await;
+
+            assert!(matches!(result, Err(WalletError::ConflictingConditions)));
+        }
+
+        Ok(())
+    }
 }
@@ -16,7 +16,7 @@ impl Wallet {
     ) -> Result<(), WalletError> {
         for (coin, conditions) in coins {
             // We need to figure out what the synthetic public key is for this p2 coin.
-            let synthetic_key = self.db.synthetic_key(coin.puzzle_hash).await?;
+            let synthetic_key = self.synthetic_key(coin.puzzle_hash).await?;
 
             // Create the standard p2 layer for the key.
             let p2 = StandardLayer::new(synthetic_key);
@@ -0,0 +1,330 @@
+use chia::{
+    clvm_traits::{FromClvm, ToClvm},
+    protocol::{Bytes32, Coin, CoinSpend},
+    puzzles::{
+        offer::{SETTLEMENT_PAYMENTS_PUZZLE_HASH, SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1},
+        singleton::SINGLETON_LAUNCHER_PUZZLE_HASH,
+    },
+};
+use chia_wallet_sdk::{run_puzzle, Condition, Puzzle};
+use clvmr::Allocator;
+use tracing::debug;
+
+use crate::{ChildKind, WalletError};
+
+use super::Wallet;
+
+/// The kind of asset that a puzzle hash locks up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuzzleKind {
+    /// A standard puzzle derived by this wallet.
+    Standard,
+    Cat {
+        asset_id: Bytes32,
+        p2_puzzle_hash: Bytes32,
+    },
+    Nft {
+        launcher_id: Bytes32,
+        p2_puzzle_hash: Bytes32,
+    },
+    Did {
+        launcher_id: Bytes32,
+        p2_puzzle_hash: Bytes32,
+    },
+    Clawback {
+        sender_puzzle_hash: Bytes32,
+        recipient_puzzle_hash: Bytes32,
+    },
+    /// Either version of the settlement payments puzzle used by offers.
+    Settlement,
+    Launcher,
+    Unknown,
+}
+
+impl Wallet {
+    /// Determines the kind of asset that a puzzle hash locks up. Outer puzzles such as CATs, NFTs, and DIDs
+    /// can't be recognized from the hash alone, so they are parsed from the spend of the parent coin that
+    /// created a coin with the puzzle hash, the same way as during sync. Eve coins aren't recognized,
+    /// and standard puzzles are only recognized if they belong to this wallet. A parent spend that
+    /// can't be parsed or run is treated the same as an unknown puzzle.
+    pub async fn classify_puzzle(
+        &self,
+        puzzle_hash: Bytes32,
+        parent_spend: Option<&CoinSpend>,
+    ) -> Result<PuzzleKind, WalletError> {
+        if puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH.into()
+            || puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1.into()
+        {
+            return Ok(PuzzleKind::Settlement);
+        }
+
+        if puzzle_hash == SINGLETON_LAUNCHER_PUZZLE_HASH.into() {
+            return Ok(PuzzleKind::Launcher);
+        }
+
+        if let Some(parent_spend) = parent_spend {
+            let kind = classify_child(puzzle_hash, parent_spend).unwrap_or_else(|error| {
+                debug!("Failed to parse the parent spend of {puzzle_hash}: {error}");
+                PuzzleKind::Unknown
+            });
+
+            if kind != PuzzleKind::Unknown {
+                return Ok(kind);
+            }
+        }
+
+        if self.db.is_p2_puzzle_hash(puzzle_hash).await? {
+            return Ok(PuzzleKind::Standard);
+        }
+
+        Ok(PuzzleKind::Unknown)
+    }
+}
+
+/// Parses the child of the parent spend with the given puzzle hash.
+fn classify_child(
+    puzzle_hash: Bytes32,
+    parent_spend: &CoinSpend,
+) -> Result<PuzzleKind, WalletError> {
+    let mut allocator = Allocator::new();
+
+    let parent_puzzle_ptr = parent_spend.puzzle_reveal.to_clvm(&mut allocator)?;
+    let parent_puzzle = Puzzle::parse(&allocator, parent_puzzle_ptr);
+    let parent_solution = parent_spend.solution.to_clvm(&mut allocator)?;
+
+    let output = run_puzzle(&mut allocator, parent_puzzle.ptr(), parent_solution)?;
+    let conditions = Vec::<Condition>::from_clvm(&allocator, output)?;
+
+    let Some(amount) = conditions
+        .iter()
+        .filter_map(|condition| condition.clone().into_create_coin())
+        .find(|create_coin| create_coin.puzzle_hash == puzzle_hash)
+        .map(|create_coin| create_coin.amount)
+    else {
+        return Ok(PuzzleKind::Unknown);
+    };
+
+    let coin = Coin::new(parent_spend.coin.coin_id(), puzzle_hash, amount);
+
+    let kind = ChildKind::from_parent_cached(
+        &mut allocator,
+        parent_spend.coin,
+        parent_puzzle,
+        parent_solution,
+        conditions,
+        coin,
+    )?;
+
+    Ok(match kind {
+        ChildKind::Cat {
+            asset_id,
+            p2_puzzle_hash,
+            ..
+        } => PuzzleKind::Cat {
+            asset_id,
+            p2_puzzle_hash,
+        },
+        ChildKind::Nft { info, .. } => PuzzleKind::Nft {
+            launcher_id: info.launcher_id,
+            p2_puzzle_hash: info.p2_puzzle_hash,
+        },
+        ChildKind::Did { info, .. } => PuzzleKind::Did {
+            launcher_id: info.launcher_id,
+            p2_puzzle_hash: info.p2_puzzle_hash,
+        },
+        ChildKind::Clawback { clawback } => PuzzleKind::Clawback {
+            sender_puzzle_hash: clawback.sender_puzzle_hash,
+            recipient_puzzle_hash: clawback.recipient_puzzle_hash,
+        },
+        ChildKind::Settlement { .. } => PuzzleKind::Settlement,
+        ChildKind::Launcher => PuzzleKind::Launcher,
+        ChildKind::Unknown { .. } => PuzzleKind::Unknown,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use chia::{clvm_utils::ToTreeHash, protocol::Program, puzzles::nft::NftMetadata};
+    use chia_wallet_sdk::Clawback;
+    use test_log::test;
+
+    use crate::{TestWallet, WalletNftMint};
+
+    use super::*;
+
+    /// Finds the spend of the coin's parent.
+    fn parent_spend(coin_spends: &[CoinSpend], coin: Coin) -> &CoinSpend {
+        coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin.coin_id() == coin.parent_coin_info)
+            .expect("missing parent spend")
+    }
+
+    #[test(tokio::test)]
+    async fn test_classify_puzzle() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1002).await?;
+
+        assert_eq!(
+            test.wallet.classify_puzzle(test.puzzle_hash, None).await?,
+            PuzzleKind::Standard
+        );
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), None)
+                .await?,
+            PuzzleKind::Settlement
+        );
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(Bytes32::new([1; 32]), None)
+                .await?,
+            PuzzleKind::Unknown
+        );
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(SINGLETON_LAUNCHER_PUZZLE_HASH.into(), None)
+                .await?,
+            PuzzleKind::Launcher
+        );
+
+        // A parent spend that can't be parsed doesn't make the puzzle hash recognizable.
+        let malformed = CoinSpend::new(
+            Coin::new(Bytes32::default(), Bytes32::default(), 1),
+            Program::from(vec![0xff]),
+            Program::from(vec![0x80]),
+        );
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(Bytes32::new([1; 32]), Some(&malformed))
+                .await?,
+            PuzzleKind::Unknown
+        );
+
+        // Clawback, which is only built and not sent, so that the coins can still be spent below.
+        let recipient_puzzle_hash = Bytes32::new([2; 32]);
+        let coin_spends = test
+            .wallet
+            .send_with_clawback(recipient_puzzle_hash, 1, 1, 0, false, true)
+            .await?;
+
+        let clawback = Clawback {
+            timelock: NonZeroU64::MIN,
+            sender_puzzle_hash: test.puzzle_hash,
+            recipient_puzzle_hash,
+        };
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(
+                    clawback.to_layer().tree_hash().into(),
+                    Some(&coin_spends[0])
+                )
+                .await?,
+            PuzzleKind::Clawback {
+                sender_puzzle_hash: test.puzzle_hash,
+                recipient_puzzle_hash,
+            }
+        );
+
+        // CAT
+        let (coin_spends, asset_id) = test.wallet.issue_cat(1000, 0, None, false, true).await?;
+        test.transact(coin_spends.clone()).await?;
+        test.wait_for_coins().await;
+
+        let cat = test
+            .wallet
+            .db
+            .spendable_cat_coins(asset_id)
+            .await?
+            .remove(0);
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(
+                    cat.coin.puzzle_hash,
+                    Some(parent_spend(&coin_spends, cat.coin))
+                )
+                .await?,
+            PuzzleKind::Cat {
+                asset_id,
+                p2_puzzle_hash: cat.p2_puzzle_hash,
+            }
+        );
+
+        // Without the parent spend, the CAT can't be recognized.
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(cat.coin.puzzle_hash, None)
+                .await?,
+            PuzzleKind::Unknown
+        );
+
+        // DID
+        let (coin_spends, did) = test.wallet.create_did(0, false, true).await?;
+        test.transact(coin_spends.clone()).await?;
+        test.wait_for_coins().await;
+
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(
+                    did.coin.puzzle_hash,
+                    Some(parent_spend(&coin_spends, did.coin))
+                )
+                .await?,
+            PuzzleKind::Did {
+                launcher_id: did.info.launcher_id,
+                p2_puzzle_hash: did.info.p2_puzzle_hash,
+            }
+        );
+
+        // Launcher
+        let launcher = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin.coin_id() == did.info.launcher_id)
+            .expect("missing launcher spend")
+            .coin;
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(
+                    launcher.puzzle_hash,
+                    Some(parent_spend(&coin_spends, launcher))
+                )
+                .await?,
+            PuzzleKind::Launcher
+        );
+
+        // NFT
+        let (coin_spends, mut nfts, _did) = test
+            .wallet
+            .bulk_mint_nfts(
+                0,
+                did.info.launcher_id,
+                vec![WalletNftMint {
+                    metadata: NftMetadata::default(),
+                    royalty_puzzle_hash: None,
+                    royalty_ten_thousandths: 0,
+                }],
+                false,
+                true,
+            )
+            .await?;
+        test.transact(coin_spends.clone()).await?;
+        test.wait_for_coins().await;
+
+        let nft = nfts.remove(0);
+        assert_eq!(
+            test.wallet
+                .classify_puzzle(
+                    nft.coin.puzzle_hash,
+                    Some(parent_spend(&coin_spends, nft.coin))
+                )
+                .await?,
+            PuzzleKind::Nft {
+                launcher_id: nft.info.launcher_id,
+                p2_puzzle_hash: nft.info.p2_puzzle_hash,
+            }
+        );
+
+        Ok(())
+    }
+}
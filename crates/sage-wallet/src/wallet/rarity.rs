@@ -0,0 +1,165 @@
+use std::collections::{BTreeSet, HashMap};
+
+use chia::protocol::Bytes32;
+
+use crate::{nft_traits, onchain_metadata_json, WalletError};
+
+use super::Wallet;
+
+/// The value given to a trait type that an NFT doesn't have, so that lacking a trait
+/// is counted towards rarity like any other value.
+const MISSING_TRAIT: &str = "none";
+
+impl Wallet {
+    /// Computes a rarity score for each NFT in the collection that the wallet knows about, from the
+    /// traits in its off-chain metadata, or its on-chain metadata if it has no off-chain metadata.
+    /// NFTs whose metadata hasn't been fetched have no traits.
+    /// The scores are sorted from rarest to most common.
+    pub async fn compute_rarity(
+        &self,
+        collection_id: Bytes32,
+    ) -> Result<Vec<(Bytes32, f64)>, WalletError> {
+        let mut nfts = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let rows = self
+                .db
+                .collection_nfts_named(collection_id, 100, offset)
+                .await?;
+
+            if rows.is_empty() {
+                break;
+            }
+
+            offset += rows.len() as u32;
+
+            for row in rows {
+                let blob = if let Some(hash) = row.metadata_hash {
+                    self.db.fetch_nft_data(hash).await?.map(|data| data.blob)
+                } else if let Some(nft) = self.db.nft(row.launcher_id).await? {
+                    onchain_metadata_json(&nft.info.metadata)
+                } else {
+                    None
+                };
+
+                let traits = nft_traits(blob.as_deref());
+
+                nfts.push((row.launcher_id, traits));
+            }
+        }
+
+        Ok(rarity_scores(nfts))
+    }
+}
+
+/// Scores each NFT as the sum of the inverse frequencies of its trait values across all of the NFTs.
+fn rarity_scores(nfts: Vec<(Bytes32, Vec<(String, String)>)>) -> Vec<(Bytes32, f64)> {
+    let trait_types: BTreeSet<String> = nfts
+        .iter()
+        .flat_map(|(_launcher_id, traits)| traits.iter().map(|(trait_type, _)| trait_type.clone()))
+        .collect();
+
+    let nfts: Vec<(Bytes32, Vec<(String, String)>)> = nfts
+        .into_iter()
+        .map(|(launcher_id, traits)| {
+            let traits = trait_types
+                .iter()
+                .map(|trait_type| {
+                    let value = traits
+                        .iter()
+                        .find(|(key, _)| key == trait_type)
+                        .map_or_else(|| MISSING_TRAIT.to_string(), |(_, value)| value.clone());
+
+                    (trait_type.clone(), value)
+                })
+                .collect();
+
+            (launcher_id, traits)
+        })
+        .collect();
+
+    let mut counts: HashMap<&(String, String), usize> = HashMap::new();
+
+    for (_launcher_id, traits) in &nfts {
+        for item in traits {
+            *counts.entry(item).or_insert(0) += 1;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let total = nfts.len() as f64;
+
+    #[allow(clippy::cast_precision_loss)]
+    let mut scores: Vec<(Bytes32, f64)> = nfts
+        .iter()
+        .map(|(launcher_id, traits)| {
+            let score = traits.iter().map(|item| total / counts[item] as f64).sum();
+
+            (*launcher_id, score)
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nft(id: u8, traits: &[(&str, &str)]) -> (Bytes32, Vec<(String, String)>) {
+        (
+            Bytes32::new([id; 32]),
+            traits
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_rarity_scores() {
+        let scores = rarity_scores(vec![
+            nft(1, &[("Background", "Blue"), ("Hat", "Cap")]),
+            nft(2, &[("Background", "Blue"), ("Hat", "Cap")]),
+            nft(3, &[("Background", "Blue")]),
+            nft(4, &[("Background", "Gold"), ("Hat", "Cap")]),
+        ]);
+
+        // Gold is unique (4) and Cap is shared by three (4/3).
+        // Missing the hat is unique (4) and Blue is shared by three (4/3).
+        // The others only have common traits (4/3 + 4/3).
+        assert_eq!(
+            scores,
+            vec![
+                (Bytes32::new([3; 32]), 4.0 + 4.0 / 3.0),
+                (Bytes32::new([4; 32]), 4.0 + 4.0 / 3.0),
+                (Bytes32::new([1; 32]), 4.0 / 3.0 + 4.0 / 3.0),
+                (Bytes32::new([2; 32]), 4.0 / 3.0 + 4.0 / 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nft_traits() {
+        let blob = br#"{
+            "attributes": [
+                { "trait_type": "Background", "value": "Blue" },
+                { "trait_type": "Level", "value": 7 },
+                { "trait_type": "Nested", "value": { "a": 1 } },
+                { "value": "Untyped" }
+            ]
+        }"#;
+
+        assert_eq!(
+            nft_traits(Some(blob)),
+            vec![
+                ("Background".to_string(), "Blue".to_string()),
+                ("Level".to_string(), "7".to_string()),
+            ]
+        );
+        assert!(nft_traits(Some(b"not json")).is_empty());
+    }
+}
@@ -1,11 +1,8 @@
 use std::collections::HashMap;
 
 use chia::{
-    bls::{
-        master_to_wallet_unhardened_intermediate, sign, DerivableKey, PublicKey, SecretKey,
-        Signature,
-    },
-    protocol::{CoinSpend, SpendBundle},
+    bls::{aggregate_verify, sign, DerivableKey, PublicKey, SecretKey, Signature},
+    protocol::{Bytes32, CoinSpend, SpendBundle},
     puzzles::DeriveSynthetic,
 };
 use chia_wallet_sdk::{AggSigConstants, Offer, RequiredSignature};
@@ -15,6 +12,15 @@ use crate::WalletError;
 
 use super::{UnsignedMakeOffer, UnsignedTakeOffer, Wallet};
 
+/// A message that's required to be signed by a coin spend, which couldn't be verified against
+/// the aggregated signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnverifiedSignature {
+    pub coin_id: Bytes32,
+    pub public_key: PublicKey,
+    pub message: Vec<u8>,
+}
+
 impl Wallet {
     pub async fn sign_make_offer(
         &self,
@@ -26,13 +32,17 @@ impl Wallet {
             mut ctx,
             coin_spends,
             builder,
+            ..
         } = info;
 
         let spend_bundle = self
             .sign_transaction(coin_spends, agg_sig_constants, master_sk, false)
             .await?;
 
-        Ok(builder.bundle(&mut ctx, spend_bundle)?)
+        let offer = builder.bundle(&mut ctx, spend_bundle)?;
+        self.reuse_spend_context(ctx);
+
+        Ok(offer)
     }
 
     pub async fn sign_take_offer(
@@ -67,13 +77,23 @@ impl Wallet {
         )?;
 
         let mut indices = HashMap::new();
+        let mut backfilled = false;
 
         for required in &required_signatures {
             let RequiredSignature::Bls(required) = required else {
                 return Err(WalletError::SecpNotSupported);
             };
             let pk = required.public_key;
-            let Some(index) = self.db.synthetic_key_index(pk).await? else {
+            let mut index = self.db.synthetic_key_index(pk).await?;
+
+            // The key may belong to a derivation that's missing from the database.
+            if index.is_none() && !backfilled {
+                self.backfill_derivations().await?;
+                backfilled = true;
+                index = self.db.synthetic_key_index(pk).await?;
+            }
+
+            let Some(index) = index else {
                 if partial {
                     continue;
                 }
@@ -82,7 +102,7 @@ impl Wallet {
             indices.insert(pk, index);
         }
 
-        let intermediate_sk = master_to_wallet_unhardened_intermediate(&master_sk);
+        let intermediate_sk = self.derivation_path.intermediate_sk(&master_sk);
 
         let secret_keys: HashMap<PublicKey, SecretKey> = indices
             .iter()
@@ -102,6 +122,163 @@ impl Wallet {
             aggregated_signature += &sign(&sk, required.message());
         }
 
-        Ok(SpendBundle::new(coin_spends, aggregated_signature))
+        let spend_bundle = SpendBundle::new(coin_spends, aggregated_signature);
+
+        if !partial {
+            self.verify_bundle(&spend_bundle).await?;
+        }
+
+        Ok(spend_bundle)
+    }
+
+    /// Checks that the aggregated signature of the spend bundle is valid for all of the
+    /// messages that its coin spends require to be signed on the wallet's network, to catch
+    /// signing bugs before broadcast.
+    ///
+    /// An aggregated signature can't be attributed to individual messages, so if verification
+    /// fails, the error lists the messages that require a key which isn't in this wallet, since
+    /// the wallet can't have signed them. If every key is known, it lists all of the messages.
+    pub async fn verify_bundle(&self, spend_bundle: &SpendBundle) -> Result<(), WalletError> {
+        let mut allocator = Allocator::new();
+        let agg_sig_constants = self.network.agg_sig_constants();
+        let mut required_signatures = Vec::new();
+
+        for coin_spend in &spend_bundle.coin_spends {
+            for required in RequiredSignature::from_coin_spends(
+                &mut allocator,
+                &[coin_spend.clone()],
+                &agg_sig_constants,
+            )? {
+                let RequiredSignature::Bls(required) = required else {
+                    return Err(WalletError::SecpNotSupported);
+                };
+                required_signatures.push(UnverifiedSignature {
+                    coin_id: coin_spend.coin.coin_id(),
+                    public_key: required.public_key,
+                    message: required.message(),
+                });
+            }
+        }
+
+        if aggregate_verify(
+            &spend_bundle.aggregated_signature,
+            required_signatures
+                .iter()
+                .map(|required| (required.public_key, required.message.as_slice())),
+        ) {
+            return Ok(());
+        }
+
+        let mut unknown = Vec::new();
+
+        for required in &required_signatures {
+            if self
+                .db
+                .synthetic_key_index(required.public_key)
+                .await?
+                .is_none()
+            {
+                unknown.push(required.clone());
+            }
+        }
+
+        Err(WalletError::InvalidSignature {
+            unverified: if unknown.is_empty() {
+                required_signatures
+            } else {
+                unknown
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::{
+        bls::Signature,
+        protocol::{Bytes32, Coin, SpendBundle},
+    };
+    use chia_wallet_sdk::{test_secret_key, Conditions, SpendContext, StandardLayer};
+    use test_log::test;
+
+    use crate::{TestWallet, WalletError};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_verify_tampered_signature() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coin_spends = test
+            .wallet
+            .send_xch(test.puzzle_hash, 1000, 0, Vec::new(), false, true)
+            .await?;
+
+        let spend_bundle = test
+            .wallet
+            .sign_transaction(coin_spends, &test.agg_sig, test.master_sk.clone(), false)
+            .await?;
+
+        test.wallet.verify_bundle(&spend_bundle).await?;
+
+        let required = RequiredSignature::from_coin_spends(
+            &mut Allocator::new(),
+            &spend_bundle.coin_spends,
+            &test.agg_sig,
+        )?;
+        let coin_id = spend_bundle.coin_spends[0].coin.coin_id();
+
+        let tampered = SpendBundle::new(spend_bundle.coin_spends, Signature::default());
+
+        // Every key belongs to the wallet, so each of the messages that were signed is reported.
+        let Err(WalletError::InvalidSignature { unverified }) =
+            test.wallet.verify_bundle(&tampered).await
+        else {
+            panic!("expected an invalid signature");
+        };
+
+        let expected: Vec<UnverifiedSignature> = required
+            .into_iter()
+            .map(|required| {
+                let RequiredSignature::Bls(required) = required else {
+                    panic!("expected a BLS signature");
+                };
+                UnverifiedSignature {
+                    coin_id,
+                    public_key: required.public_key,
+                    message: required.message(),
+                }
+            })
+            .collect();
+
+        assert!(!expected.is_empty());
+        assert_eq!(unverified, expected);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_verify_unknown_key() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let coin = Coin::new(Bytes32::default(), test.puzzle_hash, 1);
+        let pk = test_secret_key()?.public_key();
+
+        let mut ctx = SpendContext::new();
+        StandardLayer::new(pk).spend(&mut ctx, coin, Conditions::new())?;
+
+        let spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+
+        let Err(WalletError::InvalidSignature { unverified }) =
+            test.wallet.verify_bundle(&spend_bundle).await
+        else {
+            panic!("expected an invalid signature");
+        };
+
+        assert_eq!(unverified.len(), 1);
+        assert_eq!(unverified[0].coin_id, coin.coin_id());
+        assert_eq!(unverified[0].public_key, pk);
+
+        Ok(())
     }
 }
@@ -0,0 +1,183 @@
+use std::sync::Mutex;
+
+use chia_wallet_sdk::SpendContext;
+
+use super::Wallet;
+
+/// A context is discarded instead of being reused after this many builds, since nothing that's
+/// allocated in it is ever freed.
+pub const MAX_SPEND_CONTEXT_REUSES: usize = 64;
+
+/// A spend context which is kept between builds, so that the puzzles it has already deserialized,
+/// such as the settlement and CAT puzzles, don't have to be loaded again by the next offer or mint.
+///
+/// Puzzle pointers are only valid in the allocator they were created in, so they're never shared
+/// between contexts. Each fresh context starts a new generation, which invalidates every pointer
+/// cached by the previous one.
+#[derive(Debug, Default)]
+pub struct SpendContextCache(Mutex<CachedContext>);
+
+#[derive(Debug, Default)]
+struct CachedContext {
+    generation: u64,
+    uses: usize,
+    ctx: Option<SpendContext>,
+}
+
+impl SpendContextCache {
+    /// The number of fresh contexts that have been created.
+    pub fn generation(&self) -> u64 {
+        self.0.lock().expect("poisoned").generation
+    }
+
+    /// Discards the cached context, so that the next build starts from a fresh one.
+    pub fn invalidate(&self) {
+        self.0.lock().expect("poisoned").ctx = None;
+    }
+}
+
+impl Wallet {
+    /// A spend context to build a transaction or offer with. This is the context that was last
+    /// returned with [`Wallet::reuse_spend_context`] if there is one, or a fresh context otherwise.
+    pub fn spend_context(&self) -> SpendContext {
+        let mut cached = self.spend_context_cache.0.lock().expect("poisoned");
+
+        if let Some(ctx) = cached.ctx.take() {
+            return ctx;
+        }
+
+        cached.generation += 1;
+        cached.uses = 0;
+
+        SpendContext::new()
+    }
+
+    /// Keeps a spend context for the next build once its coin spends have been taken. Nothing that
+    /// was allocated by the previous build may be used after this, other than the cached puzzles.
+    pub fn reuse_spend_context(&self, mut ctx: SpendContext) {
+        let mut cached = self.spend_context_cache.0.lock().expect("poisoned");
+
+        cached.uses += 1;
+
+        if cached.uses >= MAX_SPEND_CONTEXT_REUSES {
+            return;
+        }
+
+        // Any spends left over belong to the previous build.
+        ctx.take();
+
+        cached.ctx = Some(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia::protocol::Bytes32;
+    use indexmap::indexmap;
+    use test_log::test;
+
+    use crate::{MakerSide, TakerSide, TestWallet};
+
+    use super::*;
+
+    async fn build_offer(test: &TestWallet) -> anyhow::Result<Vec<u8>> {
+        let offer = test
+            .wallet
+            .make_offer(
+                MakerSide {
+                    xch: 750,
+                    ..Default::default()
+                },
+                TakerSide {
+                    cats: indexmap! { Bytes32::new([1; 32]) => 1000 },
+                    ..Default::default()
+                },
+                None,
+                None,
+                false,
+                true,
+            )
+            .await?;
+        let offer = test
+            .wallet
+            .sign_make_offer(offer, &test.agg_sig, test.master_sk.clone())
+            .await?;
+
+        Ok(offer.to_bytes()?)
+    }
+
+    #[test(tokio::test)]
+    async fn test_spend_context_reuse() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let mut expected = None;
+
+        for _ in 0..10 {
+            test.wallet.spend_context_cache.invalidate();
+            let offer = build_offer(&test).await?;
+
+            // The same offer is built regardless of whether the context is fresh.
+            assert_eq!(*expected.get_or_insert(offer.clone()), offer);
+        }
+
+        let generation = test.wallet.spend_context_cache.generation();
+
+        for _ in 0..10 {
+            let offer = build_offer(&test).await?;
+
+            assert_eq!(expected.as_ref(), Some(&offer));
+        }
+
+        // None of the builds since the last invalidation needed a fresh context.
+        assert_eq!(test.wallet.spend_context_cache.generation(), generation);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_spend_context_reuses_puzzles() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let mut ctx = test.wallet.spend_context();
+        let settlement = ctx.settlement_payments_puzzle()?;
+        let launcher = ctx.singleton_launcher()?;
+        let updater = ctx.nft_metadata_updater()?;
+        test.wallet.reuse_spend_context(ctx);
+
+        // The puzzles are found in the reused context, without deserializing them again.
+        let mut ctx = test.wallet.spend_context();
+        let size = (ctx.allocator.atom_count(), ctx.allocator.pair_count());
+
+        assert_eq!(ctx.settlement_payments_puzzle()?, settlement);
+        assert_eq!(ctx.singleton_launcher()?, launcher);
+        assert_eq!(ctx.nft_metadata_updater()?, updater);
+        assert_eq!(
+            (ctx.allocator.atom_count(), ctx.allocator.pair_count()),
+            size
+        );
+
+        // Whereas a fresh context has to allocate them.
+        let mut ctx = SpendContext::new();
+        let size = (ctx.allocator.atom_count(), ctx.allocator.pair_count());
+        ctx.settlement_payments_puzzle()?;
+        assert!(ctx.allocator.pair_count() > size.1);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_spend_context_discarded_after_max_reuses() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        for _ in 0..MAX_SPEND_CONTEXT_REUSES {
+            let ctx = test.wallet.spend_context();
+            test.wallet.reuse_spend_context(ctx);
+        }
+
+        let generation = test.wallet.spend_context_cache.generation();
+        let _ctx = test.wallet.spend_context();
+        assert_eq!(test.wallet.spend_context_cache.generation(), generation + 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+use std::mem;
+
+use chia::protocol::{Bytes32, Coin, CoinSpend};
+use chia_wallet_sdk::{Cat, Conditions, SpendContext, StandardLayer};
+
+use crate::{NftExt, WalletError};
+
+use super::Wallet;
+
+/// Packs spends into as few transactions as the cost limit allows, based on the estimated cost
+/// of each group of spends that's added.
+#[derive(Debug)]
+struct SweepBundles {
+    bundles: Vec<Vec<CoinSpend>>,
+    current: Vec<CoinSpend>,
+    max_cost: u64,
+    remaining_cost: u64,
+}
+
+impl SweepBundles {
+    fn new(max_cost: u64) -> Self {
+        Self {
+            bundles: Vec::new(),
+            current: Vec::new(),
+            max_cost,
+            remaining_cost: max_cost,
+        }
+    }
+
+    /// Returns whether spends of the given cost fit in the current transaction,
+    /// starting a new one if they would fit on their own but the current transaction is too full.
+    fn fits(&mut self, cost: u64) -> bool {
+        if cost > self.remaining_cost && !self.current.is_empty() {
+            self.flush();
+        }
+
+        cost <= self.remaining_cost
+    }
+
+    /// Adds the spends to the current transaction. Spends that exceed the cost limit on their own
+    /// should only be added to an empty transaction, so that they're given a transaction to themselves.
+    fn push(&mut self, coin_spends: Vec<CoinSpend>, cost: u64) {
+        self.current.extend(coin_spends);
+        self.remaining_cost = self.remaining_cost.saturating_sub(cost);
+    }
+
+    fn flush(&mut self) {
+        let coin_spends = mem::take(&mut self.current);
+
+        if !coin_spends.is_empty() {
+            self.bundles.push(coin_spends);
+        }
+
+        self.remaining_cost = self.max_cost;
+    }
+
+    fn finish(mut self) -> Vec<Vec<CoinSpend>> {
+        self.flush();
+        self.bundles
+    }
+}
+
+impl Wallet {
+    /// Sends all spendable XCH to the given puzzle hash, minus the fee, and optionally all CATs and NFTs.
+    /// The spends are split across multiple transactions if their estimated cost doesn't fit within
+    /// `max_bundle_cost`, in which case the fee is only paid by the first one, which spends at least
+    /// enough of the largest coins to cover it. Each transaction can be submitted on its own.
+    pub async fn sweep_all(
+        &self,
+        puzzle_hash: Bytes32,
+        fee: u64,
+        include_cats: bool,
+        include_nfts: bool,
+    ) -> Result<Vec<Vec<CoinSpend>>, WalletError> {
+        let mut coins = self.db.spendable_coins().await?;
+        let total: u128 = coins.iter().map(|coin| coin.amount as u128).sum();
+
+        if fee as u128 > total {
+            return Err(WalletError::InsufficientFunds);
+        }
+
+        // The largest coins are spent first, so that the fee can be paid by the first transaction.
+        coins.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+        let mut ctx = SpendContext::new();
+        let mut bundles = SweepBundles::new(self.options.max_bundle_cost);
+        let mut fee = Some(fee).filter(|fee| *fee > 0);
+
+        // The first group pays the fee, so it can't be smaller than the coins that cover it.
+        let fee_coins = fee.map_or(1, |fee| {
+            coins
+                .iter()
+                .scan(0, |total, coin| {
+                    *total += coin.amount as u128;
+                    Some(*total)
+                })
+                .position(|total| total >= fee as u128)
+                .map_or(coins.len(), |index| index + 1)
+        });
+
+        // Each group of coins is halved until it fits, and the next group starts at that size.
+        let mut count = coins.len();
+
+        while !coins.is_empty() {
+            count = count.min(coins.len());
+
+            let min_count = if fee.is_some() { fee_coins } else { 1 };
+
+            self.sweep_p2_coins(&mut ctx, &coins[..count], puzzle_hash, fee)
+                .await?;
+
+            let coin_spends = ctx.take();
+            let cost = self.estimate_cost(&coin_spends)?;
+
+            if bundles.fits(cost) || count <= min_count {
+                bundles.push(coin_spends, cost);
+                coins.drain(..count);
+                fee = None;
+            } else {
+                count = (count / 2).max(min_count);
+            }
+        }
+
+        if include_cats {
+            for cat in self.db.cats_by_name().await? {
+                let mut cats: Vec<Cat> = self
+                    .db
+                    .spendable_cat_coins(cat.asset_id)
+                    .await?
+                    .into_iter()
+                    .map(|row| Cat {
+                        coin: row.coin,
+                        lineage_proof: Some(row.lineage_proof),
+                        asset_id: cat.asset_id,
+                        p2_puzzle_hash: row.p2_puzzle_hash,
+                    })
+                    .collect();
+
+                let mut count = cats.len();
+
+                while !cats.is_empty() {
+                    count = count.min(cats.len());
+
+                    self.sweep_cat_coins(&mut ctx, &cats[..count], puzzle_hash)
+                        .await?;
+
+                    let coin_spends = ctx.take();
+                    let cost = self.estimate_cost(&coin_spends)?;
+
+                    if bundles.fits(cost) || count == 1 {
+                        bundles.push(coin_spends, cost);
+                        cats.drain(..count);
+                    } else {
+                        count /= 2;
+                    }
+                }
+            }
+        }
+
+        if include_nfts {
+            for coin_id in self.db.unspent_nft_coin_ids().await? {
+                let Some(nft) = self.db.nft_by_coin_id(coin_id).await? else {
+                    continue;
+                };
+
+                let Some(nft) = self.db.spendable_nft(nft.info.launcher_id).await? else {
+                    continue;
+                };
+
+                if !self.db.is_p2_puzzle_hash(nft.info.p2_puzzle_hash).await? {
+                    continue;
+                }
+
+                let nft = nft.hashed(&mut ctx)?;
+
+                let synthetic_key = self.synthetic_key(nft.info.p2_puzzle_hash).await?;
+                let p2 = StandardLayer::new(synthetic_key);
+
+                let _nft = nft.transfer(&mut ctx, &p2, puzzle_hash, Conditions::new())?;
+
+                let coin_spends = ctx.take();
+                let cost = self.estimate_cost(&coin_spends)?;
+
+                bundles.fits(cost);
+                bundles.push(coin_spends, cost);
+            }
+        }
+
+        Ok(bundles.finish())
+    }
+
+    /// Spends the coins to a single output at the puzzle hash, with the fee taken out of it.
+    async fn sweep_p2_coins(
+        &self,
+        ctx: &mut SpendContext,
+        coins: &[Coin],
+        puzzle_hash: Bytes32,
+        fee: Option<u64>,
+    ) -> Result<(), WalletError> {
+        let mut amount: u64 = coins
+            .iter()
+            .map(|coin| coin.amount as u128)
+            .sum::<u128>()
+            .try_into()
+            .map_err(|_| WalletError::AmountOverflow { asset_id: None })?;
+
+        let mut conditions = Conditions::new();
+
+        if let Some(fee) = fee {
+            let Some(remaining) = amount.checked_sub(fee) else {
+                return Err(WalletError::InsufficientFunds);
+            };
+
+            amount = remaining;
+            conditions = conditions.reserve_fee(fee);
+        }
+
+        if amount > 0 {
+            conditions = conditions.create_coin(puzzle_hash, amount, None);
+        }
+
+        self.spend_p2_coins(ctx, coins.to_vec(), conditions).await
+    }
+
+    /// Spends the CATs to a single output at the puzzle hash, which is hinted.
+    async fn sweep_cat_coins(
+        &self,
+        ctx: &mut SpendContext,
+        cats: &[Cat],
+        puzzle_hash: Bytes32,
+    ) -> Result<(), WalletError> {
+        let amount: u64 = cats
+            .iter()
+            .map(|cat| cat.coin.amount as u128)
+            .sum::<u128>()
+            .try_into()
+            .map_err(|_| WalletError::AmountOverflow {
+                asset_id: Some(cats[0].asset_id),
+            })?;
+
+        let hint = ctx.hint(puzzle_hash)?;
+        let mut conditions = Conditions::new().create_coin(puzzle_hash, amount, Some(hint));
+
+        self.spend_cat_coins(
+            ctx,
+            cats.iter().map(|&cat| (cat, mem::take(&mut conditions))),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_log::test;
+
+    use crate::{TestWallet, WalletError, WalletOptions};
+
+    #[test(tokio::test)]
+    async fn test_sweep_all() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1500).await?;
+        let mut bob = alice.next(0).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(1000, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let bundles = alice
+            .wallet
+            .sweep_all(bob.puzzle_hash, 100, true, true)
+            .await?;
+        assert_eq!(bundles.len(), 1);
+
+        for coin_spends in bundles {
+            alice.transact(coin_spends).await?;
+        }
+        alice.wait_for_coins().await;
+        bob.wait_for_puzzles().await;
+
+        assert_eq!(alice.wallet.db.balance().await?, 0);
+        assert_eq!(alice.wallet.db.cat_balance(asset_id).await?, 0);
+        assert_eq!(bob.wallet.db.balance().await?, 400);
+        assert_eq!(bob.wallet.db.cat_balance(asset_id).await?, 1000);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_sweep_all_split_by_max_cost() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coins = alice.wallet.db.spendable_coins().await?;
+        let coin_spends = alice.wallet.split_xch(&coins, 4, 0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        // Sweeping all four coins at once is just over the limit.
+        let bundles = alice
+            .wallet
+            .sweep_all(bob.puzzle_hash, 0, false, false)
+            .await?;
+        assert_eq!(bundles.len(), 1);
+        let max_cost = alice.wallet.estimate_cost(&bundles[0])? - 1;
+
+        let wallet = alice.wallet_with(
+            alice.wallet.db.clone(),
+            WalletOptions {
+                max_bundle_cost: max_cost,
+                ..alice.wallet.options
+            },
+        );
+
+        let bundles = wallet.sweep_all(bob.puzzle_hash, 0, false, false).await?;
+        assert_eq!(bundles.len(), 2);
+        assert_eq!(bundles.iter().map(Vec::len).sum::<usize>(), 4);
+
+        for coin_spends in &bundles {
+            assert!(wallet.estimate_cost(coin_spends)? <= max_cost);
+        }
+
+        // Each transaction can be submitted on its own.
+        for coin_spends in bundles {
+            alice.transact(coin_spends).await?;
+            alice.wait_for_coins().await;
+        }
+
+        assert_eq!(alice.wallet.db.balance().await?, 0);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_sweep_all_fee_split_by_max_cost() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coins = alice.wallet.db.spendable_coins().await?;
+        let coin_spends = alice.wallet.split_xch(&coins, 4, 0, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        // No spend fits within the limit, so each coin would be in a transaction of its own.
+        let wallet = alice.wallet_with(
+            alice.wallet.db.clone(),
+            WalletOptions {
+                max_bundle_cost: 1,
+                ..alice.wallet.options
+            },
+        );
+
+        // But a single coin can't pay the fee, so the first transaction spends two of them.
+        let bundles = wallet.sweep_all(bob.puzzle_hash, 300, false, false).await?;
+        assert_eq!(bundles.iter().map(Vec::len).collect::<Vec<_>>(), [2, 1, 1]);
+
+        for coin_spends in bundles {
+            alice.transact(coin_spends).await?;
+            alice.wait_for_coins().await;
+        }
+
+        assert_eq!(alice.wallet.db.balance().await?, 0);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_sweep_all_includes_dust() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_xch(alice.puzzle_hash, 50, 0, Vec::new(), false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let wallet = alice.wallet_with(
+            alice.wallet.db.clone(),
+            WalletOptions {
+                dust_threshold: 100,
+                ..alice.wallet.options
+            },
+        );
+
+        // The 50 mojo coin is dust, so it isn't selected.
+        assert_eq!(wallet.dust_balance().await?, 50);
+        assert_eq!(wallet.select_p2_coins(1).await?[0].amount, 950);
+        assert!(matches!(
+            wallet.select_p2_coins(951).await,
+            Err(WalletError::CoinSelection(..))
+        ));
+
+        // Sweeping spends the dust as well.
+        let bundles = wallet.sweep_all(bob.puzzle_hash, 0, false, false).await?;
+        assert_eq!(bundles.len(), 1);
+        assert_eq!(bundles[0].len(), 2);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_sweep_all_insufficient_fee() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let result = alice
+            .wallet
+            .sweep_all(bob.puzzle_hash, 1001, false, false)
+            .await;
+
+        assert!(matches!(result, Err(WalletError::InsufficientFunds)));
+
+        Ok(())
+    }
+}
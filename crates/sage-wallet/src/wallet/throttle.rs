@@ -0,0 +1,133 @@
+use tokio::sync::MutexGuard;
+
+use super::Wallet;
+
+impl Wallet {
+    /// Waits for any other operation holding the guard to finish, if transactions are serialized.
+    ///
+    /// The guard should be held from selecting coins until the resulting transaction or offer has
+    /// been recorded in the database, so that concurrent operations can't select the same coins.
+    /// Each endpoint that selects coins holds it until the transaction has been submitted.
+    /// Operations are given the guard in the order that they started waiting for it.
+    pub async fn transaction_guard(&self) -> Option<MutexGuard<'_, ()>> {
+        if self.options.serialize_transactions {
+            Some(self.transaction_lock.lock().await)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::future::{join_all, try_join_all};
+    use itertools::Itertools;
+    use test_log::test;
+
+    use crate::{insert_transaction, TestWallet, Transaction, WalletOptions};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_serialized_transactions() -> anyhow::Result<()> {
+        let mut test = TestWallet::new(1000).await?;
+
+        let coins = test.wallet.db.spendable_coins().await?;
+        let coin_spends = test.wallet.split_xch(&coins, 4, 0, false, true).await?;
+        test.transact(coin_spends).await?;
+        test.wait_for_coins().await;
+
+        let wallet = Arc::new(test.wallet_with(
+            test.wallet.db.clone(),
+            WalletOptions {
+                serialize_transactions: true,
+                ..test.wallet.options
+            },
+        ));
+
+        // Both sends run at the same time, and hold the guard until their transaction is recorded,
+        // the same way that each endpoint which selects coins does.
+        let sends = (0..2).map(|_| {
+            let wallet = wallet.clone();
+            let state = test.state.clone();
+            let agg_sig = test.agg_sig;
+            let master_sk = test.master_sk.clone();
+            let puzzle_hash = test.puzzle_hash;
+
+            tokio::spawn(async move {
+                let _guard = wallet.transaction_guard().await;
+
+                let coin_spends = wallet
+                    .send_xch(puzzle_hash, 100, 0, Vec::new(), false, true)
+                    .await?;
+                let spend_bundle = wallet
+                    .sign_transaction(coin_spends, &agg_sig, master_sk, false)
+                    .await?;
+
+                let peer = state.lock().await.acquire_peer().expect("no peer");
+
+                insert_transaction(
+                    &wallet.db,
+                    &peer,
+                    wallet.network.genesis_challenge(),
+                    spend_bundle.name(),
+                    Transaction::from_coin_spends(spend_bundle.coin_spends.clone())?,
+                    spend_bundle.aggregated_signature,
+                )
+                .await?;
+
+                anyhow::Ok(
+                    spend_bundle
+                        .coin_spends
+                        .into_iter()
+                        .map(|coin_spend| coin_spend.coin.coin_id())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        });
+
+        let mut spent = Vec::new();
+
+        for coin_ids in try_join_all(sends).await? {
+            spent.extend(coin_ids?);
+        }
+
+        assert_eq!(spent.len(), 2);
+        assert!(spent.iter().all_unique());
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_transaction_guard_order() -> anyhow::Result<()> {
+        let test = TestWallet::new(0).await?;
+
+        let wallet = test.wallet_with(
+            test.wallet.db.clone(),
+            WalletOptions {
+                serialize_transactions: true,
+                ..test.wallet.options
+            },
+        );
+
+        let order = Mutex::new(Vec::new());
+
+        join_all((0..4).map(|index| {
+            let wallet = &wallet;
+            let order = &order;
+
+            async move {
+                let _guard = wallet.transaction_guard().await;
+                order.lock().expect("poisoned").push(index);
+                tokio::task::yield_now().await;
+            }
+        }))
+        .await;
+
+        assert_eq!(order.into_inner().expect("poisoned"), vec![0, 1, 2, 3]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,614 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    ops::{Bound, RangeBounds},
+};
+
+use chia::protocol::Bytes32;
+use chrono::{DateTime, SecondsFormat};
+use indexmap::{IndexMap, IndexSet};
+use sage_database::{CoinAssetRow, CoinKind};
+
+use crate::WalletError;
+
+use super::Wallet;
+
+/// The number of decimal places that XCH amounts are displayed with.
+const XCH_DECIMALS: u8 = 12;
+
+const TRANSACTIONS_CSV_HEADER: &str =
+    "height,approximate_time,type,asset_kind,asset_id,amount,fee,counterparty";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ExportAsset {
+    Xch,
+    Cat(Bytes32),
+    Nft(Bytes32),
+    Did(Bytes32),
+}
+
+/// The transaction that a coin was spent or created by. If the wallet didn't submit it, it's
+/// not known which coins were spent together, so each spent coin is its own group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SpendGroup {
+    Transaction(Bytes32),
+    Coin(Bytes32),
+}
+
+/// The net change of each asset within a transaction, along with the fee that it paid.
+#[derive(Debug, Default)]
+struct GroupChange {
+    fee: u64,
+    assets: IndexMap<ExportAsset, AssetChange>,
+}
+
+/// The net change of an asset within a transaction.
+#[derive(Debug, Default)]
+struct AssetChange {
+    amount: i128,
+    counterparties: IndexSet<Bytes32>,
+}
+
+impl Wallet {
+    /// Writes a CSV row for each asset that the wallet sent or received in each transaction within
+    /// the range of heights, from oldest to newest. The coins spent by a transaction are netted
+    /// against the coins that it created, so that change and transfers to the wallet itself aren't
+    /// counted, but separate transactions are never netted against each other, even if they're in
+    /// the same block. Only the transactions that were submitted by the wallet, and its completed
+    /// offers, are known, so otherwise each spent coin is reported on its own, and each received
+    /// coin along with the other coins created by the same parent.
+    ///
+    /// The time of each block is estimated from the timestamps of the recorded peaks, so it's only
+    /// approximate, and is left empty if no peak has been timed yet. Fees are only known for the
+    /// wallet's own transactions and completed offers, and counterparties only for clawback coins,
+    /// so those columns are empty otherwise. The XCH amount doesn't include the fee. CAT amounts
+    /// use the decimals stored for the CAT, or the given default if they aren't known. Returns the
+    /// number of rows that were written, not including the header.
+    pub async fn export_transactions_csv(
+        &self,
+        mut writer: impl Write,
+        heights: impl RangeBounds<u32>,
+        default_cat_decimals: u8,
+    ) -> Result<u64, WalletError> {
+        writeln!(writer, "{TRANSACTIONS_CSV_HEADER}")?;
+
+        let from = match heights.start_bound() {
+            Bound::Included(height) | Bound::Excluded(height) => *height,
+            Bound::Unbounded => 0,
+        };
+
+        let to = match heights.end_bound() {
+            Bound::Included(height) | Bound::Excluded(height) => *height,
+            Bound::Unbounded => u32::MAX,
+        };
+
+        let mut blocks: BTreeMap<u32, IndexMap<SpendGroup, GroupChange>> = BTreeMap::new();
+        let mut decimals = IndexMap::new();
+
+        let spent = self.db.spent_coin_assets(from, to).await?;
+        let created = self.db.created_coin_assets(from, to).await?;
+
+        for (rows, sign) in [(spent, -1), (created, 1)] {
+            for row in rows {
+                if !heights.contains(&row.height) {
+                    continue;
+                }
+
+                let group = match row.transaction_id {
+                    Some(transaction_id) => SpendGroup::Transaction(transaction_id),
+                    None if sign < 0 => SpendGroup::Coin(row.coin_id),
+                    None => SpendGroup::Coin(row.parent_coin_id),
+                };
+
+                let group = blocks
+                    .entry(row.height)
+                    .or_default()
+                    .entry(group)
+                    .or_default();
+
+                if let Some(fee) = row.fee {
+                    group.fee = fee;
+
+                    // The fee was paid with XCH, so it's reported even if nothing else was sent.
+                    if fee > 0 {
+                        group.assets.entry(ExportAsset::Xch).or_default();
+                    }
+                }
+
+                let Some(asset) = export_asset(row) else {
+                    continue;
+                };
+
+                if let (ExportAsset::Cat(asset_id), Some(value)) = (asset, row.decimals) {
+                    decimals.insert(asset_id, value);
+                }
+
+                let change = group.assets.entry(asset).or_default();
+
+                change.amount += sign * i128::from(row.amount);
+                change.counterparties.extend(row.counterparty);
+            }
+        }
+
+        let mut count = 0;
+
+        for (height, groups) in blocks {
+            let time = match self.estimate_time_at(height).await {
+                Ok(timestamp) => format_time(timestamp),
+                Err(WalletError::MissingTimedPeak) => String::new(),
+                Err(error) => return Err(error),
+            };
+
+            for group in groups.into_values() {
+                for (asset, change) in group.assets {
+                    let fee = match asset {
+                        ExportAsset::Xch => group.fee,
+                        _ => 0,
+                    };
+
+                    let amount = change.amount + i128::from(fee);
+
+                    if amount == 0 && fee == 0 {
+                        continue;
+                    }
+
+                    let kind = if amount > 0 { "receive" } else { "send" };
+
+                    let (asset_kind, asset_id, decimals) = match asset {
+                        ExportAsset::Xch => ("xch", String::new(), XCH_DECIMALS),
+                        ExportAsset::Cat(asset_id) => (
+                            "cat",
+                            asset_id.to_string(),
+                            decimals
+                                .get(&asset_id)
+                                .copied()
+                                .unwrap_or(default_cat_decimals),
+                        ),
+                        ExportAsset::Nft(launcher_id) => ("nft", launcher_id.to_string(), 0),
+                        ExportAsset::Did(launcher_id) => ("did", launcher_id.to_string(), 0),
+                    };
+
+                    let amount = format_amount(amount.unsigned_abs(), decimals);
+
+                    let fee = if fee > 0 {
+                        format_amount(fee.into(), XCH_DECIMALS)
+                    } else {
+                        String::new()
+                    };
+
+                    // The counterparty is only known if every coin that was involved agrees on it.
+                    let counterparty = if change.counterparties.len() == 1 {
+                        self.network.encode_address(change.counterparties[0])?
+                    } else {
+                        String::new()
+                    };
+
+                    writeln!(
+                        writer,
+                        "{height},{time},{kind},{asset_kind},{asset_id},{amount},{fee},{counterparty}"
+                    )?;
+
+                    count += 1;
+                }
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(count)
+    }
+
+    /// Like [`Wallet::export_transactions_csv`], but the range is given in unix timestamps, in
+    /// seconds. The timestamps are converted to heights with [`Wallet::estimate_height_at`], so
+    /// the range is approximate, and blocks close to either end of it may be included or left out.
+    pub async fn export_transactions_csv_between(
+        &self,
+        writer: impl Write,
+        timestamps: impl RangeBounds<u64>,
+        default_cat_decimals: u8,
+    ) -> Result<u64, WalletError> {
+        let start = match timestamps.start_bound() {
+            Bound::Included(timestamp) => {
+                Bound::Included(self.estimate_height_at(*timestamp).await?)
+            }
+            Bound::Excluded(timestamp) => {
+                Bound::Excluded(self.estimate_height_at(*timestamp).await?)
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        let end = match timestamps.end_bound() {
+            Bound::Included(timestamp) => {
+                Bound::Included(self.estimate_height_at(*timestamp).await?)
+            }
+            Bound::Excluded(timestamp) => {
+                Bound::Excluded(self.estimate_height_at(*timestamp).await?)
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        self.export_transactions_csv(writer, (start, end), default_cat_decimals)
+            .await
+    }
+}
+
+fn export_asset(row: CoinAssetRow) -> Option<ExportAsset> {
+    match row.kind {
+        CoinKind::Xch | CoinKind::Clawback | CoinKind::Settlement => Some(ExportAsset::Xch),
+        CoinKind::Cat => row.asset_id.map(ExportAsset::Cat),
+        CoinKind::Nft => row.asset_id.map(ExportAsset::Nft),
+        CoinKind::Did => row.asset_id.map(ExportAsset::Did),
+        CoinKind::Unknown => None,
+    }
+}
+
+/// Formats a unix timestamp, in seconds, as an ISO 8601 UTC time.
+fn format_time(timestamp: u64) -> String {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// Formats a raw amount with the given number of decimal places, without trailing zeros.
+fn format_amount(amount: u128, decimals: u8) -> String {
+    let Some(divisor) = 10u128.checked_pow(decimals.into()) else {
+        return format!("0.{amount:0>width$}", width = decimals as usize)
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string();
+    };
+
+    let whole = amount / divisor;
+    let fraction = amount % divisor;
+
+    if fraction == 0 {
+        return whole.to_string();
+    }
+
+    let fraction = format!("{fraction:0>width$}", width = decimals as usize);
+
+    format!("{whole}.{}", fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use chia::protocol::SpendBundle;
+    use sage_database::{CatRow, OfferRow, OfferStatus};
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_export_transactions_csv() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(0).await?;
+
+        // Otherwise the coin would be received in the same block that it's sent in.
+        alice.advance_height().await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_xch(bob.puzzle_hash, 250, 0, Vec::new(), false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+        bob.wait_for_coins().await;
+
+        let coin_spends = bob
+            .wallet
+            .send_xch(alice.puzzle_hash, 100, 0, Vec::new(), false, true)
+            .await?;
+        bob.transact(coin_spends).await?;
+        bob.wait_for_coins().await;
+        alice.wait_for_coins().await;
+
+        let mut csv = Vec::new();
+        let count = alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        let csv = String::from_utf8(csv)?;
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(count, 3);
+        assert_eq!(lines[0], TRANSACTIONS_CSV_HEADER);
+
+        let rows: Vec<Vec<&str>> = lines[1..]
+            .iter()
+            .map(|line| line.split(',').collect())
+            .collect();
+
+        let summary: Vec<(&str, &str)> = rows.iter().map(|row| (row[2], row[5])).collect();
+        assert_eq!(
+            summary,
+            [
+                ("receive", "0.000000001"),
+                ("send", "0.00000000025"),
+                ("receive", "0.0000000001"),
+            ]
+        );
+        assert!(rows.iter().all(|row| row.len() == 8 && row[3] == "xch"));
+
+        // The blocks were timed when they were synced, and nothing else is known about them.
+        assert!(rows.iter().all(|row| row[1].ends_with('Z')));
+        assert!(rows
+            .iter()
+            .all(|row| row[6].is_empty() && row[7].is_empty()));
+
+        // Only the blocks from the send onwards are included.
+        let send_height: u32 = rows[1][0].parse()?;
+
+        let mut csv = Vec::new();
+        let count = alice
+            .wallet
+            .export_transactions_csv(&mut csv, send_height.., 3)
+            .await?;
+        assert_eq!(count, 2);
+
+        // The peaks were recorded just now, so every block is estimated to be after the epoch,
+        // and none of them a day from now.
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        let mut csv = Vec::new();
+        let count = alice
+            .wallet
+            .export_transactions_csv_between(&mut csv, 0.., 3)
+            .await?;
+        assert_eq!(count, 3);
+
+        let mut csv = Vec::new();
+        let count = alice
+            .wallet
+            .export_transactions_csv_between(&mut csv, now + 86_400.., 3)
+            .await?;
+        assert_eq!(count, 0);
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_export_cat_decimals() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1500).await?;
+
+        let (coin_spends, asset_id) = alice.wallet.issue_cat(1500, 0, None, false, true).await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        let cat_amount = |csv: Vec<u8>| -> anyhow::Result<String> {
+            let csv = String::from_utf8(csv)?;
+            let row = csv
+                .lines()
+                .find(|line| line.contains(",cat,"))
+                .expect("missing CAT row");
+            Ok(row.split(',').nth(5).unwrap_or_default().to_string())
+        };
+
+        // The decimals aren't known yet, so the default number of decimals is used.
+        let mut csv = Vec::new();
+        alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        assert_eq!(cat_amount(csv)?, "1.5");
+
+        let mut csv = Vec::new();
+        alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 2)
+            .await?;
+        assert_eq!(cat_amount(csv)?, "15");
+
+        let cat = alice.wallet.db.cat(asset_id).await?.expect("missing CAT");
+        alice
+            .wallet
+            .db
+            .update_cat(CatRow {
+                decimals: Some(6),
+                ..cat
+            })
+            .await?;
+
+        let mut csv = Vec::new();
+        alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        assert_eq!(cat_amount(csv)?, "0.0015");
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_export_clawback_counterparty() -> anyhow::Result<()> {
+        let alice = TestWallet::new(1000).await?;
+        let mut bob = alice.next(0).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_with_clawback(bob.puzzle_hash, 1000, 3600, 0, false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        bob.wait_for_puzzles().await;
+
+        let mut csv = Vec::new();
+        let count = bob.wallet.export_transactions_csv(&mut csv, .., 3).await?;
+        let csv = String::from_utf8(csv)?;
+        let row: Vec<&str> = csv
+            .lines()
+            .nth(1)
+            .expect("missing row")
+            .split(',')
+            .collect();
+
+        assert_eq!(count, 1);
+        assert_eq!(row[2], "receive");
+        assert_eq!(row[5], "0.000000001");
+        assert_eq!(
+            row[7],
+            bob.wallet.network.encode_address(alice.puzzle_hash)?
+        );
+
+        Ok(())
+    }
+
+    /// The last XCH row that was sent, split into its columns.
+    fn xch_send_row(csv: Vec<u8>) -> anyhow::Result<Vec<String>> {
+        let csv = String::from_utf8(csv)?;
+        let row = csv
+            .lines()
+            .rfind(|line| line.contains(",send,xch,"))
+            .expect("missing send row");
+        Ok(row.split(',').map(ToString::to_string).collect())
+    }
+
+    #[test(tokio::test)]
+    async fn test_export_fee() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_xch(bob.puzzle_hash, 250, 100, Vec::new(), false, true)
+            .await?;
+        alice.transact(coin_spends).await?;
+        alice.wait_for_coins().await;
+
+        // The fee is what the transaction spent without paying it to any coin.
+        let mut csv = Vec::new();
+        alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        let row = xch_send_row(csv)?;
+        assert_eq!(
+            (row[5].as_str(), row[6].as_str()),
+            ("0.00000000025", "0.0000000001")
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_export_offer_fee() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(0).await?;
+
+        let coin_id = alice.wallet.db.spendable_coins().await?[0].coin_id();
+
+        // The spend is pushed to the peer directly, like an offer that's taken by someone else,
+        // so it isn't one of the wallet's transactions.
+        let coin_spends = alice
+            .wallet
+            .send_xch(bob.puzzle_hash, 250, 100, Vec::new(), false, true)
+            .await?;
+        let spend_bundle = alice
+            .wallet
+            .sign_transaction(coin_spends, &alice.agg_sig, alice.master_sk.clone(), false)
+            .await?;
+        alice.peer.send_transaction(spend_bundle).await?;
+        alice.wait_for_coins().await;
+
+        // Without a completed offer, the fee can't be told apart from the amount that was sent.
+        let mut csv = Vec::new();
+        alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        let row = xch_send_row(csv)?;
+        assert_eq!((row[5].as_str(), row[6].as_str()), ("0.00000000035", ""));
+
+        let offer_id = Bytes32::new([1; 32]);
+
+        let mut tx = alice.wallet.db.tx().await?;
+        tx.insert_offer(OfferRow {
+            offer_id,
+            encoded_offer: String::new(),
+            expiration_height: None,
+            expiration_timestamp: None,
+            fee: 100,
+            status: OfferStatus::Completed,
+            inserted_timestamp: 0,
+        })
+        .await?;
+        tx.insert_offered_coin(offer_id, coin_id).await?;
+        tx.commit().await?;
+
+        let mut csv = Vec::new();
+        alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        let row = xch_send_row(csv)?;
+        assert_eq!(
+            (row[5].as_str(), row[6].as_str()),
+            ("0.00000000025", "0.0000000001")
+        );
+
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn test_export_separate_spends() -> anyhow::Result<()> {
+        let mut alice = TestWallet::new(1000).await?;
+        let bob = alice.next(1000).await?;
+
+        let coin_spends = alice
+            .wallet
+            .send_xch(bob.puzzle_hash, 250, 0, Vec::new(), false, true)
+            .await?;
+        let alice_bundle = alice
+            .wallet
+            .sign_transaction(coin_spends, &alice.agg_sig, alice.master_sk.clone(), false)
+            .await?;
+
+        let coin_spends = bob
+            .wallet
+            .send_xch(alice.puzzle_hash, 250, 0, Vec::new(), false, true)
+            .await?;
+        let bob_bundle = bob
+            .wallet
+            .sign_transaction(coin_spends, &bob.agg_sig, bob.master_sk.clone(), false)
+            .await?;
+
+        // Both spends are confirmed in the same block, but the amount that Alice sent and the
+        // amount that she received don't cancel each other out.
+        alice
+            .peer
+            .send_transaction(SpendBundle::aggregate(&[alice_bundle, bob_bundle]))
+            .await?;
+        alice.wait_for_coins().await;
+
+        let mut csv = Vec::new();
+        let count = alice
+            .wallet
+            .export_transactions_csv(&mut csv, .., 3)
+            .await?;
+        let csv = String::from_utf8(csv)?;
+        let rows: Vec<Vec<&str>> = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').collect())
+            .collect();
+
+        assert_eq!(count, 3);
+        assert_eq!(rows[1][0], rows[2][0]);
+
+        let summary: Vec<(&str, &str)> = rows.iter().map(|row| (row[2], row[5])).collect();
+        assert_eq!(
+            summary,
+            [
+                ("receive", "0.000000001"),
+                ("send", "0.00000000025"),
+                ("receive", "0.00000000025"),
+            ]
+        );
+
+        Ok(())
+    }
+}
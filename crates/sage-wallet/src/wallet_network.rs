@@ -0,0 +1,196 @@
+use chia::{consensus::consensus_constants::ConsensusConstants, protocol::Bytes32};
+use chia_wallet_sdk::{
+    decode_address, encode_address, AddressError, AggSigConstants, MAINNET_CONSTANTS,
+    TESTNET11_CONSTANTS,
+};
+use sage_config::{Network, TESTNET11};
+
+use crate::WalletError;
+
+/// The parameters of the network that the wallet runs on, parsed from its [`Network`] config.
+/// Signatures commit to the network's additional data and addresses are encoded with its prefix,
+/// so using the wrong network makes both unusable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletNetwork {
+    pub address_prefix: String,
+    /// The consensus constants, which the cost of spends is estimated with. Networks other than
+    /// mainnet use the testnet11 constants, apart from their genesis challenge and additional data.
+    pub constants: ConsensusConstants,
+}
+
+impl WalletNetwork {
+    pub fn new(network: &Network) -> Result<Self, WalletError> {
+        let genesis_challenge = parse_bytes32(&network.genesis_challenge)?;
+        let agg_sig = AggSigConstants::new(parse_bytes32(&network.agg_sig_me)?);
+
+        let base = if genesis_challenge == MAINNET_CONSTANTS.genesis_challenge {
+            MAINNET_CONSTANTS.clone()
+        } else {
+            TESTNET11_CONSTANTS.clone()
+        };
+
+        Ok(Self {
+            address_prefix: network.address_prefix.clone(),
+            constants: ConsensusConstants {
+                genesis_challenge,
+                agg_sig_me_additional_data: agg_sig.me(),
+                agg_sig_parent_additional_data: agg_sig.parent(),
+                agg_sig_puzzle_additional_data: agg_sig.puzzle(),
+                agg_sig_amount_additional_data: agg_sig.amount(),
+                agg_sig_puzzle_amount_additional_data: agg_sig.puzzle_amount(),
+                agg_sig_parent_amount_additional_data: agg_sig.parent_amount(),
+                agg_sig_parent_puzzle_additional_data: agg_sig.parent_puzzle(),
+                ..base
+            },
+        })
+    }
+
+    /// A local simulator, which has the same parameters as testnet11.
+    pub fn simulator() -> Self {
+        Self::new(&TESTNET11).expect("testnet11 has valid parameters")
+    }
+
+    pub fn genesis_challenge(&self) -> Bytes32 {
+        self.constants.genesis_challenge
+    }
+
+    pub fn agg_sig_constants(&self) -> AggSigConstants {
+        AggSigConstants::from(&self.constants)
+    }
+
+    pub fn encode_address(&self, puzzle_hash: Bytes32) -> Result<String, WalletError> {
+        Ok(encode_address(puzzle_hash.into(), &self.address_prefix).map_err(AddressError::from)?)
+    }
+
+    /// Decodes an address, which must have been encoded for this network.
+    pub fn decode_address(&self, address: &str) -> Result<Bytes32, WalletError> {
+        let (puzzle_hash, prefix) = decode_address(address)?;
+
+        if prefix != self.address_prefix {
+            return Err(WalletError::AddressPrefix(prefix));
+        }
+
+        Ok(puzzle_hash.into())
+    }
+}
+
+fn parse_bytes32(input: &str) -> Result<Bytes32, WalletError> {
+    let bytes: [u8; 32] = hex::decode(input.strip_prefix("0x").unwrap_or(input))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| WalletError::InvalidNetwork(input.to_string()))?;
+
+    Ok(bytes.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use sage_config::MAINNET;
+    use test_log::test;
+
+    use crate::{TestWallet, Wallet};
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_sign_for_network() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+
+        let coin_spends = test
+            .wallet
+            .send_xch(test.puzzle_hash, 1000, 0, Vec::new(), false, true)
+            .await?;
+
+        // The same wallet, but on mainnet.
+        let mainnet = Wallet::new(
+            test.wallet.db.clone(),
+            test.wallet.fingerprint,
+            &test.master_sk.public_key(),
+            test.wallet.derivation_path.clone(),
+            WalletNetwork::new(&MAINNET)?,
+            test.wallet.options,
+        );
+
+        let mainnet_bundle = mainnet
+            .sign_transaction(
+                coin_spends.clone(),
+                &mainnet.network.agg_sig_constants(),
+                test.master_sk.clone(),
+                false,
+            )
+            .await?;
+        let simulator_bundle = test
+            .wallet
+            .sign_transaction(coin_spends, &test.agg_sig, test.master_sk.clone(), false)
+            .await?;
+
+        // Each signature is only valid on the network it was made for.
+        assert_ne!(
+            mainnet_bundle.aggregated_signature,
+            simulator_bundle.aggregated_signature
+        );
+        mainnet.verify_bundle(&mainnet_bundle).await?;
+        test.wallet.verify_bundle(&simulator_bundle).await?;
+        assert!(matches!(
+            test.wallet.verify_bundle(&mainnet_bundle).await,
+            Err(WalletError::InvalidSignature { .. })
+        ));
+
+        // The simulator only accepts spends signed for it.
+        test.push_bundle(simulator_bundle).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_addresses() -> anyhow::Result<()> {
+        let puzzle_hash = Bytes32::new([7; 32]);
+
+        let mainnet = WalletNetwork::new(&MAINNET)?;
+        let testnet11 = WalletNetwork::new(&TESTNET11)?;
+
+        let address = mainnet.encode_address(puzzle_hash)?;
+        assert!(address.starts_with("xch1"));
+        assert_eq!(mainnet.decode_address(&address)?, puzzle_hash);
+
+        assert!(testnet11.encode_address(puzzle_hash)?.starts_with("txch1"));
+        assert!(matches!(
+            testnet11.decode_address(&address),
+            Err(WalletError::AddressPrefix(prefix)) if prefix == "xch"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_constants() -> anyhow::Result<()> {
+        assert_eq!(WalletNetwork::new(&MAINNET)?.constants, *MAINNET_CONSTANTS);
+        assert_eq!(WalletNetwork::simulator().constants, *TESTNET11_CONSTANTS);
+
+        // A custom network keeps its own genesis challenge and signs with its own data.
+        let custom = WalletNetwork::new(&Network {
+            genesis_challenge: hex::encode([1; 32]),
+            agg_sig_me: hex::encode([2; 32]),
+            ..TESTNET11.clone()
+        })?;
+        assert_eq!(custom.genesis_challenge(), Bytes32::new([1; 32]));
+        assert_eq!(
+            custom.agg_sig_constants(),
+            AggSigConstants::new(Bytes32::new([2; 32]))
+        );
+        assert_eq!(
+            custom.constants.max_block_cost_clvm,
+            TESTNET11_CONSTANTS.max_block_cost_clvm
+        );
+
+        assert!(matches!(
+            WalletNetwork::new(&Network {
+                genesis_challenge: "not hex".to_string(),
+                ..TESTNET11.clone()
+            }),
+            Err(WalletError::InvalidNetwork(..))
+        ));
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::net::SocketAddr;
+use std::{future::Future, net::SocketAddr, time::Duration};
 
 use chia::protocol::{
     Bytes32, CoinSpend, CoinState, CoinStateFilters, Program, RejectStateReason,
@@ -6,9 +6,20 @@ use chia::protocol::{
     TransactionAck,
 };
 use chia_wallet_sdk::Peer;
+use tokio::time::timeout;
 
 use crate::WalletError;
 
+/// Runs a request against a peer, failing with [`WalletError::PeerTimeout`] if it doesn't respond in time.
+pub async fn peer_timeout<T>(
+    duration: Duration,
+    request: impl Future<Output = Result<T, WalletError>>,
+) -> Result<T, WalletError> {
+    timeout(duration, request)
+        .await
+        .map_err(|_| WalletError::PeerTimeout)?
+}
+
 #[derive(Debug, Clone)]
 pub struct WalletPeer {
     peer: Peer,
@@ -80,7 +91,7 @@ impl WalletPeer {
             .peer
             .request_puzzle_and_solution(coin_id, spent_height)
             .await?
-            .map_err(|_| WalletError::MissingSpend(coin_id))?;
+            .map_err(|_| WalletError::ChainDataMissing(coin_id))?;
 
         Ok((response.puzzle, response.solution))
     }
@@ -91,7 +102,9 @@ impl WalletPeer {
         genesis_challenge: Bytes32,
     ) -> Result<CoinSpend, WalletError> {
         let coin_state = self.fetch_coin(coin_id, genesis_challenge).await?;
-        let spent_height = coin_state.spent_height.ok_or(WalletError::PeerMisbehaved)?;
+        let spent_height = coin_state
+            .spent_height
+            .ok_or(WalletError::MissingSpend(coin_id))?;
         let (puzzle_reveal, solution) = self.fetch_puzzle_solution(coin_id, spent_height).await?;
         Ok(CoinSpend::new(coin_state.coin, puzzle_reveal, solution))
     }
@@ -104,7 +117,9 @@ impl WalletPeer {
         let Some(coin_state) = self.fetch_optional_coin(coin_id, genesis_challenge).await? else {
             return Ok(None);
         };
-        let spent_height = coin_state.spent_height.ok_or(WalletError::PeerMisbehaved)?;
+        let spent_height = coin_state
+            .spent_height
+            .ok_or(WalletError::MissingSpend(coin_id))?;
         let (puzzle_reveal, solution) = self.fetch_puzzle_solution(coin_id, spent_height).await?;
         Ok(Some(CoinSpend::new(
             coin_state.coin,
@@ -203,3 +218,58 @@ impl WalletPeer {
             .map(|block| block.timestamp))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::future::pending;
+
+    use chia_wallet_sdk::ClientError;
+    use test_log::test;
+
+    use crate::TestWallet;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn test_peer_errors() -> anyhow::Result<()> {
+        let test = TestWallet::new(1000).await?;
+        let peer = WalletPeer::new(test.peer.clone());
+
+        // The coin hasn't been spent, so there's no spend to request.
+        let coin_id = test.wallet.db.spendable_coins().await?[0].coin_id();
+
+        let error = peer
+            .fetch_coin_spend(coin_id, test.wallet.network.genesis_challenge())
+            .await
+            .expect_err("coin is unspent");
+        assert!(matches!(error, WalletError::MissingSpend(id) if id == coin_id));
+        assert!(!error.is_network_error());
+
+        // A peer that never responds times out.
+        let result = peer_timeout(Duration::from_millis(10), pending::<Result<(), _>>()).await;
+        assert!(matches!(result, Err(WalletError::PeerTimeout)));
+
+        // Requests to a peer whose connection has been closed fail, and can be retried elsewhere.
+        let (closed, _receiver) = test.sim.connect_raw().await?;
+        closed.close().await?;
+
+        let error = peer_timeout(
+            Duration::from_secs(5),
+            WalletPeer::new(closed).fetch_coin(coin_id, test.wallet.network.genesis_challenge()),
+        )
+        .await
+        .expect_err("connection is closed");
+        assert!(matches!(error, WalletError::PeerUnavailable(..)));
+        assert!(error.is_network_error());
+
+        let error = WalletError::from(ClientError::BannedPeer);
+        assert!(matches!(error, WalletError::PeerUnavailable(..)));
+
+        // Protocol errors aren't transient.
+        let error = WalletError::from(ClientError::MissingHandshake);
+        assert!(matches!(error, WalletError::Client(..)));
+        assert!(!error.is_network_error());
+
+        Ok(())
+    }
+}
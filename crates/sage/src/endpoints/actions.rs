@@ -1,10 +1,12 @@
 use sage_api::{
-    RemoveCat, RemoveCatResponse, UpdateCat, UpdateCatResponse, UpdateDid, UpdateDidResponse,
-    UpdateNft, UpdateNftResponse,
+    AddBurnAddress, AddBurnAddressResponse, FreezeCoins, FreezeCoinsResponse, RemoveBurnAddress,
+    RemoveBurnAddressResponse, RemoveCat, RemoveCatResponse, SetCoinLabel, SetCoinLabelResponse,
+    UnfreezeCoins, UnfreezeCoinsResponse, UpdateCat, UpdateCatResponse, UpdateDid,
+    UpdateDidResponse, UpdateNft, UpdateNftResponse,
 };
 use sage_database::{CatRow, DidRow};
 
-use crate::{parse_asset_id, parse_did_id, parse_nft_id, Error, Result, Sage};
+use crate::{parse_asset_id, parse_coin_id, parse_did_id, parse_nft_id, Error, Result, Sage};
 
 impl Sage {
     pub async fn remove_cat(&self, req: RemoveCat) -> Result<RemoveCatResponse> {
@@ -31,6 +33,7 @@ impl Sage {
                 icon: req.record.icon_url,
                 visible: req.record.visible,
                 fetched: true,
+                decimals: Some(req.record.decimals),
             })
             .await?;
 
@@ -69,4 +72,76 @@ impl Sage {
 
         Ok(UpdateNftResponse {})
     }
+
+    pub async fn freeze_coins(&self, req: FreezeCoins) -> Result<FreezeCoinsResponse> {
+        let wallet = self.wallet()?;
+
+        let coin_ids = req
+            .coin_ids
+            .into_iter()
+            .map(parse_coin_id)
+            .collect::<Result<Vec<_>>>()?;
+
+        for &coin_id in &coin_ids {
+            if wallet.db.coin_state(coin_id).await?.is_none() {
+                return Err(Error::MissingCoin(coin_id));
+            }
+        }
+
+        for coin_id in coin_ids {
+            wallet.db.freeze_coin(coin_id).await?;
+        }
+
+        Ok(FreezeCoinsResponse {})
+    }
+
+    pub async fn unfreeze_coins(&self, req: UnfreezeCoins) -> Result<UnfreezeCoinsResponse> {
+        let wallet = self.wallet()?;
+
+        for coin_id in req.coin_ids {
+            let coin_id = parse_coin_id(coin_id)?;
+            wallet.db.unfreeze_coin(coin_id).await?;
+        }
+
+        Ok(UnfreezeCoinsResponse {})
+    }
+
+    pub async fn set_coin_label(&self, req: SetCoinLabel) -> Result<SetCoinLabelResponse> {
+        let wallet = self.wallet()?;
+
+        let coin_id = parse_coin_id(req.coin_id)?;
+
+        if wallet.db.coin_state(coin_id).await?.is_none() {
+            return Err(Error::MissingCoin(coin_id));
+        }
+
+        // An empty label is the same as removing it.
+        let label = req.label.filter(|label| !label.trim().is_empty());
+
+        wallet.db.set_coin_label(coin_id, label).await?;
+
+        Ok(SetCoinLabelResponse {})
+    }
+
+    /// NFTs sent to a burn address are flagged as burned once the transfer is synced.
+    pub async fn add_burn_address(&self, req: AddBurnAddress) -> Result<AddBurnAddressResponse> {
+        let wallet = self.wallet()?;
+
+        let puzzle_hash = self.parse_address(req.address)?;
+        wallet.db.insert_burn_puzzle_hash(puzzle_hash).await?;
+
+        Ok(AddBurnAddressResponse {})
+    }
+
+    pub async fn remove_burn_address(
+        &self,
+        req: RemoveBurnAddress,
+    ) -> Result<RemoveBurnAddressResponse> {
+        let wallet = self.wallet()?;
+
+        let puzzle_hash = self.parse_address(req.address)?;
+        wallet.db.remove_burn_puzzle_hash(puzzle_hash).await?;
+
+        Ok(RemoveBurnAddressResponse {})
+    }
 }
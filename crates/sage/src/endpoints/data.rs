@@ -1,35 +1,74 @@
+use std::fs;
+
 use base64::{prelude::BASE64_STANDARD, Engine};
 use chia::{
     clvm_traits::{FromClvm, ToClvm},
-    protocol::Program,
+    protocol::{Bytes32, Program},
     puzzles::nft::NftMetadata,
 };
 use chia_wallet_sdk::{encode_address, Nft};
 use clvmr::Allocator;
 use hex_literal::hex;
 use sage_api::{
-    Amount, AssetKind, CatRecord, CoinRecord, DerivationRecord, DidRecord, GetCat, GetCatCoins,
-    GetCatCoinsResponse, GetCatResponse, GetCats, GetCatsResponse, GetDerivations,
-    GetDerivationsResponse, GetDids, GetDidsResponse, GetNft, GetNftCollection,
-    GetNftCollectionResponse, GetNftCollections, GetNftCollectionsResponse, GetNftData,
-    GetNftDataResponse, GetNftResponse, GetNftStatus, GetNftStatusResponse, GetNfts,
-    GetNftsResponse, GetPendingTransactions, GetPendingTransactionsResponse, GetSyncStatus,
-    GetSyncStatusResponse, GetTransactions, GetTransactionsResponse, GetXchCoins,
-    GetXchCoinsResponse, NftCollectionRecord, NftData, NftRecord, NftSortMode,
+    Amount, AssetKind, CatRecord, CoinRecord, DerivationRecord, DidRecord, GetBurnAddresses,
+    GetBurnAddressesResponse, GetCat, GetCatCoins, GetCatCoinsResponse, GetCatResponse, GetCats,
+    GetCatsResponse, GetDerivations, GetDerivationsResponse, GetDids, GetDidsResponse,
+    GetFrozenCoins, GetFrozenCoinsResponse, GetKnownCats, GetKnownCatsResponse, GetNft,
+    GetNftCollection, GetNftCollectionResponse, GetNftCollections, GetNftCollectionsResponse,
+    GetNftCount, GetNftCountResponse, GetNftData, GetNftDataResponse, GetNftGroups,
+    GetNftGroupsResponse, GetNftProvenance, GetNftProvenanceResponse, GetNftResponse, GetNftStatus,
+    GetNftStatusResponse, GetNfts, GetNftsResponse, GetPendingTransactions,
+    GetPendingTransactionsResponse, GetRarity, GetRarityResponse, GetSpendableCoins,
+    GetSpendableCoinsResponse, GetSyncStatus, GetSyncStatusResponse, GetTransactions,
+    GetTransactionsResponse, GetXchCoins, GetXchCoinsResponse, KnownCatRecord, NftCollectionRecord,
+    NftData, NftGroup, NftProvenanceEntry, NftRarity, NftRecord, NftSortMode,
     PendingTransactionRecord, TransactionCoin, TransactionRecord,
 };
-use sage_database::{CoinKind, CoinStateRow, Database, NftRow};
-use sage_wallet::WalletError;
+use sage_config::{MAINNET, TESTNET11};
+use sage_database::{CoinKind, CoinStateRow, Database, LabeledCoinStateRow, NftRow};
+use sage_wallet::{
+    bundled_known_cats, fetch_known_cats, onchain_metadata_json, parse_known_cats, SyncHealth,
+    WalletError,
+};
 
-use crate::{parse_asset_id, parse_collection_id, parse_nft_id, Result, Sage};
+use crate::{
+    parse_asset_id, parse_cat_amount, parse_collection_id, parse_nft_id, Error, Result, Sage,
+};
+
+/// How many blocks the wallet can be behind the peak while still being considered synced.
+const SYNCED_HEIGHT_TOLERANCE: u32 = 2;
 
 impl Sage {
-    pub async fn get_sync_status(&self, _req: GetSyncStatus) -> Result<GetSyncStatusResponse> {
+    pub async fn get_sync_status(&self, req: GetSyncStatus) -> Result<GetSyncStatusResponse> {
         let wallet = self.wallet()?;
 
-        let balance = wallet.db.balance().await?;
+        // Only the active wallet is synced, so the status of any other wallet would be stale.
+        if req
+            .fingerprint
+            .is_some_and(|fingerprint| fingerprint != wallet.fingerprint)
+        {
+            return Err(Error::UnknownFingerprint);
+        }
+
+        // Dust is left out of the balance, since it isn't used by coin selection.
+        let dust_balance = wallet.dust_balance().await?;
+        let balance = wallet.db.balance().await?.saturating_sub(dust_balance);
         let total_coins = wallet.db.total_coin_count().await?;
         let synced_coins = wallet.db.synced_coin_count().await?;
+        let derivations =
+            wallet.db.derivation_index(false).await? + wallet.db.derivation_index(true).await?;
+
+        let synced_height = wallet.db.latest_peak().await?.map(|(height, _)| height);
+        let (peak_height, stalled) = {
+            let peer_state = self.peer_state.lock().await;
+            let peak_height = peer_state.peak().map(|(height, _)| height);
+            (peak_height, peer_state.sync_health() == SyncHealth::Stalled)
+        };
+
+        // Peers can briefly disagree about the peak, so a wallet that's a block or two behind is still synced.
+        let synced = synced_height
+            .zip(peak_height)
+            .is_some_and(|(synced, peak)| synced.saturating_add(SYNCED_HEIGHT_TOLERANCE) >= peak);
 
         let puzzle_hash = match wallet.p2_puzzle_hash(false, false).await {
             Ok(puzzle_hash) => Some(puzzle_hash),
@@ -38,21 +77,24 @@ impl Sage {
         };
 
         let receive_address = puzzle_hash
-            .map(|puzzle_hash| {
-                encode_address(puzzle_hash.to_bytes(), &self.network().address_prefix)
-            })
+            .map(|puzzle_hash| wallet.network.encode_address(puzzle_hash))
             .transpose()?;
 
         Ok(GetSyncStatusResponse {
             balance: Amount::u128(balance),
+            dust_balance: Amount::u128(dust_balance),
             unit: self.unit.clone(),
             total_coins,
             synced_coins,
+            synced_height,
+            peak_height,
+            synced,
+            stalled,
+            derivations,
             receive_address: receive_address.unwrap_or_default(),
-            burn_address: encode_address(
-                hex!("000000000000000000000000000000000000000000000000000000000000dead"),
-                &self.network().address_prefix,
-            )?,
+            burn_address: wallet.network.encode_address(Bytes32::new(hex!(
+                "000000000000000000000000000000000000000000000000000000000000dead"
+            )))?,
         })
     }
 
@@ -68,10 +110,7 @@ impl Sage {
                 Ok(DerivationRecord {
                     index: row.index,
                     public_key: hex::encode(row.synthetic_key.to_bytes()),
-                    address: encode_address(
-                        row.p2_puzzle_hash.to_bytes(),
-                        &self.network().address_prefix,
-                    )?,
+                    address: wallet.network.encode_address(row.p2_puzzle_hash)?,
                 })
             })
             .collect::<Result<Vec<_>>>()?;
@@ -86,7 +125,7 @@ impl Sage {
 
         let rows = wallet.db.p2_coin_states().await?;
 
-        for row in rows {
+        for LabeledCoinStateRow { row, label } in rows {
             let cs = row.coin_state;
 
             let spend_transaction_id = wallet
@@ -103,22 +142,73 @@ impl Sage {
 
             coins.push(CoinRecord {
                 coin_id: hex::encode(cs.coin.coin_id()),
-                address: encode_address(
-                    cs.coin.puzzle_hash.to_bytes(),
-                    &self.network().address_prefix,
-                )?,
+                address: wallet.network.encode_address(cs.coin.puzzle_hash)?,
                 amount: Amount::u64(cs.coin.amount),
                 created_height: cs.created_height,
                 spent_height: cs.spent_height,
                 create_transaction_id: row.transaction_id.map(hex::encode),
                 spend_transaction_id,
                 offer_id,
+                label,
             });
         }
 
         Ok(GetXchCoinsResponse { coins })
     }
 
+    pub async fn get_frozen_coins(&self, _req: GetFrozenCoins) -> Result<GetFrozenCoinsResponse> {
+        let wallet = self.wallet()?;
+
+        let mut coins = Vec::new();
+
+        for LabeledCoinStateRow { row, label } in wallet.db.frozen_coins().await? {
+            let cs = row.coin_state;
+
+            let spend_transaction_id = wallet
+                .db
+                .coin_transaction_id(cs.coin.coin_id())
+                .await?
+                .map(hex::encode);
+
+            let offer_id = wallet
+                .db
+                .coin_offer_id(cs.coin.coin_id())
+                .await?
+                .map(hex::encode);
+
+            coins.push(CoinRecord {
+                coin_id: hex::encode(cs.coin.coin_id()),
+                address: wallet.network.encode_address(cs.coin.puzzle_hash)?,
+                amount: Amount::u64(cs.coin.amount),
+                created_height: cs.created_height,
+                spent_height: cs.spent_height,
+                create_transaction_id: row.transaction_id.map(hex::encode),
+                spend_transaction_id,
+                offer_id,
+                label,
+            });
+        }
+
+        Ok(GetFrozenCoinsResponse { coins })
+    }
+
+    pub async fn get_burn_addresses(
+        &self,
+        _req: GetBurnAddresses,
+    ) -> Result<GetBurnAddressesResponse> {
+        let wallet = self.wallet()?;
+
+        let addresses = wallet
+            .db
+            .burn_puzzle_hashes()
+            .await?
+            .into_iter()
+            .map(|puzzle_hash| Ok(wallet.network.encode_address(puzzle_hash)?))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(GetBurnAddressesResponse { addresses })
+    }
+
     pub async fn get_cat_coins(&self, req: GetCatCoins) -> Result<GetCatCoinsResponse> {
         let wallet = self.wallet()?;
         let asset_id = parse_asset_id(req.asset_id)?;
@@ -127,7 +217,7 @@ impl Sage {
 
         let rows = wallet.db.cat_coin_states(asset_id).await?;
 
-        for row in rows {
+        for LabeledCoinStateRow { row, label } in rows {
             let cs = row.coin_state;
 
             let spend_transaction_id = wallet
@@ -144,22 +234,79 @@ impl Sage {
 
             coins.push(CoinRecord {
                 coin_id: hex::encode(cs.coin.coin_id()),
-                address: encode_address(
-                    cs.coin.puzzle_hash.to_bytes(),
-                    &self.network().address_prefix,
-                )?,
+                address: wallet.network.encode_address(cs.coin.puzzle_hash)?,
                 amount: Amount::u64(cs.coin.amount),
                 created_height: cs.created_height,
                 spent_height: cs.spent_height,
                 create_transaction_id: row.transaction_id.map(hex::encode),
                 spend_transaction_id,
                 offer_id,
+                label,
             });
         }
 
         Ok(GetCatCoinsResponse { coins })
     }
 
+    pub async fn get_spendable_coins(
+        &self,
+        req: GetSpendableCoins,
+    ) -> Result<GetSpendableCoinsResponse> {
+        let wallet = self.wallet()?;
+
+        let (asset_id, amount) = if let Some(asset_id) = req.asset_id {
+            (
+                Some(parse_asset_id(asset_id)?),
+                parse_cat_amount(req.amount)?,
+            )
+        } else {
+            (None, self.parse_amount(req.amount)?)
+        };
+
+        let selected_coins = wallet
+            .preview_coin_selection(asset_id, amount as u128)
+            .await?;
+
+        let selected = selected_coins
+            .iter()
+            .try_fold(0u64, |sum, (coin, _)| sum.checked_add(coin.amount))
+            .ok_or(WalletError::AmountOverflow { asset_id })?;
+
+        let coin_ids: Vec<Bytes32> = selected_coins
+            .iter()
+            .map(|(coin, _)| coin.coin_id())
+            .collect();
+        let mut labels = wallet.db.coin_labels(&coin_ids).await?;
+
+        let mut coins = Vec::with_capacity(selected_coins.len());
+
+        for (coin, p2_puzzle_hash) in selected_coins {
+            let coin_id = coin.coin_id();
+
+            let Some(coin_state) = wallet.db.coin_state(coin_id).await? else {
+                return Err(Error::MissingCoin(coin_id));
+            };
+
+            coins.push(CoinRecord {
+                coin_id: hex::encode(coin_id),
+                address: wallet.network.encode_address(p2_puzzle_hash)?,
+                amount: Amount::u64(coin.amount),
+                created_height: coin_state.created_height,
+                spent_height: coin_state.spent_height,
+                create_transaction_id: None,
+                spend_transaction_id: None,
+                offer_id: None,
+                label: labels.remove(&coin_id),
+            });
+        }
+
+        Ok(GetSpendableCoinsResponse {
+            coins,
+            selected: Amount::u64(selected),
+            change: Amount::u64(selected - amount),
+        })
+    }
+
     pub async fn get_cats(&self, _req: GetCats) -> Result<GetCatsResponse> {
         let wallet = self.wallet()?;
         let cats = wallet.db.cats_by_name().await?;
@@ -177,6 +324,7 @@ impl Sage {
                 icon_url: cat.icon,
                 visible: cat.visible,
                 balance: Amount::u128(balance),
+                decimals: cat.decimals.unwrap_or(self.config.app.default_cat_decimals),
             });
         }
 
@@ -200,6 +348,7 @@ impl Sage {
                     icon_url: cat.icon,
                     visible: cat.visible,
                     balance: Amount::u128(balance),
+                    decimals: cat.decimals.unwrap_or(self.config.app.default_cat_decimals),
                 })
             })
             .transpose()?;
@@ -207,6 +356,45 @@ impl Sage {
         Ok(GetCatResponse { cat })
     }
 
+    /// Lists the CATs in the token list, which is bundled for mainnet so that it works offline.
+    /// Once refreshed, the token list is cached for the current network and used from then on.
+    pub async fn get_known_cats(&self, req: GetKnownCats) -> Result<GetKnownCatsResponse> {
+        let genesis_challenge = &self.network().genesis_challenge;
+        let mainnet = *genesis_challenge == MAINNET.genesis_challenge;
+        let testnet = *genesis_challenge == TESTNET11.genesis_challenge;
+
+        let network_id = &self.config.network.network_id;
+        let cache_path = self.path.join(format!("known_cats-{network_id}.json"));
+
+        let cats = if req.refresh && (mainnet || testnet) {
+            let cats = fetch_known_cats(testnet, self.config.app.default_cat_decimals).await?;
+            fs::write(
+                &cache_path,
+                serde_json::to_string(&cats).map_err(WalletError::from)?,
+            )?;
+            cats
+        } else if cache_path.try_exists()? {
+            parse_known_cats(&fs::read_to_string(&cache_path)?)?
+        } else if mainnet {
+            bundled_known_cats()
+        } else {
+            Vec::new()
+        };
+
+        Ok(GetKnownCatsResponse {
+            cats: cats
+                .into_iter()
+                .map(|cat| KnownCatRecord {
+                    asset_id: cat.asset_id,
+                    name: cat.name,
+                    ticker: cat.ticker,
+                    decimals: cat.decimals,
+                    icon_url: cat.icon,
+                })
+                .collect(),
+        })
+    }
+
     pub async fn get_dids(&self, _req: GetDids) -> Result<GetDidsResponse> {
         let wallet = self.wallet()?;
 
@@ -222,10 +410,7 @@ impl Sage {
                 name: row.name,
                 visible: row.visible,
                 coin_id: hex::encode(did.coin_id),
-                address: encode_address(
-                    did.p2_puzzle_hash.to_bytes(),
-                    &self.network().address_prefix,
-                )?,
+                address: wallet.network.encode_address(did.p2_puzzle_hash)?,
                 amount: Amount::u64(did.amount),
                 created_height: did.created_height,
                 create_transaction_id: did.transaction_id.map(hex::encode),
@@ -241,20 +426,38 @@ impl Sage {
     ) -> Result<GetPendingTransactionsResponse> {
         let wallet = self.wallet()?;
 
-        let transactions = wallet
-            .db
-            .transactions()
-            .await?
-            .into_iter()
-            .map(|tx| {
-                Result::Ok(PendingTransactionRecord {
-                    transaction_id: hex::encode(tx.transaction_id),
-                    fee: Amount::u64(tx.fee),
-                    // TODO: Date format?
-                    submitted_at: tx.submitted_at.map(|ts| ts.to_string()),
-                })
-            })
-            .collect::<Result<Vec<_>>>()?;
+        let mut transactions = Vec::new();
+
+        for tx in wallet.db.transactions().await? {
+            let mut spent = Vec::new();
+            let mut created = Vec::new();
+
+            for row in wallet
+                .db
+                .get_coin_states_spent_by_transaction(tx.transaction_id)
+                .await?
+            {
+                spent.push(self.transaction_coin(&wallet.db, row).await?);
+            }
+
+            for row in wallet
+                .db
+                .get_coin_states_created_by_transaction(tx.transaction_id)
+                .await?
+            {
+                created.push(self.transaction_coin(&wallet.db, row).await?);
+            }
+
+            transactions.push(PendingTransactionRecord {
+                transaction_id: hex::encode(tx.transaction_id),
+                fee: Amount::u64(tx.fee),
+                // TODO: Date format?
+                submitted_at: tx.submitted_at.map(|ts| ts.to_string()),
+                spent,
+                created,
+                rebroadcasts: tx.submission_count.saturating_sub(1),
+            });
+        }
 
         Ok(GetPendingTransactionsResponse { transactions })
     }
@@ -314,6 +517,23 @@ impl Sage {
         })
     }
 
+    pub async fn get_nft_count(&self, req: GetNftCount) -> Result<GetNftCountResponse> {
+        let wallet = self.wallet()?;
+
+        if req
+            .fingerprint
+            .is_some_and(|fingerprint| fingerprint != wallet.fingerprint)
+        {
+            return Err(Error::UnknownFingerprint);
+        }
+
+        let collection_id = req.collection_id.map(parse_collection_id).transpose()?;
+
+        let (total, pending) = wallet.db.owned_nft_counts(collection_id).await?;
+
+        Ok(GetNftCountResponse { total, pending })
+    }
+
     pub async fn get_nft_collections(
         &self,
         req: GetNftCollections,
@@ -345,6 +565,7 @@ impl Sage {
                 visible: col.visible,
                 name: col.name,
                 icon: col.icon,
+                banner: col.banner,
                 nfts: total,
                 visible_nfts: total_visible,
             });
@@ -392,6 +613,7 @@ impl Sage {
                 visible: collection.visible,
                 name: collection.name,
                 icon: collection.icon,
+                banner: collection.banner,
                 nfts: total,
                 visible_nfts: total_visible,
             }
@@ -403,6 +625,7 @@ impl Sage {
                 visible: true,
                 name: Some("Uncategorized".to_string()),
                 icon: None,
+                banner: None,
                 nfts: total,
                 visible_nfts: total_visible,
             }
@@ -413,6 +636,50 @@ impl Sage {
         })
     }
 
+    pub async fn get_nft_groups(&self, _req: GetNftGroups) -> Result<GetNftGroupsResponse> {
+        let wallet = self.wallet()?;
+
+        let groups = wallet
+            .db
+            .nft_groups_by_metadata_hash()
+            .await?
+            .into_iter()
+            .map(|group| {
+                Ok(NftGroup {
+                    metadata_hash: group.metadata_hash.map(hex::encode),
+                    nft_ids: group
+                        .launcher_ids
+                        .into_iter()
+                        .map(|launcher_id| encode_address(launcher_id.to_bytes(), "nft"))
+                        .collect::<std::result::Result<_, _>>()?,
+                    count: group.count,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(GetNftGroupsResponse { groups })
+    }
+
+    pub async fn get_rarity(&self, req: GetRarity) -> Result<GetRarityResponse> {
+        let wallet = self.wallet()?;
+
+        let collection_id = parse_collection_id(req.collection_id)?;
+
+        let nfts = wallet
+            .compute_rarity(collection_id)
+            .await?
+            .into_iter()
+            .map(|(launcher_id, score)| {
+                Ok(NftRarity {
+                    nft_id: encode_address(launcher_id.to_bytes(), "nft")?,
+                    score,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(GetRarityResponse { nfts })
+    }
+
     pub async fn get_nfts(&self, req: GetNfts) -> Result<GetNftsResponse> {
         let wallet = self.wallet()?;
 
@@ -539,6 +806,35 @@ impl Sage {
         })
     }
 
+    pub async fn get_nft_provenance(
+        &self,
+        req: GetNftProvenance,
+    ) -> Result<GetNftProvenanceResponse> {
+        let wallet = self.wallet()?;
+
+        let nft_id = parse_nft_id(req.nft_id)?;
+
+        let provenance = wallet.nft_provenance(nft_id).await?;
+
+        let entries = provenance
+            .entries
+            .into_iter()
+            .map(|entry| {
+                Ok(NftProvenanceEntry {
+                    coin_id: hex::encode(entry.coin_id),
+                    owner_address: wallet.network.encode_address(entry.owner_puzzle_hash)?,
+                    created_height: entry.created_height,
+                    spent_height: entry.spent_height,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(GetNftProvenanceResponse {
+            entries,
+            complete: provenance.complete,
+        })
+    }
+
     pub async fn get_nft_data(&self, req: GetNftData) -> Result<GetNftDataResponse> {
         let wallet = self.wallet()?;
 
@@ -571,13 +867,22 @@ impl Sage {
             data: Some(NftData {
                 blob: data.as_ref().map(|data| BASE64_STANDARD.encode(&data.blob)),
                 mime_type: data.map(|data| data.mime_type),
-                metadata_json: offchain_metadata.and_then(|offchain_metadata| {
+                metadata_json: if let Some(offchain_metadata) = offchain_metadata {
                     if offchain_metadata.mime_type == "application/json" {
                         String::from_utf8(offchain_metadata.blob).ok()
                     } else {
                         None
                     }
-                }),
+                } else if metadata
+                    .as_ref()
+                    .map_or(true, |metadata| metadata.metadata_uris.is_empty())
+                {
+                    // NFTs without off-chain metadata may describe themselves on-chain instead.
+                    onchain_metadata_json(&nft.info.metadata)
+                        .and_then(|blob| String::from_utf8(blob).ok())
+                } else {
+                    None
+                },
             }),
         })
     }
@@ -596,6 +901,8 @@ impl Sage {
         let metadata_hash = metadata.as_ref().and_then(|m| m.metadata_hash);
         let license_hash = metadata.as_ref().and_then(|m| m.license_hash);
 
+        let network = self.wallet_network()?;
+
         Ok(NftRecord {
             launcher_id: encode_address(nft_row.launcher_id.to_bytes(), "nft")?,
             collection_id: nft_row
@@ -614,15 +921,10 @@ impl Sage {
             visible: nft_row.visible,
             name: nft_row.name,
             sensitive_content: nft_row.sensitive_content,
+            burned: nft_row.is_burned,
             coin_id: hex::encode(nft.coin.coin_id()),
-            address: encode_address(
-                nft.info.p2_puzzle_hash.to_bytes(),
-                &self.network().address_prefix,
-            )?,
-            royalty_address: encode_address(
-                nft.info.royalty_puzzle_hash.to_bytes(),
-                &self.network().address_prefix,
-            )?,
+            address: network.encode_address(nft.info.p2_puzzle_hash)?,
+            royalty_address: network.encode_address(nft.info.royalty_puzzle_hash)?,
             royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
             data_uris: metadata
                 .as_ref()
@@ -657,6 +959,8 @@ impl Sage {
         let (kind, p2_puzzle_hash) = match coin.kind {
             CoinKind::Unknown => (AssetKind::Unknown, None),
             CoinKind::Xch => (AssetKind::Xch, Some(coin.coin_state.coin.puzzle_hash)),
+            CoinKind::Clawback => (AssetKind::Clawback, None),
+            CoinKind::Settlement => (AssetKind::Xch, None),
             CoinKind::Cat => {
                 if let Some(cat) = db.cat_coin(coin_id).await? {
                     if let Some(row) = db.cat(cat.asset_id).await? {
@@ -734,14 +1038,15 @@ impl Sage {
             }
         };
 
+        let network = self.wallet_network()?;
+
         Ok(TransactionCoin {
             coin_id: hex::encode(coin_id),
             address: p2_puzzle_hash
-                .map(|p2_puzzle_hash| {
-                    encode_address(p2_puzzle_hash.to_bytes(), &self.network().address_prefix)
-                })
+                .map(|p2_puzzle_hash| network.encode_address(p2_puzzle_hash))
                 .transpose()?,
             amount: Amount::u64(coin.coin_state.coin.amount),
+            asset: kind.asset_id(),
             kind,
         })
     }
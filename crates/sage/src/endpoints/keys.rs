@@ -12,7 +12,7 @@ use sage_api::{
     RenameKeyResponse, Resync, ResyncResponse, SecretKeyInfo,
 };
 
-use crate::{Error, Result, Sage};
+use crate::{parse_derivation_path, Error, Result, Sage};
 
 impl Sage {
     pub async fn login(&mut self, req: Login) -> Result<LoginResponse> {
@@ -82,6 +82,8 @@ impl Sage {
     }
 
     pub async fn import_key(&mut self, req: ImportKey) -> Result<ImportKeyResponse> {
+        let derivation_path = req.derivation_path.map(parse_derivation_path).transpose()?;
+
         let mut key_hex = req.key.as_str();
 
         if key_hex.starts_with("0x") || key_hex.starts_with("0X") {
@@ -116,6 +118,7 @@ impl Sage {
 
         let config = self.wallet_config_mut(fingerprint);
         config.name = req.name;
+        config.derivation_path = derivation_path.map(|path| path.to_string());
         self.config.app.active_fingerprint = Some(fingerprint);
 
         self.save_keychain()?;
@@ -1,32 +1,35 @@
 use std::{
+    cmp::Ordering,
     collections::HashMap,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use base64::{prelude::BASE64_STANDARD, Engine};
-use chia::{clvm_traits::FromClvm, protocol::SpendBundle, puzzles::nft::NftMetadata};
-use chia_wallet_sdk::{encode_address, AggSigConstants, Offer, SpendContext};
+use chia::{clvm_traits::FromClvm, protocol::Bytes, puzzles::nft::NftMetadata};
+use chia_wallet_sdk::{encode_address, SpendContext};
 use chrono::{Local, TimeZone};
 use clvmr::Allocator;
 use indexmap::IndexMap;
 use sage_api::{
-    Amount, CatAmount, DeleteOffer, DeleteOfferResponse, GetOffer, GetOfferResponse, GetOffers,
-    GetOffersResponse, ImportOffer, ImportOfferResponse, MakeOffer, MakeOfferResponse, OfferAssets,
-    OfferCat, OfferNft, OfferRecord, OfferRecordStatus, OfferSummary, OfferXch, TakeOffer,
+    Amount, AssetId, BestOffer, CatAmount, CompareOffers, CompareOffersResponse, DeleteOffer,
+    DeleteOfferResponse, GetOffer, GetOfferResponse, GetOffers, GetOffersResponse, ImportOffer,
+    ImportOfferResponse, MakeOffer, MakeOfferResponse, OfferAssets, OfferCat, OfferNft,
+    OfferRecord, OfferRecordStatus, OfferSummary, OfferXch, SpendBundleJson, TakeOffer,
     TakeOfferResponse, ViewOffer, ViewOfferResponse,
 };
 use sage_database::{OfferCatRow, OfferNftRow, OfferRow, OfferStatus, OfferXchRow};
 use sage_wallet::{
-    calculate_royalties, fetch_nft_offer_details, insert_transaction, lookup_from_uris_with_hash,
-    parse_locked_coins, parse_offer_payments, MakerSide, NftRoyaltyInfo, SyncCommand, TakerSide,
-    Transaction, Wallet,
+    calculate_royalties, decode_offer, fetch_nft_offer_details, insert_transaction,
+    lookup_from_uris_with_hash, parse_locked_coins, parse_offer_payments, BindingMode, MakerSide,
+    NftRoyaltyInfo, OfferAmounts, OfferExt, OfferPrice, OverfillPolicy, SyncCommand, TakeOptions,
+    TakerSide, Transaction, Wallet,
 };
 use tracing::{debug, warn};
 
 use crate::{
-    extract_nft_data, json_bundle, lookup_coin_creation, offer_expiration, parse_asset_id,
-    parse_cat_amount, parse_genesis_challenge, parse_nft_id, parse_offer_id, ConfirmationInfo,
-    Error, ExtractedNftData, Result, Sage,
+    encoded_offer_royalties, extract_nft_data, lookup_coin_creation, offer_expiration,
+    parse_asset_id, parse_cat_amount, parse_coin_id, parse_genesis_challenge, parse_nft_id,
+    parse_offer_id, parse_puzzle_hash, ConfirmationInfo, Error, ExtractedNftData, Result, Sage,
 };
 
 impl Sage {
@@ -37,8 +40,24 @@ impl Sage {
 
         let mut offered_cats = IndexMap::new();
 
-        for CatAmount { asset_id, amount } in req.offered_assets.cats {
-            offered_cats.insert(parse_asset_id(asset_id)?, parse_cat_amount(amount)?);
+        for CatAmount {
+            asset_id,
+            amount,
+            puzzle_hash,
+        } in req.offered_assets.cats
+        {
+            let asset_id = parse_asset_id(asset_id)?;
+
+            // The puzzle hash is only where requested CATs are paid to.
+            if puzzle_hash.is_some() {
+                return Err(Error::OfferedCatPuzzleHash(asset_id));
+            }
+
+            offered_cats.insert(
+                asset_id,
+                self.parse_cat_amount_with_units(asset_id, amount, req.display_units)
+                    .await?,
+            );
         }
 
         let mut offered_nfts = Vec::new();
@@ -50,9 +69,25 @@ impl Sage {
         let requested_xch = self.parse_amount(req.requested_assets.xch)?;
 
         let mut requested_cats = IndexMap::new();
+        let mut requested_cat_puzzle_hashes = IndexMap::new();
 
-        for CatAmount { asset_id, amount } in req.requested_assets.cats {
-            requested_cats.insert(parse_asset_id(asset_id)?, parse_cat_amount(amount)?);
+        for CatAmount {
+            asset_id,
+            amount,
+            puzzle_hash,
+        } in req.requested_assets.cats
+        {
+            let asset_id = parse_asset_id(asset_id)?;
+
+            requested_cats.insert(
+                asset_id,
+                self.parse_cat_amount_with_units(asset_id, amount, req.display_units)
+                    .await?,
+            );
+
+            if let Some(puzzle_hash) = puzzle_hash {
+                requested_cat_puzzle_hashes.insert(asset_id, parse_puzzle_hash(puzzle_hash)?);
+            }
         }
 
         let mut requested_nfts = IndexMap::new();
@@ -65,17 +100,47 @@ impl Sage {
 
             let peer = peer.as_ref().ok_or(Error::NoPeers)?;
 
+            let memos = req
+                .requested_nft_memos
+                .get(&nft_id)
+                .cloned()
+                .unwrap_or_default();
+
             let nft_id = parse_nft_id(nft_id)?;
 
-            let Some(offer_details) = fetch_nft_offer_details(peer, nft_id).await? else {
+            let Some(mut offer_details) = fetch_nft_offer_details(peer, nft_id).await? else {
                 return Err(Error::CouldNotFetchNft(nft_id));
             };
 
+            for memo in memos {
+                offer_details.memos.push(Bytes::from(hex::decode(memo)?));
+            }
+
             requested_nfts.insert(nft_id, offer_details);
         }
 
         let fee = self.parse_amount(req.fee)?;
 
+        let coin_ids = req
+            .coin_ids
+            .into_iter()
+            .map(parse_coin_id)
+            .collect::<Result<Vec<_>>>()?;
+
+        let recipient_override = req
+            .recipient_puzzle_hash
+            .map(parse_puzzle_hash)
+            .transpose()?;
+
+        let offer_memo = req
+            .offer_memo
+            .map(hex::decode)
+            .transpose()?
+            .map(Bytes::from);
+
+        // The offered coins aren't locked until the offer is imported, so this only keeps them
+        // from being selected by a transaction that's being built at the same time.
+        let _guard = wallet.transaction_guard().await;
         let unsigned = wallet
             .make_offer(
                 MakerSide {
@@ -83,12 +148,19 @@ impl Sage {
                     cats: offered_cats,
                     nfts: offered_nfts,
                     fee,
+                    coin_ids,
+                    allow_change: req.allow_change,
+                    binding_mode: BindingMode::default(),
                 },
                 TakerSide {
                     xch: requested_xch,
                     cats: requested_cats,
                     nfts: requested_nfts,
+                    cat_puzzle_hashes: requested_cat_puzzle_hashes,
+                    recipient_override,
+                    offer_memo,
                 },
+                req.starts_at_second,
                 req.expires_at_second,
                 false,
                 true,
@@ -104,24 +176,59 @@ impl Sage {
         let offer = wallet
             .sign_make_offer(
                 unsigned,
-                &AggSigConstants::new(parse_genesis_challenge(self.network().agg_sig_me.clone())?),
+                &self.wallet_network()?.agg_sig_constants(),
                 master_sk,
             )
             .await?;
 
         Ok(MakeOfferResponse {
             offer: offer.encode()?,
-            offer_id: hex::encode(SpendBundle::from(offer).name()),
+            offer_id: hex::encode(offer.offer_id()),
         })
     }
 
     pub async fn take_offer(&self, req: TakeOffer) -> Result<TakeOfferResponse> {
         let wallet = self.wallet()?;
 
-        let offer = Offer::decode(&req.offer)?;
+        let offer = decode_offer(&req.offer)?;
         let fee = self.parse_amount(req.fee)?;
 
-        let unsigned = wallet.take_offer(offer, fee, false, true).await?;
+        let mut overfill = OfferAmounts {
+            xch: req
+                .overfill_xch
+                .map(|amount| self.parse_amount(amount))
+                .transpose()?
+                .unwrap_or(0),
+            cats: IndexMap::new(),
+        };
+
+        for (asset_id, amount) in req.overfill_cats {
+            overfill
+                .cats
+                .insert(parse_asset_id(asset_id)?, parse_cat_amount(amount)?);
+        }
+
+        // Nothing is overfilled unless it's asked for, in which case it goes to the maker rather
+        // than being left in the settlement coin.
+        let policy = if overfill.xch > 0 || overfill.cats.values().any(|&amount| amount > 0) {
+            OverfillPolicy::PayMaker
+        } else {
+            OverfillPolicy::Reject
+        };
+
+        let _guard = wallet.transaction_guard().await;
+        let unsigned = wallet
+            .take_offer_with_options(
+                offer,
+                TakeOptions {
+                    fee,
+                    overfill,
+                    policy,
+                    allow_royalty_waiver: req.allow_royalty_waiver,
+                    ..Default::default()
+                },
+            )
+            .await?;
 
         let (_mnemonic, Some(master_sk)) =
             self.keychain.extract_secrets(wallet.fingerprint, b"")?
@@ -132,14 +239,14 @@ impl Sage {
         let spend_bundle = wallet
             .sign_take_offer(
                 unsigned,
-                &AggSigConstants::new(parse_genesis_challenge(self.network().agg_sig_me.clone())?),
+                &self.wallet_network()?.agg_sig_constants(),
                 master_sk,
             )
             .await?;
 
         debug!(
             "{}",
-            serde_json::to_string(&json_bundle(&spend_bundle)).expect("msg")
+            serde_json::to_string(&SpendBundleJson::from(&spend_bundle)).expect("msg")
         );
 
         if req.auto_submit {
@@ -153,7 +260,7 @@ impl Sage {
             let subscriptions = insert_transaction(
                 &wallet.db,
                 &peer,
-                wallet.genesis_challenge,
+                wallet.network.genesis_challenge(),
                 spend_bundle.name(),
                 Transaction::from_coin_spends(spend_bundle.coin_spends.clone())?,
                 spend_bundle.aggregated_signature.clone(),
@@ -167,7 +274,7 @@ impl Sage {
                 .await?;
         }
 
-        let json_bundle = json_bundle(&spend_bundle);
+        let json_bundle = SpendBundleJson::from(&spend_bundle);
         let transaction_id = hex::encode(spend_bundle.name());
 
         Ok(TakeOfferResponse {
@@ -180,15 +287,39 @@ impl Sage {
     }
 
     pub async fn view_offer(&self, req: ViewOffer) -> Result<ViewOfferResponse> {
-        let offer = self.summarize_offer(Offer::decode(&req.offer)?).await?;
+        let offer = self.summarize_offer(decode_offer(&req.offer)?).await?;
 
         Ok(ViewOfferResponse { offer })
     }
 
+    pub fn compare_offers(&self, req: CompareOffers) -> Result<CompareOffersResponse> {
+        let first = OfferPrice::from_offer(decode_offer(&req.first)?)?;
+        let second = OfferPrice::from_offer(decode_offer(&req.second)?)?;
+
+        let best = first
+            .as_ref()
+            .zip(second.as_ref())
+            .and_then(|(first, second)| first.compare(second))
+            .map(|ordering| match ordering {
+                Ordering::Less => BestOffer::First,
+                Ordering::Greater => BestOffer::Second,
+                Ordering::Equal => BestOffer::Equal,
+            });
+
+        Ok(CompareOffersResponse {
+            comparable: best.is_some(),
+            first_price: first.as_ref().map(OfferPrice::price),
+            second_price: second.as_ref().map(OfferPrice::price),
+            first_fee: first.as_ref().map(|price| Amount::u64(price.fee)),
+            second_fee: second.as_ref().map(|price| Amount::u64(price.fee)),
+            best,
+        })
+    }
+
     pub async fn import_offer(&self, req: ImportOffer) -> Result<ImportOfferResponse> {
         let wallet = self.wallet()?;
-        let offer = Offer::decode(&req.offer)?;
-        let spend_bundle: SpendBundle = offer.clone().into();
+        let offer = decode_offer(&req.offer)?;
+        let offer_id = offer.offer_id();
         let peer = self.peer_state.lock().await.acquire_peer();
 
         let mut allocator = Allocator::new();
@@ -227,7 +358,7 @@ impl Sage {
                 })
                 .collect::<Vec<_>>(),
         )?
-        .amounts();
+        .amounts()?;
 
         let taker_royalties = calculate_royalties(
             &taker_amounts,
@@ -241,9 +372,7 @@ impl Sage {
                 })
                 .collect::<Vec<_>>(),
         )?
-        .amounts();
-
-        let offer_id = spend_bundle.name();
+        .amounts()?;
 
         let mut cat_rows = Vec::new();
         let mut nft_rows = Vec::new();
@@ -489,7 +618,6 @@ impl Sage {
         let offer_id = hex::decode(&req.offer_id)?;
 
         wallet.db.delete_offer(offer_id.try_into()?).await?;
-
         Ok(DeleteOfferResponse {})
     }
 
@@ -498,6 +626,10 @@ impl Sage {
         let cats = wallet.db.offer_cats(offer.offer_id).await?;
         let nfts = wallet.db.offer_nfts(offer.offer_id).await?;
 
+        // Only the royalty totals are stored, so the individual payments are recalculated
+        // from the encoded offer.
+        let (maker_royalties, taker_royalties) = encoded_offer_royalties(&offer.encoded_offer)?;
+
         let mut maker_xch_amount = 0u128;
         let mut maker_xch_royalty = 0u128;
         let mut taker_xch_amount = 0u128;
@@ -519,12 +651,29 @@ impl Sage {
         for cat in cats {
             let asset_id = hex::encode(cat.asset_id);
 
+            let royalties = if cat.requested {
+                &taker_royalties
+            } else {
+                &maker_royalties
+            };
+
             let record = OfferCat {
+                asset: AssetId::cat(asset_id.clone()),
                 amount: Amount::u64(cat.amount),
                 royalty: Amount::u64(cat.royalty),
+                royalties: self.royalty_payments(
+                    royalties.cats.get(&cat.asset_id).map_or(&[], Vec::as_slice),
+                )?,
+                decimals: wallet
+                    .db
+                    .cat(cat.asset_id)
+                    .await?
+                    .and_then(|row| row.decimals)
+                    .unwrap_or(self.config.app.default_cat_decimals),
                 name: cat.name,
                 ticker: cat.ticker,
                 icon_url: cat.icon,
+                tail_revealed: false,
             };
 
             if cat.requested {
@@ -541,10 +690,8 @@ impl Sage {
             let nft_id = encode_address(nft.launcher_id.into(), "nft")?;
 
             let record = OfferNft {
-                royalty_address: encode_address(
-                    nft.royalty_puzzle_hash.into(),
-                    &self.network().address_prefix,
-                )?,
+                asset: AssetId::nft(nft_id.clone()),
+                royalty_address: wallet.network.encode_address(nft.royalty_puzzle_hash)?,
                 royalty_ten_thousandths: nft.royalty_ten_thousandths,
                 name: nft.name,
                 image_data: nft.thumbnail.map(|data| BASE64_STANDARD.encode(data)),
@@ -566,6 +713,7 @@ impl Sage {
                 OfferStatus::Completed => OfferRecordStatus::Completed,
                 OfferStatus::Cancelled => OfferRecordStatus::Cancelled,
                 OfferStatus::Expired => OfferRecordStatus::Expired,
+                OfferStatus::PendingCancel => OfferRecordStatus::PendingCancel,
             },
             creation_date: Local
                 .timestamp_opt(offer.inserted_timestamp.try_into()?, 0)
@@ -575,19 +723,27 @@ impl Sage {
             summary: OfferSummary {
                 maker: OfferAssets {
                     xch: OfferXch {
+                        asset: AssetId::Xch,
                         amount: Amount::u128(maker_xch_amount),
                         royalty: Amount::u128(maker_xch_royalty),
+                        royalties: self.royalty_payments(&maker_royalties.xch)?,
+                        decimals: self.network().precision,
                     },
                     cats: maker_cats,
                     nfts: maker_nfts,
+                    unknown: Vec::new(),
                 },
                 taker: OfferAssets {
                     xch: OfferXch {
+                        asset: AssetId::Xch,
                         amount: Amount::u128(taker_xch_amount),
                         royalty: Amount::u128(taker_xch_royalty),
+                        royalties: self.royalty_payments(&taker_royalties.xch)?,
+                        decimals: self.network().precision,
                     },
                     cats: taker_cats,
                     nfts: taker_nfts,
+                    unknown: Vec::new(),
                 },
                 fee: Amount::u64(offer.fee),
             },
@@ -5,8 +5,11 @@ use sage_api::{
     AddPeer, AddPeerResponse, GetNetworks, GetNetworksResponse, GetPeers, GetPeersResponse,
     PeerRecord, RemovePeer, RemovePeerResponse, SetDerivationBatchSize,
     SetDerivationBatchSizeResponse, SetDeriveAutomatically, SetDeriveAutomaticallyResponse,
-    SetDiscoverPeers, SetDiscoverPeersResponse, SetNetworkId, SetNetworkIdResponse, SetTargetPeers,
-    SetTargetPeersResponse,
+    SetDiscoverPeers, SetDiscoverPeersResponse, SetDustThreshold, SetDustThresholdResponse,
+    SetMaxBundleCost, SetMaxBundleCostResponse, SetMaxDerivationIndex,
+    SetMaxDerivationIndexResponse, SetMaxOfferSpends, SetMaxOfferSpendsResponse,
+    SetMinConfirmations, SetMinConfirmationsResponse, SetNetworkId, SetNetworkIdResponse,
+    SetTargetPeers, SetTargetPeersResponse,
 };
 use sage_wallet::SyncCommand;
 
@@ -134,6 +137,84 @@ impl Sage {
         Ok(SetDerivationBatchSizeResponse {})
     }
 
+    pub async fn set_max_derivation_index(
+        &mut self,
+        req: SetMaxDerivationIndex,
+    ) -> Result<SetMaxDerivationIndexResponse> {
+        let config = self.try_wallet_config_mut(req.fingerprint);
+        config.max_derivation_index = req.max_derivation_index;
+        self.save_config()?;
+
+        if self.config.app.active_fingerprint == Some(req.fingerprint) {
+            self.switch_wallet().await?;
+        }
+
+        Ok(SetMaxDerivationIndexResponse {})
+    }
+
+    pub async fn set_max_bundle_cost(
+        &mut self,
+        req: SetMaxBundleCost,
+    ) -> Result<SetMaxBundleCostResponse> {
+        let config = self.try_wallet_config_mut(req.fingerprint);
+        config.max_bundle_cost = req.max_bundle_cost;
+        self.save_config()?;
+
+        // The limit is passed to the wallet when it's loaded, so reload it if it's active.
+        if self.config.app.active_fingerprint == Some(req.fingerprint) {
+            self.switch_wallet().await?;
+        }
+
+        Ok(SetMaxBundleCostResponse {})
+    }
+
+    pub async fn set_max_offer_spends(
+        &mut self,
+        req: SetMaxOfferSpends,
+    ) -> Result<SetMaxOfferSpendsResponse> {
+        let config = self.try_wallet_config_mut(req.fingerprint);
+        config.max_offer_spends = req.max_offer_spends;
+        self.save_config()?;
+
+        if self.config.app.active_fingerprint == Some(req.fingerprint) {
+            self.switch_wallet().await?;
+        }
+
+        Ok(SetMaxOfferSpendsResponse {})
+    }
+
+    pub async fn set_dust_threshold(
+        &mut self,
+        req: SetDustThreshold,
+    ) -> Result<SetDustThresholdResponse> {
+        let dust_threshold = self.parse_amount(req.dust_threshold)?;
+
+        let config = self.try_wallet_config_mut(req.fingerprint);
+        config.dust_threshold = dust_threshold;
+        self.save_config()?;
+
+        if self.config.app.active_fingerprint == Some(req.fingerprint) {
+            self.switch_wallet().await?;
+        }
+
+        Ok(SetDustThresholdResponse {})
+    }
+
+    pub async fn set_min_confirmations(
+        &mut self,
+        req: SetMinConfirmations,
+    ) -> Result<SetMinConfirmationsResponse> {
+        let config = self.try_wallet_config_mut(req.fingerprint);
+        config.min_confirmations = req.min_confirmations;
+        self.save_config()?;
+
+        if self.config.app.active_fingerprint == Some(req.fingerprint) {
+            self.switch_wallet().await?;
+        }
+
+        Ok(SetMinConfirmationsResponse {})
+    }
+
     pub fn get_networks(&mut self, _req: GetNetworks) -> Result<GetNetworksResponse> {
         Ok(GetNetworksResponse {
             networks: self.networks.clone(),
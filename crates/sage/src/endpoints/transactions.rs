@@ -1,22 +1,24 @@
 use std::time::Duration;
 
 use chia::{
-    protocol::{Bytes, CoinSpend},
+    protocol::{Bytes, Bytes32, CoinSpend, SpendBundle},
     puzzles::nft::NftMetadata,
 };
 use chia_wallet_sdk::MetadataUpdate;
 use sage_api::{
-    AddNftUri, AssignNftsToDid, BulkMintNfts, CombineCat, CombineXch, CreateDid, IssueCat,
-    NftUriKind, SendCat, SendXch, SignCoinSpends, SignCoinSpendsResponse, SplitCat, SplitXch,
-    SubmitTransaction, SubmitTransactionResponse, TransactionResponse, TransferDids, TransferNfts,
-    ViewCoinSpends, ViewCoinSpendsResponse,
+    decode_spend_bundle, encode_spend_bundle, AddNftUri, AssignNftsToDid, BulkMintNfts,
+    CoinSpendJson, CombineCat, CombineXch, CreateDid, DecodeSpendBundle, DecodeSpendBundleResponse,
+    EncodeSpendBundle, EncodeSpendBundleResponse, IssueCat, NftOwner, NftUriKind, SendCat, SendXch,
+    SignCoinSpends, SignCoinSpendsResponse, SpendBundleJson, SplitCat, SplitXch, SubmitTransaction,
+    SubmitTransactionResponse, TransactionResponse, TransferDids, TransferNfts, ViewCoinSpends,
+    ViewCoinSpendsResponse,
 };
 use sage_database::CatRow;
 use sage_wallet::{fetch_uris, WalletNftMint};
 
 use crate::{
-    fetch_cats, fetch_coins, json_bundle, json_spend, parse_asset_id, parse_cat_amount,
-    parse_did_id, parse_nft_id, rust_bundle, rust_spend, ConfirmationInfo, Result, Sage,
+    fetch_cats, fetch_coins, parse_asset_id, parse_cat_amount, parse_did_id, parse_nft_id,
+    ConfirmationInfo, Result, Sage,
 };
 
 impl Sage {
@@ -32,6 +34,7 @@ impl Sage {
             memos.push(Bytes::from(hex::decode(memo)?));
         }
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
             .send_xch(puzzle_hash, amount, fee, memos, false, true)
             .await?;
@@ -43,6 +46,7 @@ impl Sage {
         let fee = self.parse_amount(req.fee)?;
         let coins = fetch_coins(&wallet, req.coin_ids).await?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet.combine_xch(coins, fee, false, true).await?;
         self.transact(coin_spends, req.auto_submit).await
     }
@@ -52,6 +56,7 @@ impl Sage {
         let fee = self.parse_amount(req.fee)?;
         let coins = fetch_coins(&wallet, req.coin_ids).await?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
             .split_xch(&coins, req.output_count as usize, fee, false, true)
             .await?;
@@ -63,6 +68,7 @@ impl Sage {
         let fee = self.parse_amount(req.fee)?;
         let cats = fetch_cats(&wallet, req.coin_ids).await?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet.combine_cat(cats, fee, false, true).await?;
         self.transact(coin_spends, req.auto_submit).await
     }
@@ -72,6 +78,7 @@ impl Sage {
         let fee = self.parse_amount(req.fee)?;
         let cats = fetch_cats(&wallet, req.coin_ids).await?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
             .split_cat(cats, req.output_count as usize, fee, false, true)
             .await?;
@@ -83,6 +90,7 @@ impl Sage {
         let amount = parse_cat_amount(req.amount)?;
         let fee = self.parse_amount(req.fee)?;
 
+        let _guard = wallet.transaction_guard().await;
         let (coin_spends, asset_id) = wallet.issue_cat(amount, fee, None, false, true).await?;
         wallet
             .db
@@ -94,6 +102,7 @@ impl Sage {
                 icon: None,
                 visible: true,
                 fetched: true,
+                decimals: None,
             })
             .await?;
 
@@ -104,7 +113,9 @@ impl Sage {
         let wallet = self.wallet()?;
         let asset_id = parse_asset_id(req.asset_id)?;
         let puzzle_hash = self.parse_address(req.address)?;
-        let amount = parse_cat_amount(req.amount)?;
+        let amount = self
+            .parse_cat_amount_with_units(asset_id, req.amount, req.display_units)
+            .await?;
         let fee = self.parse_amount(req.fee)?;
 
         let mut memos = Vec::new();
@@ -113,6 +124,7 @@ impl Sage {
             memos.push(Bytes::from(hex::decode(memo)?));
         }
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
             .send_cat(asset_id, puzzle_hash, amount, fee, memos, false, true)
             .await?;
@@ -123,6 +135,7 @@ impl Sage {
         let wallet = self.wallet()?;
         let fee = self.parse_amount(req.fee)?;
 
+        let _guard = wallet.transaction_guard().await;
         let (coin_spends, did) = wallet.create_did(fee, false, true).await?;
         wallet
             .db
@@ -214,6 +227,7 @@ impl Sage {
             });
         }
 
+        let _guard = wallet.transaction_guard().await;
         let (coin_spends, _nfts, _did) = wallet
             .bulk_mint_nfts(fee, did_id, mints, false, true)
             .await?;
@@ -228,10 +242,16 @@ impl Sage {
             .map(parse_nft_id)
             .collect::<Result<Vec<_>>>()?;
         let puzzle_hash = self.parse_address(req.address)?;
+        let new_owner = match req.new_owner {
+            Some(NftOwner::Did { did_id }) => Some(parse_did_id(did_id)?),
+            Some(NftOwner::None) => Some(Bytes32::default()),
+            None => None,
+        };
         let fee = self.parse_amount(req.fee)?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
-            .transfer_nfts(nft_ids, puzzle_hash, fee, false, true)
+            .transfer_nfts(nft_ids, puzzle_hash, new_owner, fee, false, true)
             .await?;
         self.transact(coin_spends, req.auto_submit).await
     }
@@ -247,6 +267,7 @@ impl Sage {
             NftUriKind::License => MetadataUpdate::NewLicenseUri(req.uri),
         };
 
+        let _guard = wallet.transaction_guard().await;
         let (coin_spends, _new_nft) = wallet.add_nft_uri(nft_id, fee, uri, false, true).await?;
         self.transact(coin_spends, req.auto_submit).await
     }
@@ -261,6 +282,7 @@ impl Sage {
         let did_id = req.did_id.map(parse_did_id).transpose()?;
         let fee = self.parse_amount(req.fee)?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
             .assign_nfts(nft_ids, did_id, fee, false, true)
             .await?;
@@ -277,6 +299,7 @@ impl Sage {
         let puzzle_hash = self.parse_address(req.address)?;
         let fee = self.parse_amount(req.fee)?;
 
+        let _guard = wallet.transaction_guard().await;
         let coin_spends = wallet
             .transfer_dids(did_ids, puzzle_hash, fee, false, true)
             .await?;
@@ -287,10 +310,10 @@ impl Sage {
         let coin_spends = req
             .coin_spends
             .into_iter()
-            .map(rust_spend)
-            .collect::<Result<Vec<_>>>()?;
+            .map(|coin_spend| Ok(coin_spend.try_into()?))
+            .collect::<Result<Vec<CoinSpend>>>()?;
         let spend_bundle = self.sign(coin_spends, req.partial).await?;
-        let json_bundle = json_bundle(&spend_bundle);
+        let json_bundle = SpendBundleJson::from(&spend_bundle);
 
         if req.auto_submit {
             self.submit(spend_bundle).await?;
@@ -305,8 +328,8 @@ impl Sage {
         let coin_spends = req
             .coin_spends
             .into_iter()
-            .map(rust_spend)
-            .collect::<Result<Vec<_>>>()?;
+            .map(|coin_spend| Ok(coin_spend.try_into()?))
+            .collect::<Result<Vec<CoinSpend>>>()?;
 
         Ok(ViewCoinSpendsResponse {
             summary: self
@@ -319,12 +342,28 @@ impl Sage {
         &self,
         req: SubmitTransaction,
     ) -> Result<SubmitTransactionResponse> {
-        let spend_bundle = rust_bundle(req.spend_bundle)?;
+        let spend_bundle = SpendBundle::try_from(req.spend_bundle)?;
         self.submit(spend_bundle).await?;
 
         Ok(SubmitTransactionResponse {})
     }
 
+    pub fn encode_spend_bundle(&self, req: EncodeSpendBundle) -> Result<EncodeSpendBundleResponse> {
+        let spend_bundle = SpendBundle::try_from(req.spend_bundle)?;
+
+        Ok(EncodeSpendBundleResponse {
+            spend_bundle: encode_spend_bundle(&spend_bundle)?,
+        })
+    }
+
+    pub fn decode_spend_bundle(&self, req: DecodeSpendBundle) -> Result<DecodeSpendBundleResponse> {
+        let spend_bundle = decode_spend_bundle(&req.spend_bundle)?;
+
+        Ok(DecodeSpendBundleResponse {
+            spend_bundle: SpendBundleJson::from(&spend_bundle),
+        })
+    }
+
     async fn transact(
         &self,
         coin_spends: Vec<CoinSpend>,
@@ -345,7 +384,7 @@ impl Sage {
             self.submit(spend_bundle).await?;
         }
 
-        let json_spends = coin_spends.iter().map(json_spend).collect();
+        let json_spends = coin_spends.iter().map(CoinSpendJson::from).collect();
 
         Ok(TransactionResponse {
             summary: self.summarize(coin_spends, info).await?,
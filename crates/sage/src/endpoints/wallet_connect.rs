@@ -1,5 +1,5 @@
 use chia::{
-    bls::{master_to_wallet_unhardened, sign},
+    bls::{sign, DerivableKey},
     clvm_utils::ToTreeHash,
     protocol::{Bytes, Coin, CoinSpend, SpendBundle},
     puzzles::{cat::CatArgs, standard::StandardArgs, DeriveSynthetic, Proof},
@@ -28,9 +28,9 @@ impl Sage {
         let mut coin_ids = Vec::new();
 
         for coin_id in req.coin_ids {
-            if !wallet
+            if wallet
                 .db
-                .is_coin_locked(parse_coin_id(coin_id.clone())?)
+                .is_coin_unlocked(parse_coin_id(coin_id.clone())?)
                 .await?
             {
                 coin_ids.push(coin_id);
@@ -84,7 +84,7 @@ impl Sage {
                             return Err(Error::MissingCatCoin(cs.coin.coin_id()));
                         };
 
-                        let synthetic_key = wallet.db.synthetic_key(cat.p2_puzzle_hash).await?;
+                        let synthetic_key = wallet.synthetic_key(cat.p2_puzzle_hash).await?;
 
                         let mut ctx = SpendContext::new();
                         let p2_puzzle = ctx.curry(StandardArgs::new(synthetic_key))?;
@@ -157,8 +157,7 @@ impl Sage {
                             return Err(Error::MissingCoin(cs.coin.coin_id()));
                         };
 
-                        let synthetic_key =
-                            wallet.db.synthetic_key(did.info.p2_puzzle_hash).await?;
+                        let synthetic_key = wallet.synthetic_key(did.info.p2_puzzle_hash).await?;
 
                         let mut ctx = SpendContext::new();
                         let p2_puzzle = ctx.curry(StandardArgs::new(synthetic_key))?;
@@ -239,8 +238,7 @@ impl Sage {
                             return Err(Error::MissingCoin(cs.coin.coin_id()));
                         };
 
-                        let synthetic_key =
-                            wallet.db.synthetic_key(nft.info.p2_puzzle_hash).await?;
+                        let synthetic_key = wallet.synthetic_key(nft.info.p2_puzzle_hash).await?;
 
                         let mut ctx = SpendContext::new();
                         let p2_puzzle = ctx.curry(StandardArgs::new(synthetic_key))?;
@@ -300,7 +298,7 @@ impl Sage {
                     continue;
                 }
 
-                let synthetic_key = wallet.db.synthetic_key(cs.coin.puzzle_hash).await?;
+                let synthetic_key = wallet.synthetic_key(cs.coin.puzzle_hash).await?;
 
                 let mut ctx = SpendContext::new();
                 let puzzle = ctx.curry(StandardArgs::new(synthetic_key))?;
@@ -340,7 +338,11 @@ impl Sage {
             return Err(Error::NoSigningKey);
         };
 
-        let secret_key = master_to_wallet_unhardened(&master_sk, index).derive_synthetic();
+        let secret_key = wallet
+            .derivation_path
+            .intermediate_sk(&master_sk)
+            .derive_unhardened(index)
+            .derive_synthetic();
 
         let decoded_message = Bytes::from(hex::decode(&req.message)?);
         let signature = sign(
@@ -367,7 +369,13 @@ impl Sage {
 
         let transaction_id = spend_bundle.name();
 
-        match submit_to_peers(&peers, wallet.genesis_challenge, spend_bundle.clone()).await? {
+        match submit_to_peers(
+            &peers,
+            wallet.network.genesis_challenge(),
+            spend_bundle.clone(),
+        )
+        .await?
+        {
             Status::Success => {
                 info!("Transaction {transaction_id} has already been confirmed, not submitting again.");
 
@@ -387,7 +395,7 @@ impl Sage {
                 let subscriptions = insert_transaction(
                     &wallet.db,
                     &peer,
-                    wallet.genesis_challenge,
+                    wallet.network.genesis_challenge(),
                     spend_bundle.name(),
                     Transaction::from_coin_spends(spend_bundle.coin_spends)?,
                     spend_bundle.aggregated_signature,
@@ -12,7 +12,7 @@ use chia::{
 use chia_wallet_sdk::{AddressError, ClientError, DriverError, OfferError};
 use clvmr::reduction::EvalErr;
 use hex::FromHexError;
-use sage_api::ErrorKind;
+use sage_api::{AmountError, ErrorKind, SpendBundleError};
 use sage_database::DatabaseError;
 use sage_keychain::KeychainError;
 use sage_wallet::{SyncCommand, UriError, WalletError};
@@ -132,6 +132,9 @@ pub enum Error {
     #[error("Invalid coin amount: {0}")]
     InvalidCoinAmount(String),
 
+    #[error("Amount error: {0}")]
+    Amount(#[from] AmountError),
+
     #[error("Invalid genesis id: {0}")]
     InvalidGenesisChallenge(String),
 
@@ -162,9 +165,15 @@ pub enum Error {
     #[error("Invalid signature: {0}")]
     InvalidSignature(String),
 
+    #[error("Spend bundle error: {0}")]
+    SpendBundle(#[from] SpendBundleError),
+
     #[error("Invalid public key: {0}")]
     InvalidPublicKey(String),
 
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+
     #[error("Wallet is cold and cannot be used for signing")]
     NoSigningKey,
 
@@ -200,6 +209,9 @@ pub enum Error {
 
     #[error("Missing asset id")]
     MissingAssetId,
+
+    #[error("Offered CAT can't have a puzzle hash: {0}")]
+    OfferedCatPuzzleHash(Bytes32),
 }
 
 impl Error {
@@ -251,6 +263,8 @@ impl Error {
             | Self::AddressPrefix(..)
             | Self::InvalidAmount(..)
             | Self::InvalidCoinAmount(..)
+            | Self::OfferedCatPuzzleHash(..)
+            | Self::Amount(..)
             | Self::Address(..)
             | Self::InvalidDidId(..)
             | Self::InvalidNftId(..)
@@ -263,6 +277,8 @@ impl Error {
             | Self::InvalidPercentage(..)
             | Self::InvalidSignature(..)
             | Self::InvalidPublicKey(..)
+            | Self::SpendBundle(..)
+            | Self::InvalidDerivationPath(..)
             | Self::CoinSpent(..)
             | Self::Uri(..)
             | Self::IpAddrParse(..)
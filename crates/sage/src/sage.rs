@@ -6,14 +6,17 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use chia::{bls::master_to_wallet_unhardened_intermediate, protocol::Bytes32};
-use chia_wallet_sdk::{create_rustls_connector, decode_address, load_ssl_cert, Connector};
+use chia::protocol::Bytes32;
+use chia_wallet_sdk::{create_rustls_connector, load_ssl_cert, Connector};
 use indexmap::{indexmap, IndexMap};
 use sage_api::{Amount, Unit, XCH};
 use sage_config::{Config, Network, WalletConfig, MAINNET, TESTNET11};
 use sage_database::Database;
 use sage_keychain::Keychain;
-use sage_wallet::{PeerState, SyncCommand, SyncEvent, SyncManager, SyncOptions, Timeouts, Wallet};
+use sage_wallet::{
+    CoinSelectionMode, MetadataWorkerOptions, PeerState, SyncCommand, SyncEvent, SyncManager,
+    SyncOptions, Timeouts, Wallet, WalletError, WalletNetwork, WalletOptions,
+};
 use sqlx::{
     sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
     ConnectOptions, SqlitePool,
@@ -25,7 +28,9 @@ use tracing_subscriber::{
     filter::filter_fn, fmt, layer::SubscriberExt, EnvFilter, Layer, Registry,
 };
 
-use crate::{peers::Peers, Error, Result};
+use crate::{
+    parse_cat_amount, parse_cat_display_amount, parse_derivation_path, peers::Peers, Error, Result,
+};
 
 #[derive(Debug)]
 pub struct Sage {
@@ -192,7 +197,22 @@ impl Sage {
                 max_peer_age_seconds: 3600 * 8,
                 dns_batch_size: 10,
                 connection_batch_size: 30,
+                sync_retries: self.config.network.sync_retries.try_into()?,
                 timeouts: Timeouts::default(),
+                metadata_worker: Some(MetadataWorkerOptions {
+                    cache_dir: self
+                        .config
+                        .app
+                        .metadata_cache_dir
+                        .clone()
+                        .map(PathBuf::from),
+                    prefetch_delay: self
+                        .config
+                        .app
+                        .prefetch_nft_metadata
+                        .then_some(Duration::from_millis(100)),
+                    ..Default::default()
+                }),
                 testing: false,
             },
             self.peer_state.clone(),
@@ -227,7 +247,26 @@ impl Sage {
             return Err(Error::UnknownFingerprint);
         };
 
-        let intermediate_pk = master_to_wallet_unhardened_intermediate(&master_pk);
+        let config = self.try_wallet_config(fingerprint);
+        let options = WalletOptions {
+            max_bundle_cost: config.max_bundle_cost,
+            max_offer_spends: config.max_offer_spends as usize,
+            dust_threshold: config.dust_threshold,
+            min_confirmations: config.min_confirmations,
+            coin_selection_mode: if config.spend_oldest_coins_first {
+                CoinSelectionMode::Oldest
+            } else {
+                CoinSelectionMode::Knapsack
+            },
+            max_derivation_index: config.max_derivation_index,
+            serialize_transactions: config.serialize_transactions,
+        };
+        let derivation_path = config
+            .derivation_path
+            .clone()
+            .map(parse_derivation_path)
+            .transpose()?
+            .unwrap_or_default();
 
         let pool = self.connect_to_database(fingerprint).await?;
         let db = Database::new(pool);
@@ -235,8 +274,10 @@ impl Sage {
         let wallet = Arc::new(Wallet::new(
             db.clone(),
             fingerprint,
-            intermediate_pk,
-            hex::decode(&self.network().genesis_challenge)?.try_into()?,
+            &master_pk,
+            derivation_path,
+            self.wallet_network()?,
+            options,
         ));
 
         self.wallet = Some(wallet.clone());
@@ -333,13 +374,13 @@ impl Sage {
 
     #[allow(clippy::needless_pass_by_value)]
     pub fn parse_address(&self, input: String) -> Result<Bytes32> {
-        let (puzzle_hash, prefix) = decode_address(&input)?;
-
-        if prefix != self.network().address_prefix {
-            return Err(Error::AddressPrefix(prefix));
-        }
-
-        Ok(puzzle_hash.into())
+        self.wallet_network()?
+            .decode_address(&input)
+            .map_err(|error| match error {
+                WalletError::Address(error) => Error::Address(error),
+                WalletError::AddressPrefix(prefix) => Error::AddressPrefix(prefix),
+                error => error.into(),
+            })
     }
 
     #[allow(clippy::needless_pass_by_value)]
@@ -351,6 +392,29 @@ impl Sage {
         Ok(amount)
     }
 
+    /// Parses a CAT amount, which is in display units if requested. The CAT's own decimals are
+    /// used if they're known, otherwise the configured default.
+    pub async fn parse_cat_amount_with_units(
+        &self,
+        asset_id: Bytes32,
+        input: Amount,
+        display_units: bool,
+    ) -> Result<u64> {
+        if !display_units {
+            return parse_cat_amount(input);
+        }
+
+        let decimals = self
+            .wallet()?
+            .db
+            .cat(asset_id)
+            .await?
+            .and_then(|cat| cat.decimals)
+            .unwrap_or(self.config.app.default_cat_decimals);
+
+        parse_cat_display_amount(input, decimals)
+    }
+
     pub async fn connect_to_database(&self, fingerprint: u32) -> Result<SqlitePool> {
         let path = self.wallet_db_path(fingerprint)?;
 
@@ -383,6 +447,11 @@ impl Sage {
             .expect("network not found")
     }
 
+    /// The active network's parameters, parsed for signing, address encoding and cost estimates.
+    pub fn wallet_network(&self) -> Result<WalletNetwork> {
+        Ok(WalletNetwork::new(self.network())?)
+    }
+
     pub fn wallet(&self) -> Result<Arc<Wallet>> {
         let Some(fingerprint) = self.config.app.active_fingerprint else {
             return Err(Error::NotLoggedIn);
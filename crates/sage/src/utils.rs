@@ -8,4 +8,5 @@ mod spends;
 pub use coins::*;
 pub use confirmation::*;
 pub use offer_status::*;
+pub use offer_summary::*;
 pub use parse::*;
@@ -2,21 +2,15 @@ use std::collections::HashMap;
 
 use base64::{prelude::BASE64_STANDARD, Engine};
 use chia::{
-    protocol::{Bytes32, Coin, CoinSpend, SpendBundle},
+    protocol::{Bytes32, CoinSpend},
     puzzles::nft::NftMetadata,
 };
 use chia_wallet_sdk::encode_address;
-use hex_literal::hex;
-use sage_api::{
-    Amount, AssetKind, CoinJson, CoinSpendJson, SpendBundleJson, TransactionInput,
-    TransactionOutput, TransactionSummary,
-};
+use sage_api::{Amount, AssetKind, TransactionInput, TransactionOutput, TransactionSummary};
 use sage_database::Database;
 use sage_wallet::{compute_nft_info, ChildKind, CoinKind, Data, Transaction};
 
-use crate::{Error, Result, Sage};
-
-use super::{parse_coin_id, parse_program, parse_puzzle_hash, parse_signature};
+use crate::{Result, Sage};
 
 #[derive(Debug, Default)]
 pub struct ConfirmationInfo {
@@ -34,6 +28,17 @@ impl Sage {
 
         let transaction = Transaction::from_coin_spends(coin_spends)?;
 
+        let pending_spends = wallet
+            .db
+            .conflicting_pending_spends(
+                &transaction
+                    .inputs
+                    .iter()
+                    .map(|input| input.coin_spend.coin.coin_id())
+                    .collect::<Vec<_>>(),
+            )
+            .await?;
+
         let mut inputs = Vec::with_capacity(transaction.inputs.len());
 
         for input in transaction.inputs {
@@ -90,7 +95,7 @@ impl Sage {
                 }
             };
 
-            let address = encode_address(p2_puzzle_hash.into(), &self.network().address_prefix)?;
+            let address = wallet.network.encode_address(p2_puzzle_hash)?;
 
             let mut outputs = Vec::new();
 
@@ -101,18 +106,18 @@ impl Sage {
                     ChildKind::Cat { p2_puzzle_hash, .. } => p2_puzzle_hash,
                     ChildKind::Did { info, .. } => info.p2_puzzle_hash,
                     ChildKind::Nft { info, .. } => info.p2_puzzle_hash,
+                    ChildKind::Clawback { clawback } => clawback.recipient_puzzle_hash,
+                    ChildKind::Settlement { hint } => hint,
                 };
 
-                let address =
-                    encode_address(p2_puzzle_hash.into(), &self.network().address_prefix)?;
+                let address = wallet.network.encode_address(p2_puzzle_hash)?;
 
                 outputs.push(TransactionOutput {
                     coin_id: hex::encode(output.coin.coin_id()),
                     amount: Amount::u64(output.coin.amount),
                     address,
                     receiving: wallet.db.is_p2_puzzle_hash(p2_puzzle_hash).await?,
-                    burning: p2_puzzle_hash.to_bytes()
-                        == hex!("000000000000000000000000000000000000000000000000000000000000dead"),
+                    burning: wallet.db.is_burn_puzzle_hash(p2_puzzle_hash).await?,
                 });
             }
 
@@ -122,6 +127,7 @@ impl Sage {
                 address,
                 kind,
                 outputs,
+                pending_spend: pending_spends.contains(&coin.coin_id()),
             });
         }
 
@@ -176,59 +182,3 @@ pub async fn extract_nft_data(
 
     Ok(result)
 }
-
-pub fn json_bundle(spend_bundle: &SpendBundle) -> SpendBundleJson {
-    SpendBundleJson {
-        coin_spends: spend_bundle.coin_spends.iter().map(json_spend).collect(),
-        aggregated_signature: format!(
-            "0x{}",
-            hex::encode(spend_bundle.aggregated_signature.to_bytes())
-        ),
-    }
-}
-
-pub fn json_spend(coin_spend: &CoinSpend) -> CoinSpendJson {
-    CoinSpendJson {
-        coin: json_coin(&coin_spend.coin),
-        puzzle_reveal: hex::encode(&coin_spend.puzzle_reveal),
-        solution: hex::encode(&coin_spend.solution),
-    }
-}
-
-pub fn json_coin(coin: &Coin) -> CoinJson {
-    CoinJson {
-        parent_coin_info: format!("0x{}", hex::encode(coin.parent_coin_info)),
-        puzzle_hash: format!("0x{}", hex::encode(coin.puzzle_hash)),
-        amount: Amount::u64(coin.amount),
-    }
-}
-
-pub fn rust_bundle(spend_bundle: SpendBundleJson) -> Result<SpendBundle> {
-    Ok(SpendBundle {
-        coin_spends: spend_bundle
-            .coin_spends
-            .into_iter()
-            .map(rust_spend)
-            .collect::<Result<_>>()?,
-        aggregated_signature: parse_signature(spend_bundle.aggregated_signature)?,
-    })
-}
-
-pub fn rust_spend(coin_spend: CoinSpendJson) -> Result<CoinSpend> {
-    Ok(CoinSpend {
-        coin: rust_coin(coin_spend.coin)?,
-        puzzle_reveal: parse_program(coin_spend.puzzle_reveal)?,
-        solution: parse_program(coin_spend.solution)?,
-    })
-}
-
-pub fn rust_coin(coin: CoinJson) -> Result<Coin> {
-    Ok(Coin {
-        parent_coin_info: parse_coin_id(coin.parent_coin_info)?,
-        puzzle_hash: parse_puzzle_hash(coin.puzzle_hash)?,
-        amount: coin
-            .amount
-            .to_u64()
-            .ok_or(Error::InvalidCoinAmount(coin.amount.to_string()))?,
-    })
-}
@@ -3,10 +3,14 @@ use std::time::Duration;
 use chia::{clvm_traits::FromClvm, puzzles::nft::NftMetadata};
 use chia_wallet_sdk::{encode_address, Offer, SpendContext};
 use indexmap::IndexMap;
-use sage_api::{Amount, OfferAssets, OfferCat, OfferNft, OfferSummary, OfferXch};
+use sage_api::{
+    Amount, AssetId, OfferAssets, OfferCat, OfferNft, OfferSummary, OfferUnknown, OfferXch,
+    RoyaltyPaymentRecord,
+};
 use sage_wallet::{
-    calculate_royalties, lookup_from_uris_with_hash, parse_locked_coins, parse_offer_payments,
-    NftRoyaltyInfo,
+    calculate_royalties, decode_offer, lookup_from_uris_with_hash, parse_cat_tails,
+    parse_locked_coins, parse_offer_payments, LockedCoins, NftRoyaltyInfo, RequestedPayments,
+    Royalties, RoyaltyPayment,
 };
 
 use crate::{Result, Sage};
@@ -22,47 +26,29 @@ impl Sage {
         let offer = offer.parse(&mut ctx.allocator)?;
         let (locked_coins, _original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
         let maker_amounts = locked_coins.amounts();
+        let cat_tails = parse_cat_tails(&mut ctx.allocator, &offer)?;
 
         let mut builder = offer.take();
         let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
         let taker_amounts = requested_payments.amounts();
 
-        let maker_royalties = calculate_royalties(
-            &maker_amounts,
-            &requested_payments
-                .nfts
-                .values()
-                .map(|(nft, _payments)| NftRoyaltyInfo {
-                    launcher_id: nft.launcher_id,
-                    royalty_puzzle_hash: nft.royalty_puzzle_hash,
-                    royalty_ten_thousandths: nft.royalty_ten_thousandths,
-                })
-                .collect::<Vec<_>>(),
-        )?;
-
-        let taker_royalties = calculate_royalties(
-            &taker_amounts,
-            &locked_coins
-                .nfts
-                .values()
-                .map(|nft| NftRoyaltyInfo {
-                    launcher_id: nft.info.launcher_id,
-                    royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
-                    royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
-                })
-                .collect::<Vec<_>>(),
-        )?;
+        let (maker_royalties, taker_royalties) =
+            offer_royalties(&locked_coins, &requested_payments)?;
 
-        let maker_royalties = maker_royalties.amounts();
-        let taker_royalties = taker_royalties.amounts();
+        let maker_royalty_amounts = maker_royalties.amounts()?;
+        let taker_royalty_amounts = taker_royalties.amounts()?;
 
         let mut maker = OfferAssets {
             xch: OfferXch {
+                asset: AssetId::Xch,
                 amount: Amount::u64(maker_amounts.xch),
-                royalty: Amount::u64(maker_royalties.xch),
+                royalty: Amount::u64(maker_royalty_amounts.xch),
+                royalties: self.royalty_payments(&maker_royalties.xch)?,
+                decimals: self.network().precision,
             },
             cats: IndexMap::new(),
             nfts: IndexMap::new(),
+            unknown: Vec::new(),
         };
 
         for (asset_id, amount) in maker_amounts.cats {
@@ -71,11 +57,29 @@ impl Sage {
             maker.cats.insert(
                 hex::encode(asset_id),
                 OfferCat {
+                    asset: AssetId::cat(hex::encode(asset_id)),
                     amount: Amount::u64(amount),
-                    royalty: Amount::u64(maker_royalties.cats.get(&asset_id).copied().unwrap_or(0)),
+                    royalty: Amount::u64(
+                        maker_royalty_amounts
+                            .cats
+                            .get(&asset_id)
+                            .copied()
+                            .unwrap_or(0),
+                    ),
+                    royalties: self.royalty_payments(
+                        maker_royalties
+                            .cats
+                            .get(&asset_id)
+                            .map_or(&[], Vec::as_slice),
+                    )?,
+                    decimals: cat
+                        .as_ref()
+                        .and_then(|cat| cat.decimals)
+                        .unwrap_or(self.config.app.default_cat_decimals),
                     name: cat.as_ref().and_then(|cat| cat.name.clone()),
                     ticker: cat.as_ref().and_then(|cat| cat.ticker.clone()),
                     icon_url: cat.as_ref().and_then(|cat| cat.icon.clone()),
+                    tail_revealed: cat_tails.get(&asset_id).is_some_and(Option::is_some),
                 },
             );
         }
@@ -117,28 +121,40 @@ impl Sage {
                 ExtractedNftData::default()
             };
 
+            let nft_id = encode_address(launcher_id.to_bytes(), "nft")?;
+
             maker.nfts.insert(
-                encode_address(launcher_id.to_bytes(), "nft")?,
+                nft_id.clone(),
                 OfferNft {
+                    asset: AssetId::nft(nft_id),
                     image_data: info.image_data,
                     image_mime_type: info.image_mime_type,
                     name: info.name,
                     royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
-                    royalty_address: encode_address(
-                        nft.info.royalty_puzzle_hash.into(),
-                        &self.network().address_prefix,
-                    )?,
+                    royalty_address: wallet
+                        .network
+                        .encode_address(nft.info.royalty_puzzle_hash)?,
                 },
             );
         }
 
         let mut taker = OfferAssets {
             xch: OfferXch {
+                asset: AssetId::Xch,
                 amount: Amount::u64(taker_amounts.xch),
-                royalty: Amount::u64(taker_royalties.xch),
+                royalty: Amount::u64(taker_royalty_amounts.xch),
+                royalties: self.royalty_payments(&taker_royalties.xch)?,
+                decimals: self.network().precision,
             },
             cats: IndexMap::new(),
             nfts: IndexMap::new(),
+            unknown: requested_payments
+                .unknown
+                .keys()
+                .map(|puzzle_hash| OfferUnknown {
+                    puzzle_hash: hex::encode(puzzle_hash),
+                })
+                .collect(),
         };
 
         for (asset_id, amount) in taker_amounts.cats {
@@ -147,11 +163,29 @@ impl Sage {
             taker.cats.insert(
                 hex::encode(asset_id),
                 OfferCat {
+                    asset: AssetId::cat(hex::encode(asset_id)),
                     amount: Amount::u64(amount),
-                    royalty: Amount::u64(taker_royalties.cats.get(&asset_id).copied().unwrap_or(0)),
+                    royalty: Amount::u64(
+                        taker_royalty_amounts
+                            .cats
+                            .get(&asset_id)
+                            .copied()
+                            .unwrap_or(0),
+                    ),
+                    royalties: self.royalty_payments(
+                        taker_royalties
+                            .cats
+                            .get(&asset_id)
+                            .map_or(&[], Vec::as_slice),
+                    )?,
+                    decimals: cat
+                        .as_ref()
+                        .and_then(|cat| cat.decimals)
+                        .unwrap_or(self.config.app.default_cat_decimals),
                     name: cat.as_ref().and_then(|cat| cat.name.clone()),
                     ticker: cat.as_ref().and_then(|cat| cat.ticker.clone()),
                     icon_url: cat.as_ref().and_then(|cat| cat.icon.clone()),
+                    tail_revealed: cat_tails.get(&asset_id).is_some_and(Option::is_some),
                 },
             );
         }
@@ -165,17 +199,17 @@ impl Sage {
             )
             .await?;
 
+            let nft_id = encode_address(launcher_id.to_bytes(), "nft")?;
+
             taker.nfts.insert(
-                encode_address(launcher_id.to_bytes(), "nft")?,
+                nft_id.clone(),
                 OfferNft {
+                    asset: AssetId::nft(nft_id),
                     image_data: info.image_data,
                     image_mime_type: info.image_mime_type,
                     name: info.name,
                     royalty_ten_thousandths: nft.royalty_ten_thousandths,
-                    royalty_address: encode_address(
-                        nft.royalty_puzzle_hash.into(),
-                        &self.network().address_prefix,
-                    )?,
+                    royalty_address: wallet.network.encode_address(nft.royalty_puzzle_hash)?,
                 },
             );
         }
@@ -186,4 +220,72 @@ impl Sage {
             taker,
         })
     }
+
+    /// Converts the royalty payments into records, with the NFT id and royalty address encoded.
+    pub(crate) fn royalty_payments(
+        &self,
+        royalties: &[RoyaltyPayment],
+    ) -> Result<Vec<RoyaltyPaymentRecord>> {
+        let network = self.wallet_network()?;
+
+        royalties
+            .iter()
+            .map(|royalty| {
+                Ok(RoyaltyPaymentRecord {
+                    nft_id: encode_address(royalty.nft_id.to_bytes(), "nft")?,
+                    address: network.encode_address(royalty.p2_puzzle_hash)?,
+                    amount: Amount::u64(royalty.amount),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The royalties paid by the maker and by the taker of an encoded offer.
+pub fn encoded_offer_royalties(encoded_offer: &str) -> Result<(Royalties, Royalties)> {
+    let mut ctx = SpendContext::new();
+
+    let offer = decode_offer(encoded_offer)?.parse(&mut ctx.allocator)?;
+    let (locked_coins, _original_coin_ids) = parse_locked_coins(&mut ctx.allocator, &offer)?;
+
+    let mut builder = offer.take();
+    let requested_payments = parse_offer_payments(&mut ctx, &mut builder)?;
+
+    offer_royalties(&locked_coins, &requested_payments)
+}
+
+/// The royalties paid by the maker and by the taker of an offer. The maker pays royalties on the
+/// offered assets for each requested NFT, and the taker pays royalties on the requested assets
+/// for each offered NFT.
+pub fn offer_royalties(
+    locked_coins: &LockedCoins,
+    requested_payments: &RequestedPayments,
+) -> Result<(Royalties, Royalties)> {
+    let maker_royalties = calculate_royalties(
+        &locked_coins.amounts(),
+        &requested_payments
+            .nfts
+            .values()
+            .map(|(nft, _payments)| NftRoyaltyInfo {
+                launcher_id: nft.launcher_id,
+                royalty_puzzle_hash: nft.royalty_puzzle_hash,
+                royalty_ten_thousandths: nft.royalty_ten_thousandths,
+            })
+            .collect::<Vec<_>>(),
+    )?;
+
+    let taker_royalties = calculate_royalties(
+        &requested_payments.amounts(),
+        &locked_coins
+            .nfts
+            .values()
+            .map(|nft| NftRoyaltyInfo {
+                launcher_id: nft.info.launcher_id,
+                royalty_puzzle_hash: nft.info.royalty_puzzle_hash,
+                royalty_ten_thousandths: nft.info.royalty_ten_thousandths,
+            })
+            .collect::<Vec<_>>(),
+    )?;
+
+    Ok((maker_royalties, taker_royalties))
 }
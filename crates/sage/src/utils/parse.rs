@@ -5,7 +5,8 @@ use chia::{
     protocol::{Bytes32, Program},
 };
 use chia_wallet_sdk::decode_address;
-use sage_api::Amount;
+use sage_api::{cat_display_to_raw, Amount};
+use sage_wallet::DerivationPath;
 
 use crate::{Error, Result};
 
@@ -81,6 +82,12 @@ pub fn parse_cat_amount(input: Amount) -> Result<u64> {
     Ok(amount)
 }
 
+/// Parses a CAT amount in display units with the given number of decimals, such as `1.5` for
+/// 1500 mojos with 3 decimals.
+pub fn parse_cat_display_amount(input: Amount, decimals: u8) -> Result<u64> {
+    Ok(cat_display_to_raw(&input.to_string(), decimals)?)
+}
+
 pub fn parse_puzzle_hash(input: String) -> Result<Bytes32> {
     let stripped = if let Some(stripped) = input.strip_prefix("0x") {
         stripped
@@ -130,3 +137,9 @@ pub fn parse_program(input: String) -> Result<Program> {
 
     Ok(hex::decode(stripped)?.into())
 }
+
+pub fn parse_derivation_path(input: String) -> Result<DerivationPath> {
+    input
+        .parse()
+        .map_err(|_| Error::InvalidDerivationPath(input))
+}
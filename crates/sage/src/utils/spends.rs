@@ -1,11 +1,8 @@
 use chia::protocol::{CoinSpend, SpendBundle};
-use chia_wallet_sdk::AggSigConstants;
 use sage_wallet::{insert_transaction, SyncCommand, Transaction};
 
 use crate::{Error, Result, Sage};
 
-use super::parse_genesis_challenge;
-
 impl Sage {
     pub(crate) async fn sign(
         &self,
@@ -23,7 +20,7 @@ impl Sage {
         let spend_bundle = wallet
             .sign_transaction(
                 coin_spends,
-                &AggSigConstants::new(parse_genesis_challenge(self.network().agg_sig_me.clone())?),
+                &self.wallet_network()?.agg_sig_constants(),
                 master_sk,
                 partial,
             )
@@ -44,7 +41,7 @@ impl Sage {
         let subscriptions = insert_transaction(
             &wallet.db,
             &peer,
-            wallet.genesis_challenge,
+            wallet.network.genesis_challenge(),
             spend_bundle.name(),
             Transaction::from_coin_spends(spend_bundle.coin_spends)?,
             spend_bundle.aggregated_signature,
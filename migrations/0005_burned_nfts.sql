@@ -0,0 +1,8 @@
+ALTER TABLE `nfts` ADD COLUMN `is_burned` BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE `burn_puzzle_hashes` (
+    `puzzle_hash` BLOB NOT NULL PRIMARY KEY
+);
+
+INSERT INTO `burn_puzzle_hashes` (`puzzle_hash`)
+VALUES (X'000000000000000000000000000000000000000000000000000000000000dead');
@@ -0,0 +1,10 @@
+CREATE TABLE `clawback_coins` (
+    `coin_id` BLOB NOT NULL PRIMARY KEY,
+    `sender_puzzle_hash` BLOB NOT NULL,
+    `recipient_puzzle_hash` BLOB NOT NULL,
+    `timelock` BLOB NOT NULL,
+    FOREIGN KEY (`coin_id`) REFERENCES `coin_states` (`coin_id`) ON DELETE CASCADE
+);
+
+CREATE INDEX `clawback_sender` ON `clawback_coins` (`sender_puzzle_hash`);
+CREATE INDEX `clawback_recipient` ON `clawback_coins` (`recipient_puzzle_hash`);
@@ -0,0 +1 @@
+ALTER TABLE `transactions` ADD COLUMN `submission_count` INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+CREATE TABLE `frozen_coins` (
+    `coin_id` BLOB NOT NULL PRIMARY KEY,
+    FOREIGN KEY (`coin_id`) REFERENCES `coin_states` (`coin_id`) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+CREATE TABLE `coin_labels` (
+    `coin_id` BLOB NOT NULL PRIMARY KEY,
+    `label` TEXT NOT NULL,
+    FOREIGN KEY (`coin_id`) REFERENCES `coin_states` (`coin_id`) ON DELETE CASCADE
+);
@@ -0,0 +1 @@
+ALTER TABLE `cat_coins` ADD COLUMN `memo` BLOB;
@@ -0,0 +1,5 @@
+ALTER TABLE `derivations` ADD COLUMN `subscribed` BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX `derivation_subscribed` ON `derivations` (`subscribed`);
+
+UPDATE `derivations` SET `subscribed` = TRUE WHERE EXISTS (SELECT 1 FROM `peaks`);
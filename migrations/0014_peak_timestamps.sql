@@ -0,0 +1,3 @@
+ALTER TABLE `peaks` ADD COLUMN `timestamp` INTEGER;
+
+CREATE INDEX `peak_timestamp` ON `peaks` (`timestamp`);
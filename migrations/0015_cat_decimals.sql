@@ -0,0 +1 @@
+ALTER TABLE `cats` ADD COLUMN `decimals` INTEGER;
@@ -0,0 +1 @@
+ALTER TABLE `nft_uris` ADD COLUMN `checked_timestamp` INTEGER;
@@ -0,0 +1,12 @@
+CREATE TABLE `transaction_history` (
+    `transaction_id` BLOB NOT NULL PRIMARY KEY,
+    `fee` BLOB NOT NULL
+);
+
+CREATE TABLE `transaction_history_coins` (
+    `coin_id` BLOB NOT NULL PRIMARY KEY,
+    `transaction_id` BLOB NOT NULL,
+    FOREIGN KEY (`transaction_id`) REFERENCES `transaction_history` (`transaction_id`) ON DELETE CASCADE
+);
+
+CREATE INDEX `history_transaction_id` ON `transaction_history_coins` (`transaction_id`);
@@ -6,7 +6,7 @@ use std::{
 
 use sage::Sage;
 use sage_api::SyncEvent as ApiEvent;
-use sage_wallet::SyncEvent;
+use sage_wallet::{SyncEvent, SyncHealth};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::Mutex;
 
@@ -60,6 +60,9 @@ impl AppStateInner {
                     SyncEvent::Stop => ApiEvent::Stop,
                     SyncEvent::Subscribed => ApiEvent::Subscribed,
                     SyncEvent::DerivationIndex { .. } => ApiEvent::Derivation,
+                    SyncEvent::DerivationLimitReached { max_index } => {
+                        ApiEvent::DerivationLimitReached { max_index }
+                    }
                     // TODO: New event?
                     SyncEvent::CoinsUpdated { .. }
                     | SyncEvent::TransactionEnded { .. }
@@ -68,6 +71,12 @@ impl AppStateInner {
                     SyncEvent::CatInfo => ApiEvent::CatInfo,
                     SyncEvent::DidInfo => ApiEvent::DidInfo,
                     SyncEvent::NftData => ApiEvent::NftData,
+                    SyncEvent::NftPrefetch { fetched, pending } => {
+                        ApiEvent::NftPrefetch { fetched, pending }
+                    }
+                    SyncEvent::SyncHealth(health) => ApiEvent::SyncHealth {
+                        stalled: health == SyncHealth::Stalled,
+                    },
                 };
                 if app_handle.emit("sync-event", event).is_err() {
                     break;
@@ -1,6 +1,5 @@
 use std::time::Duration;
 
-use chia_wallet_sdk::decode_address;
 use sage_api::{wallet_connect::*, *};
 use sage_config::{NetworkConfig, WalletConfig};
 use specta::specta;
@@ -106,10 +105,7 @@ pub async fn generate_mnemonic(
 #[specta]
 pub async fn validate_address(state: State<'_, AppState>, address: String) -> Result<bool> {
     let state = state.lock().await;
-    let Some((_puzzle_hash, prefix)) = decode_address(&address).ok() else {
-        return Ok(false);
-    };
-    Ok(prefix == state.network().address_prefix)
+    Ok(state.wallet_network()?.decode_address(&address).is_ok())
 }
 
 #[command]
@@ -238,6 +234,24 @@ pub async fn submit_transaction(
     Ok(state.lock().await.submit_transaction(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn encode_spend_bundle(
+    state: State<'_, AppState>,
+    req: EncodeSpendBundle,
+) -> Result<EncodeSpendBundleResponse> {
+    Ok(state.lock().await.encode_spend_bundle(req)?)
+}
+
+#[command]
+#[specta]
+pub async fn decode_spend_bundle(
+    state: State<'_, AppState>,
+    req: DecodeSpendBundle,
+) -> Result<DecodeSpendBundleResponse> {
+    Ok(state.lock().await.decode_spend_bundle(req)?)
+}
+
 #[command]
 #[specta]
 pub async fn make_offer(state: State<'_, AppState>, req: MakeOffer) -> Result<MakeOfferResponse> {
@@ -256,6 +270,15 @@ pub async fn view_offer(state: State<'_, AppState>, req: ViewOffer) -> Result<Vi
     Ok(state.lock().await.view_offer(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn compare_offers(
+    state: State<'_, AppState>,
+    req: CompareOffers,
+) -> Result<CompareOffersResponse> {
+    Ok(state.lock().await.compare_offers(req)?)
+}
+
 #[command]
 #[specta]
 pub async fn import_offer(
@@ -322,6 +345,33 @@ pub async fn get_cat_coins(
     Ok(state.lock().await.get_cat_coins(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn get_frozen_coins(
+    state: State<'_, AppState>,
+    req: GetFrozenCoins,
+) -> Result<GetFrozenCoinsResponse> {
+    Ok(state.lock().await.get_frozen_coins(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn get_burn_addresses(
+    state: State<'_, AppState>,
+    req: GetBurnAddresses,
+) -> Result<GetBurnAddressesResponse> {
+    Ok(state.lock().await.get_burn_addresses(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn get_spendable_coins(
+    state: State<'_, AppState>,
+    req: GetSpendableCoins,
+) -> Result<GetSpendableCoinsResponse> {
+    Ok(state.lock().await.get_spendable_coins(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_cats(state: State<'_, AppState>, req: GetCats) -> Result<GetCatsResponse> {
@@ -334,6 +384,15 @@ pub async fn get_cat(state: State<'_, AppState>, req: GetCat) -> Result<GetCatRe
     Ok(state.lock().await.get_cat(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn get_known_cats(
+    state: State<'_, AppState>,
+    req: GetKnownCats,
+) -> Result<GetKnownCatsResponse> {
+    Ok(state.lock().await.get_known_cats(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_dids(state: State<'_, AppState>, req: GetDids) -> Result<GetDidsResponse> {
@@ -367,6 +426,15 @@ pub async fn get_nft_status(
     Ok(state.lock().await.get_nft_status(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn get_nft_count(
+    state: State<'_, AppState>,
+    req: GetNftCount,
+) -> Result<GetNftCountResponse> {
+    Ok(state.lock().await.get_nft_count(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_nft_collections(
@@ -385,6 +453,21 @@ pub async fn get_nft_collection(
     Ok(state.lock().await.get_nft_collection(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn get_rarity(state: State<'_, AppState>, req: GetRarity) -> Result<GetRarityResponse> {
+    Ok(state.lock().await.get_rarity(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn get_nft_groups(
+    state: State<'_, AppState>,
+    req: GetNftGroups,
+) -> Result<GetNftGroupsResponse> {
+    Ok(state.lock().await.get_nft_groups(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_nfts(state: State<'_, AppState>, req: GetNfts) -> Result<GetNftsResponse> {
@@ -397,6 +480,15 @@ pub async fn get_nft(state: State<'_, AppState>, req: GetNft) -> Result<GetNftRe
     Ok(state.lock().await.get_nft(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn get_nft_provenance(
+    state: State<'_, AppState>,
+    req: GetNftProvenance,
+) -> Result<GetNftProvenanceResponse> {
+    Ok(state.lock().await.get_nft_provenance(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_nft_data(
@@ -430,6 +522,51 @@ pub async fn update_nft(state: State<'_, AppState>, req: UpdateNft) -> Result<Up
     Ok(state.lock().await.update_nft(req).await?)
 }
 
+#[command]
+#[specta]
+pub async fn freeze_coins(
+    state: State<'_, AppState>,
+    req: FreezeCoins,
+) -> Result<FreezeCoinsResponse> {
+    Ok(state.lock().await.freeze_coins(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn unfreeze_coins(
+    state: State<'_, AppState>,
+    req: UnfreezeCoins,
+) -> Result<UnfreezeCoinsResponse> {
+    Ok(state.lock().await.unfreeze_coins(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn set_coin_label(
+    state: State<'_, AppState>,
+    req: SetCoinLabel,
+) -> Result<SetCoinLabelResponse> {
+    Ok(state.lock().await.set_coin_label(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn add_burn_address(
+    state: State<'_, AppState>,
+    req: AddBurnAddress,
+) -> Result<AddBurnAddressResponse> {
+    Ok(state.lock().await.add_burn_address(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn remove_burn_address(
+    state: State<'_, AppState>,
+    req: RemoveBurnAddress,
+) -> Result<RemoveBurnAddressResponse> {
+    Ok(state.lock().await.remove_burn_address(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_peers(state: State<'_, AppState>, req: GetPeers) -> Result<GetPeersResponse> {
@@ -510,6 +647,51 @@ pub async fn set_derivation_batch_size(
     Ok(state.lock().await.set_derivation_batch_size(req)?)
 }
 
+#[command]
+#[specta]
+pub async fn set_max_derivation_index(
+    state: State<'_, AppState>,
+    req: SetMaxDerivationIndex,
+) -> Result<SetMaxDerivationIndexResponse> {
+    Ok(state.lock().await.set_max_derivation_index(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn set_max_bundle_cost(
+    state: State<'_, AppState>,
+    req: SetMaxBundleCost,
+) -> Result<SetMaxBundleCostResponse> {
+    Ok(state.lock().await.set_max_bundle_cost(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn set_max_offer_spends(
+    state: State<'_, AppState>,
+    req: SetMaxOfferSpends,
+) -> Result<SetMaxOfferSpendsResponse> {
+    Ok(state.lock().await.set_max_offer_spends(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn set_dust_threshold(
+    state: State<'_, AppState>,
+    req: SetDustThreshold,
+) -> Result<SetDustThresholdResponse> {
+    Ok(state.lock().await.set_dust_threshold(req).await?)
+}
+
+#[command]
+#[specta]
+pub async fn set_min_confirmations(
+    state: State<'_, AppState>,
+    req: SetMinConfirmations,
+) -> Result<SetMinConfirmationsResponse> {
+    Ok(state.lock().await.set_min_confirmations(req).await?)
+}
+
 #[command]
 #[specta]
 pub async fn get_networks(
@@ -44,18 +44,28 @@ pub fn run() {
             commands::sign_coin_spends,
             commands::view_coin_spends,
             commands::submit_transaction,
+            commands::encode_spend_bundle,
+            commands::decode_spend_bundle,
             commands::get_sync_status,
             commands::get_derivations,
             commands::get_xch_coins,
             commands::get_cat_coins,
+            commands::get_frozen_coins,
+            commands::get_burn_addresses,
+            commands::get_spendable_coins,
             commands::get_cats,
             commands::get_cat,
+            commands::get_known_cats,
             commands::get_dids,
             commands::get_nft_status,
+            commands::get_nft_count,
             commands::get_nft_collections,
             commands::get_nft_collection,
+            commands::get_rarity,
+            commands::get_nft_groups,
             commands::get_nfts,
             commands::get_nft,
+            commands::get_nft_provenance,
             commands::get_nft_data,
             commands::get_pending_transactions,
             commands::get_transactions,
@@ -63,6 +73,7 @@ pub fn run() {
             commands::make_offer,
             commands::take_offer,
             commands::view_offer,
+            commands::compare_offers,
             commands::import_offer,
             commands::get_offers,
             commands::get_offer,
@@ -74,11 +85,21 @@ pub fn run() {
             commands::wallet_config,
             commands::set_derive_automatically,
             commands::set_derivation_batch_size,
+            commands::set_max_derivation_index,
+            commands::set_max_bundle_cost,
+            commands::set_max_offer_spends,
+            commands::set_dust_threshold,
+            commands::set_min_confirmations,
             commands::get_networks,
             commands::update_cat,
             commands::remove_cat,
             commands::update_did,
             commands::update_nft,
+            commands::freeze_coins,
+            commands::unfreeze_coins,
+            commands::set_coin_label,
+            commands::add_burn_address,
+            commands::remove_burn_address,
             commands::get_peers,
             commands::add_peer,
             commands::remove_peer,
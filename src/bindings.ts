@@ -86,6 +86,12 @@ async viewCoinSpends(req: ViewCoinSpends) : Promise<ViewCoinSpendsResponse> {
 async submitTransaction(req: SubmitTransaction) : Promise<SubmitTransactionResponse> {
     return await TAURI_INVOKE("submit_transaction", { req });
 },
+async encodeSpendBundle(req: EncodeSpendBundle) : Promise<EncodeSpendBundleResponse> {
+    return await TAURI_INVOKE("encode_spend_bundle", { req });
+},
+async decodeSpendBundle(req: DecodeSpendBundle) : Promise<DecodeSpendBundleResponse> {
+    return await TAURI_INVOKE("decode_spend_bundle", { req });
+},
 async getSyncStatus(req: GetSyncStatus) : Promise<GetSyncStatusResponse> {
     return await TAURI_INVOKE("get_sync_status", { req });
 },
@@ -98,30 +104,54 @@ async getXchCoins(req: GetXchCoins) : Promise<GetXchCoinsResponse> {
 async getCatCoins(req: GetCatCoins) : Promise<GetCatCoinsResponse> {
     return await TAURI_INVOKE("get_cat_coins", { req });
 },
+async getFrozenCoins(req: GetFrozenCoins) : Promise<GetFrozenCoinsResponse> {
+    return await TAURI_INVOKE("get_frozen_coins", { req });
+},
+async getBurnAddresses(req: GetBurnAddresses) : Promise<GetBurnAddressesResponse> {
+    return await TAURI_INVOKE("get_burn_addresses", { req });
+},
+async getSpendableCoins(req: GetSpendableCoins) : Promise<GetSpendableCoinsResponse> {
+    return await TAURI_INVOKE("get_spendable_coins", { req });
+},
 async getCats(req: GetCats) : Promise<GetCatsResponse> {
     return await TAURI_INVOKE("get_cats", { req });
 },
 async getCat(req: GetCat) : Promise<GetCatResponse> {
     return await TAURI_INVOKE("get_cat", { req });
 },
+async getKnownCats(req: GetKnownCats) : Promise<GetKnownCatsResponse> {
+    return await TAURI_INVOKE("get_known_cats", { req });
+},
 async getDids(req: GetDids) : Promise<GetDidsResponse> {
     return await TAURI_INVOKE("get_dids", { req });
 },
 async getNftStatus(req: GetNftStatus) : Promise<GetNftStatusResponse> {
     return await TAURI_INVOKE("get_nft_status", { req });
 },
+async getNftCount(req: GetNftCount) : Promise<GetNftCountResponse> {
+    return await TAURI_INVOKE("get_nft_count", { req });
+},
 async getNftCollections(req: GetNftCollections) : Promise<GetNftCollectionsResponse> {
     return await TAURI_INVOKE("get_nft_collections", { req });
 },
 async getNftCollection(req: GetNftCollection) : Promise<GetNftCollectionResponse> {
     return await TAURI_INVOKE("get_nft_collection", { req });
 },
+async getRarity(req: GetRarity) : Promise<GetRarityResponse> {
+    return await TAURI_INVOKE("get_rarity", { req });
+},
+async getNftGroups(req: GetNftGroups) : Promise<GetNftGroupsResponse> {
+    return await TAURI_INVOKE("get_nft_groups", { req });
+},
 async getNfts(req: GetNfts) : Promise<GetNftsResponse> {
     return await TAURI_INVOKE("get_nfts", { req });
 },
 async getNft(req: GetNft) : Promise<GetNftResponse> {
     return await TAURI_INVOKE("get_nft", { req });
 },
+async getNftProvenance(req: GetNftProvenance) : Promise<GetNftProvenanceResponse> {
+    return await TAURI_INVOKE("get_nft_provenance", { req });
+},
 async getNftData(req: GetNftData) : Promise<GetNftDataResponse> {
     return await TAURI_INVOKE("get_nft_data", { req });
 },
@@ -143,6 +173,9 @@ async takeOffer(req: TakeOffer) : Promise<TakeOfferResponse> {
 async viewOffer(req: ViewOffer) : Promise<ViewOfferResponse> {
     return await TAURI_INVOKE("view_offer", { req });
 },
+async compareOffers(req: CompareOffers) : Promise<CompareOffersResponse> {
+    return await TAURI_INVOKE("compare_offers", { req });
+},
 async importOffer(req: ImportOffer) : Promise<ImportOfferResponse> {
     return await TAURI_INVOKE("import_offer", { req });
 },
@@ -176,6 +209,21 @@ async setDeriveAutomatically(req: SetDeriveAutomatically) : Promise<SetDeriveAut
 async setDerivationBatchSize(req: SetDerivationBatchSize) : Promise<SetDerivationBatchSizeResponse> {
     return await TAURI_INVOKE("set_derivation_batch_size", { req });
 },
+async setMaxDerivationIndex(req: SetMaxDerivationIndex) : Promise<SetMaxDerivationIndexResponse> {
+    return await TAURI_INVOKE("set_max_derivation_index", { req });
+},
+async setMaxBundleCost(req: SetMaxBundleCost) : Promise<SetMaxBundleCostResponse> {
+    return await TAURI_INVOKE("set_max_bundle_cost", { req });
+},
+async setMaxOfferSpends(req: SetMaxOfferSpends) : Promise<SetMaxOfferSpendsResponse> {
+    return await TAURI_INVOKE("set_max_offer_spends", { req });
+},
+async setDustThreshold(req: SetDustThreshold) : Promise<SetDustThresholdResponse> {
+    return await TAURI_INVOKE("set_dust_threshold", { req });
+},
+async setMinConfirmations(req: SetMinConfirmations) : Promise<SetMinConfirmationsResponse> {
+    return await TAURI_INVOKE("set_min_confirmations", { req });
+},
 async getNetworks(req: GetNetworks) : Promise<GetNetworksResponse> {
     return await TAURI_INVOKE("get_networks", { req });
 },
@@ -191,6 +239,21 @@ async updateDid(req: UpdateDid) : Promise<UpdateDidResponse> {
 async updateNft(req: UpdateNft) : Promise<UpdateNftResponse> {
     return await TAURI_INVOKE("update_nft", { req });
 },
+async freezeCoins(req: FreezeCoins) : Promise<FreezeCoinsResponse> {
+    return await TAURI_INVOKE("freeze_coins", { req });
+},
+async unfreezeCoins(req: UnfreezeCoins) : Promise<UnfreezeCoinsResponse> {
+    return await TAURI_INVOKE("unfreeze_coins", { req });
+},
+async setCoinLabel(req: SetCoinLabel) : Promise<SetCoinLabelResponse> {
+    return await TAURI_INVOKE("set_coin_label", { req });
+},
+async addBurnAddress(req: AddBurnAddress) : Promise<AddBurnAddressResponse> {
+    return await TAURI_INVOKE("add_burn_address", { req });
+},
+async removeBurnAddress(req: RemoveBurnAddress) : Promise<RemoveBurnAddressResponse> {
+    return await TAURI_INVOKE("remove_burn_address", { req });
+},
 async getPeers(req: GetPeers) : Promise<GetPeersResponse> {
     return await TAURI_INVOKE("get_peers", { req });
 },
@@ -229,37 +292,51 @@ syncEvent: "sync-event"
 
 /** user-defined types **/
 
+export type AddBurnAddress = { address: string }
+export type AddBurnAddressResponse = Record<string, never>
 export type AddNftUri = { nft_id: string; uri: string; fee: Amount; kind: NftUriKind; auto_submit?: boolean }
 export type AddPeer = { ip: string; trusted: boolean }
 export type AddPeerResponse = Record<string, never>
 export type Amount = string | number
 export type AssetCoinType = "cat" | "did" | "nft"
+export type AssetId = { type: "xch" } | { type: "cat"; asset_id: string } | { type: "nft"; launcher_id: string }
 export type Assets = { xch: Amount; cats: CatAmount[]; nfts: string[] }
 export type AssignNftsToDid = { nft_ids: string[]; did_id: string | null; fee: Amount; auto_submit?: boolean }
+export type BestOffer = "first" | "second" | "equal"
 export type BulkMintNfts = { mints: NftMint[]; did_id: string; fee: Amount; auto_submit?: boolean }
-export type CatAmount = { asset_id: string; amount: Amount }
-export type CatRecord = { asset_id: string; name: string | null; ticker: string | null; description: string | null; icon_url: string | null; visible: boolean; balance: Amount }
+export type CatAmount = { asset_id: string; amount: Amount; puzzle_hash?: string | null }
+export type CatRecord = { asset_id: string; name: string | null; ticker: string | null; description: string | null; icon_url: string | null; visible: boolean; balance: Amount; decimals: number }
 export type Coin = { parent_coin_info: string; puzzle_hash: string; amount: number }
 export type CoinJson = { parent_coin_info: string; puzzle_hash: string; amount: Amount }
-export type CoinRecord = { coin_id: string; address: string; amount: Amount; created_height: number | null; spent_height: number | null; create_transaction_id: string | null; spend_transaction_id: string | null; offer_id: string | null }
+export type CoinRecord = { coin_id: string; address: string; amount: Amount; created_height: number | null; spent_height: number | null; create_transaction_id: string | null; spend_transaction_id: string | null; offer_id: string | null; label: string | null }
 export type CoinSpend = { coin: Coin; puzzle_reveal: string; solution: string }
 export type CoinSpendJson = { coin: CoinJson; puzzle_reveal: string; solution: string }
 export type CombineCat = { coin_ids: string[]; fee: Amount; auto_submit?: boolean }
 export type CombineXch = { coin_ids: string[]; fee: Amount; auto_submit?: boolean }
+export type CompareOffers = { first: string; second: string }
+export type CompareOffersResponse = { comparable: boolean; first_price: number | null; second_price: number | null; first_fee: Amount | null; second_fee: Amount | null; best: BestOffer | null }
 export type CreateDid = { name: string; fee: Amount; auto_submit?: boolean }
+export type DecodeSpendBundle = { spend_bundle: string }
+export type DecodeSpendBundleResponse = { spend_bundle: SpendBundleJson }
 export type DeleteKey = { fingerprint: number }
 export type DeleteKeyResponse = Record<string, never>
 export type DeleteOffer = { offer_id: string }
 export type DeleteOfferResponse = Record<string, never>
 export type DerivationRecord = { index: number; public_key: string; address: string }
 export type DidRecord = { launcher_id: string; name: string | null; visible: boolean; coin_id: string; address: string; amount: Amount; created_height: number | null; create_transaction_id: string | null }
+export type EncodeSpendBundle = { spend_bundle: SpendBundleJson }
+export type EncodeSpendBundleResponse = { spend_bundle: string }
 export type Error = { kind: ErrorKind; reason: string }
 export type ErrorKind = "wallet" | "api" | "not_found" | "unauthorized" | "internal"
 export type FilterUnlockedCoins = { coin_ids: string[] }
 export type FilterUnlockedCoinsResponse = { coin_ids: string[] }
+export type FreezeCoins = { coin_ids: string[] }
+export type FreezeCoinsResponse = Record<string, never>
 export type GenerateMnemonic = { use_24_words: boolean }
 export type GenerateMnemonicResponse = { mnemonic: string }
 export type GetAssetCoins = { type?: AssetCoinType | null; assetId?: string | null; includedLocked?: boolean | null; offset?: number | null; limit?: number | null }
+export type GetBurnAddresses = Record<string, never>
+export type GetBurnAddressesResponse = { addresses: string[] }
 export type GetCat = { asset_id: string }
 export type GetCatCoins = { asset_id: string }
 export type GetCatCoinsResponse = { coins: CoinRecord[] }
@@ -270,10 +347,14 @@ export type GetDerivations = { offset: number; limit: number }
 export type GetDerivationsResponse = { derivations: DerivationRecord[] }
 export type GetDids = Record<string, never>
 export type GetDidsResponse = { dids: DidRecord[] }
+export type GetFrozenCoins = Record<string, never>
+export type GetFrozenCoinsResponse = { coins: CoinRecord[] }
 export type GetKey = { fingerprint?: number | null }
 export type GetKeyResponse = { key: KeyInfo | null }
 export type GetKeys = Record<string, never>
 export type GetKeysResponse = { keys: KeyInfo[] }
+export type GetKnownCats = { refresh?: boolean }
+export type GetKnownCatsResponse = { cats: KnownCatRecord[] }
 export type GetNetworks = Record<string, never>
 export type GetNetworksResponse = { networks: { [key in string]: Network } }
 export type GetNft = { nft_id: string }
@@ -281,8 +362,14 @@ export type GetNftCollection = { collection_id: string | null }
 export type GetNftCollectionResponse = { collection: NftCollectionRecord | null }
 export type GetNftCollections = { offset: number; limit: number; include_hidden: boolean }
 export type GetNftCollectionsResponse = { collections: NftCollectionRecord[] }
+export type GetNftCount = { fingerprint?: number | null; collection_id?: string | null }
+export type GetNftCountResponse = { total: number; pending: number }
 export type GetNftData = { nft_id: string }
 export type GetNftDataResponse = { data: NftData | null }
+export type GetNftGroups = Record<string, never>
+export type GetNftGroupsResponse = { groups: NftGroup[] }
+export type GetNftProvenance = { nft_id: string }
+export type GetNftProvenanceResponse = { entries: NftProvenanceEntry[]; complete: boolean }
 export type GetNftResponse = { nft: NftRecord | null }
 export type GetNftStatus = Record<string, never>
 export type GetNftStatusResponse = { nfts: number; visible_nfts: number; collections: number; visible_collections: number }
@@ -296,45 +383,57 @@ export type GetPeers = Record<string, never>
 export type GetPeersResponse = { peers: PeerRecord[] }
 export type GetPendingTransactions = Record<string, never>
 export type GetPendingTransactionsResponse = { transactions: PendingTransactionRecord[] }
+export type GetRarity = { collection_id: string }
+export type GetRarityResponse = { nfts: NftRarity[] }
 export type GetSecretKey = { fingerprint: number }
 export type GetSecretKeyResponse = { secrets: SecretKeyInfo | null }
-export type GetSyncStatus = Record<string, never>
-export type GetSyncStatusResponse = { balance: Amount; unit: Unit; synced_coins: number; total_coins: number; receive_address: string; burn_address: string }
+export type GetSpendableCoins = { asset_id: string | null; amount: Amount }
+export type GetSpendableCoinsResponse = { coins: CoinRecord[]; selected: Amount; change: Amount }
+export type GetSyncStatus = { fingerprint?: number | null }
+export type GetSyncStatusResponse = { balance: Amount; dust_balance: Amount; unit: Unit; synced_coins: number; total_coins: number; synced_height: number | null; peak_height: number | null; synced: boolean; stalled: boolean; derivations: number; receive_address: string; burn_address: string }
 export type GetTransactions = { offset: number; limit: number }
 export type GetTransactionsResponse = { transactions: TransactionRecord[]; total: number }
 export type GetXchCoins = Record<string, never>
 export type GetXchCoinsResponse = { coins: CoinRecord[] }
-export type ImportKey = { name: string; key: string; save_secrets?: boolean; login?: boolean }
+export type ImportKey = { name: string; key: string; save_secrets?: boolean; login?: boolean; derivation_path?: string | null }
 export type ImportKeyResponse = { fingerprint: number }
 export type ImportOffer = { offer: string }
 export type ImportOfferResponse = Record<string, never>
 export type IssueCat = { name: string; ticker: string; amount: Amount; fee: Amount; auto_submit?: boolean }
 export type KeyInfo = { name: string; fingerprint: number; public_key: string; kind: KeyKind; has_secrets: boolean }
 export type KeyKind = "bls"
+export type KnownCatRecord = { asset_id: string; name: string; ticker: string; decimals: number; icon_url: string | null }
 export type LineageProof = { parentName: string | null; innerPuzzleHash: string | null; amount: number | null }
 export type Login = { fingerprint: number }
 export type LoginResponse = Record<string, never>
 export type Logout = Record<string, never>
 export type LogoutResponse = Record<string, never>
-export type MakeOffer = { requested_assets: Assets; offered_assets: Assets; fee: Amount; expires_at_second: number | null }
+export type MakeOffer = { requested_assets: Assets; offered_assets: Assets; fee: Amount; expires_at_second: number | null; starts_at_second?: number | null; coin_ids?: string[]; requested_nft_memos?: { [key in string]: string[] }; allow_change?: boolean; recipient_puzzle_hash?: string | null; offer_memo?: string | null; display_units?: boolean }
 export type MakeOfferResponse = { offer: string; offer_id: string }
 export type Network = { default_port: number; ticker: string; address_prefix: string; precision: number; genesis_challenge: string; agg_sig_me: string; dns_introducers: string[] }
-export type NetworkConfig = { network_id: string; target_peers: number; discover_peers: boolean }
-export type NftCollectionRecord = { collection_id: string; did_id: string; metadata_collection_id: string; visible: boolean; name: string | null; icon: string | null; nfts: number; visible_nfts: number }
+export type NetworkConfig = { network_id: string; target_peers: number; discover_peers: boolean; sync_retries: number }
+export type NftCollectionRecord = { collection_id: string; did_id: string; metadata_collection_id: string; visible: boolean; name: string | null; icon: string | null; banner: string | null; nfts: number; visible_nfts: number }
 export type NftData = { blob: string | null; mime_type: string | null; metadata_json: string | null }
+export type NftGroup = { metadata_hash: string | null; nft_ids: string[]; count: number }
 export type NftMint = { edition_number: number | null; edition_total: number | null; data_uris: string[]; metadata_uris: string[]; license_uris: string[]; royalty_address: string | null; royalty_ten_thousandths: number }
-export type NftRecord = { launcher_id: string; collection_id: string | null; collection_name: string | null; minter_did: string | null; owner_did: string | null; visible: boolean; sensitive_content: boolean; name: string | null; created_height: number | null; coin_id: string; address: string; royalty_address: string; royalty_ten_thousandths: number; data_uris: string[]; data_hash: string | null; metadata_uris: string[]; metadata_hash: string | null; license_uris: string[]; license_hash: string | null; edition_number: number | null; edition_total: number | null }
+export type NftOwner = { type: "did"; did_id: string } | { type: "none" }
+export type NftProvenanceEntry = { coin_id: string; owner_address: string; created_height: number; spent_height: number | null }
+export type NftRarity = { nft_id: string; score: number }
+export type NftRecord = { launcher_id: string; collection_id: string | null; collection_name: string | null; minter_did: string | null; owner_did: string | null; visible: boolean; sensitive_content: boolean; burned: boolean; name: string | null; created_height: number | null; coin_id: string; address: string; royalty_address: string; royalty_ten_thousandths: number; data_uris: string[]; data_hash: string | null; metadata_uris: string[]; metadata_hash: string | null; license_uris: string[]; license_hash: string | null; edition_number: number | null; edition_total: number | null }
 export type NftSortMode = "name" | "recent"
 export type NftUriKind = "data" | "metadata" | "license"
-export type OfferAssets = { xch: OfferXch; cats: { [key in string]: OfferCat }; nfts: { [key in string]: OfferNft } }
-export type OfferCat = { amount: Amount; royalty: Amount; name: string | null; ticker: string | null; icon_url: string | null }
-export type OfferNft = { image_data: string | null; image_mime_type: string | null; name: string | null; royalty_ten_thousandths: number; royalty_address: string }
+export type OfferAssets = { xch: OfferXch; cats: { [key in string]: OfferCat }; nfts: { [key in string]: OfferNft }; unknown: OfferUnknown[] }
+export type OfferCat = { asset: AssetId; amount: Amount; royalty: Amount; royalties: RoyaltyPaymentRecord[]; decimals: number; name: string | null; ticker: string | null; icon_url: string | null; tail_revealed: boolean }
+export type OfferNft = { asset: AssetId; image_data: string | null; image_mime_type: string | null; name: string | null; royalty_ten_thousandths: number; royalty_address: string }
 export type OfferRecord = { offer_id: string; offer: string; status: OfferRecordStatus; creation_date: string; summary: OfferSummary }
-export type OfferRecordStatus = "active" | "completed" | "cancelled" | "expired"
+export type OfferRecordStatus = "active" | "completed" | "cancelled" | "expired" | "pending_cancel"
 export type OfferSummary = { fee: Amount; maker: OfferAssets; taker: OfferAssets }
-export type OfferXch = { amount: Amount; royalty: Amount }
+export type OfferUnknown = { puzzle_hash: string }
+export type OfferXch = { asset: AssetId; amount: Amount; royalty: Amount; royalties: RoyaltyPaymentRecord[]; decimals: number }
 export type PeerRecord = { ip_addr: string; port: number; trusted: boolean; peak_height: number }
-export type PendingTransactionRecord = { transaction_id: string; fee: Amount; submitted_at: string | null }
+export type PendingTransactionRecord = { transaction_id: string; fee: Amount; submitted_at: string | null; spent: TransactionCoin[]; created: TransactionCoin[]; rebroadcasts: number }
+export type RemoveBurnAddress = { address: string }
+export type RemoveBurnAddressResponse = Record<string, never>
 export type RemoveCat = { asset_id: string }
 export type RemoveCatResponse = Record<string, never>
 export type RemovePeer = { ip: string; ban: boolean }
@@ -343,17 +442,30 @@ export type RenameKey = { fingerprint: number; name: string }
 export type RenameKeyResponse = Record<string, never>
 export type Resync = { fingerprint: number; delete_offer_files?: boolean }
 export type ResyncResponse = Record<string, never>
+export type RoyaltyPaymentRecord = { nft_id: string; address: string; amount: Amount }
 export type SecretKeyInfo = { mnemonic: string | null; secret_key: string }
-export type SendCat = { asset_id: string; address: string; amount: Amount; fee: Amount; memos?: string[]; auto_submit?: boolean }
+export type SendCat = { asset_id: string; address: string; amount: Amount; fee: Amount; memos?: string[]; display_units?: boolean; auto_submit?: boolean }
 export type SendTransactionImmediately = { spend_bundle: SpendBundle }
 export type SendTransactionImmediatelyResponse = { status: number; error: string | null }
 export type SendXch = { address: string; amount: Amount; fee: Amount; memos?: string[]; auto_submit?: boolean }
+export type SetCoinLabel = { coin_id: string; label: string | null }
+export type SetCoinLabelResponse = Record<string, never>
 export type SetDerivationBatchSize = { fingerprint: number; derivation_batch_size: number }
 export type SetDerivationBatchSizeResponse = Record<string, never>
 export type SetDeriveAutomatically = { fingerprint: number; derive_automatically: boolean }
 export type SetDeriveAutomaticallyResponse = Record<string, never>
 export type SetDiscoverPeers = { discover_peers: boolean }
 export type SetDiscoverPeersResponse = Record<string, never>
+export type SetDustThreshold = { fingerprint: number; dust_threshold: Amount }
+export type SetDustThresholdResponse = Record<string, never>
+export type SetMaxBundleCost = { fingerprint: number; max_bundle_cost: number }
+export type SetMaxBundleCostResponse = Record<string, never>
+export type SetMaxDerivationIndex = { fingerprint: number; max_derivation_index: number }
+export type SetMaxDerivationIndexResponse = Record<string, never>
+export type SetMaxOfferSpends = { fingerprint: number; max_offer_spends: number }
+export type SetMaxOfferSpendsResponse = Record<string, never>
+export type SetMinConfirmations = { fingerprint: number; min_confirmations: number }
+export type SetMinConfirmationsResponse = Record<string, never>
 export type SetNetworkId = { network_id: string }
 export type SetNetworkIdResponse = Record<string, never>
 export type SetTargetPeers = { target_peers: number }
@@ -369,17 +481,19 @@ export type SplitCat = { coin_ids: string[]; output_count: number; fee: Amount;
 export type SplitXch = { coin_ids: string[]; output_count: number; fee: Amount; auto_submit?: boolean }
 export type SubmitTransaction = { spend_bundle: SpendBundleJson }
 export type SubmitTransactionResponse = Record<string, never>
-export type SyncEvent = { type: "start"; ip: string } | { type: "stop" } | { type: "subscribed" } | { type: "derivation" } | { type: "coin_state" } | { type: "puzzle_batch_synced" } | { type: "cat_info" } | { type: "did_info" } | { type: "nft_data" }
-export type TakeOffer = { offer: string; fee: Amount; auto_submit?: boolean }
+export type SyncEvent = { type: "start"; ip: string } | { type: "stop" } | { type: "subscribed" } | { type: "derivation" } | { type: "derivation_limit_reached"; max_index: number } | { type: "coin_state" } | { type: "puzzle_batch_synced" } | { type: "cat_info" } | { type: "did_info" } | { type: "nft_data" } | { type: "nft_prefetch"; fetched: number; pending: number } | { type: "sync_health"; stalled: boolean }
+export type TakeOffer = { offer: string; fee: Amount; auto_submit?: boolean; allow_royalty_waiver?: boolean; overfill_xch?: Amount | null; overfill_cats?: { [key in string]: Amount } }
 export type TakeOfferResponse = { summary: TransactionSummary; spend_bundle: SpendBundleJson; transaction_id: string }
-export type TransactionCoin = ({ type: "unknown" } | { type: "xch" } | { type: "launcher" } | { type: "cat"; asset_id: string; name: string | null; ticker: string | null; icon_url: string | null } | { type: "did"; launcher_id: string; name: string | null } | { type: "nft"; launcher_id: string; image_data: string | null; image_mime_type: string | null; name: string | null }) & { coin_id: string; amount: Amount; address: string | null }
-export type TransactionInput = ({ type: "unknown" } | { type: "xch" } | { type: "launcher" } | { type: "cat"; asset_id: string; name: string | null; ticker: string | null; icon_url: string | null } | { type: "did"; launcher_id: string; name: string | null } | { type: "nft"; launcher_id: string; image_data: string | null; image_mime_type: string | null; name: string | null }) & { coin_id: string; amount: Amount; address: string; outputs: TransactionOutput[] }
+export type TransactionCoin = ({ type: "unknown" } | { type: "xch" } | { type: "clawback" } | { type: "launcher" } | { type: "cat"; asset_id: string; name: string | null; ticker: string | null; icon_url: string | null } | { type: "did"; launcher_id: string; name: string | null } | { type: "nft"; launcher_id: string; image_data: string | null; image_mime_type: string | null; name: string | null }) & { coin_id: string; amount: Amount; address: string | null; asset: AssetId | null }
+export type TransactionInput = ({ type: "unknown" } | { type: "xch" } | { type: "clawback" } | { type: "launcher" } | { type: "cat"; asset_id: string; name: string | null; ticker: string | null; icon_url: string | null } | { type: "did"; launcher_id: string; name: string | null } | { type: "nft"; launcher_id: string; image_data: string | null; image_mime_type: string | null; name: string | null }) & { coin_id: string; amount: Amount; address: string; outputs: TransactionOutput[]; pending_spend: boolean }
 export type TransactionOutput = { coin_id: string; amount: Amount; address: string; receiving: boolean; burning: boolean }
 export type TransactionRecord = { height: number; spent: TransactionCoin[]; created: TransactionCoin[] }
 export type TransactionResponse = { summary: TransactionSummary; coin_spends: CoinSpendJson[] }
 export type TransactionSummary = { fee: Amount; inputs: TransactionInput[] }
 export type TransferDids = { did_ids: string[]; address: string; fee: Amount; auto_submit?: boolean }
-export type TransferNfts = { nft_ids: string[]; address: string; fee: Amount; auto_submit?: boolean }
+export type TransferNfts = { nft_ids: string[]; address: string; new_owner?: NftOwner | null; fee: Amount; auto_submit?: boolean }
+export type UnfreezeCoins = { coin_ids: string[] }
+export type UnfreezeCoinsResponse = Record<string, never>
 export type Unit = { ticker: string; decimals: number }
 export type UpdateCat = { record: CatRecord }
 export type UpdateCatResponse = Record<string, never>
@@ -391,7 +505,7 @@ export type ViewCoinSpends = { coin_spends: CoinSpendJson[] }
 export type ViewCoinSpendsResponse = { summary: TransactionSummary }
 export type ViewOffer = { offer: string }
 export type ViewOfferResponse = { offer: OfferSummary }
-export type WalletConfig = { name: string; derive_automatically: boolean; derivation_batch_size: number }
+export type WalletConfig = { name: string; derive_automatically: boolean; derivation_batch_size: number; max_derivation_index: number; max_bundle_cost: number; max_offer_spends: number; dust_threshold: number; min_confirmations: number; derivation_path: string | null; serialize_transactions: boolean; spend_oldest_coins_first: boolean }
 
 /** tauri-specta globals **/
 
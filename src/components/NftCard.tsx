@@ -192,7 +192,7 @@ export function NftCard({ nft, updateNfts, selectionState }: NftProps) {
   return (
     <>
       <div
-        className={`cursor-pointer group${`${!nft.visible ? ' opacity-50 grayscale' : !nft.created_height ? ' pulsate-opacity' : ''}`}`}
+        className={`cursor-pointer group${`${!nft.visible || nft.burned ? ' opacity-50 grayscale' : !nft.created_height ? ' pulsate-opacity' : ''}`}`}
         onClick={() => {
           if (selectionState === null) {
             navigate(`/nfts/${nft.launcher_id}`);
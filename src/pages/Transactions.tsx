@@ -131,7 +131,8 @@ function Transaction({ transaction }: TransactionProps) {
   ] as const) {
     for (const coin of coins) {
       switch (coin.type) {
-        case 'xch': {
+        case 'xch':
+        case 'clawback': {
           if (add) {
             xch = xch.plus(coin.amount);
           } else {